@@ -2,6 +2,9 @@ use crate::hash::H256;
 use crate::header::Header;
 use bitcoin::blockdata::transaction::Transaction;
 use bitcoin::consensus::deserialize;
+use bitcoin::consensus::encode::{deserialize_partial, serialize, Decodable, VarInt};
+use bitcoin::hashes::Hash;
+use bitcoin::{BlockHash, TxMerkleNode};
 use near_sdk::near;
 
 #[allow(clippy::module_name_repetitions)]
@@ -9,14 +12,226 @@ use near_sdk::near;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AuxData {
     pub coinbase_tx: Vec<u8>,
+    /// `hashBlock` of the coinbase transaction, ignored by the consensus and kept
+    /// to serialize the AuxPoW as received
+    pub parent_block_hash: H256,
     pub merkle_proof: Vec<H256>,
     pub chain_merkle_proof: Vec<H256>,
     pub chain_id: usize,
     pub parent_block: Header,
 }
 
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuxDataError {
+    InvalidCoinbaseTx,
+    InvalidParentBlockHash,
+    InvalidMerkleBranch,
+    InvalidMerkleIndex,
+    NotCoinbase,
+    InvalidChainMerkleBranch,
+    InvalidChainIndex,
+    InvalidParentBlock,
+    TrailingBytes,
+}
+
+impl std::fmt::Display for AuxDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuxDataError::InvalidCoinbaseTx => write!(f, "Invalid AuxPoW coinbase transaction"),
+            AuxDataError::InvalidParentBlockHash => {
+                write!(f, "Invalid AuxPoW coinbase parent block hash")
+            }
+            AuxDataError::InvalidMerkleBranch => write!(f, "Invalid AuxPoW merkle branch"),
+            AuxDataError::InvalidMerkleIndex => write!(f, "Invalid AuxPoW merkle index"),
+            AuxDataError::NotCoinbase => write!(f, "AuxPoW is not a generate"),
+            AuxDataError::InvalidChainMerkleBranch => {
+                write!(f, "Invalid AuxPoW chain merkle branch")
+            }
+            AuxDataError::InvalidChainIndex => write!(f, "Invalid AuxPoW chain index"),
+            AuxDataError::InvalidParentBlock => write!(f, "Invalid AuxPoW parent block header"),
+            AuxDataError::TrailingBytes => write!(f, "Unexpected bytes after AuxPoW"),
+        }
+    }
+}
+
+impl std::error::Error for AuxDataError {}
+
 impl AuxData {
-    pub fn get_coinbase_tx(&self) -> Transaction {
-        deserialize(&self.coinbase_tx).unwrap()
+    /// # Errors
+    /// If `coinbase_tx` is not a valid transaction
+    pub fn get_coinbase_tx(&self) -> Result<Transaction, AuxDataError> {
+        deserialize(&self.coinbase_tx).map_err(|_| AuxDataError::InvalidCoinbaseTx)
+    }
+
+    /// Consensus serialization of the AuxPoW, which follows a merge-mined block header.
+    /// The coinbase index must be zero, so it is not kept and serialized as zero.
+    ///
+    /// # Panics
+    /// If `chain_id` does not fit into `u32`
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn write_branch(out: &mut Vec<u8>, branch: &[H256]) {
+            out.extend(serialize(&VarInt::from(branch.len())));
+            for node in branch {
+                out.extend_from_slice(&node.0);
+            }
+        }
+
+        let mut out = self.coinbase_tx.clone();
+        out.extend_from_slice(&self.parent_block_hash.0);
+        write_branch(&mut out, &self.merkle_proof);
+        out.extend_from_slice(&0u32.to_le_bytes());
+        write_branch(&mut out, &self.chain_merkle_proof);
+        out.extend_from_slice(
+            &u32::try_from(self.chain_id)
+                .expect("chain index fits into u32")
+                .to_le_bytes(),
+        );
+        out.extend(self.parent_block.to_block_header_vec());
+        out
+    }
+
+    /// Parses a consensus-serialized AuxPoW, the counterpart of [`Self::to_bytes`]
+    ///
+    /// # Errors
+    /// If the bytes are not a valid AuxPoW or have extra bytes after it
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AuxDataError> {
+        let (aux_data, read_len) = Self::deserialize_partial(bytes)?;
+        if read_len != bytes.len() {
+            return Err(AuxDataError::TrailingBytes);
+        }
+        Ok(aux_data)
+    }
+
+    /// Parses the consensus-serialized AuxPoW which follows a merge-mined block header
+    /// and returns it together with the number of bytes read.
+    ///
+    /// # Errors
+    /// If the bytes do not start with a valid AuxPoW
+    pub fn deserialize_partial(bytes: &[u8]) -> Result<(Self, usize), AuxDataError> {
+        fn read<T: Decodable>(
+            bytes: &[u8],
+            cursor: &mut usize,
+            err: AuxDataError,
+        ) -> Result<T, AuxDataError> {
+            let (value, read_len) = deserialize_partial(&bytes[*cursor..]).map_err(|_| err)?;
+            *cursor += read_len;
+            Ok(value)
+        }
+
+        fn into_h256(branch: Vec<TxMerkleNode>) -> Vec<H256> {
+            branch
+                .into_iter()
+                .map(|node| H256::from(node.to_byte_array()))
+                .collect()
+        }
+
+        let mut cursor = 0;
+        read::<Transaction>(bytes, &mut cursor, AuxDataError::InvalidCoinbaseTx)?;
+        let coinbase_tx = bytes[..cursor].to_vec();
+        let parent_block_hash: BlockHash =
+            read(bytes, &mut cursor, AuxDataError::InvalidParentBlockHash)?;
+        let merkle_branch: Vec<TxMerkleNode> =
+            read(bytes, &mut cursor, AuxDataError::InvalidMerkleBranch)?;
+        let merkle_index: u32 = read(bytes, &mut cursor, AuxDataError::InvalidMerkleIndex)?;
+        if merkle_index != 0 {
+            return Err(AuxDataError::NotCoinbase);
+        }
+        let chain_merkle_branch: Vec<TxMerkleNode> =
+            read(bytes, &mut cursor, AuxDataError::InvalidChainMerkleBranch)?;
+        let chain_index: u32 = read(bytes, &mut cursor, AuxDataError::InvalidChainIndex)?;
+
+        let parent_block = bytes
+            .get(cursor..cursor + Header::SIZE)
+            .and_then(|parent_block| Header::from_block_header_vec(parent_block).ok())
+            .ok_or(AuxDataError::InvalidParentBlock)?;
+        cursor += Header::SIZE;
+
+        Ok((
+            Self {
+                coinbase_tx,
+                parent_block_hash: H256::from(parent_block_hash.to_byte_array()),
+                merkle_proof: into_h256(merkle_branch),
+                chain_merkle_proof: into_h256(chain_merkle_branch),
+                chain_id: usize::try_from(chain_index)
+                    .map_err(|_| AuxDataError::InvalidChainIndex)?,
+                parent_block,
+            },
+            cursor,
+        ))
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "zcash_header"))]
+mod tests {
+    use super::*;
+
+    fn aux_data() -> AuxData {
+        let parent_block: Header = "04e1ff2f1d761d390c19df86dc01f970c0f53663171a75288c2406000000000000000000245470d64414a15c7333cae23c3fa9caa92cb4490f61a6a215660e09aa134e53f1e7b2607b5f0d1792aed66f".parse().unwrap();
+        AuxData {
+            coinbase_tx: hex::decode(concat!(
+                "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff",
+                "0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae",
+                "1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82c",
+                "bf2342c858eeac00000000"
+            ))
+            .unwrap(),
+            parent_block_hash: parent_block.block_hash(),
+            merkle_proof: vec![parent_block.merkle_root.clone()],
+            chain_merkle_proof: vec![parent_block.prev_block_hash.clone()],
+            chain_id: 3,
+            parent_block,
+        }
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let aux_data = aux_data();
+        let bytes = aux_data.to_bytes();
+        assert_eq!(AuxData::from_bytes(&bytes), Ok(aux_data.clone()));
+        assert_eq!(
+            AuxData::deserialize_partial(&[bytes.as_slice(), &[0u8; 4]].concat()),
+            Ok((aux_data.clone(), bytes.len()))
+        );
+        assert!(aux_data.get_coinbase_tx().unwrap().is_coinbase());
+    }
+
+    #[test]
+    fn test_invalid_bytes() {
+        let aux_data = aux_data();
+        let bytes = aux_data.to_bytes();
+        let coinbase_tx_len = aux_data.coinbase_tx.len();
+
+        assert_eq!(
+            AuxData::from_bytes(&[bytes.as_slice(), &[0u8]].concat()),
+            Err(AuxDataError::TrailingBytes)
+        );
+        assert_eq!(
+            AuxData::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(AuxDataError::InvalidParentBlock)
+        );
+        assert_eq!(
+            AuxData::from_bytes(&bytes[1..]),
+            Err(AuxDataError::InvalidCoinbaseTx)
+        );
+
+        // The coinbase index follows the coinbase, its parent block hash and a single node branch
+        let mut not_coinbase = bytes.clone();
+        not_coinbase[coinbase_tx_len + 32 + 1 + 32] = 1;
+        assert_eq!(
+            AuxData::from_bytes(&not_coinbase),
+            Err(AuxDataError::NotCoinbase)
+        );
+
+        let invalid_coinbase = AuxData {
+            coinbase_tx: vec![0],
+            ..aux_data
+        };
+        assert_eq!(
+            invalid_coinbase.get_coinbase_tx(),
+            Err(AuxDataError::InvalidCoinbaseTx)
+        );
     }
 }
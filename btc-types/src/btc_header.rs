@@ -1,10 +1,12 @@
 use near_sdk::near;
 
-use crate::hash::{double_sha256, H256};
+#[cfg(not(feature = "x11_hash"))]
+use crate::hash::double_sha256;
+use crate::hash::H256;
 
 pub type Error = crate::utils::DecodeHeaderError;
 
-// Represents a Bitcoin/Litecoin/Dogecoin block header, which contains metadata about the block
+// Represents a Bitcoin/Litecoin/Dogecoin/Dash block header, which contains metadata about the block
 #[near(serializers = [borsh, json])]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Header {
@@ -31,28 +33,51 @@ impl Header {
 
     #[must_use]
     pub fn block_hash(&self) -> H256 {
-        let block_header = self.get_block_header_vec();
-        double_sha256(&block_header)
+        let block_header = self.to_block_header_vec();
+        // Dash identifies the blocks by the X11 hash
+        #[cfg(feature = "x11_hash")]
+        {
+            H256::from(crate::x11::x11_hash(&block_header))
+        }
+
+        #[cfg(not(feature = "x11_hash"))]
+        {
+            double_sha256(&block_header)
+        }
     }
 
     pub fn block_hash_pow(&self) -> H256 {
-        let block_header = self.get_block_header_vec();
-        #[cfg(feature = "scrypt_hash")]
+        #[cfg(feature = "x11_hash")]
         {
-            let params = scrypt::Params::new(10, 1, 1, 32).unwrap(); // N=1024 (2^10), r=1, p=1
+            self.block_hash()
+        }
 
-            let mut output = [0u8; 32];
-            scrypt::scrypt(&block_header, &block_header, &params, &mut output).unwrap();
-            H256::from(output)
+        #[cfg(feature = "scrypt_hash")]
+        {
+            self.block_hash_scrypt()
         }
 
-        #[cfg(not(feature = "scrypt_hash"))]
+        #[cfg(not(any(feature = "scrypt_hash", feature = "x11_hash")))]
         {
-            double_sha256(&block_header)
+            double_sha256(&self.to_block_header_vec())
         }
     }
 
-    fn get_block_header_vec(&self) -> Vec<u8> {
+    /// The proof of work hash of Litecoin and Dogecoin
+    #[cfg(feature = "scrypt_hash")]
+    #[must_use]
+    pub fn block_hash_scrypt(&self) -> H256 {
+        let block_header = self.to_block_header_vec();
+        let params = scrypt::Params::new(10, 1, 1, 32).unwrap(); // N=1024 (2^10), r=1, p=1
+
+        let mut output = [0u8; 32];
+        scrypt::scrypt(&block_header, &block_header, &params, &mut output).unwrap();
+        H256::from(output)
+    }
+
+    /// Consensus serialization of the header, the counterpart of [`Self::from_block_header_vec`]
+    #[must_use]
+    pub fn to_block_header_vec(&self) -> Vec<u8> {
         let mut block_header = Vec::with_capacity(Self::SIZE);
         block_header.extend_from_slice(&self.version.to_le_bytes());
         block_header.extend(self.prev_block_hash.0);
@@ -68,6 +93,12 @@ impl Header {
         self.version >> 16
     }
 
+    /// Size of the consensus-serialized header at the start of `bytes`, which is always
+    /// [`Self::SIZE`]. The counterpart of the Zcash header, whose Equihash solution varies.
+    pub fn consensus_size(_bytes: &[u8]) -> Result<usize, Error> {
+        Ok(Self::SIZE)
+    }
+
     pub fn from_block_header_vec(block_header: &[u8]) -> Result<Self, Error> {
         if block_header.len() != Self::SIZE {
             return Err(Error::InvalidLength);
@@ -114,6 +145,21 @@ impl Header {
     }
 }
 
+/// Formats the header as its consensus hex, as returned by `getblockheader <hash> false`
+impl std::fmt::Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.to_block_header_vec()))
+    }
+}
+
+impl std::str::FromStr for Header {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_block_header_vec(&hex::decode(s).map_err(|_| Error::InvalidHex)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Header;
@@ -159,4 +205,197 @@ mod tests {
             "000000000000000000016f0484972d135afba541c837d0c07c1530ffeee293cd"
         );
     }
+
+    #[test]
+    #[cfg(not(any(
+        feature = "auxpow_header",
+        feature = "zcash_header",
+        feature = "x11_hash"
+    )))]
+    fn test_decode_compact_headers() {
+        let compact_headers_hex = concat!(
+            "04e0ff2f1d761d390c19df86dc01f970c0f53663171a75288c2406000000000000000000245470d64414a15c7333cae23c3fa9caa92cb4490f61a6a215660e09aa134e53f1e7b2607b5f0d1792aed66f",
+            "04e000205864a6f4aa3014d858cfe319149af7a742b7a49a5e5e57ba6e356896312b3e1fafedb2607b5f0d1730f277cf"
+        );
+        let compact_headers_bytes = hex::decode(compact_headers_hex).unwrap();
+        let headers =
+            crate::header::block_headers_from_compact_vec(&compact_headers_bytes).unwrap();
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[1].prev_block_hash, headers[0].block_hash());
+        assert_eq!(
+            headers[1].block_hash().to_string(),
+            "00000000000000000003fb78da0a99e751b6cf726723a99b9ca2dc6e8bb45544"
+        );
+
+        assert!(crate::header::block_headers_from_compact_vec(
+            &compact_headers_bytes[..compact_headers_bytes.len() - 1]
+        )
+        .is_err());
+        assert!(crate::header::block_headers_from_compact_vec(&[]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "auxpow_header")]
+    fn test_decode_compact_headers_without_auxpow() {
+        let compact_headers_hex = concat!(
+            "04e1ff2f1d761d390c19df86dc01f970c0f53663171a75288c2406000000000000000000245470d64414a15c7333cae23c3fa9caa92cb4490f61a6a215660e09aa134e53f1e7b2607b5f0d1792aed66f",
+            "04e000205864a6f4aa3014d858cfe319149af7a742b7a49a5e5e57ba6e356896312b3e1fafedb2607b5f0d1730f277cf"
+        );
+        let compact_headers_bytes = hex::decode(compact_headers_hex).unwrap();
+        // The first header sets the AuxPoW version bit, but is not followed by AuxPoW
+        let headers = crate::header::block_headers_from_compact_vec_with_auxpow(
+            &compact_headers_bytes,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].0.version & 0x100, 0x100);
+        assert!(headers.iter().all(|(_, aux_data)| aux_data.is_none()));
+        assert_eq!(headers[1].0.prev_block_hash, headers[0].0.block_hash());
+    }
+
+    #[test]
+    fn test_consensus_hex() {
+        let block_header_hex = "04e0ff2f1d761d390c19df86dc01f970c0f53663171a75288c2406000000000000000000245470d64414a15c7333cae23c3fa9caa92cb4490f61a6a215660e09aa134e53f1e7b2607b5f0d1792aed66f";
+        let header: Header = block_header_hex.parse().unwrap();
+        assert_eq!(header.to_string(), block_header_hex);
+        assert_eq!(
+            header.to_block_header_vec(),
+            hex::decode(block_header_hex).unwrap()
+        );
+        assert_eq!(
+            "zz".parse::<Header>(),
+            Err(crate::utils::DecodeHeaderError::InvalidHex)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "zcash_header"))]
+    fn test_extended_header_consensus_hex() {
+        use crate::header::ExtendedHeader;
+        use crate::u256::U256;
+
+        let block_header_hex = "04e0ff2f1d761d390c19df86dc01f970c0f53663171a75288c2406000000000000000000245470d64414a15c7333cae23c3fa9caa92cb4490f61a6a215660e09aa134e53f1e7b2607b5f0d1792aed66f";
+        let header: Header = block_header_hex.parse().unwrap();
+        let extended_header = ExtendedHeader {
+            block_hash: header.block_hash(),
+            block_header: header.into_light(),
+            chain_work: U256::ONE,
+            block_height: 686_096,
+            recent_timestamps: vec![],
+        };
+        let value = crate::utils::serd_extended_header_consensus_hex::serialize(
+            &extended_header,
+            serde_json::value::Serializer,
+        )
+        .unwrap();
+        assert_eq!(value["block_header"], json!(block_header_hex));
+        assert_eq!(
+            crate::utils::serd_extended_header_consensus_hex::deserialize(value).unwrap(),
+            extended_header
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "auxpow_header")]
+    fn test_auxpow_consensus_hex() {
+        use crate::aux::AuxData;
+        use crate::header::ConsensusHex;
+
+        let header: Header = "04e1ff2f1d761d390c19df86dc01f970c0f53663171a75288c2406000000000000000000245470d64414a15c7333cae23c3fa9caa92cb4490f61a6a215660e09aa134e53f1e7b2607b5f0d1792aed66f".parse().unwrap();
+        let aux_data = AuxData {
+            coinbase_tx: hex::decode(concat!(
+                "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff",
+                "0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae",
+                "1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82c",
+                "bf2342c858eeac00000000"
+            ))
+            .unwrap(),
+            parent_block_hash: crate::hash::H256::default(),
+            merkle_proof: vec![header.merkle_root.clone()],
+            chain_merkle_proof: vec![],
+            chain_id: 0,
+            parent_block: header.clone(),
+        };
+
+        let block_header = (header.clone(), Some(aux_data));
+        let hex_str = block_header.to_consensus_hex();
+        assert_eq!(
+            <(Header, Option<AuxData>)>::from_consensus_hex(&hex_str).unwrap(),
+            block_header
+        );
+        assert_eq!(
+            <(Header, Option<AuxData>)>::from_consensus_hex(&hex_str[..hex_str.len() - 2]),
+            Err(crate::utils::DecodeHeaderError::InvalidAuxPow(
+                crate::aux::AuxDataError::InvalidParentBlock
+            ))
+        );
+
+        let plain_header = (header, None);
+        assert_eq!(
+            <(Header, Option<AuxData>)>::from_consensus_hex(&plain_header.to_consensus_hex())
+                .unwrap(),
+            plain_header
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "auxpow_header")]
+    fn test_dogecoin_auxpow_round_trip() {
+        use crate::aux::AuxData;
+        use crate::header::ConsensusHex;
+
+        // Dogecoin block 5_800_013 merge-mined with Litecoin block 2_935_420. The AuxPoW is the one
+        // of the block, with the hash of the Litecoin block as the hashBlock of the coinbase.
+        let hex_str = concat!(
+            "040162004dd758d422b454c0f65325cc71070090283c5a3062fff5db893c5afa82c7dbc615400018",
+            "78a8f3d510c01c104e8bc022825988cc8d467e6520c671358c53cf37c9c77c682ea5001a00000000",
+            "01000000010000000000000000000000000000000000000000000000000000000000000000ffffff",
+            "ff57037cca2c253030486173685370616365303000000000e375651dde1b6d6200047525120f0000",
+            "000000002cfabe6d6d6cff50d08b209ee04f1fdfc369595c9ab75a6d738feed0d70394dbf7b27d11",
+            "e820000000f9ad6a09ffffffff02cbc75525000000001976a914448e79e54a421c38ef3d1a0d1491",
+            "71761f98cdd288ac0000000000000000266a24aa21a9edfcaff70cf3c321829c6ad57ce2a32b76d8",
+            "1fcbf055949a1826b5036547a00ec700000000ffb981c7e9a6daec9a7d8433ffcb16b25a8351898e",
+            "82d120d27ba9f9ff91ee010992111b3bbdf5d2d08bbe6149cc2248860620b3afb67c10fc1ddd5399",
+            "9ff4d1098fc50b9b95bab6e62fd0c2f926e3890a56d7013ea5542b66f919e9dd644bfa88ad093d7e",
+            "d64efe72d11b3828afc5c2965fc3d697eab2a7d8e0cea48d74d531fa46170d48dc8514b4047e743c",
+            "4bdad757710c49761e29eb662f655a720731b2e96db576df54eec7c88fffca63f2b4257e8f099994",
+            "52449f4edbec3f6412b6bed21f5588d3d0751c25ee0f566752ac5294ff86546b21a8aeac107e9b8b",
+            "b6301e22eabf1ca1dabe8c5b3379adcada15bf89292a72aac9fdbc58c144e45794ecdcb605431cff",
+            "e4c8daec0e5b4c26c41f72995bb40eb568a1b426d038a4d6aa9262889fdc24fa2e034dba1fde283e",
+            "7cb4f966bc061ea4f5caa5e5c1f5179166420ab70000000005000000000000000000000000000000",
+            "0000000000000000000000000000000000e2f61c3f71d1defd3fa999dfa36953755c690689799962",
+            "b48bebd836974e8cf9872a409b81e63cc0cf694745c5f7c04c2972a4bcd7e03849d03ea34402fadc",
+            "65d73ede2b377fef1258aaf85f05b79b869ff3f553dd3cc8f7078653e7a53c257769b54b997c17ac",
+            "2678bdded741189ff69abf911597077a52cb6ec40d3f34ce4e190000000000002074463428ae5ece",
+            "9b55cb779a3a74a4c4783c3eb211fb57264ff22fc99cb71d0d688d49bcd14332aaba5a91b6c8fb80",
+            "257623faeb127784e3b6ced40557029c0441c87c68659230193d880849",
+        );
+
+        let (header, aux_data) = <(Header, Option<AuxData>)>::from_consensus_hex(hex_str).unwrap();
+        let aux_data = aux_data.unwrap();
+        assert_eq!(
+            header.block_hash().to_string(),
+            "919970f385fa583800f89d8b84249b98a1cb73ef732ea30d5fa176ffcbf88e18"
+        );
+        assert_eq!(
+            aux_data.parent_block_hash,
+            aux_data.parent_block.block_hash()
+        );
+        assert_eq!(aux_data.chain_id, 25);
+        assert_eq!((header.clone(), Some(aux_data)).to_consensus_hex(), hex_str);
+
+        // The AuxPoW is skipped, e.g. to read the partial merkle tree of a merkle block
+        let bytes = hex::decode(hex_str).unwrap();
+        assert_eq!(
+            crate::header::deserialize_partial_header(&bytes, true),
+            Ok((header.clone(), bytes.len()))
+        );
+        assert_eq!(
+            crate::header::deserialize_partial_header(&bytes, false),
+            Ok((header, Header::SIZE))
+        );
+    }
 }
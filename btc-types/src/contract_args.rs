@@ -1,6 +1,11 @@
 use near_sdk::near;
 
-use crate::{hash::H256, header::Header, network::Network};
+use crate::{
+    hash::H256,
+    header::Header,
+    network::{Chain, Network},
+    u256::U256,
+};
 
 #[near(serializers = [borsh, json])]
 #[derive(Clone, Debug)]
@@ -11,6 +16,31 @@ pub struct InitArgs {
     pub gc_threshold: u64,
     pub network: Network,
     pub submit_blocks: Vec<Header>,
+    /// Cumulative chain work of the genesis block as reported by `getblockheader`.
+    /// If not set, the chain work is counted from the genesis block only.
+    #[serde(default, with = "crate::utils::serd_u256_hex_option")]
+    pub initial_chain_work: Option<U256>,
+    /// Minimum chain work the initial chain tip must have, like `nMinimumChainWork` in Bitcoin Core.
+    #[serde(default, with = "crate::utils::serd_u256_hex_option")]
+    pub min_chain_work: Option<U256>,
+    /// Challenge script of a signet, as a hex string. Only for `Network::Signet`,
+    /// the challenge of the default signet is used if not set.
+    #[serde(default, with = "crate::utils::serd_bytes_hex_option")]
+    pub signet_challenge: Option<Vec<u8>>,
+    /// Chain whose consensus rules are followed. Required if the contract is built with
+    /// several chains, the built chain is used if not set.
+    #[serde(default)]
+    pub chain: Option<Chain>,
+}
+
+/// A signet block header with the data required to check its block solution, see BIP325
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct SignetBlockArgs {
+    pub header: crate::btc_header::Header,
+    #[serde(with = "hex::serde")]
+    pub coinbase_tx: Vec<u8>,
+    pub coinbase_merkle_proof: Vec<H256>,
 }
 
 #[near(serializers = [borsh, json])]
@@ -35,6 +65,76 @@ pub struct ProofArgsV2 {
     pub confirmations: u64,
 }
 
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct ProofArgsV3 {
+    pub tx_id: H256,
+    pub tx_block_blockhash: H256,
+    pub tx_index: u64,
+    pub merkle_proof: Vec<H256>,
+    pub coinbase_tx_id: H256,
+    pub coinbase_merkle_proof: Vec<H256>,
+    /// Number of transactions in the block, both merkle proofs are checked against the shape
+    /// of its tree, which rejects mutated trees (CVE-2012-2459)
+    pub tx_count: u64,
+    pub confirmations: u64,
+}
+
+/// Inclusion proof of a transaction as a BIP37 merkle block, see `gettxoutproof`
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct MerkleBlockProofArgs {
+    /// Consensus-serialized merkle block: the block header, with its AuxPoW for the merge-mined
+    /// chains, followed by a partial merkle tree
+    #[serde(with = "hex::serde")]
+    pub merkle_block: Vec<u8>,
+    pub tx_id: H256,
+    pub confirmations: u64,
+}
+
+/// Inclusion proof of a transaction in a Litecoin block with MWEB, see LIP-0003
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct MwebProofArgs {
+    pub proof: ProofArgsV2,
+    /// Number of transactions in the canonical block, the `HogEx` being the last one
+    pub tx_count: u64,
+    /// Consensus-serialized `HogEx` transaction, with the MWEB flag
+    #[serde(with = "hex::serde")]
+    pub hogex_tx: Vec<u8>,
+    pub hogex_merkle_proof: Vec<H256>,
+    /// If set, the MWEB header hash committed by the `HogEx` is checked against it
+    pub mweb_header_hash: Option<H256>,
+}
+
+/// Inclusion proof of a Zcash v5 transaction, whose txid is computed from the raw transaction
+/// as in ZIP-244
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct ZcashProofArgs {
+    /// Consensus-serialized v5 transaction, with transparent components only
+    #[serde(with = "hex::serde")]
+    pub tx: Vec<u8>,
+    pub tx_block_blockhash: H256,
+    pub tx_index: u64,
+    pub merkle_proof: Vec<H256>,
+    pub coinbase_tx_id: H256,
+    pub coinbase_merkle_proof: Vec<H256>,
+    pub confirmations: u64,
+    /// If set, the authorizing data of the transaction is checked against the block commitments
+    pub auth_data_proof: Option<AuthDataProof>,
+}
+
+/// Proof of the auth digest of a transaction in the `hashBlockCommitments` of a block, see ZIP-244
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug)]
+pub struct AuthDataProof {
+    /// Merkle proof of the auth digest in the auth data tree of the block
+    pub auth_data_merkle_proof: Vec<H256>,
+    /// The `hashChainHistoryRoot` of the block, committed together with the auth data root
+    pub chain_history_root: H256,
+}
+
 impl From<ProofArgsV2> for ProofArgs {
     fn from(args: ProofArgsV2) -> Self {
         Self {
@@ -46,3 +146,18 @@ impl From<ProofArgsV2> for ProofArgs {
         }
     }
 }
+
+/// Drops the number of transactions, for the contracts without `verify_transaction_inclusion_v3`
+impl From<ProofArgsV3> for ProofArgsV2 {
+    fn from(args: ProofArgsV3) -> Self {
+        Self {
+            tx_id: args.tx_id,
+            tx_block_blockhash: args.tx_block_blockhash,
+            tx_index: args.tx_index,
+            merkle_proof: args.merkle_proof,
+            coinbase_tx_id: args.coinbase_tx_id,
+            coinbase_merkle_proof: args.coinbase_merkle_proof,
+            confirmations: args.confirmations,
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use near_sdk::near;
 
-#[cfg(feature = "dogecoin_header")]
+#[cfg(feature = "auxpow_header")]
 use crate::aux::AuxData;
-use crate::{hash::H256, u256::U256};
+use crate::{hash::H256, network::MEDIAN_TIME_SPAN, u256::U256, utils::DecodeHeaderError};
 
 pub type Target = U256;
 pub type Work = U256;
@@ -13,10 +13,10 @@ pub use super::zcash_header::{Header, LightHeader};
 #[cfg(not(feature = "zcash_header"))]
 pub use super::btc_header::{Header, LightHeader};
 
-#[cfg(not(feature = "dogecoin_header"))]
+#[cfg(not(feature = "auxpow_header"))]
 pub type BlockHeader = Header;
 
-#[cfg(feature = "dogecoin_header")]
+#[cfg(feature = "auxpow_header")]
 pub type BlockHeader = (Header, Option<AuxData>);
 
 #[allow(clippy::module_name_repetitions)]
@@ -34,4 +34,175 @@ pub struct ExtendedHeader {
     pub chain_work: Work,
     /// Block height in the Bitcoin network
     pub block_height: u64,
+    /// Timestamps of this block and its ancestors, newest first. Holds `MEDIAN_TIME_SPAN`
+    /// entries, so the median time past is computed without reading the previous headers.
+    /// Fewer entries are cached for the blocks close to the initial one, and none for the
+    /// headers stored before the cache was introduced.
+    #[serde(default)]
+    pub recent_timestamps: Vec<u32>,
+}
+
+impl ExtendedHeader {
+    /// Median time past of this block, if the timestamps of the last `MEDIAN_TIME_SPAN`
+    /// blocks are cached in the header
+    #[must_use]
+    pub fn median_time_past(&self) -> Option<u32> {
+        let mut median_time = [0u32; MEDIAN_TIME_SPAN];
+        median_time.copy_from_slice(self.recent_timestamps.get(..MEDIAN_TIME_SPAN)?);
+        median_time.sort_unstable();
+        Some(median_time[median_time.len() / 2])
+    }
+}
+
+/// Headers with a consensus serialization, hex-encoded as in the RPC of the nodes
+/// (`getblockheader <hash> false`)
+pub trait ConsensusHex: Sized {
+    fn to_consensus_hex(&self) -> String;
+
+    /// # Errors
+    /// If the string is not hex or does not hold a single header
+    fn from_consensus_hex(s: &str) -> Result<Self, DecodeHeaderError>;
+}
+
+impl ConsensusHex for Header {
+    fn to_consensus_hex(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_consensus_hex(s: &str) -> Result<Self, DecodeHeaderError> {
+        s.parse()
+    }
+}
+
+/// The merge-mined header followed by its AuxPoW, if any
+#[cfg(feature = "auxpow_header")]
+impl ConsensusHex for (Header, Option<AuxData>) {
+    fn to_consensus_hex(&self) -> String {
+        let mut bytes = self.0.to_block_header_vec();
+        if let Some(aux_data) = &self.1 {
+            bytes.extend(aux_data.to_bytes());
+        }
+        hex::encode(bytes)
+    }
+
+    fn from_consensus_hex(s: &str) -> Result<Self, DecodeHeaderError> {
+        let bytes = hex::decode(s).map_err(|_| DecodeHeaderError::InvalidHex)?;
+        let header = bytes
+            .get(..Header::SIZE)
+            .ok_or(DecodeHeaderError::InvalidLength)
+            .and_then(Header::from_block_header_vec)?;
+        let aux_bytes = &bytes[Header::SIZE..];
+        if aux_bytes.is_empty() {
+            return Ok((header, None));
+        }
+
+        Ok((header, Some(AuxData::from_bytes(aux_bytes)?)))
+    }
+}
+
+/// Location of `prev_block_hash` in a consensus-serialized header
+const PREV_BLOCK_HASH_RANGE: std::ops::Range<usize> = 4..36;
+
+/// The header version bit signalling that the header is followed by AuxPoW
+#[cfg(feature = "auxpow_header")]
+const BLOCK_VERSION_AUXPOW: i32 = 0x100;
+
+/// Parses a consensus-serialized header at the start of `bytes` and skips the AuxPoW following
+/// it if `auxpow` is set and the header has the AuxPoW version bit, as in the merkle blocks of
+/// the merge-mined chains. Returns the header and the number of bytes read. The AuxPoW is
+/// not skipped without `auxpow_header`.
+///
+/// # Errors
+/// If `bytes` is truncated or the header or the AuxPoW cannot be decoded
+#[cfg_attr(not(feature = "auxpow_header"), allow(unused_variables))]
+pub fn deserialize_partial_header(
+    bytes: &[u8],
+    auxpow: bool,
+) -> Result<(Header, usize), DecodeHeaderError> {
+    let header_len = Header::consensus_size(bytes)?;
+    let header = Header::from_block_header_vec(
+        bytes
+            .get(..header_len)
+            .ok_or(DecodeHeaderError::InvalidLength)?,
+    )?;
+
+    #[cfg(feature = "auxpow_header")]
+    if auxpow && header.version & BLOCK_VERSION_AUXPOW != 0 {
+        let (_, aux_data_len) = AuxData::deserialize_partial(&bytes[header_len..])?;
+        return Ok((header, header_len + aux_data_len));
+    }
+
+    Ok((header, header_len))
+}
+
+/// Parses a contiguous batch of consensus-serialized headers. Every header but the first one
+/// omits `prev_block_hash`, which is implied to be the hash of the previous header in the batch.
+/// With `auxpow_header`, the headers with the AuxPoW version bit set are followed by AuxPoW.
+///
+/// # Errors
+/// If the batch is empty or truncated, or some header cannot be decoded
+pub fn block_headers_from_compact_vec(bytes: &[u8]) -> Result<Vec<BlockHeader>, DecodeHeaderError> {
+    block_headers_from_compact_vec_with_auxpow(bytes, cfg!(feature = "auxpow_header"))
+}
+
+/// Same as [`block_headers_from_compact_vec`], but the AuxPoW is parsed only if `auxpow`
+/// is set, since the chains without merge mining may set the AuxPoW version bit for other
+/// purposes. Has no effect without `auxpow_header`.
+///
+/// # Errors
+/// If the batch is empty or truncated, or some header cannot be decoded
+#[cfg_attr(not(feature = "auxpow_header"), allow(unused_variables))]
+pub fn block_headers_from_compact_vec_with_auxpow(
+    mut bytes: &[u8],
+    auxpow: bool,
+) -> Result<Vec<BlockHeader>, DecodeHeaderError> {
+    let mut headers = Vec::new();
+    let mut header_bytes = Vec::with_capacity(Header::SIZE);
+    let mut prev_block_hash: Option<H256> = None;
+
+    while !bytes.is_empty() {
+        header_bytes.clear();
+        let omitted_len = match &prev_block_hash {
+            Some(prev_block_hash) => {
+                header_bytes.extend_from_slice(
+                    bytes
+                        .get(..PREV_BLOCK_HASH_RANGE.start)
+                        .ok_or(DecodeHeaderError::InvalidLength)?,
+                );
+                header_bytes.extend_from_slice(&prev_block_hash.0);
+                PREV_BLOCK_HASH_RANGE.len()
+            }
+            None => 0,
+        };
+        // No header is larger than `Header::SIZE`, the size of a Zcash header depends on
+        // its Equihash solution
+        let rest = &bytes[header_bytes.len() - omitted_len..];
+        header_bytes.extend_from_slice(&rest[..rest.len().min(Header::SIZE - header_bytes.len())]);
+        let header_len = Header::consensus_size(&header_bytes)?;
+        if header_len > header_bytes.len() {
+            return Err(DecodeHeaderError::InvalidLength);
+        }
+        header_bytes.truncate(header_len);
+        bytes = &bytes[header_len - omitted_len..];
+
+        let header = Header::from_block_header_vec(&header_bytes)?;
+        prev_block_hash = Some(header.block_hash());
+
+        #[cfg(feature = "auxpow_header")]
+        let header = if !auxpow || header.version & BLOCK_VERSION_AUXPOW == 0 {
+            (header, None)
+        } else {
+            let (aux_data, aux_data_len) = AuxData::deserialize_partial(bytes)?;
+            bytes = &bytes[aux_data_len..];
+            (header, Some(aux_data))
+        };
+
+        headers.push(header);
+    }
+
+    if headers.is_empty() {
+        return Err(DecodeHeaderError::InvalidLength);
+    }
+
+    Ok(headers)
 }
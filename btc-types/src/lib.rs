@@ -3,7 +3,15 @@ pub mod btc_header;
 pub mod contract_args;
 pub mod hash;
 pub mod header;
+pub mod mweb;
 pub mod network;
+#[cfg(feature = "signet")]
+pub mod script;
+pub mod signet;
 pub mod u256;
 pub mod utils;
+pub mod validation;
+#[cfg(feature = "x11_hash")]
+pub mod x11;
 pub mod zcash_header;
+pub mod zcash_tx;
@@ -0,0 +1,247 @@
+use crate::hash::H256;
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::encode::Decodable;
+use bitcoin::hashes::Hash;
+use bitcoin::transaction::Version;
+use bitcoin::{Transaction, TxIn, TxOut, Witness};
+
+/// Transaction serialization flag of the segregated witness data, see BIP144
+const WITNESS_FLAG: u8 = 0x01;
+
+/// Transaction serialization flag of the MWEB data, see LIP-0003
+const MWEB_FLAG: u8 = 0x08;
+
+/// `OP_8 OP_PUSHBYTES_32`, the `HogAddr` script of the `HogEx` commits to the MWEB header hash
+/// as a witness version 8 program
+const HOG_ADDR_PREFIX: [u8; 2] = [0x58, 0x20];
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum MwebError {
+    InvalidTransaction,
+    UnsupportedMwebTransaction,
+    NotHogEx,
+    NoHogAddr,
+}
+
+impl std::fmt::Display for MwebError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MwebError::InvalidTransaction => write!(f, "Invalid Litecoin transaction"),
+            MwebError::UnsupportedMwebTransaction => {
+                write!(
+                    f,
+                    "MWEB-only transactions are not part of the canonical block"
+                )
+            }
+            MwebError::NotHogEx => write!(f, "Transaction is not a HogEx transaction"),
+            MwebError::NoHogAddr => write!(f, "HogEx transaction has no HogAddr output"),
+        }
+    }
+}
+
+/// A transaction of the canonical Litecoin block, which may use the MWEB-extended
+/// serialization.
+// https://github.com/litecoin-project/litecoin/blob/v0.21.4/src/primitives/transaction.h#L230
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug)]
+pub struct LitecoinTransaction {
+    pub tx: Transaction,
+    /// The integrating transaction of the MWEB, which is the last transaction of the block.
+    /// It is flagged with MWEB data which is empty.
+    pub is_hogex: bool,
+}
+
+impl LitecoinTransaction {
+    /// Parses a consensus-serialized Litecoin transaction.
+    ///
+    /// # Errors
+    /// If the bytes are not a valid transaction or the transaction carries MWEB data
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, MwebError> {
+        fn read<T: Decodable>(bytes: &mut &[u8]) -> Result<T, MwebError> {
+            T::consensus_decode(bytes).map_err(|_| MwebError::InvalidTransaction)
+        }
+
+        let version: Version = read(&mut bytes)?;
+
+        // A legacy transaction can not have zero inputs, so zero is the extended serialization marker
+        let flags = if bytes.first() == Some(&0) {
+            let _marker: u8 = read(&mut bytes)?;
+            let flags: u8 = read(&mut bytes)?;
+            if flags == 0 || flags & !(WITNESS_FLAG | MWEB_FLAG) != 0 {
+                return Err(MwebError::InvalidTransaction);
+            }
+            flags
+        } else {
+            0
+        };
+
+        let mut input: Vec<TxIn> = read(&mut bytes)?;
+        let output: Vec<TxOut> = read(&mut bytes)?;
+
+        if flags & WITNESS_FLAG != 0 {
+            for tx_in in &mut input {
+                tx_in.witness = read::<Witness>(&mut bytes)?;
+            }
+        }
+
+        let mut is_hogex = false;
+        if flags & MWEB_FLAG != 0 {
+            // Optional pointer to the MWEB transaction, which is only null for the HogEx
+            let has_mweb_tx: u8 = read(&mut bytes)?;
+            if has_mweb_tx != 0 {
+                return Err(MwebError::UnsupportedMwebTransaction);
+            }
+            is_hogex = true;
+        }
+
+        let lock_time: LockTime = read(&mut bytes)?;
+        if !bytes.is_empty() {
+            return Err(MwebError::InvalidTransaction);
+        }
+
+        Ok(Self {
+            tx: Transaction {
+                version,
+                lock_time,
+                input,
+                output,
+            },
+            is_hogex,
+        })
+    }
+
+    /// The transaction id, which commits neither to the witness nor to the MWEB data
+    #[must_use]
+    pub fn txid(&self) -> H256 {
+        H256::from(self.tx.compute_txid().to_raw_hash().to_byte_array())
+    }
+
+    /// Returns the MWEB header hash committed by the `HogAddr`, the first output of the `HogEx`.
+    ///
+    /// # Errors
+    /// If the transaction is not a `HogEx` or its first output is not a `HogAddr`
+    pub fn mweb_header_hash(&self) -> Result<H256, MwebError> {
+        if !self.is_hogex {
+            return Err(MwebError::NotHogEx);
+        }
+
+        let script = self
+            .tx
+            .output
+            .first()
+            .ok_or(MwebError::NoHogAddr)?
+            .script_pubkey
+            .as_bytes();
+
+        match script.strip_prefix(&HOG_ADDR_PREFIX) {
+            Some(hash) => H256::try_from(hash).map_err(|_| MwebError::NoHogAddr),
+            None => Err(MwebError::NoHogAddr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::consensus::serialize;
+    use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Txid};
+
+    const MWEB_HEADER_HASH: [u8; 32] = [0x42; 32];
+
+    fn hogex_tx() -> Transaction {
+        let mut hog_addr = HOG_ADDR_PREFIX.to_vec();
+        hog_addr.extend(MWEB_HEADER_HASH);
+
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(100_000),
+                script_pubkey: ScriptBuf::from_bytes(hog_addr),
+            }],
+        }
+    }
+
+    /// Serializes the transaction with the MWEB flag and a null MWEB transaction
+    fn serialize_hogex(tx: &Transaction) -> Vec<u8> {
+        let mut bytes = serialize(&tx.version);
+        bytes.extend([0, MWEB_FLAG]);
+        bytes.extend(serialize(&tx.input));
+        bytes.extend(serialize(&tx.output));
+        bytes.push(0);
+        bytes.extend(serialize(&tx.lock_time));
+        bytes
+    }
+
+    #[test]
+    fn test_parse_hogex() {
+        let tx = hogex_tx();
+        let hogex = LitecoinTransaction::from_bytes(&serialize_hogex(&tx)).unwrap();
+
+        assert!(hogex.is_hogex);
+        assert_eq!(
+            hogex.txid(),
+            H256::from(tx.compute_txid().to_raw_hash().to_byte_array())
+        );
+        assert_eq!(
+            hogex.mweb_header_hash().unwrap(),
+            H256::from(MWEB_HEADER_HASH)
+        );
+    }
+
+    #[test]
+    fn test_parse_canonical_transaction() {
+        let tx = hogex_tx();
+        let parsed = LitecoinTransaction::from_bytes(&serialize(&tx)).unwrap();
+
+        assert!(!parsed.is_hogex);
+        assert_eq!(
+            parsed.txid(),
+            LitecoinTransaction::from_bytes(&serialize_hogex(&tx))
+                .unwrap()
+                .txid()
+        );
+        assert_eq!(parsed.mweb_header_hash(), Err(MwebError::NotHogEx));
+
+        let mut witness_tx = tx;
+        witness_tx.input[0].witness = Witness::from_slice(&[[1u8; 72]]);
+        let parsed = LitecoinTransaction::from_bytes(&serialize(&witness_tx)).unwrap();
+        assert_eq!(parsed.tx, witness_tx);
+    }
+
+    #[test]
+    fn test_parse_invalid_transactions() {
+        let mut bytes = serialize_hogex(&hogex_tx());
+
+        // Trailing data
+        bytes.push(0);
+        assert_eq!(
+            LitecoinTransaction::from_bytes(&bytes).unwrap_err(),
+            MwebError::InvalidTransaction
+        );
+        bytes.pop();
+
+        // The MWEB transaction is set
+        let lock_time_len = 4;
+        let mweb_tx_pos = bytes.len() - lock_time_len - 1;
+        bytes[mweb_tx_pos] = 1;
+        assert_eq!(
+            LitecoinTransaction::from_bytes(&bytes).unwrap_err(),
+            MwebError::UnsupportedMwebTransaction
+        );
+
+        // Truncated
+        bytes[mweb_tx_pos] = 0;
+        assert_eq!(
+            LitecoinTransaction::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            MwebError::InvalidTransaction
+        );
+    }
+}
@@ -16,11 +16,91 @@ pub const MAX_FUTURE_BLOCK_TIME_MTP: u32 = 90 * 60;
  */
 pub const MAX_FUTURE_BLOCK_TIME_LOCAL: u32 = 2 * 60 * 60;
 
+/**
+ * Maximum amount of time that the first block of a difficulty adjustment
+ * period is allowed to be behind the previous block, see BIP94.
+ */
+pub const MAX_TIMEWARP: u32 = 600;
+
 #[near(serializers = [borsh, json])]
 #[derive(Clone, Copy, Debug)]
 pub enum Network {
     Mainnet,
     Testnet,
+    /// Bitcoin testnet4, see BIP94
+    Testnet4,
+    /// Local regression test network, e.g. `bitcoind -regtest`
+    Regtest,
+    /// Bitcoin signet, see BIP325
+    Signet,
+}
+
+/// Chain whose consensus rules the light client follows, chosen at the initialization
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chain {
+    Bitcoin,
+    Litecoin,
+    Dogecoin,
+    Namecoin,
+    BitcoinCash,
+    Dash,
+    Zcash,
+}
+
+impl Chain {
+    /// Whether the chain defines the network. The config getters panic for the networks
+    /// the chain does not define, so the contract checks this at the initialization.
+    pub fn supports(self, network: Network) -> bool {
+        match network {
+            Network::Mainnet => true,
+            Network::Testnet => self != Chain::Namecoin,
+            Network::Regtest => self != Chain::Dash,
+            Network::Testnet4 | Network::Signet => self == Chain::Bitcoin,
+        }
+    }
+}
+
+/// Config of any chain, serialized as the config itself
+#[near(serializers = [json])]
+#[derive(Clone, Copy, Debug)]
+#[serde(untagged)]
+pub enum ChainConfig {
+    Bitcoin(NetworkConfig),
+    AuxPow(AuxPowConfig),
+    BitcoinCash(BitcoinCashConfig),
+    Dash(DashConfig),
+    Zcash(ZcashConfig),
+}
+
+impl From<NetworkConfig> for ChainConfig {
+    fn from(config: NetworkConfig) -> Self {
+        Self::Bitcoin(config)
+    }
+}
+
+impl From<AuxPowConfig> for ChainConfig {
+    fn from(config: AuxPowConfig) -> Self {
+        Self::AuxPow(config)
+    }
+}
+
+impl From<BitcoinCashConfig> for ChainConfig {
+    fn from(config: BitcoinCashConfig) -> Self {
+        Self::BitcoinCash(config)
+    }
+}
+
+impl From<DashConfig> for ChainConfig {
+    fn from(config: DashConfig) -> Self {
+        Self::Dash(config)
+    }
+}
+
+impl From<ZcashConfig> for ChainConfig {
+    fn from(config: ZcashConfig) -> Self {
+        Self::Zcash(config)
+    }
 }
 
 pub fn get_bitcoin_config(network: Network) -> NetworkConfig {
@@ -35,6 +115,8 @@ pub fn get_bitcoin_config(network: Network) -> NetworkConfig {
                 0x0000_0000_ffff_ffff_ffff_ffff_ffff_ffff,
                 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
             ),
+            enforce_bip94: false,
+            pow_no_retargeting: false,
         },
         Network::Testnet => NetworkConfig {
             difficulty_adjustment_interval: 2016,
@@ -46,6 +128,50 @@ pub fn get_bitcoin_config(network: Network) -> NetworkConfig {
                 0x0000_0000_ffff_ffff_ffff_ffff_ffff_ffff,
                 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
             ),
+            enforce_bip94: false,
+            pow_no_retargeting: false,
+        },
+        // https://github.com/bitcoin/bitcoin/blob/v28.0/src/kernel/chainparams.cpp
+        Network::Testnet4 => NetworkConfig {
+            difficulty_adjustment_interval: 2016,
+            pow_target_timespan: 2016 * 600, // difficulty_adjustment_interval * target_block_time_secs,
+            proof_of_work_limit_bits: 0x1d00ffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: true,
+            pow_limit: U256::new(
+                0x0000_0000_ffff_ffff_ffff_ffff_ffff_ffff,
+                0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+            ),
+            enforce_bip94: true,
+            pow_no_retargeting: false,
+        },
+        // https://github.com/bitcoin/bitcoin/blob/v28.0/src/kernel/chainparams.cpp
+        Network::Regtest => NetworkConfig {
+            difficulty_adjustment_interval: 2016,
+            pow_target_timespan: 2016 * 600, // difficulty_adjustment_interval * target_block_time_secs,
+            proof_of_work_limit_bits: 0x207fffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: true,
+            pow_limit: U256::new(
+                0x7fff_ff00_0000_0000_0000_0000_0000_0000,
+                0x0000_0000_0000_0000_0000_0000_0000_0000,
+            ),
+            enforce_bip94: false,
+            pow_no_retargeting: true,
+        },
+        // https://github.com/bitcoin/bitcoin/blob/v28.0/src/kernel/chainparams.cpp
+        Network::Signet => NetworkConfig {
+            difficulty_adjustment_interval: 2016,
+            pow_target_timespan: 2016 * 600, // difficulty_adjustment_interval * target_block_time_secs,
+            proof_of_work_limit_bits: 0x1e0377ae,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: false,
+            pow_limit: U256::new(
+                0x0000_0377_ae00_0000_0000_0000_0000_0000,
+                0x0000_0000_0000_0000_0000_0000_0000_0000,
+            ),
+            enforce_bip94: false,
+            pow_no_retargeting: false,
         },
     }
 }
@@ -62,6 +188,8 @@ pub fn get_litecoin_config(network: Network) -> NetworkConfig {
                 0x0000_0fff_ffff_ffff_ffff_ffff_ffff_ffff,
                 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
             ),
+            enforce_bip94: false,
+            pow_no_retargeting: false,
         },
         Network::Testnet => NetworkConfig {
             difficulty_adjustment_interval: 2016,
@@ -73,13 +201,31 @@ pub fn get_litecoin_config(network: Network) -> NetworkConfig {
                 0x0000_0fff_ffff_ffff_ffff_ffff_ffff_ffff,
                 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
             ),
+            enforce_bip94: false,
+            pow_no_retargeting: false,
+        },
+        // https://github.com/litecoin-project/litecoin/blob/v0.21.4/src/chainparams.cpp
+        Network::Regtest => NetworkConfig {
+            difficulty_adjustment_interval: 2016,
+            pow_target_timespan: 2016 * 150,
+            proof_of_work_limit_bits: 0x207fffff,
+            pow_target_spacing: 150, // 2.5 minutes
+            pow_allow_min_difficulty_blocks: true,
+            pow_limit: U256::new(
+                0x7fff_ff00_0000_0000_0000_0000_0000_0000,
+                0x0000_0000_0000_0000_0000_0000_0000_0000,
+            ),
+            enforce_bip94: false,
+            pow_no_retargeting: true,
         },
+        Network::Testnet4 => panic!("Testnet4 is supported only for Bitcoin"),
+        Network::Signet => panic!("Signet is supported only for Bitcoin"),
     }
 }
 
-pub fn get_dogecoin_config(network: Network) -> DogecoinConfig {
+pub fn get_dogecoin_config(network: Network) -> AuxPowConfig {
     match network {
-        Network::Mainnet => DogecoinConfig {
+        Network::Mainnet => AuxPowConfig {
             difficulty_adjustment_interval: 1,
             pow_target_timespan: 60,
             proof_of_work_limit_bits: 0x1e0fffff,
@@ -90,8 +236,9 @@ pub fn get_dogecoin_config(network: Network) -> DogecoinConfig {
                 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
             ),
             aux_chain_id: 0x0062,
+            pow_no_retargeting: false,
         },
-        Network::Testnet => DogecoinConfig {
+        Network::Testnet => AuxPowConfig {
             difficulty_adjustment_interval: 1,
             pow_target_timespan: 60,
             proof_of_work_limit_bits: 0x1e0fffff,
@@ -102,7 +249,90 @@ pub fn get_dogecoin_config(network: Network) -> DogecoinConfig {
                 0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
             ),
             aux_chain_id: 0x0062,
+            pow_no_retargeting: false,
+        },
+        // https://github.com/dogecoin/dogecoin/blob/v1.14.9/src/chainparams.cpp
+        Network::Regtest => AuxPowConfig {
+            difficulty_adjustment_interval: 1,
+            pow_target_timespan: 1,
+            proof_of_work_limit_bits: 0x207fffff,
+            pow_target_spacing: 1, // 1 second
+            pow_allow_min_difficulty_blocks: true,
+            pow_limit: U256::new(
+                0x7fff_ff00_0000_0000_0000_0000_0000_0000,
+                0x0000_0000_0000_0000_0000_0000_0000_0000,
+            ),
+            aux_chain_id: 0x0062,
+            pow_no_retargeting: true,
+        },
+        Network::Testnet4 => panic!("Testnet4 is supported only for Bitcoin"),
+        Network::Signet => panic!("Signet is supported only for Bitcoin"),
+    }
+}
+
+pub fn get_namecoin_config(network: Network) -> AuxPowConfig {
+    match network {
+        // https://github.com/namecoin/namecoin-core/blob/nc28.0/src/kernel/chainparams.cpp
+        Network::Mainnet => AuxPowConfig {
+            difficulty_adjustment_interval: 2016,
+            pow_target_timespan: 2016 * 600, // difficulty_adjustment_interval * target_block_time_secs,
+            proof_of_work_limit_bits: 0x1d00ffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: false,
+            pow_limit: U256::new(
+                0x0000_0000_ffff_ffff_ffff_ffff_ffff_ffff,
+                0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+            ),
+            aux_chain_id: 0x0001,
+            pow_no_retargeting: false,
+        },
+        Network::Regtest => AuxPowConfig {
+            difficulty_adjustment_interval: 2016,
+            pow_target_timespan: 2016 * 600,
+            proof_of_work_limit_bits: 0x207fffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: true,
+            pow_limit: U256::new(
+                0x7fff_ff00_0000_0000_0000_0000_0000_0000,
+                0x0000_0000_0000_0000_0000_0000_0000_0000,
+            ),
+            aux_chain_id: 0x0001,
+            pow_no_retargeting: true,
+        },
+        Network::Testnet => panic!("Namecoin testnet is not supported"),
+        Network::Testnet4 => panic!("Testnet4 is supported only for Bitcoin"),
+        Network::Signet => panic!("Signet is supported only for Bitcoin"),
+    }
+}
+
+pub fn get_dash_config(network: Network) -> DashConfig {
+    match network {
+        // https://github.com/dashpay/dash/blob/v22.0.0/src/chainparams.cpp
+        Network::Mainnet => DashConfig {
+            proof_of_work_limit_bits: 0x1e0fffff,
+            pow_limit: U256::new(
+                0x0000_0fff_ffff_ffff_ffff_ffff_ffff_ffff,
+                0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+            ),
+            pow_target_spacing: 150, // 2.5 minutes
+            pow_allow_min_difficulty_blocks: false,
+            dgw_height: 34_140,
+            dgw_past_blocks: 24,
+        },
+        Network::Testnet => DashConfig {
+            proof_of_work_limit_bits: 0x1e0fffff,
+            pow_limit: U256::new(
+                0x0000_0fff_ffff_ffff_ffff_ffff_ffff_ffff,
+                0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+            ),
+            pow_target_spacing: 150, // 2.5 minutes
+            pow_allow_min_difficulty_blocks: true,
+            dgw_height: 4001,
+            dgw_past_blocks: 24,
         },
+        Network::Regtest => panic!("Dash regtest is not supported"),
+        Network::Testnet4 => panic!("Testnet4 is supported only for Bitcoin"),
+        Network::Signet => panic!("Signet is supported only for Bitcoin"),
     }
 }
 
@@ -118,6 +348,8 @@ pub fn get_zcash_config(network: Network) -> ZcashConfig {
             ),
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L104
             pow_averaging_window: 17,
+            //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.h#L243
+            pre_blossom_pow_target_spacing: 150,
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.h#L244
             post_blossom_pow_target_spacing: 75,
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L429
@@ -126,6 +358,20 @@ pub fn get_zcash_config(network: Network) -> ZcashConfig {
             pow_max_adjust_up: 16, // 16% adjustment up
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L110
             pow_allow_min_difficulty_blocks_after_height: None,
+            pow_no_retargeting: false,
+            equihash_n: 200,
+            equihash_k: 9,
+            // https://github.com/zcash/zcash/blob/v6.2.0/src/chainparams.cpp#L127
+            activation_heights: ZcashActivationHeights {
+                overwinter: Some(347_500),
+                sapling: Some(419_200),
+                blossom: Some(653_600),
+                heartwood: Some(903_000),
+                canopy: Some(1_046_400),
+                nu5: Some(1_687_104),
+                nu6: Some(2_726_400),
+                nu6_1: Some(3_146_400),
+            },
         },
         Network::Testnet => ZcashConfig {
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L629
@@ -137,6 +383,8 @@ pub fn get_zcash_config(network: Network) -> ZcashConfig {
             ),
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L427
             pow_averaging_window: 17,
+            //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.h#L243
+            pre_blossom_pow_target_spacing: 150,
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.h#L244
             post_blossom_pow_target_spacing: 75,
             //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L429
@@ -145,7 +393,115 @@ pub fn get_zcash_config(network: Network) -> ZcashConfig {
             pow_max_adjust_up: 16,
             // https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/chainparams.cpp#L433
             pow_allow_min_difficulty_blocks_after_height: Some(299187),
+            pow_no_retargeting: false,
+            equihash_n: 200,
+            equihash_k: 9,
+            // https://github.com/zcash/zcash/blob/v6.2.0/src/chainparams.cpp#L431
+            activation_heights: ZcashActivationHeights {
+                overwinter: Some(207_500),
+                sapling: Some(280_000),
+                blossom: Some(584_000),
+                heartwood: Some(903_800),
+                canopy: Some(1_028_500),
+                nu5: Some(1_842_420),
+                nu6: Some(2_976_000),
+                nu6_1: Some(3_536_500),
+            },
+        },
+        // https://github.com/zcash/zcash/blob/v6.2.0/src/chainparams.cpp
+        Network::Regtest => ZcashConfig {
+            proof_of_work_limit_bits: 0x200f0f0f,
+            pow_limit: U256::new(
+                0x0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f,
+                0x0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f_0f0f,
+            ),
+            pow_averaging_window: 17,
+            pre_blossom_pow_target_spacing: 150,
+            post_blossom_pow_target_spacing: 75,
+            pow_max_adjust_down: 0, // Turn off adjustment down
+            pow_max_adjust_up: 0,   // Turn off adjustment up
+            pow_allow_min_difficulty_blocks_after_height: Some(0),
+            pow_no_retargeting: true,
+            equihash_n: 48,
+            equihash_k: 5,
+            // The upgrades are not active by default on regtest, they are activated
+            // from the first block as with `-nuparams` in the zcashd tests
+            activation_heights: ZcashActivationHeights {
+                overwinter: Some(1),
+                sapling: Some(1),
+                blossom: Some(1),
+                heartwood: Some(1),
+                canopy: Some(1),
+                nu5: Some(1),
+                nu6: Some(1),
+                nu6_1: Some(1),
+            },
+        },
+        Network::Testnet4 => panic!("Testnet4 is supported only for Bitcoin"),
+        Network::Signet => panic!("Signet is supported only for Bitcoin"),
+    }
+}
+
+pub fn get_bitcoincash_config(network: Network) -> BitcoinCashConfig {
+    match network {
+        // https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/chainparams.cpp
+        Network::Mainnet => BitcoinCashConfig {
+            proof_of_work_limit_bits: 0x1d00ffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: false,
+            pow_no_retargeting: false,
+            pow_limit: U256::new(
+                0x0000_0000_ffff_ffff_ffff_ffff_ffff_ffff,
+                0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+            ),
+            daa_height: 504_031,
+            asert_activation_height: 661_647,
+            asert_anchor: AsertAnchor {
+                height: 661_647,
+                bits: 0x1804dafe,
+                prev_block_time: 1_605_447_844,
+            },
+            asert_half_life: 2 * 24 * 60 * 60, // two days
         },
+        Network::Testnet => BitcoinCashConfig {
+            proof_of_work_limit_bits: 0x1d00ffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: true,
+            pow_no_retargeting: false,
+            pow_limit: U256::new(
+                0x0000_0000_ffff_ffff_ffff_ffff_ffff_ffff,
+                0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff,
+            ),
+            daa_height: 1_188_697,
+            asert_activation_height: 1_421_481,
+            asert_anchor: AsertAnchor {
+                height: 1_421_481,
+                bits: 0x1d00ffff,
+                prev_block_time: 1_605_445_400,
+            },
+            asert_half_life: 60 * 60, // one hour
+        },
+        Network::Regtest => BitcoinCashConfig {
+            proof_of_work_limit_bits: 0x207fffff,
+            pow_target_spacing: 600, // 10 minutes
+            pow_allow_min_difficulty_blocks: true,
+            pow_no_retargeting: true,
+            pow_limit: U256::new(
+                0x7fff_ff00_0000_0000_0000_0000_0000_0000,
+                0x0000_0000_0000_0000_0000_0000_0000_0000,
+            ),
+            daa_height: 0,
+            asert_activation_height: 0,
+            // Unused, the difficulty is never adjusted on regtest
+            asert_anchor: AsertAnchor {
+                height: 0,
+                bits: 0x207fffff,
+                prev_block_time: 0,
+            },
+            asert_half_life: 2 * 24 * 60 * 60,
+        },
+        Network::Testnet4 => panic!("Testnet4 is supported only for Bitcoin"),
+        Network::Signet => panic!("Signet is supported only for Bitcoin"),
     }
 }
 
@@ -158,11 +514,16 @@ pub struct NetworkConfig {
     pub pow_target_spacing: u32,
     pub pow_allow_min_difficulty_blocks: bool,
     pub pow_limit: U256,
+    /// BIP94: timewarp fix and retargeting based on the first block of the period
+    pub enforce_bip94: bool,
+    /// Keep the difficulty of the previous block on retarget, used by regtest
+    pub pow_no_retargeting: bool,
 }
 
+/// Config of the merge-mined chains, which accept AuxPoW headers
 #[near(serializers = [borsh, json])]
 #[derive(Clone, Copy, Debug)]
-pub struct DogecoinConfig {
+pub struct AuxPowConfig {
     pub pow_target_timespan: i64,
     pub difficulty_adjustment_interval: u64,
     pub proof_of_work_limit_bits: u32,
@@ -171,6 +532,21 @@ pub struct DogecoinConfig {
     pub pow_limit: U256,
     // https://github.com/dogecoin/dogecoin/blob/master/src/chainparams.cpp#L276
     pub aux_chain_id: i32,
+    /// Keep the difficulty of the previous block on retarget, used by regtest
+    pub pow_no_retargeting: bool,
+}
+
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, Debug)]
+pub struct DashConfig {
+    pub proof_of_work_limit_bits: u32,
+    pub pow_limit: U256,
+    pub pow_target_spacing: i64,
+    pub pow_allow_min_difficulty_blocks: bool,
+    /// Height from which the Dark Gravity Wave v3 difficulty adjustment is used
+    pub dgw_height: u64,
+    /// Number of blocks averaged by Dark Gravity Wave
+    pub dgw_past_blocks: u64,
 }
 
 #[near(serializers = [borsh, json])]
@@ -179,30 +555,250 @@ pub struct ZcashConfig {
     pub proof_of_work_limit_bits: u32,
     pub pow_limit: U256,
     pub pow_averaging_window: i64,
+    pub pre_blossom_pow_target_spacing: i64,
     pub post_blossom_pow_target_spacing: i64,
     pub pow_max_adjust_down: i64,
     pub pow_max_adjust_up: i64,
     pub pow_allow_min_difficulty_blocks_after_height: Option<u64>,
+    /// Keep the difficulty of the previous block, used by regtest
+    pub pow_no_retargeting: bool,
+    /// Equihash parameters, (200, 9) everywhere except regtest
+    pub equihash_n: u32,
+    pub equihash_k: u32,
+    pub activation_heights: ZcashActivationHeights,
+}
+
+/// Activation heights of the Zcash network upgrades, `None` if the upgrade is not scheduled
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, Debug)]
+pub struct ZcashActivationHeights {
+    pub overwinter: Option<u64>,
+    pub sapling: Option<u64>,
+    pub blossom: Option<u64>,
+    pub heartwood: Option<u64>,
+    pub canopy: Option<u64>,
+    pub nu5: Option<u64>,
+    pub nu6: Option<u64>,
+    pub nu6_1: Option<u64>,
+}
+
+/// Zcash network upgrades, in the activation order
+// https://github.com/zcash/zcash/blob/v6.2.0/src/consensus/upgrades.cpp
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ZcashNetworkUpgrade {
+    Sprout,
+    Overwinter,
+    Sapling,
+    Blossom,
+    Heartwood,
+    Canopy,
+    Nu5,
+    Nu6,
+    Nu6_1,
+}
+
+impl ZcashNetworkUpgrade {
+    pub const ALL: [Self; 9] = [
+        Self::Sprout,
+        Self::Overwinter,
+        Self::Sapling,
+        Self::Blossom,
+        Self::Heartwood,
+        Self::Canopy,
+        Self::Nu5,
+        Self::Nu6,
+        Self::Nu6_1,
+    ];
+
+    /// Consensus branch id of the upgrade, see ZIP-200
+    pub fn consensus_branch_id(self) -> u32 {
+        match self {
+            Self::Sprout => 0,
+            Self::Overwinter => 0x5ba8_1b19,
+            Self::Sapling => 0x76b8_09bb,
+            Self::Blossom => 0x2bb4_0e60,
+            Self::Heartwood => 0xf5b9_230b,
+            Self::Canopy => 0xe9ff_75a6,
+            Self::Nu5 => 0xc2d6_d0b4,
+            Self::Nu6 => 0xc8e7_1055,
+            Self::Nu6_1 => 0x4dec_4df0,
+        }
+    }
+}
+
+/// Anchor block of the aserti3-2d difficulty adjustment algorithm
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, Debug)]
+pub struct AsertAnchor {
+    pub height: u64,
+    pub bits: u32,
+    /// Timestamp of the parent of the anchor block
+    pub prev_block_time: i64,
+}
+
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, Debug)]
+pub struct BitcoinCashConfig {
+    pub proof_of_work_limit_bits: u32,
+    pub pow_target_spacing: i64,
+    pub pow_allow_min_difficulty_blocks: bool,
+    /// Keep the difficulty of the previous block, used by regtest
+    pub pow_no_retargeting: bool,
+    pub pow_limit: U256,
+    /// Height of the last block before the cw-144 DAA activation (November 2017)
+    pub daa_height: u64,
+    /// Height of the last block before the aserti3-2d activation (Axion, November 2020)
+    pub asert_activation_height: u64,
+    pub asert_anchor: AsertAnchor,
+    pub asert_half_life: i64,
 }
 
 impl ZcashConfig {
+    /// Returns the latest network upgrade active at the given height
+    pub fn network_upgrade(&self, height: u64) -> ZcashNetworkUpgrade {
+        ZcashNetworkUpgrade::ALL
+            .into_iter()
+            .rev()
+            .find(|upgrade| self.is_activated(*upgrade, height))
+            .unwrap_or(ZcashNetworkUpgrade::Sprout)
+    }
+
+    pub fn is_activated(&self, upgrade: ZcashNetworkUpgrade, height: u64) -> bool {
+        self.activation_height(upgrade)
+            .is_some_and(|activation_height| activation_height <= height)
+    }
+
+    pub fn is_activation_height(&self, upgrade: ZcashNetworkUpgrade, height: u64) -> bool {
+        self.activation_height(upgrade) == Some(height)
+    }
+
+    // https://github.com/zcash/zcash/blob/v6.2.0/src/consensus/params.h#L39
+    pub fn activation_height(&self, upgrade: ZcashNetworkUpgrade) -> Option<u64> {
+        let heights = &self.activation_heights;
+        match upgrade {
+            ZcashNetworkUpgrade::Sprout => Some(0),
+            ZcashNetworkUpgrade::Overwinter => heights.overwinter,
+            ZcashNetworkUpgrade::Sapling => heights.sapling,
+            ZcashNetworkUpgrade::Blossom => heights.blossom,
+            ZcashNetworkUpgrade::Heartwood => heights.heartwood,
+            ZcashNetworkUpgrade::Canopy => heights.canopy,
+            ZcashNetworkUpgrade::Nu5 => heights.nu5,
+            ZcashNetworkUpgrade::Nu6 => heights.nu6,
+            ZcashNetworkUpgrade::Nu6_1 => heights.nu6_1,
+        }
+    }
+
     //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.cpp#L397
-    pub fn pow_target_spacing(&self) -> i64 {
-        self.post_blossom_pow_target_spacing
+    pub fn pow_target_spacing(&self, height: u64) -> i64 {
+        if self.is_activated(ZcashNetworkUpgrade::Blossom, height) {
+            self.post_blossom_pow_target_spacing
+        } else {
+            self.pre_blossom_pow_target_spacing
+        }
     }
 
     //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.cpp#L406
-    pub fn averaging_window_timespan(&self) -> i64 {
-        self.pow_averaging_window * self.pow_target_spacing()
+    pub fn averaging_window_timespan(&self, height: u64) -> i64 {
+        self.pow_averaging_window * self.pow_target_spacing(height)
     }
 
     //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.cpp#L410
-    pub fn min_actual_timespan(&self) -> i64 {
-        (self.averaging_window_timespan() * (100 - self.pow_max_adjust_up)) / 100
+    pub fn min_actual_timespan(&self, height: u64) -> i64 {
+        (self.averaging_window_timespan(height) * (100 - self.pow_max_adjust_up)) / 100
     }
 
     //https://github.com/zcash/zcash/blob/2352fbc1ed650ac4369006bea11f7f20ee046b84/src/consensus/params.cpp#L414
-    pub fn max_actual_timespan(&self) -> i64 {
-        (self.averaging_window_timespan() * (100 + self.pow_max_adjust_down)) / 100
+    pub fn max_actual_timespan(&self, height: u64) -> i64 {
+        (self.averaging_window_timespan(height) * (100 + self.pow_max_adjust_down)) / 100
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_config(chain: Chain, network: Network) -> ChainConfig {
+        match chain {
+            Chain::Bitcoin => get_bitcoin_config(network).into(),
+            Chain::Litecoin => get_litecoin_config(network).into(),
+            Chain::Dogecoin => get_dogecoin_config(network).into(),
+            Chain::Namecoin => get_namecoin_config(network).into(),
+            Chain::BitcoinCash => get_bitcoincash_config(network).into(),
+            Chain::Dash => get_dash_config(network).into(),
+            Chain::Zcash => get_zcash_config(network).into(),
+        }
+    }
+
+    #[test]
+    fn test_chain_supports_network() {
+        let chains = [
+            Chain::Bitcoin,
+            Chain::Litecoin,
+            Chain::Dogecoin,
+            Chain::Namecoin,
+            Chain::BitcoinCash,
+            Chain::Dash,
+            Chain::Zcash,
+        ];
+        let networks = [
+            Network::Mainnet,
+            Network::Testnet,
+            Network::Testnet4,
+            Network::Regtest,
+            Network::Signet,
+        ];
+        for chain in chains {
+            for network in networks {
+                let config = std::panic::catch_unwind(|| get_config(chain, network));
+                assert_eq!(
+                    config.is_ok(),
+                    chain.supports(network),
+                    "{chain:?} {network:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_regtest_pow_limit() {
+        // The regtest limit is the target of the min-difficulty bits on every chain
+        let pow_limit = crate::utils::target_from_bits(0x207f_ffff);
+        assert_eq!(get_bitcoin_config(Network::Regtest).pow_limit, pow_limit);
+        assert_eq!(get_litecoin_config(Network::Regtest).pow_limit, pow_limit);
+        assert_eq!(get_dogecoin_config(Network::Regtest).pow_limit, pow_limit);
+        assert_eq!(get_namecoin_config(Network::Regtest).pow_limit, pow_limit);
+        assert_eq!(
+            get_bitcoincash_config(Network::Regtest).pow_limit,
+            pow_limit
+        );
+    }
+
+    #[test]
+    fn test_network_upgrades() {
+        // Mainnet Blossom activation height
+        const BLOSSOM_HEIGHT: u64 = 653_600;
+        const PRE_BLOSSOM_HEIGHT: u64 = BLOSSOM_HEIGHT - 1;
+
+        let config = get_zcash_config(Network::Mainnet);
+
+        assert_eq!(config.network_upgrade(0), ZcashNetworkUpgrade::Sprout);
+        assert_eq!(
+            config.network_upgrade(PRE_BLOSSOM_HEIGHT),
+            ZcashNetworkUpgrade::Sapling
+        );
+        assert_eq!(
+            config.network_upgrade(BLOSSOM_HEIGHT),
+            ZcashNetworkUpgrade::Blossom
+        );
+        assert_eq!(
+            config.network_upgrade(1_687_104).consensus_branch_id(),
+            0xc2d6_d0b4
+        );
+        assert!(config.is_activation_height(ZcashNetworkUpgrade::Heartwood, 903_000));
+        assert!(!config.is_activation_height(ZcashNetworkUpgrade::Heartwood, 903_001));
+
+        assert_eq!(config.pow_target_spacing(PRE_BLOSSOM_HEIGHT), 150);
+        assert_eq!(config.pow_target_spacing(BLOSSOM_HEIGHT), 75);
     }
 }
@@ -0,0 +1,1362 @@
+use bitcoin::consensus::{serialize, Encodable};
+use bitcoin::hashes::{hash160, ripemd160, sha1, sha256, sha256d, Hash};
+use bitcoin::opcodes::all::{
+    OP_2DROP, OP_CHECKMULTISIG, OP_CHECKMULTISIGVERIFY, OP_CHECKSIG, OP_CHECKSIGVERIFY,
+    OP_CODESEPARATOR, OP_DEPTH, OP_DROP, OP_DUP, OP_ELSE, OP_ENDIF, OP_EQUAL, OP_EQUALVERIFY,
+    OP_HASH160, OP_HASH256, OP_IF, OP_NIP, OP_NOP, OP_NOTIF, OP_OVER, OP_PUSHNUM_1, OP_PUSHNUM_16,
+    OP_PUSHNUM_NEG1, OP_RETURN, OP_RIPEMD160, OP_SHA1, OP_SHA256, OP_SIZE, OP_SWAP, OP_VERIFY,
+};
+use bitcoin::opcodes::Opcode;
+use bitcoin::script::{Builder, Instruction};
+use bitcoin::secp256k1::{ecdsa, Message, PublicKey, Secp256k1, VerifyOnly};
+use bitcoin::sighash::SighashCache;
+use bitcoin::{Amount, Script, Transaction, Witness, WitnessVersion};
+
+const MAX_SCRIPT_SIZE: usize = 10_000;
+const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+const MAX_OPS_PER_SCRIPT: usize = 201;
+const MAX_PUBKEYS_PER_MULTISIG: i64 = 20;
+const MAX_STACK_SIZE: usize = 1000;
+
+const WITNESS_V0_KEYHASH_SIZE: usize = 20;
+const WITNESS_V0_SCRIPTHASH_SIZE: usize = 32;
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScriptError {
+    EvalFalse,
+    OpReturn,
+    ScriptSize,
+    PushSize,
+    OpCount,
+    StackSize,
+    SigCount,
+    PubkeyCount,
+    InvalidNumber,
+    Verify,
+    EqualVerify,
+    CheckSigVerify,
+    CheckMultiSigVerify,
+    BadOpcode,
+    DisabledOpcode,
+    UnsupportedOpcode(Opcode),
+    InvalidStackOperation,
+    UnbalancedConditional,
+    SigDer,
+    SigNullDummy,
+    SigPushOnly,
+    CleanStack,
+    WitnessProgramWrongLength,
+    WitnessProgramWitnessEmpty,
+    WitnessProgramMismatch,
+    WitnessMalleated,
+    WitnessMalleatedP2sh,
+    WitnessUnexpected,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::EvalFalse => {
+                write!(f, "Script evaluated without error but finished with a false/empty top stack element")
+            }
+            ScriptError::OpReturn => write!(f, "OP_RETURN was encountered"),
+            ScriptError::ScriptSize => write!(f, "Script is too big"),
+            ScriptError::PushSize => write!(f, "Push value size limit exceeded"),
+            ScriptError::OpCount => write!(f, "Operation limit exceeded"),
+            ScriptError::StackSize => write!(f, "Stack size limit exceeded"),
+            ScriptError::SigCount => {
+                write!(f, "Signature count negative or greater than pubkey count")
+            }
+            ScriptError::PubkeyCount => write!(f, "Pubkey count negative or limit exceeded"),
+            ScriptError::InvalidNumber => write!(f, "Script number overflow"),
+            ScriptError::Verify => write!(f, "Script failed an OP_VERIFY operation"),
+            ScriptError::EqualVerify => write!(f, "Script failed an OP_EQUALVERIFY operation"),
+            ScriptError::CheckSigVerify => {
+                write!(f, "Script failed an OP_CHECKSIGVERIFY operation")
+            }
+            ScriptError::CheckMultiSigVerify => {
+                write!(f, "Script failed an OP_CHECKMULTISIGVERIFY operation")
+            }
+            ScriptError::BadOpcode => write!(f, "Opcode missing or not understood"),
+            ScriptError::DisabledOpcode => write!(f, "Attempted to use a disabled opcode"),
+            ScriptError::UnsupportedOpcode(opcode) => {
+                write!(f, "Opcode {opcode} is not supported by the light client")
+            }
+            ScriptError::InvalidStackOperation => {
+                write!(f, "Operation not valid with the current stack size")
+            }
+            ScriptError::UnbalancedConditional => write!(f, "Invalid OP_IF construction"),
+            ScriptError::SigDer => write!(f, "Non-canonical DER signature"),
+            ScriptError::SigNullDummy => write!(f, "Dummy CHECKMULTISIG argument must be zero"),
+            ScriptError::SigPushOnly => write!(f, "Only push operators allowed in signatures"),
+            ScriptError::CleanStack => write!(f, "Stack size must be exactly one after execution"),
+            ScriptError::WitnessProgramWrongLength => {
+                write!(f, "Witness program has incorrect length")
+            }
+            ScriptError::WitnessProgramWitnessEmpty => {
+                write!(f, "Witness program was passed an empty witness")
+            }
+            ScriptError::WitnessProgramMismatch => write!(f, "Witness program hash mismatch"),
+            ScriptError::WitnessMalleated => write!(f, "Witness requires empty scriptSig"),
+            ScriptError::WitnessMalleatedP2sh => {
+                write!(f, "Witness requires only-redeemscript scriptSig")
+            }
+            ScriptError::WitnessUnexpected => write!(f, "Witness provided for non-witness script"),
+        }
+    }
+}
+
+/// Verifies that the input of the transaction satisfies the script it spends with the flags
+/// of the signet block solutions: `P2SH`, `DERSIG`, `NULLDUMMY` and `WITNESS`.
+///
+/// The interpreter covers the legacy and witness v0 scripts built from the push, flow control,
+/// stack, equality, hash and signature opcodes, which includes the standard templates.
+/// The remaining opcodes fail the verification when executed. Future witness versions are
+/// not verified, as in Bitcoin Core without the `TAPROOT` flag.
+///
+/// # Errors
+/// If the input does not satisfy the script
+// source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L1975
+pub fn verify_script(
+    script_sig: &Script,
+    script_pubkey: &Script,
+    witness: &Witness,
+    tx: &Transaction,
+    input_index: usize,
+    amount: Amount,
+) -> Result<(), ScriptError> {
+    let interpreter = Interpreter {
+        tx,
+        input_index,
+        amount,
+        secp: Secp256k1::verification_only(),
+    };
+
+    let mut stack = Vec::new();
+    interpreter.eval_script(script_sig, &mut stack, SigVersion::Base)?;
+    let stack_copy = stack.clone();
+    interpreter.eval_script(script_pubkey, &mut stack, SigVersion::Base)?;
+    if !stack.last().is_some_and(|top| cast_to_bool(top)) {
+        return Err(ScriptError::EvalFalse);
+    }
+
+    let mut had_witness = false;
+    if let Some(version) = script_pubkey.witness_version() {
+        had_witness = true;
+        if !script_sig.is_empty() {
+            return Err(ScriptError::WitnessMalleated);
+        }
+        interpreter.verify_witness_program(witness, version, &script_pubkey.as_bytes()[2..])?;
+    }
+
+    if script_pubkey.is_p2sh() {
+        if !script_sig.is_push_only() {
+            return Err(ScriptError::SigPushOnly);
+        }
+        stack = stack_copy;
+        let redeem_script = stack.pop().ok_or(ScriptError::InvalidStackOperation)?;
+        let redeem_script = Script::from_bytes(&redeem_script);
+
+        interpreter.eval_script(redeem_script, &mut stack, SigVersion::Base)?;
+        if !stack.last().is_some_and(|top| cast_to_bool(top)) {
+            return Err(ScriptError::EvalFalse);
+        }
+
+        if let Some(version) = redeem_script.witness_version() {
+            had_witness = true;
+            if script_sig.as_bytes() != push_script(redeem_script.as_bytes()) {
+                return Err(ScriptError::WitnessMalleatedP2sh);
+            }
+            interpreter.verify_witness_program(witness, version, &redeem_script.as_bytes()[2..])?;
+        }
+    }
+
+    if !had_witness && !witness.is_empty() {
+        return Err(ScriptError::WitnessUnexpected);
+    }
+
+    Ok(())
+}
+
+/// Checks that the interpreter implements every opcode of the script, so that a challenge
+/// set at the initialization is not rejected only when its branch is executed.
+/// The scripts committed by a P2SH or a witness program are revealed by the solutions
+/// and could not be checked in advance.
+///
+/// # Errors
+/// If the script could not be parsed or has an opcode the interpreter does not implement
+pub fn check_supported_opcodes(script: &Script) -> Result<(), ScriptError> {
+    for instruction in script.instructions() {
+        if let Instruction::Op(opcode) = instruction.map_err(|_| ScriptError::BadOpcode)? {
+            if !is_supported(opcode) {
+                return Err(ScriptError::UnsupportedOpcode(opcode));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SigVersion {
+    Base,
+    WitnessV0,
+}
+
+struct Interpreter<'a> {
+    tx: &'a Transaction,
+    input_index: usize,
+    amount: Amount,
+    secp: Secp256k1<VerifyOnly>,
+}
+
+impl Interpreter<'_> {
+    // source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L1896
+    fn verify_witness_program(
+        &self,
+        witness: &Witness,
+        version: WitnessVersion,
+        program: &[u8],
+    ) -> Result<(), ScriptError> {
+        if version != WitnessVersion::V0 {
+            return Ok(());
+        }
+
+        let mut stack: Vec<Vec<u8>> = witness.iter().map(<[u8]>::to_vec).collect();
+        let witness_script = match program.len() {
+            WITNESS_V0_SCRIPTHASH_SIZE => {
+                let witness_script = stack.pop().ok_or(ScriptError::WitnessProgramWitnessEmpty)?;
+                if sha256::Hash::hash(&witness_script).as_byte_array() != program {
+                    return Err(ScriptError::WitnessProgramMismatch);
+                }
+                witness_script
+            }
+            WITNESS_V0_KEYHASH_SIZE => {
+                if stack.len() != 2 {
+                    return Err(ScriptError::WitnessProgramMismatch);
+                }
+                Builder::new()
+                    .push_opcode(OP_DUP)
+                    .push_opcode(OP_HASH160)
+                    .push_slice(<&[u8; WITNESS_V0_KEYHASH_SIZE]>::try_from(program).unwrap())
+                    .push_opcode(OP_EQUALVERIFY)
+                    .push_opcode(OP_CHECKSIG)
+                    .into_bytes()
+            }
+            _ => return Err(ScriptError::WitnessProgramWrongLength),
+        };
+
+        if stack
+            .iter()
+            .any(|item| item.len() > MAX_SCRIPT_ELEMENT_SIZE)
+        {
+            return Err(ScriptError::PushSize);
+        }
+        self.eval_script(
+            Script::from_bytes(&witness_script),
+            &mut stack,
+            SigVersion::WitnessV0,
+        )?;
+
+        // The witness scripts implicitly require the clean stack
+        if stack.len() != 1 {
+            return Err(ScriptError::CleanStack);
+        }
+        if !cast_to_bool(&stack[0]) {
+            return Err(ScriptError::EvalFalse);
+        }
+        Ok(())
+    }
+
+    // source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L406
+    #[allow(clippy::too_many_lines)]
+    fn eval_script(
+        &self,
+        script: &Script,
+        stack: &mut Vec<Vec<u8>>,
+        sig_version: SigVersion,
+    ) -> Result<(), ScriptError> {
+        if script.len() > MAX_SCRIPT_SIZE {
+            return Err(ScriptError::ScriptSize);
+        }
+
+        let mut exec_stack: Vec<bool> = Vec::new();
+        let mut op_count = 0;
+        let mut code_start = 0;
+
+        for instruction in script.instruction_indices() {
+            let (index, instruction) = instruction.map_err(|_| ScriptError::BadOpcode)?;
+            let executing = exec_stack.iter().all(|&exec| exec);
+
+            let opcode = match instruction {
+                Instruction::PushBytes(push_bytes) => {
+                    if push_bytes.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                        return Err(ScriptError::PushSize);
+                    }
+                    if executing {
+                        stack.push(push_bytes.as_bytes().to_vec());
+                    }
+                    check_stack_size(stack)?;
+                    continue;
+                }
+                Instruction::Op(opcode) => opcode,
+            };
+
+            if opcode.to_u8() > OP_PUSHNUM_16.to_u8() {
+                op_count += 1;
+                if op_count > MAX_OPS_PER_SCRIPT {
+                    return Err(ScriptError::OpCount);
+                }
+            }
+            if is_disabled(opcode) {
+                return Err(ScriptError::DisabledOpcode);
+            }
+
+            match opcode {
+                OP_IF | OP_NOTIF => {
+                    let mut value = false;
+                    if executing {
+                        let condition = stack.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                        value = cast_to_bool(&condition) == (opcode == OP_IF);
+                    }
+                    exec_stack.push(value);
+                }
+                OP_ELSE => {
+                    let last = exec_stack
+                        .last_mut()
+                        .ok_or(ScriptError::UnbalancedConditional)?;
+                    *last = !*last;
+                }
+                OP_ENDIF => {
+                    exec_stack.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                }
+                // OP_VERIF and OP_VERNOTIF fail even in the unexecuted branches
+                _ if matches!(opcode.to_u8(), 0x65 | 0x66) => return Err(ScriptError::BadOpcode),
+                _ if !executing => {}
+                OP_PUSHNUM_NEG1 => stack.push(vec![0x81]),
+                _ if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&opcode.to_u8()) => {
+                    stack.push(vec![opcode.to_u8() - OP_PUSHNUM_1.to_u8() + 1]);
+                }
+                // OP_NOP, OP_NOP1 and OP_NOP4 to OP_NOP10. OP_CHECKLOCKTIMEVERIFY and
+                // OP_CHECKSEQUENCEVERIFY are not enabled by the flags and act as OP_NOP too.
+                _ if opcode == OP_NOP || (0xb0..=0xb9).contains(&opcode.to_u8()) => {}
+                OP_VERIFY => {
+                    if !cast_to_bool(&pop(stack)?) {
+                        return Err(ScriptError::Verify);
+                    }
+                }
+                OP_RETURN => return Err(ScriptError::OpReturn),
+                OP_DUP => stack.push(top(stack, 1)?.to_vec()),
+                OP_DROP => {
+                    pop(stack)?;
+                }
+                OP_2DROP => {
+                    pop(stack)?;
+                    pop(stack)?;
+                }
+                OP_NIP => {
+                    let item = pop(stack)?;
+                    pop(stack)?;
+                    stack.push(item);
+                }
+                OP_OVER => stack.push(top(stack, 2)?.to_vec()),
+                OP_SWAP => {
+                    top(stack, 2)?;
+                    let len = stack.len();
+                    stack.swap(len - 1, len - 2);
+                }
+                OP_DEPTH => stack.push(encode_number(i64::try_from(stack.len()).unwrap())),
+                OP_SIZE => {
+                    let size = i64::try_from(top(stack, 1)?.len()).unwrap();
+                    stack.push(encode_number(size));
+                }
+                OP_EQUAL | OP_EQUALVERIFY => {
+                    let equal = pop(stack)? == pop(stack)?;
+                    if opcode == OP_EQUALVERIFY {
+                        if !equal {
+                            return Err(ScriptError::EqualVerify);
+                        }
+                    } else {
+                        stack.push(encode_bool(equal));
+                    }
+                }
+                OP_RIPEMD160 | OP_SHA1 | OP_SHA256 | OP_HASH160 | OP_HASH256 => {
+                    let item = pop(stack)?;
+                    stack.push(match opcode {
+                        OP_RIPEMD160 => ripemd160::Hash::hash(&item).to_byte_array().to_vec(),
+                        OP_SHA1 => sha1::Hash::hash(&item).to_byte_array().to_vec(),
+                        OP_SHA256 => sha256::Hash::hash(&item).to_byte_array().to_vec(),
+                        OP_HASH160 => hash160::Hash::hash(&item).to_byte_array().to_vec(),
+                        _ => sha256d::Hash::hash(&item).to_byte_array().to_vec(),
+                    });
+                }
+                OP_CODESEPARATOR => code_start = index + 1,
+                OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                    let pubkey = pop(stack)?;
+                    let signature = pop(stack)?;
+                    let mut script_code = script.as_bytes()[code_start..].to_vec();
+                    if sig_version == SigVersion::Base {
+                        script_code = find_and_delete(&script_code, &signature);
+                    }
+
+                    let success =
+                        self.check_signature(&signature, &pubkey, &script_code, sig_version)?;
+                    if opcode == OP_CHECKSIGVERIFY {
+                        if !success {
+                            return Err(ScriptError::CheckSigVerify);
+                        }
+                    } else {
+                        stack.push(encode_bool(success));
+                    }
+                }
+                OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                    let success = self.check_multisig(
+                        stack,
+                        &script.as_bytes()[code_start..],
+                        sig_version,
+                        &mut op_count,
+                    )?;
+                    if opcode == OP_CHECKMULTISIGVERIFY {
+                        if !success {
+                            return Err(ScriptError::CheckMultiSigVerify);
+                        }
+                    } else {
+                        stack.push(encode_bool(success));
+                    }
+                }
+                // OP_RESERVED, OP_VER, OP_RESERVED1, OP_RESERVED2 and the undefined opcodes
+                _ if matches!(opcode.to_u8(), 0x50 | 0x62 | 0x89 | 0x8a)
+                    || opcode.to_u8() > 0xb9 =>
+                {
+                    return Err(ScriptError::BadOpcode)
+                }
+                _ => return Err(ScriptError::UnsupportedOpcode(opcode)),
+            }
+
+            check_stack_size(stack)?;
+        }
+
+        if exec_stack.is_empty() {
+            Ok(())
+        } else {
+            Err(ScriptError::UnbalancedConditional)
+        }
+    }
+
+    /// Pops the arguments of `OP_CHECKMULTISIG` and returns whether the signatures match
+    /// the public keys in order
+    // source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L1100
+    fn check_multisig(
+        &self,
+        stack: &mut Vec<Vec<u8>>,
+        script_code: &[u8],
+        sig_version: SigVersion,
+        op_count: &mut usize,
+    ) -> Result<bool, ScriptError> {
+        let keys_count = decode_number(top(stack, 1)?)?;
+        if !(0..=MAX_PUBKEYS_PER_MULTISIG).contains(&keys_count) {
+            return Err(ScriptError::PubkeyCount);
+        }
+        let keys_count = usize::try_from(keys_count).unwrap();
+        *op_count += keys_count;
+        if *op_count > MAX_OPS_PER_SCRIPT {
+            return Err(ScriptError::OpCount);
+        }
+
+        let sigs_count = decode_number(top(stack, keys_count + 2)?)?;
+        if !(0..=i64::try_from(keys_count).unwrap()).contains(&sigs_count) {
+            return Err(ScriptError::SigCount);
+        }
+        let sigs_count = usize::try_from(sigs_count).unwrap();
+        // The dummy element consumed by the off-by-one bug
+        top(stack, keys_count + sigs_count + 3)?;
+
+        let len = stack.len();
+        let pubkeys: Vec<Vec<u8>> = stack.drain(len - keys_count - 1..len - 1).rev().collect();
+        stack.pop();
+        let len = stack.len();
+        let signatures: Vec<Vec<u8>> = stack.drain(len - sigs_count - 1..len - 1).rev().collect();
+        stack.pop();
+
+        let mut script_code = script_code.to_vec();
+        if sig_version == SigVersion::Base {
+            for signature in &signatures {
+                script_code = find_and_delete(&script_code, signature);
+            }
+        }
+
+        let mut signatures = signatures.iter().peekable();
+        let mut pubkeys = pubkeys.iter();
+        let mut success = true;
+        while let Some(signature) = signatures.peek() {
+            let Some(pubkey) = pubkeys.next() else {
+                success = false;
+                break;
+            };
+            if self.check_signature(signature, pubkey, &script_code, sig_version)? {
+                signatures.next();
+            }
+            if signatures.len() > pubkeys.len() {
+                success = false;
+                break;
+            }
+        }
+
+        let dummy = pop(stack)?;
+        if !dummy.is_empty() {
+            return Err(ScriptError::SigNullDummy);
+        }
+        Ok(success)
+    }
+
+    /// Checks an ECDSA signature with the sighash type appended
+    ///
+    /// # Errors
+    /// If the signature is not strictly DER encoded
+    fn check_signature(
+        &self,
+        signature: &[u8],
+        pubkey: &[u8],
+        script_code: &[u8],
+        sig_version: SigVersion,
+    ) -> Result<bool, ScriptError> {
+        let Some((&hash_type, signature)) = signature.split_last() else {
+            return Ok(false);
+        };
+        if !is_valid_signature_encoding(signature) {
+            return Err(ScriptError::SigDer);
+        }
+
+        let Ok(pubkey) = PublicKey::from_slice(pubkey) else {
+            return Ok(false);
+        };
+        let Ok(mut signature) = ecdsa::Signature::from_der_lax(signature) else {
+            return Ok(false);
+        };
+        signature.normalize_s();
+
+        let sighash = match sig_version {
+            // The legacy signature hash skips the OP_CODESEPARATORs of the script code
+            SigVersion::Base => SighashCache::new(self.tx)
+                .legacy_signature_hash(
+                    self.input_index,
+                    Script::from_bytes(&filter_instructions(script_code, |instruction| {
+                        instruction != [OP_CODESEPARATOR.to_u8()]
+                    })),
+                    hash_type.into(),
+                )
+                .map(|sighash| sighash.to_byte_array())
+                .ok(),
+            SigVersion::WitnessV0 => self.segwit_v0_signature_hash(script_code, hash_type.into()),
+        };
+        let Some(sighash) = sighash else {
+            return Ok(false);
+        };
+
+        Ok(self
+            .secp
+            .verify_ecdsa(&Message::from_digest(sighash), &signature, &pubkey)
+            .is_ok())
+    }
+
+    /// The BIP143 signature hash committing to the sighash type of the signature as is.
+    /// The sighash types outside of the standard ones are valid in the consensus and sign
+    /// the whole transaction like `SIGHASH_ALL`, so they are not mapped to a standard type.
+    // source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L1568
+    fn segwit_v0_signature_hash(&self, script_code: &[u8], hash_type: u32) -> Option<[u8; 32]> {
+        const SIGHASH_NONE: u32 = 0x02;
+        const SIGHASH_SINGLE: u32 = 0x03;
+        const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+        let input = self.tx.input.get(self.input_index)?;
+        let anyone_can_pay = hash_type & SIGHASH_ANYONECANPAY != 0;
+        let base_type = hash_type & 0x1f;
+
+        let mut hash_prevouts = [0; 32];
+        let mut hash_sequence = [0; 32];
+        let mut hash_outputs = [0; 32];
+        if !anyone_can_pay {
+            hash_prevouts =
+                hash_serialized(self.tx.input.iter().map(|input| &input.previous_output));
+        }
+        if !anyone_can_pay && base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+            hash_sequence = hash_serialized(self.tx.input.iter().map(|input| &input.sequence));
+        }
+        if base_type != SIGHASH_SINGLE && base_type != SIGHASH_NONE {
+            hash_outputs = hash_serialized(&self.tx.output);
+        } else if base_type == SIGHASH_SINGLE {
+            if let Some(output) = self.tx.output.get(self.input_index) {
+                hash_outputs = hash_serialized([output]);
+            }
+        }
+
+        let mut preimage = serialize(&self.tx.version);
+        preimage.extend(hash_prevouts);
+        preimage.extend(hash_sequence);
+        preimage.extend(serialize(&input.previous_output));
+        preimage.extend(serialize(&script_code.to_vec()));
+        preimage.extend(serialize(&self.amount));
+        preimage.extend(serialize(&input.sequence));
+        preimage.extend(hash_outputs);
+        preimage.extend(serialize(&self.tx.lock_time));
+        preimage.extend(hash_type.to_le_bytes());
+        Some(sha256d::Hash::hash(&preimage).to_byte_array())
+    }
+}
+
+/// The double SHA-256 of the concatenated consensus serializations of the items
+fn hash_serialized<'a, T: Encodable + 'a>(items: impl IntoIterator<Item = &'a T>) -> [u8; 32] {
+    let data: Vec<u8> = items.into_iter().flat_map(serialize).collect();
+    sha256d::Hash::hash(&data).to_byte_array()
+}
+
+fn check_stack_size(stack: &[Vec<u8>]) -> Result<(), ScriptError> {
+    if stack.len() > MAX_STACK_SIZE {
+        return Err(ScriptError::StackSize);
+    }
+    Ok(())
+}
+
+fn pop(stack: &mut Vec<Vec<u8>>) -> Result<Vec<u8>, ScriptError> {
+    stack.pop().ok_or(ScriptError::InvalidStackOperation)
+}
+
+/// Returns the element at the given depth, the top element is at depth 1
+fn top(stack: &[Vec<u8>], depth: usize) -> Result<&[u8], ScriptError> {
+    stack
+        .len()
+        .checked_sub(depth)
+        .map(|index| stack[index].as_slice())
+        .ok_or(ScriptError::InvalidStackOperation)
+}
+
+/// Whether the opcode fails the script even in an unexecuted branch
+fn is_disabled(opcode: Opcode) -> bool {
+    // OP_CAT to OP_RIGHT, OP_INVERT to OP_XOR, OP_2MUL, OP_2DIV and OP_MUL to OP_RSHIFT
+    matches!(opcode.to_u8(), 0x7e..=0x81 | 0x83..=0x86 | 0x8d | 0x8e | 0x95..=0x99)
+}
+
+/// Whether the interpreter evaluates the opcode as Bitcoin Core does, the disabled and
+/// undefined opcodes included since they fail the script there too
+fn is_supported(opcode: Opcode) -> bool {
+    is_disabled(opcode)
+        || matches!(
+            opcode,
+            OP_IF
+                | OP_NOTIF
+                | OP_ELSE
+                | OP_ENDIF
+                | OP_PUSHNUM_NEG1
+                | OP_NOP
+                | OP_VERIFY
+                | OP_RETURN
+                | OP_DUP
+                | OP_DROP
+                | OP_2DROP
+                | OP_NIP
+                | OP_OVER
+                | OP_SWAP
+                | OP_DEPTH
+                | OP_SIZE
+                | OP_EQUAL
+                | OP_EQUALVERIFY
+                | OP_RIPEMD160
+                | OP_SHA1
+                | OP_SHA256
+                | OP_HASH160
+                | OP_HASH256
+                | OP_CODESEPARATOR
+                | OP_CHECKSIG
+                | OP_CHECKSIGVERIFY
+                | OP_CHECKMULTISIG
+                | OP_CHECKMULTISIGVERIFY
+        )
+        // OP_RESERVED, OP_VER, OP_VERIF, OP_VERNOTIF, OP_RESERVED1 and OP_RESERVED2
+        || matches!(opcode.to_u8(), 0x50 | 0x62 | 0x65 | 0x66 | 0x89 | 0x8a)
+        // OP_1 to OP_16, the OP_NOPs and the undefined opcodes
+        || (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&opcode.to_u8())
+        || opcode.to_u8() >= 0xb0
+}
+
+fn cast_to_bool(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .enumerate()
+        // Negative zero is false
+        .any(|(i, &byte)| byte != 0 && !(i == bytes.len() - 1 && byte == 0x80))
+}
+
+fn encode_bool(value: bool) -> Vec<u8> {
+    if value {
+        vec![1]
+    } else {
+        vec![]
+    }
+}
+
+/// Encodes a number as a little-endian sign-magnitude stack element
+fn encode_number(value: i64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut magnitude = value.unsigned_abs();
+    while magnitude > 0 {
+        bytes.push((magnitude & 0xff) as u8);
+        magnitude >>= 8;
+    }
+    if bytes.last().is_some_and(|&byte| byte & 0x80 != 0) {
+        bytes.push(if value < 0 { 0x80 } else { 0 });
+    } else if value < 0 {
+        *bytes.last_mut().unwrap() |= 0x80;
+    }
+    bytes
+}
+
+/// Decodes a number of at most 4 bytes from a stack element
+fn decode_number(bytes: &[u8]) -> Result<i64, ScriptError> {
+    let Some((&last, _)) = bytes.split_last() else {
+        return Ok(0);
+    };
+    if bytes.len() > 4 {
+        return Err(ScriptError::InvalidNumber);
+    }
+
+    let value = bytes
+        .iter()
+        .rev()
+        .fold(0, |value, &byte| (value << 8) | i64::from(byte));
+    if last & 0x80 == 0 {
+        Ok(value)
+    } else {
+        Ok(-(value & !(0x80 << (8 * (bytes.len() - 1)))))
+    }
+}
+
+/// The script pushing the data, as encoded by Bitcoin Core
+fn push_script(data: &[u8]) -> Vec<u8> {
+    let mut builder = Builder::new();
+    if let Ok(push_bytes) = <&bitcoin::script::PushBytes>::try_from(data) {
+        builder = builder.push_slice(push_bytes);
+    }
+    builder.into_bytes()
+}
+
+/// Removes the pushes of the signature from the script code of a legacy signature
+// source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L253
+fn find_and_delete(script_code: &[u8], signature: &[u8]) -> Vec<u8> {
+    let signature_push = push_script(signature);
+    if signature.is_empty() || signature_push.is_empty() {
+        return script_code.to_vec();
+    }
+    filter_instructions(script_code, |instruction| instruction != signature_push)
+}
+
+/// Keeps the instructions of the script matching the predicate, the unparsable end of
+/// the script is kept as is
+fn filter_instructions(script_code: &[u8], keep: impl Fn(&[u8]) -> bool) -> Vec<u8> {
+    let mut result = Vec::with_capacity(script_code.len());
+    let mut instructions = Script::from_bytes(script_code).instruction_indices();
+    let mut start = 0;
+    while let Some(Ok(_)) = instructions.next() {
+        let end = match instructions.clone().next() {
+            Some(Ok((index, _))) => index,
+            Some(Err(_)) | None => script_code.len(),
+        };
+        if keep(&script_code[start..end]) {
+            result.extend_from_slice(&script_code[start..end]);
+        }
+        start = end;
+    }
+    result.extend_from_slice(&script_code[start..]);
+    result
+}
+
+// source https://github.com/bitcoin/bitcoin/blob/v28.0/src/script/interpreter.cpp#L97
+/// Checks the strict DER encoding of BIP66, without the sighash type
+fn is_valid_signature_encoding(signature: &[u8]) -> bool {
+    let len = signature.len();
+    if !(8..=72).contains(&len) || signature[0] != 0x30 || usize::from(signature[1]) != len - 2 {
+        return false;
+    }
+
+    let len_r = usize::from(signature[3]);
+    if 5 + len_r >= len {
+        return false;
+    }
+    let len_s = usize::from(signature[5 + len_r]);
+    if len_r + len_s + 6 != len {
+        return false;
+    }
+
+    let is_valid_integer = |offset: usize, int_len: usize| {
+        signature[offset - 2] == 0x02
+            && int_len != 0
+            && signature[offset] & 0x80 == 0
+            && !(int_len > 1 && signature[offset] == 0 && signature[offset + 1] & 0x80 == 0)
+    };
+    is_valid_integer(4, len_r) && is_valid_integer(len_r + 6, len_s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::opcodes::all::{OP_ADD, OP_CAT, OP_PUSHNUM_2};
+    use bitcoin::script::PushBytesBuf;
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::sighash::EcdsaSighashType;
+    use bitcoin::transaction::Version;
+    use bitcoin::{OutPoint, ScriptBuf, Sequence, TxIn, TxOut, Txid};
+
+    const AMOUNT: Amount = Amount::from_sat(1_000);
+
+    fn secret_key(seed: u8) -> SecretKey {
+        SecretKey::from_slice(&[seed; 32]).unwrap()
+    }
+
+    fn pubkey(seed: u8) -> bitcoin::PublicKey {
+        bitcoin::PublicKey::new(secret_key(seed).public_key(&Secp256k1::new()))
+    }
+
+    fn spending_tx(script_sig: ScriptBuf, witness: Witness) -> Transaction {
+        Transaction {
+            version: Version(0),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), 0),
+                script_sig,
+                sequence: Sequence::ZERO,
+                witness,
+            }],
+            output: vec![TxOut {
+                value: Amount::ZERO,
+                script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+            }],
+        }
+    }
+
+    fn sign(seed: u8, tx: &Transaction, script_code: &Script, witness_v0: bool) -> Vec<u8> {
+        let mut cache = SighashCache::new(tx);
+        let sighash = if witness_v0 {
+            cache
+                .p2wsh_signature_hash(0, script_code, AMOUNT, EcdsaSighashType::All)
+                .unwrap()
+                .to_byte_array()
+        } else {
+            cache
+                .legacy_signature_hash(0, script_code, EcdsaSighashType::All.to_u32())
+                .unwrap()
+                .to_byte_array()
+        };
+        let signature =
+            Secp256k1::new().sign_ecdsa(&Message::from_digest(sighash), &secret_key(seed));
+        bitcoin::ecdsa::Signature::sighash_all(signature).to_vec()
+    }
+
+    /// Signs the witness v0 input with a sighash type signing the whole transaction like
+    /// `SIGHASH_ALL`, the preimage of the bitcoin crate ending with the sighash type
+    fn sign_witness_v0_with_hash_type(
+        seed: u8,
+        tx: &Transaction,
+        script_code: &Script,
+        hash_type: u8,
+    ) -> Vec<u8> {
+        let mut preimage = Vec::new();
+        SighashCache::new(tx)
+            .segwit_v0_encode_signing_data_to(
+                &mut preimage,
+                0,
+                script_code,
+                AMOUNT,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+        preimage.truncate(preimage.len() - 4);
+        preimage.extend(u32::from(hash_type).to_le_bytes());
+
+        let sighash = sha256d::Hash::hash(&preimage).to_byte_array();
+        let signature =
+            Secp256k1::new().sign_ecdsa(&Message::from_digest(sighash), &secret_key(seed));
+        let mut signature = signature.serialize_der().to_vec();
+        signature.push(hash_type);
+        signature
+    }
+
+    fn push(data: &[u8]) -> PushBytesBuf {
+        PushBytesBuf::try_from(data.to_vec()).unwrap()
+    }
+
+    fn verify(script_pubkey: &Script, tx: &Transaction) -> Result<(), ScriptError> {
+        let input = &tx.input[0];
+        verify_script(
+            &input.script_sig,
+            script_pubkey,
+            &input.witness,
+            tx,
+            0,
+            AMOUNT,
+        )
+    }
+
+    fn multisig_1_of_2() -> ScriptBuf {
+        Builder::new()
+            .push_opcode(OP_PUSHNUM_1)
+            .push_key(&pubkey(1))
+            .push_key(&pubkey(2))
+            .push_opcode(OP_PUSHNUM_2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    #[test]
+    fn test_bare_multisig() {
+        let challenge = multisig_1_of_2();
+        let signature = sign(
+            2,
+            &spending_tx(ScriptBuf::new(), Witness::new()),
+            &challenge,
+            false,
+        );
+
+        let script_sig = |dummy: &[u8], signature: &[u8]| {
+            Builder::new()
+                .push_slice(push(dummy))
+                .push_slice(push(signature))
+                .into_script()
+        };
+        let tx = spending_tx(script_sig(&[], &signature), Witness::new());
+        assert_eq!(verify(&challenge, &tx), Ok(()));
+
+        let tx = spending_tx(script_sig(&[0x01], &signature), Witness::new());
+        assert_eq!(verify(&challenge, &tx), Err(ScriptError::SigNullDummy));
+
+        // Signed by a key which is not in the challenge
+        let signature = sign(3, &tx, &challenge, false);
+        let tx = spending_tx(script_sig(&[], &signature), Witness::new());
+        assert_eq!(verify(&challenge, &tx), Err(ScriptError::EvalFalse));
+
+        let mut signature = signature;
+        signature[0] = 0x31;
+        let tx = spending_tx(script_sig(&[], &signature), Witness::new());
+        assert_eq!(verify(&challenge, &tx), Err(ScriptError::SigDer));
+    }
+
+    #[test]
+    fn test_p2wpkh() {
+        let script_pubkey = ScriptBuf::new_p2wpkh(&pubkey(1).wpubkey_hash().unwrap());
+        let script_code = script_pubkey.p2wpkh_script_code().unwrap();
+        let signature = sign(
+            1,
+            &spending_tx(ScriptBuf::new(), Witness::new()),
+            &script_code,
+            true,
+        );
+
+        let tx = spending_tx(
+            ScriptBuf::new(),
+            Witness::from_slice(&[signature.clone(), pubkey(1).to_bytes()]),
+        );
+        assert_eq!(verify(&script_pubkey, &tx), Ok(()));
+
+        let tx = spending_tx(
+            ScriptBuf::new(),
+            Witness::from_slice(&[signature, pubkey(2).to_bytes()]),
+        );
+        assert_eq!(verify(&script_pubkey, &tx), Err(ScriptError::EqualVerify));
+
+        let tx = spending_tx(Builder::new().push_int(1).into_script(), Witness::new());
+        assert_eq!(
+            verify(&script_pubkey, &tx),
+            Err(ScriptError::WitnessMalleated)
+        );
+    }
+
+    #[test]
+    fn test_p2sh_p2wsh() {
+        let witness_script = multisig_1_of_2();
+        let redeem_script = ScriptBuf::new_p2wsh(&witness_script.wscript_hash());
+        let script_pubkey = ScriptBuf::new_p2sh(&redeem_script.script_hash());
+        let script_sig = Builder::new()
+            .push_slice(push(redeem_script.as_bytes()))
+            .into_script();
+
+        let signature = sign(
+            1,
+            &spending_tx(script_sig.clone(), Witness::new()),
+            &witness_script,
+            true,
+        );
+        let witness = Witness::from_slice(&[vec![], signature, witness_script.to_bytes()]);
+        assert_eq!(
+            verify(&script_pubkey, &spending_tx(script_sig, witness.clone())),
+            Ok(())
+        );
+
+        // The redeem script must be the only push of the scriptSig
+        let script_sig = Builder::new()
+            .push_int(1)
+            .push_slice(push(redeem_script.as_bytes()))
+            .into_script();
+        assert_eq!(
+            verify(&script_pubkey, &spending_tx(script_sig, witness)),
+            Err(ScriptError::WitnessMalleatedP2sh)
+        );
+    }
+
+    #[test]
+    fn test_p2wsh_non_standard_hash_type() {
+        let witness_script = Builder::new()
+            .push_key(&pubkey(1))
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let script_pubkey = ScriptBuf::new_p2wsh(&witness_script.wscript_hash());
+        let unsigned_tx = spending_tx(ScriptBuf::new(), Witness::new());
+
+        // Undefined base type and an undefined bit set on SIGHASH_ALL
+        for hash_type in [0x04, 0x41] {
+            let signature =
+                sign_witness_v0_with_hash_type(1, &unsigned_tx, &witness_script, hash_type);
+            let witness = Witness::from_slice(&[signature.clone(), witness_script.to_bytes()]);
+            assert_eq!(
+                verify(&script_pubkey, &spending_tx(ScriptBuf::new(), witness)),
+                Ok(()),
+                "{hash_type:#x}"
+            );
+
+            // The signature commits to the sighash type as is
+            let mut signature = signature;
+            *signature.last_mut().unwrap() = 0x01;
+            let witness = Witness::from_slice(&[signature, witness_script.to_bytes()]);
+            assert_eq!(
+                verify(&script_pubkey, &spending_tx(ScriptBuf::new(), witness)),
+                Err(ScriptError::EvalFalse),
+                "{hash_type:#x}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_segwit_v0_signature_hash() {
+        // source https://github.com/bitcoin/bips/blob/master/bip-0143.mediawiki#p2sh-p2wsh
+        let tx: Transaction = bitcoin::consensus::deserialize(
+            &hex::decode(
+                "010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e01000000\
+                 00ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0\
+                 832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac00000000",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let witness_script = hex::decode(
+            "56210307b8ae49ac90a048e9b53357a2354b3334e9c8bee813ecb98e99a7e07e8c3ba32103b28f0c28\
+             bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b21034b8113d703413d57761b8b\
+             9781957b8c0ac1dfe69f492580ca4195f50376ba4a21033400f6afecb833092a9a21cfdf1ed1376e58\
+             c5d1f47de74683123987e967a8f42103a6d48b1131e94ba04d9737d61acdaa1322008af9602b3b1486\
+             2c07a1789aac162102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b\
+             56ae",
+        )
+        .unwrap();
+        let interpreter = Interpreter {
+            tx: &tx,
+            input_index: 0,
+            amount: Amount::from_sat(987_654_321),
+            secp: Secp256k1::verification_only(),
+        };
+
+        for (hash_type, sighash) in [
+            (
+                0x01,
+                "185c0be5263dce5b4bb50a047973c1b6272bfbd0103a89444597dc40b248ee7c",
+            ),
+            (
+                0x02,
+                "e9733bc60ea13c95c6527066bb975a2ff29a925e80aa14c213f686cbae5d2f36",
+            ),
+            (
+                0x03,
+                "1e1f1c303dc025bd664acb72e583e933fae4cff9148bf78c157d1e8f78530aea",
+            ),
+            (
+                0x81,
+                "2a67f03e63a6a422125878b40b82da593be8d4efaafe88ee528af6e5a9955c6e",
+            ),
+            (
+                0x82,
+                "781ba15f3779d5542ce8ecb5c18716733a5ee42a6f51488ec96154934e2c890a",
+            ),
+            (
+                0x83,
+                "511e8e52ed574121fc1b654970395502128263f62662e076dc6baf05c2e6a99b",
+            ),
+        ] {
+            let hash = interpreter
+                .segwit_v0_signature_hash(&witness_script, hash_type)
+                .unwrap();
+            assert_eq!(hex::encode(hash), sighash, "{hash_type:#x}");
+        }
+    }
+
+    /// Parses a script in the notation of the tests of Bitcoin Core: numbers and strings are
+    /// pushed, hex is inserted as is and the opcodes are named with or without `OP_`
+    // source https://github.com/bitcoin/bitcoin/blob/v28.0/src/core_read.cpp#L63
+    fn parse_script(source: &str) -> ScriptBuf {
+        let mut bytes = Vec::new();
+        for word in source.split_whitespace() {
+            if let Ok(number) = word.parse::<i64>() {
+                bytes.extend(Builder::new().push_int(number).into_bytes());
+            } else if let Some(hex) = word.strip_prefix("0x") {
+                bytes.extend(hex::decode(hex).unwrap());
+            } else if let Some(string) = word.strip_prefix('\'').and_then(|w| w.strip_suffix('\''))
+            {
+                bytes.extend(push_script(string.as_bytes()));
+            } else {
+                let name = match word.trim_start_matches("OP_") {
+                    "CHECKLOCKTIMEVERIFY" => "CLTV",
+                    "CHECKSEQUENCEVERIFY" => "CSV",
+                    name => name,
+                };
+                let opcode = (0..=u8::MAX)
+                    .map(Opcode::from)
+                    .find(|opcode| opcode.to_string() == format!("OP_{name}"))
+                    .unwrap_or_else(|| panic!("unknown opcode {word}"));
+                bytes.push(opcode.to_u8());
+            }
+        }
+        ScriptBuf::from_bytes(bytes)
+    }
+
+    #[test]
+    fn test_script_vectors() {
+        // Vectors in the format of the script tests of Bitcoin Core, restricted to the opcodes
+        // of the interpreter, with the expected error named as in Core
+        // source https://github.com/bitcoin/bitcoin/blob/v28.0/src/test/data/script_tests.json
+        let vectors = [
+            ("", "DEPTH 0 EQUAL", "OK"),
+            ("1 2", "2 EQUALVERIFY 1 EQUAL", "OK"),
+            ("0x01 0x0b", "11 EQUAL", "OK"),
+            ("0x02 0x417a", "'Az' EQUAL", "OK"),
+            ("0x4c 0x01 0x07", "7 EQUAL", "OK"),
+            ("0x4d 0x0100 0x08", "8 EQUAL", "OK"),
+            ("0x4e 0x01000000 0x09", "9 EQUAL", "OK"),
+            ("1 1", "VERIFY", "OK"),
+            ("1 0x05 0x01 0x00 0x00 0x00 0x00", "VERIFY", "OK"),
+            ("1 0x01 0x80", "IF 0 ENDIF", "OK"),
+            ("1 1", "IF IF 1 ELSE 0 ENDIF ENDIF", "OK"),
+            ("0 0", "IF IF 1 ELSE 0 ENDIF ELSE IF 0 ELSE 1 ENDIF ENDIF", "OK"),
+            ("1 0", "NOTIF IF 1 ELSE 0 ENDIF ENDIF", "OK"),
+            ("1", "IF 1 ELSE ELSE 0 ENDIF", "EVAL_FALSE"),
+            ("0", "IF RETURN ENDIF 1", "OK"),
+            ("0", "IF VER ELSE 1 ENDIF", "OK"),
+            ("0", "IF RESERVED ENDIF 1", "OK"),
+            ("0", "IF 0x50 ENDIF 1", "OK"),
+            ("1", "NOP1 CHECKLOCKTIMEVERIFY CHECKSEQUENCEVERIFY NOP4 NOP5 NOP6 NOP7 NOP8 NOP9 NOP10 1 EQUAL", "OK"),
+            ("0", "SIZE 0 EQUAL", "OK"),
+            ("-1", "SIZE 1 EQUAL", "OK"),
+            ("127", "SIZE 1 EQUAL", "OK"),
+            ("128", "SIZE 2 EQUAL", "OK"),
+            ("32767", "SIZE 2 EQUAL", "OK"),
+            ("32768", "SIZE 3 EQUAL", "OK"),
+            ("2147483647", "SIZE 4 EQUAL", "OK"),
+            ("2147483648", "SIZE 5 EQUAL", "OK"),
+            ("-2147483648", "SIZE 5 EQUAL", "OK"),
+            ("'abcdefghijklmnopqrstuvwxyz'", "SIZE 26 EQUAL", "OK"),
+            ("0 0 1", "DEPTH 3 EQUALVERIFY 2DROP 0 EQUAL", "OK"),
+            ("0 1", "NIP", "OK"),
+            ("1 0", "OVER DEPTH 3 EQUALVERIFY", "OK"),
+            ("0 1", "SWAP 0 EQUALVERIFY 1 EQUAL", "OK"),
+            ("''", "RIPEMD160 0x14 0x9c1185a5c5e9fc54612808977ee8f548b2258d31 EQUAL", "OK"),
+            ("'a'", "RIPEMD160 0x14 0x0bdc9d2d256b3ee9daae347be6f4dc835a467ffe EQUAL", "OK"),
+            ("''", "SHA1 0x14 0xda39a3ee5e6b4b0d3255bfef95601890afd80709 EQUAL", "OK"),
+            ("'a'", "SHA1 0x14 0x86f7e437faa5a7fce15d1ddcb9eaeaea377667b8 EQUAL", "OK"),
+            ("''", "SHA256 0x20 0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 EQUAL", "OK"),
+            ("'a'", "SHA256 0x20 0xca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb EQUAL", "OK"),
+            ("''", "DUP HASH160 SWAP SHA256 RIPEMD160 EQUAL", "OK"),
+            ("''", "DUP HASH256 SWAP SHA256 SHA256 EQUAL", "OK"),
+            ("''", "NOP HASH160 0x14 0xb472a266d0bd89c13706a4132ccfb16f7c3b9fcb EQUAL", "OK"),
+            ("'a'", "HASH160 NOP 0x14 0x994355199e516ff76c4fa4aab39337b9d84cf12b EQUAL", "OK"),
+            ("''", "HASH256 0x20 0x5df6e0e2761359d30a8275058e299fcc0381534545f55cf43e41983f5d4c9456 EQUAL", "OK"),
+            ("", "DEPTH", "EVAL_FALSE"),
+            ("1", "VERIFY", "EVAL_FALSE"),
+            ("0", "VERIFY 1", "VERIFY"),
+            ("1 2", "EQUALVERIFY 1", "EQUALVERIFY"),
+            ("1", "RETURN", "OP_RETURN"),
+            ("1", "DUP IF ENDIF", "OK"),
+            ("0", "IF", "UNBALANCED_CONDITIONAL"),
+            ("1", "ELSE 1", "UNBALANCED_CONDITIONAL"),
+            ("1", "ENDIF", "UNBALANCED_CONDITIONAL"),
+            ("1", "IF ELSE ELSE", "UNBALANCED_CONDITIONAL"),
+            ("", "IF 1 ENDIF", "UNBALANCED_CONDITIONAL"),
+            ("", "DUP 1", "INVALID_STACK_OPERATION"),
+            ("1", "NIP", "INVALID_STACK_OPERATION"),
+            ("1", "OVER", "INVALID_STACK_OPERATION"),
+            ("1", "SWAP", "INVALID_STACK_OPERATION"),
+            ("1", "2DROP 1", "INVALID_STACK_OPERATION"),
+            ("", "SIZE 1", "INVALID_STACK_OPERATION"),
+            ("1", "VER", "BAD_OPCODE"),
+            ("1", "IF RESERVED ENDIF 1", "BAD_OPCODE"),
+            ("0", "IF VERIF ELSE 1 ENDIF", "BAD_OPCODE"),
+            ("0", "IF VERNOTIF ELSE 1 ENDIF", "BAD_OPCODE"),
+            ("1", "0xba", "BAD_OPCODE"),
+            ("1", "0xff", "BAD_OPCODE"),
+            ("0x4c01", "0x01 NOP", "BAD_OPCODE"),
+            ("'a' 'b'", "CAT", "DISABLED_OPCODE"),
+            ("0", "IF CAT ELSE 1 ENDIF", "DISABLED_OPCODE"),
+            ("0", "IF 2MUL ELSE 1 ENDIF", "DISABLED_OPCODE"),
+            ("1 1 1", "CHECKMULTISIG 1", "INVALID_STACK_OPERATION"),
+            ("0 0", "0 0 CHECKMULTISIG", "OK"),
+            ("0", "0 0 CHECKMULTISIGVERIFY DEPTH 0 EQUAL", "OK"),
+            ("0 1", "0 0 CHECKMULTISIG", "SIG_NULLDUMMY"),
+            ("0 0", "21 CHECKMULTISIG 1", "PUBKEY_COUNT"),
+            ("0 0", "-1 CHECKMULTISIG 1", "PUBKEY_COUNT"),
+            ("0 1 0", "CHECKMULTISIG 1", "SIG_COUNT"),
+        ];
+
+        for (script_sig, script_pubkey, expected) in vectors {
+            let (script_sig, script_pubkey) =
+                (parse_script(script_sig), parse_script(script_pubkey));
+            let expected = match expected {
+                "OK" => Ok(()),
+                "EVAL_FALSE" => Err(ScriptError::EvalFalse),
+                "VERIFY" => Err(ScriptError::Verify),
+                "EQUALVERIFY" => Err(ScriptError::EqualVerify),
+                "OP_RETURN" => Err(ScriptError::OpReturn),
+                "UNBALANCED_CONDITIONAL" => Err(ScriptError::UnbalancedConditional),
+                "INVALID_STACK_OPERATION" => Err(ScriptError::InvalidStackOperation),
+                "BAD_OPCODE" => Err(ScriptError::BadOpcode),
+                "DISABLED_OPCODE" => Err(ScriptError::DisabledOpcode),
+                "SIG_NULLDUMMY" => Err(ScriptError::SigNullDummy),
+                "PUBKEY_COUNT" => Err(ScriptError::PubkeyCount),
+                "SIG_COUNT" => Err(ScriptError::SigCount),
+                _ => unreachable!(),
+            };
+            assert_eq!(check_supported_opcodes(&script_pubkey), Ok(()));
+            let tx = spending_tx(script_sig.clone(), Witness::new());
+            assert_eq!(
+                verify(&script_pubkey, &tx),
+                expected,
+                "{script_sig} {script_pubkey}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_opcodes() {
+        let tx = spending_tx(ScriptBuf::new(), Witness::new());
+        let script = |builder: Builder| builder.into_script();
+
+        assert_eq!(
+            verify(
+                &script(
+                    Builder::new()
+                        .push_int(0)
+                        .push_opcode(OP_IF)
+                        .push_opcode(OP_RETURN)
+                        .push_opcode(OP_ELSE)
+                        .push_int(1)
+                        .push_opcode(OP_ENDIF)
+                ),
+                &tx
+            ),
+            Ok(())
+        );
+        // Disabled opcodes fail even in an unexecuted branch
+        assert_eq!(
+            verify(
+                &script(
+                    Builder::new()
+                        .push_int(0)
+                        .push_opcode(OP_IF)
+                        .push_opcode(OP_CAT)
+                        .push_opcode(OP_ENDIF)
+                        .push_int(1)
+                ),
+                &tx
+            ),
+            Err(ScriptError::DisabledOpcode)
+        );
+        assert_eq!(
+            verify(&script(Builder::new().push_int(1).push_opcode(OP_IF)), &tx),
+            Err(ScriptError::UnbalancedConditional)
+        );
+        assert_eq!(
+            verify(
+                &script(Builder::new().push_int(1).push_int(1).push_opcode(OP_ADD)),
+                &tx
+            ),
+            Err(ScriptError::UnsupportedOpcode(OP_ADD))
+        );
+        // Unknown witness versions are not verified
+        assert_eq!(
+            verify(
+                &script(Builder::new().push_int(1).push_slice([0x11; 32])),
+                &tx
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_check_supported_opcodes() {
+        let tx = spending_tx(ScriptBuf::new(), Witness::new());
+        let interpreter = Interpreter {
+            tx: &tx,
+            input_index: 0,
+            amount: AMOUNT,
+            secp: Secp256k1::verification_only(),
+        };
+
+        // The check rejects exactly the opcodes the interpreter fails on as unsupported
+        for opcode in OP_PUSHNUM_NEG1.to_u8()..=u8::MAX {
+            let script = ScriptBuf::from_bytes(vec![opcode]);
+            let unsupported = matches!(
+                interpreter.eval_script(&script, &mut vec![], SigVersion::Base),
+                Err(ScriptError::UnsupportedOpcode(_))
+            );
+            assert_eq!(
+                check_supported_opcodes(&script).is_err(),
+                unsupported,
+                "{opcode:#x}"
+            );
+        }
+
+        let challenge = hex::decode(crate::signet::DEFAULT_SIGNET_CHALLENGE).unwrap();
+        assert_eq!(
+            check_supported_opcodes(Script::from_bytes(&challenge)),
+            Ok(())
+        );
+        // Unsupported opcodes are rejected even in an unexecuted branch
+        assert_eq!(
+            check_supported_opcodes(
+                &Builder::new()
+                    .push_int(0)
+                    .push_opcode(OP_IF)
+                    .push_opcode(OP_ADD)
+                    .push_opcode(OP_ENDIF)
+                    .push_int(1)
+                    .into_script()
+            ),
+            Err(ScriptError::UnsupportedOpcode(OP_ADD))
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        for value in [
+            0,
+            1,
+            -1,
+            127,
+            128,
+            -128,
+            255,
+            256,
+            0x7fff_ffff,
+            -0x7fff_ffff,
+        ] {
+            assert_eq!(decode_number(&encode_number(value)), Ok(value));
+        }
+        assert_eq!(encode_number(128), vec![0x80, 0x00]);
+        assert_eq!(encode_number(-1), vec![0x81]);
+        assert_eq!(decode_number(&[0; 5]), Err(ScriptError::InvalidNumber));
+        assert!(!cast_to_bool(&[0x00, 0x80]));
+        assert!(cast_to_bool(&[0x80, 0x00]));
+    }
+}
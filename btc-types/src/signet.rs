@@ -0,0 +1,380 @@
+use crate::btc_header::Header;
+use crate::hash::{double_sha256, H256};
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::encode::{deserialize, deserialize_partial};
+use bitcoin::hashes::Hash;
+use bitcoin::opcodes::all::OP_RETURN;
+use bitcoin::opcodes::OP_0;
+use bitcoin::script::{Builder, Instruction, PushBytesBuf};
+use bitcoin::transaction::Version;
+use bitcoin::{Amount, OutPoint, Script, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+
+/// Challenge of the default signet, a 1-of-2 multisig
+pub const DEFAULT_SIGNET_CHALLENGE: &str = "512103ad5e0edad18cb1f0fc0d28a3d4f1f3e445640337489abb10404f2d8e086be430210359ef5021964fe22d6f8e05b2463c9540ce96883fe3b278760f048f5189f2e6c452ae";
+
+/// The header of the signet solution pushed in the witness commitment
+const SIGNET_HEADER: [u8; 4] = [0xec, 0xc7, 0xda, 0xa2];
+
+/// The header of the witness commitment output, see BIP141
+const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+const MINIMUM_WITNESS_COMMITMENT: usize = 38;
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum SignetError {
+    InvalidCoinbaseTx,
+    InvalidCoinbaseProof,
+    NoWitnessCommitment,
+    InvalidSolution,
+    #[cfg(feature = "signet")]
+    ScriptVerification(crate::script::ScriptError),
+}
+
+impl std::fmt::Display for SignetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignetError::InvalidCoinbaseTx => write!(f, "Invalid coinbase transaction"),
+            SignetError::InvalidCoinbaseProof => {
+                write!(f, "Coinbase transaction is not included in the block")
+            }
+            SignetError::NoWitnessCommitment => write!(f, "No witness commitment in the coinbase"),
+            SignetError::InvalidSolution => write!(f, "Invalid signet solution encoding"),
+            #[cfg(feature = "signet")]
+            SignetError::ScriptVerification(err) => {
+                write!(f, "Signet solution does not satisfy the challenge: {err}")
+            }
+        }
+    }
+}
+
+/// The virtual transactions of a signet block, see BIP325: `to_spend` pays to the
+/// challenge and commits to the block, `to_sign` spends it with the signet solution.
+#[allow(clippy::module_name_repetitions)]
+pub struct SignetTxs {
+    pub to_spend: Transaction,
+    pub to_sign: Transaction,
+}
+
+impl SignetTxs {
+    /// Builds the signet transactions of a block from its header, coinbase transaction and
+    /// the merkle proof of the coinbase transaction.
+    ///
+    /// # Errors
+    /// If the coinbase transaction cannot be decoded, is not included in the block,
+    /// has no witness commitment or the signet solution is malformed
+    pub fn create(
+        header: &Header,
+        coinbase_tx: &[u8],
+        coinbase_merkle_proof: &[H256],
+        challenge: &Script,
+    ) -> Result<Self, SignetError> {
+        let mut coinbase_tx: Transaction =
+            deserialize(coinbase_tx).map_err(|_| SignetError::InvalidCoinbaseTx)?;
+        if compute_coinbase_merkle_root(&coinbase_tx, coinbase_merkle_proof) != header.merkle_root {
+            return Err(SignetError::InvalidCoinbaseProof);
+        }
+
+        let witness_commitment = coinbase_tx
+            .output
+            .iter_mut()
+            .rev()
+            .find(|output| is_witness_commitment(&output.script_pubkey))
+            .ok_or(SignetError::NoWitnessCommitment)?;
+
+        let mut script_sig = ScriptBuf::new();
+        let mut witness = Witness::new();
+        // No solution is allowed to support `OP_TRUE` as a trivial challenge
+        if let Some(solution) = fetch_and_clear_solution(&mut witness_commitment.script_pubkey) {
+            let (solution_script_sig, read_len): (ScriptBuf, usize) =
+                deserialize_partial(&solution).map_err(|_| SignetError::InvalidSolution)?;
+            let (solution_witness, witness_len): (Witness, usize) =
+                deserialize_partial(&solution[read_len..])
+                    .map_err(|_| SignetError::InvalidSolution)?;
+            if read_len + witness_len != solution.len() {
+                return Err(SignetError::InvalidSolution);
+            }
+            script_sig = solution_script_sig;
+            witness = solution_witness;
+        }
+
+        let signet_merkle_root = compute_coinbase_merkle_root(&coinbase_tx, coinbase_merkle_proof);
+        let mut block_data = Vec::with_capacity(72);
+        block_data.extend_from_slice(&header.version.to_le_bytes());
+        block_data.extend(header.prev_block_hash.0);
+        block_data.extend(signet_merkle_root.0);
+        block_data.extend_from_slice(&header.time.to_le_bytes());
+
+        let to_spend = Transaction {
+            version: Version(0),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new()
+                    .push_opcode(OP_0)
+                    .push_slice(
+                        PushBytesBuf::try_from(block_data)
+                            .map_err(|_| SignetError::InvalidSolution)?,
+                    )
+                    .into_script(),
+                sequence: Sequence::ZERO,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::ZERO,
+                script_pubkey: challenge.to_owned(),
+            }],
+        };
+
+        let to_sign = Transaction {
+            version: Version(0),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(to_spend.compute_txid(), 0),
+                script_sig,
+                sequence: Sequence::ZERO,
+                witness,
+            }],
+            output: vec![TxOut {
+                value: Amount::ZERO,
+                script_pubkey: Builder::new().push_opcode(OP_RETURN).into_script(),
+            }],
+        };
+
+        Ok(Self { to_spend, to_sign })
+    }
+
+    /// Checks that the signet solution satisfies the challenge
+    ///
+    /// # Errors
+    /// If the script verification fails
+    #[cfg(feature = "signet")]
+    pub fn verify(&self) -> Result<(), SignetError> {
+        let input = &self.to_sign.input[0];
+        let spent_output = &self.to_spend.output[0];
+        crate::script::verify_script(
+            &input.script_sig,
+            &spent_output.script_pubkey,
+            &input.witness,
+            &self.to_sign,
+            0,
+            spent_output.value,
+        )
+        .map_err(SignetError::ScriptVerification)
+    }
+}
+
+fn is_witness_commitment(script_pubkey: &Script) -> bool {
+    let script_pubkey = script_pubkey.as_bytes();
+    script_pubkey.len() >= MINIMUM_WITNESS_COMMITMENT
+        && script_pubkey.starts_with(&WITNESS_COMMITMENT_HEADER)
+}
+
+// source https://github.com/bitcoin/bitcoin/blob/v28.0/src/signet.cpp#L28
+/// Removes the signet solution from the witness commitment, leaving its header in place,
+/// and returns the solution if found.
+fn fetch_and_clear_solution(witness_commitment: &mut ScriptBuf) -> Option<Vec<u8>> {
+    let mut replacement = Builder::new();
+    let mut solution = None;
+
+    for instruction in witness_commitment.instructions() {
+        let Ok(instruction) = instruction else {
+            break;
+        };
+        match instruction {
+            Instruction::PushBytes(push_bytes) => {
+                let bytes = push_bytes.as_bytes();
+                if solution.is_none()
+                    && bytes.len() > SIGNET_HEADER.len()
+                    && bytes.starts_with(&SIGNET_HEADER)
+                {
+                    solution = Some(bytes[SIGNET_HEADER.len()..].to_vec());
+                    replacement = replacement.push_slice(SIGNET_HEADER);
+                } else {
+                    replacement = replacement.push_slice(push_bytes);
+                }
+            }
+            Instruction::Op(opcode) => replacement = replacement.push_opcode(opcode),
+        }
+    }
+
+    if solution.is_some() {
+        *witness_commitment = replacement.into_script();
+    }
+    solution
+}
+
+/// Computes the merkle root of a block from its coinbase transaction, which is always
+/// the first leaf, and the merkle proof of it
+fn compute_coinbase_merkle_root(coinbase_tx: &Transaction, merkle_proof: &[H256]) -> H256 {
+    merkle_proof.iter().fold(
+        H256::from(coinbase_tx.compute_txid().to_byte_array()),
+        |current_hash, sibling| {
+            let mut concat = Vec::with_capacity(64);
+            concat.extend(current_hash.0);
+            concat.extend(sibling.0);
+            double_sha256(&concat)
+        },
+    )
+}
+
+#[cfg(test)]
+#[cfg(feature = "signet")]
+mod tests {
+    use super::*;
+    use bitcoin::consensus::encode::serialize;
+    use bitcoin::opcodes::all::{OP_CHECKSIG, OP_PUSHNUM_1};
+    use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+    use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+
+    fn coinbase_tx(witness_commitment: ScriptBuf) -> Transaction {
+        Transaction {
+            version: Version(2),
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new().push_int(1_000).into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![
+                TxOut {
+                    value: Amount::from_sat(5_000_000_000),
+                    script_pubkey: Builder::new().push_opcode(OP_PUSHNUM_1).into_script(),
+                },
+                TxOut {
+                    value: Amount::ZERO,
+                    script_pubkey: witness_commitment,
+                },
+            ],
+        }
+    }
+
+    fn witness_commitment(solution: Option<&[u8]>) -> ScriptBuf {
+        let mut commitment = WITNESS_COMMITMENT_HEADER[2..].to_vec();
+        commitment.extend([0x11; 32]);
+        let builder = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(PushBytesBuf::try_from(commitment).unwrap());
+
+        match solution {
+            Some(solution) => {
+                let mut signet_commitment = SIGNET_HEADER.to_vec();
+                signet_commitment.extend_from_slice(solution);
+                builder.push_slice(PushBytesBuf::try_from(signet_commitment).unwrap())
+            }
+            None => builder,
+        }
+        .into_script()
+    }
+
+    fn header_for(coinbase_tx: &Transaction) -> Header {
+        Header {
+            version: 0x2000_0000,
+            prev_block_hash: H256::from([0x22; 32]),
+            merkle_root: H256::from(coinbase_tx.compute_txid().to_byte_array()),
+            time: 1_700_000_000,
+            bits: 0x1e03_77ae,
+            nonce: 0,
+        }
+    }
+
+    fn solution(script_sig: &ScriptBuf) -> Vec<u8> {
+        let mut solution = serialize(script_sig);
+        solution.extend(serialize(&Witness::new()));
+        solution
+    }
+
+    #[test]
+    fn test_trivial_challenge() {
+        let coinbase_tx = coinbase_tx(witness_commitment(None));
+        let header = header_for(&coinbase_tx);
+        let challenge = Builder::new().push_opcode(OP_PUSHNUM_1).into_script();
+
+        SignetTxs::create(&header, &serialize(&coinbase_tx), &[], &challenge)
+            .unwrap()
+            .verify()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_signed_challenge() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let public_key = bitcoin::PublicKey::new(secret_key.public_key(&secp));
+        let challenge = Builder::new()
+            .push_key(&public_key)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        // The signed transactions do not depend on the solution, so they are built
+        // with an empty one first, which leaves the same signet header in the commitment
+        let unsigned_coinbase_tx = coinbase_tx(witness_commitment(Some(&[])));
+        let unsigned_header = header_for(&unsigned_coinbase_tx);
+        let signet_txs = SignetTxs::create(
+            &unsigned_header,
+            &serialize(&unsigned_coinbase_tx),
+            &[],
+            &challenge,
+        )
+        .unwrap();
+
+        let sighash = SighashCache::new(&signet_txs.to_sign)
+            .legacy_signature_hash(0, &challenge, EcdsaSighashType::All.to_u32())
+            .unwrap();
+        let signature = bitcoin::ecdsa::Signature::sighash_all(
+            secp.sign_ecdsa(&Message::from_digest(sighash.to_byte_array()), &secret_key),
+        );
+        let script_sig = Builder::new()
+            .push_slice(PushBytesBuf::try_from(signature.to_vec()).unwrap())
+            .into_script();
+
+        let coinbase_tx = coinbase_tx(witness_commitment(Some(&solution(&script_sig))));
+        let header = Header {
+            merkle_root: H256::from(coinbase_tx.compute_txid().to_byte_array()),
+            ..unsigned_header.clone()
+        };
+        SignetTxs::create(&header, &serialize(&coinbase_tx), &[], &challenge)
+            .unwrap()
+            .verify()
+            .unwrap();
+
+        // The signature commits to the block
+        let header = Header {
+            time: header.time + 1,
+            ..header
+        };
+        assert!(
+            SignetTxs::create(&header, &serialize(&coinbase_tx), &[], &challenge)
+                .unwrap()
+                .verify()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_coinbase_not_in_block() {
+        let coinbase_tx = coinbase_tx(witness_commitment(None));
+        let header = Header {
+            merkle_root: H256::from([0x33; 32]),
+            ..header_for(&coinbase_tx)
+        };
+        let challenge = Builder::new().push_opcode(OP_PUSHNUM_1).into_script();
+
+        assert!(matches!(
+            SignetTxs::create(&header, &serialize(&coinbase_tx), &[], &challenge),
+            Err(SignetError::InvalidCoinbaseProof)
+        ));
+    }
+
+    #[test]
+    fn test_no_witness_commitment() {
+        let coinbase_tx = coinbase_tx(Builder::new().push_opcode(OP_RETURN).into_script());
+        let header = header_for(&coinbase_tx);
+        let challenge = Builder::new().push_opcode(OP_PUSHNUM_1).into_script();
+
+        assert!(matches!(
+            SignetTxs::create(&header, &serialize(&coinbase_tx), &[], &challenge),
+            Err(SignetError::NoWitnessCommitment)
+        ));
+    }
+}
@@ -1,5 +1,8 @@
-use std::ops::{Div, Not, Rem, Shl, Shr};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Not, Rem, Shl, Shr, Sub};
+use std::str::FromStr;
 
+use bitcoin::{Target, Work};
 use near_sdk::near;
 
 #[near(serializers = [borsh, json])]
@@ -47,6 +50,80 @@ impl U256 {
         out
     }
 
+    /// Converts `U256` to a little-endian array of `u8`s.
+    #[must_use]
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut out = [0; 32];
+        out[..16].copy_from_slice(&self.1.to_le_bytes());
+        out[16..].copy_from_slice(&self.0.to_le_bytes());
+        out
+    }
+
+    /// Parses a number in the given radix, as `u128::from_str_radix` does.
+    ///
+    /// # Errors
+    /// If the string is empty, has a character which is not a digit in the radix or
+    /// the number does not fit into 256 bits
+    ///
+    /// # Panics
+    /// If `radix` is not in the range from 2 to 36
+    pub fn from_str_radix(src: &str, radix: u32) -> Result<Self, ParseU256Error> {
+        assert!(
+            (2..=36).contains(&radix),
+            "from_str_radix_int: must lie in the range `[2, 36]` - found {radix}"
+        );
+
+        let digits = src.strip_prefix('+').unwrap_or(src);
+        if digits.is_empty() {
+            return Err(ParseU256Error::Empty);
+        }
+
+        let mut ret = U256::ZERO;
+        for c in digits.chars() {
+            let digit = c.to_digit(radix).ok_or(ParseU256Error::InvalidDigit)?;
+            ret = ret
+                .checked_mul(U256::from(radix))
+                .and_then(|shifted| shifted.checked_add(U256::from(digit)))
+                .ok_or(ParseU256Error::Overflow)?;
+        }
+
+        Ok(ret)
+    }
+
+    /// Parses a hex number with an optional `0x` prefix, as returned by the `chainwork`
+    /// field of the node RPC.
+    ///
+    /// # Errors
+    /// If the string is not a hex number fitting into 256 bits
+    pub fn from_hex(src: &str) -> Result<Self, ParseU256Error> {
+        let digits = src
+            .strip_prefix("0x")
+            .or_else(|| src.strip_prefix("0X"))
+            .unwrap_or(src);
+        if digits.starts_with('+') {
+            return Err(ParseU256Error::InvalidDigit);
+        }
+        Self::from_str_radix(digits, 16)
+    }
+
+    /// Little-endian `u64` limbs of the number.
+    #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+    fn to_limbs(self) -> [u64; 4] {
+        [
+            self.1 as u64,
+            (self.1 >> 64) as u64,
+            self.0 as u64,
+            (self.0 >> 64) as u64,
+        ]
+    }
+
+    fn from_limbs(limbs: [u64; 4]) -> Self {
+        U256(
+            u128::from(limbs[3]) << 64 | u128::from(limbs[2]),
+            u128::from(limbs[1]) << 64 | u128::from(limbs[0]),
+        )
+    }
+
     /// Calculates 2^256 / (x + 1) where x is a 256 bit unsigned integer.
     ///
     /// 2**256 / (x + 1) == ~x / (x + 1) + 1
@@ -92,7 +169,7 @@ impl U256 {
         n_compact
     }
 
-    fn is_zero(&self) -> bool {
+    pub fn is_zero(&self) -> bool {
         self.0 == 0 && self.1 == 0
     }
 
@@ -113,18 +190,114 @@ impl U256 {
         }
     }
 
+    /// Calculates `self` * `rhs`
+    ///
+    /// Returns a tuple of the multiplication along with a boolean indicating whether an arithmetic
+    /// overflow would occur. If an overflow would have occurred then the wrapped value is returned.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
     pub fn overflowing_mul(self, rhs: u64) -> (Self, bool) {
-        let (high, overflow) = self.0.overflowing_mul(u128::from(rhs));
-        let (low, overflow_low) = self.1.overflowing_mul(u128::from(rhs));
+        let product = self.widening_mul(U256::from(rhs));
+        (product.low(), !product.high().is_zero())
+    }
 
-        if !overflow_low {
-            return (Self(high, low), overflow);
+    /// Calculates the full 512 bit product of `self` and `rhs`.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    #[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+    pub fn widening_mul(self, rhs: Self) -> U512 {
+        let lhs = self.to_limbs();
+        let rhs = rhs.to_limbs();
+        let mut ret = [0u64; 8];
+
+        for (i, lhs_limb) in lhs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, rhs_limb) in rhs.iter().enumerate() {
+                // Can not overflow: (2^64 - 1)^2 + 2 * (2^64 - 1) == 2^128 - 1
+                let t =
+                    u128::from(*lhs_limb) * u128::from(*rhs_limb) + u128::from(ret[i + j]) + carry;
+                ret[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            ret[i + 4] = carry as u64;
         }
 
-        let carry = ((self.1 >> 64) * (u128::from(rhs))) >> 64;
-        let (high, overflow_add) = high.overflowing_add(carry);
+        U512(
+            U256::from_limbs([ret[4], ret[5], ret[6], ret[7]]),
+            U256::from_limbs([ret[0], ret[1], ret[2], ret[3]]),
+        )
+    }
 
-        (Self(high, low), overflow | overflow_add)
+    /// Checked addition. Returns `None` if overflow occurred.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (ret, false) => Some(ret),
+            (_, true) => None,
+        }
+    }
+
+    /// Checked subtraction. Returns `None` if overflow occurred.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (ret, false) => Some(ret),
+            (_, true) => None,
+        }
+    }
+
+    /// Checked multiplication. Returns `None` if overflow occurred.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let product = self.widening_mul(rhs);
+        if product.high().is_zero() {
+            Some(product.low())
+        } else {
+            None
+        }
+    }
+
+    /// Checked division. Returns `None` if `rhs` is zero.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            None
+        } else {
+            Some(self.div_rem(rhs).0)
+        }
+    }
+
+    /// Checked remainder. Returns `None` if `rhs` is zero.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            None
+        } else {
+            Some(self.div_rem(rhs).1)
+        }
+    }
+
+    /// Saturating addition. Computes `self + rhs`, saturating at `U256::MAX`.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        self.checked_add(rhs).unwrap_or(U256::MAX)
+    }
+
+    /// Saturating subtraction. Computes `self - rhs`, saturating at zero.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs).unwrap_or(U256::ZERO)
+    }
+
+    /// Saturating multiplication. Computes `self * rhs`, saturating at `U256::MAX`.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs).unwrap_or(U256::MAX)
+    }
+
+    /// Wrapping (modular) multiplication. Computes `self * rhs`, wrapping around at the boundary
+    /// of the type.
+    #[must_use = "this returns the result of the operation, without modifying the original"]
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        self.widening_mul(rhs).low()
     }
 
     /// Calculates quotient and remainder.
@@ -200,7 +373,7 @@ impl U256 {
     /// Returns a tuple of the subtraction along with a boolean indicating whether an arithmetic
     /// overflow would occur. If an overflow would have occurred then the wrapped value is returned.
     #[must_use = "this returns the result of the operation, without modifying the original"]
-    fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+    pub fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
         let ret = self.wrapping_add(!rhs).wrapping_add(Self::ONE);
         let overflow = rhs > self;
         (ret, overflow)
@@ -209,7 +382,7 @@ impl U256 {
     /// Wrapping (modular) addition. Computes `self + rhs`, wrapping around at the boundary of the
     /// type.
     #[must_use = "this returns the result of the operation, without modifying the original"]
-    fn wrapping_add(self, rhs: Self) -> Self {
+    pub fn wrapping_add(self, rhs: Self) -> Self {
         let (ret, _overflow) = self.overflowing_add(rhs);
         ret
     }
@@ -217,7 +390,7 @@ impl U256 {
     /// Wrapping (modular) subtraction. Computes `self - rhs`, wrapping around at the boundary of
     /// the type.
     #[must_use = "this returns the result of the operation, without modifying the original"]
-    fn wrapping_sub(self, rhs: Self) -> Self {
+    pub fn wrapping_sub(self, rhs: Self) -> Self {
         let (ret, _overflow) = self.overflowing_sub(rhs);
         ret
     }
@@ -300,9 +473,217 @@ fn split_in_half(a: &[u8; 32]) -> ([u8; 16], [u8; 16]) {
     (high, low)
 }
 
-impl<T: Into<u128>> From<T> for U256 {
-    fn from(x: T) -> Self {
-        U256(0, x.into())
+/// Divides little-endian `u64` limbs by `rhs` in place and returns the remainder.
+#[allow(clippy::as_conversions, clippy::cast_possible_truncation)]
+fn div_rem_limbs(limbs: &mut [u64], rhs: u64) -> u64 {
+    let mut rem = 0u128;
+    for limb in limbs.iter_mut().rev() {
+        let current = rem << 64 | u128::from(*limb);
+        *limb = (current / u128::from(rhs)) as u64;
+        rem = current % u128::from(rhs);
+    }
+    rem as u64
+}
+
+/// Formats little-endian `u64` limbs as a decimal number.
+fn fmt_decimal(mut limbs: Vec<u64>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    use fmt::Write;
+
+    const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+    let mut chunks = Vec::new();
+    while limbs.iter().any(|limb| *limb != 0) {
+        chunks.push(div_rem_limbs(&mut limbs, CHUNK));
+    }
+
+    let mut digits = chunks.pop().unwrap_or(0).to_string();
+    for chunk in chunks.iter().rev() {
+        write!(digits, "{chunk:019}")?;
+    }
+    f.pad_integral(true, "", &digits)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseU256Error {
+    Empty,
+    InvalidDigit,
+    Overflow,
+}
+
+impl fmt::Display for ParseU256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseU256Error::Empty => write!(f, "cannot parse U256 from empty string"),
+            ParseU256Error::InvalidDigit => write!(f, "invalid digit found in string"),
+            ParseU256Error::Overflow => write!(f, "number too large to fit in U256"),
+        }
+    }
+}
+
+impl std::error::Error for ParseU256Error {}
+
+/// The number does not fit into the target integer type
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TryFromU256Error;
+
+impl fmt::Display for TryFromU256Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "out of range integral type conversion attempted")
+    }
+}
+
+impl std::error::Error for TryFromU256Error {}
+
+macro_rules! impl_from_unsigned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for U256 {
+                fn from(x: $ty) -> Self {
+                    U256(0, u128::from(x))
+                }
+            }
+
+            impl TryFrom<U256> for $ty {
+                type Error = TryFromU256Error;
+
+                fn try_from(x: U256) -> Result<Self, Self::Error> {
+                    if x.0 != 0 {
+                        return Err(TryFromU256Error);
+                    }
+                    <$ty>::try_from(x.1).map_err(|_| TryFromU256Error)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, u128);
+
+macro_rules! impl_try_from_signed {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<$ty> for U256 {
+                type Error = TryFromU256Error;
+
+                fn try_from(x: $ty) -> Result<Self, Self::Error> {
+                    u128::try_from(x)
+                        .map(U256::from)
+                        .map_err(|_| TryFromU256Error)
+                }
+            }
+
+            impl TryFrom<U256> for $ty {
+                type Error = TryFromU256Error;
+
+                fn try_from(x: U256) -> Result<Self, Self::Error> {
+                    if x.0 != 0 {
+                        return Err(TryFromU256Error);
+                    }
+                    <$ty>::try_from(x.1).map_err(|_| TryFromU256Error)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_signed!(i8, i16, i32, i64, i128, isize);
+
+impl From<usize> for U256 {
+    fn from(x: usize) -> Self {
+        U256(0, u128::try_from(x).expect("usize is at most 128 bits"))
+    }
+}
+
+impl TryFrom<U256> for usize {
+    type Error = TryFromU256Error;
+
+    fn try_from(x: U256) -> Result<Self, Self::Error> {
+        if x.0 != 0 {
+            return Err(TryFromU256Error);
+        }
+        usize::try_from(x.1).map_err(|_| TryFromU256Error)
+    }
+}
+
+impl From<Work> for U256 {
+    fn from(work: Work) -> Self {
+        U256::from_be_bytes(&work.to_be_bytes())
+    }
+}
+
+impl From<U256> for Work {
+    fn from(work: U256) -> Self {
+        Work::from_be_bytes(work.to_be_bytes())
+    }
+}
+
+impl From<Target> for U256 {
+    fn from(target: Target) -> Self {
+        U256::from_be_bytes(&target.to_be_bytes())
+    }
+}
+
+impl From<U256> for Target {
+    fn from(target: U256) -> Self {
+        Target::from_be_bytes(target.to_be_bytes())
+    }
+}
+
+impl FromStr for U256 {
+    type Err = ParseU256Error;
+
+    /// Parses a decimal number
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str_radix(s, 10)
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_decimal(self.to_limbs().to_vec(), f)
+    }
+}
+
+/// Formats all 64 hex digits, as `bitcoin::Work` and `bitcoin::Target` do
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode(self.to_be_bytes()))
+    }
+}
+
+impl fmt::UpperHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        f.write_str(&hex::encode_upper(self.to_be_bytes()))
+    }
+}
+
+impl Add for U256 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.checked_add(rhs)
+            .expect("Addition of U256 values overflowed")
+    }
+}
+
+impl Sub for U256 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.checked_sub(rhs)
+            .expect("Subtraction of U256 values overflowed")
+    }
+}
+
+impl Mul for U256 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.checked_mul(rhs)
+            .expect("Multiplication of U256 values overflowed")
     }
 }
 
@@ -341,3 +722,226 @@ impl Shr<u32> for U256 {
         self.wrapping_shr(shift)
     }
 }
+
+/// Unsigned 512 bit integer, the result of [`U256::widening_mul`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct U512(U256, U256);
+
+impl U512 {
+    pub const fn new(high: U256, low: U256) -> Self {
+        U512(high, low)
+    }
+
+    /// The most significant 256 bits
+    pub fn high(&self) -> U256 {
+        self.0
+    }
+
+    /// The least significant 256 bits
+    pub fn low(&self) -> U256 {
+        self.1
+    }
+
+    /// Converts `U512` to a big-endian array of `u8`s.
+    #[must_use]
+    pub fn to_be_bytes(self) -> [u8; 64] {
+        let mut out = [0; 64];
+        out[..32].copy_from_slice(&self.0.to_be_bytes());
+        out[32..].copy_from_slice(&self.1.to_be_bytes());
+        out
+    }
+}
+
+impl From<U256> for U512 {
+    fn from(x: U256) -> Self {
+        U512(U256::ZERO, x)
+    }
+}
+
+impl TryFrom<U512> for U256 {
+    type Error = TryFromU256Error;
+
+    fn try_from(x: U512) -> Result<Self, Self::Error> {
+        if x.0.is_zero() {
+            Ok(x.1)
+        } else {
+            Err(TryFromU256Error)
+        }
+    }
+}
+
+impl fmt::Display for U512 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut limbs = self.1.to_limbs().to_vec();
+        limbs.extend_from_slice(&self.0.to_limbs());
+        fmt_decimal(limbs, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn u256() -> impl Strategy<Value = U256> {
+        any::<[u8; 32]>().prop_map(|bytes| U256::from_be_bytes(&bytes))
+    }
+
+    #[test]
+    fn test_format_and_parse() {
+        assert_eq!(U256::ZERO.to_string(), "0");
+        assert_eq!(
+            U256::from(10_000_000_000_000_000_000_u64).to_string(),
+            "10000000000000000000"
+        );
+        assert_eq!(
+            U256::MAX.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(U256::MAX.to_string().parse::<U256>(), Ok(U256::MAX));
+        assert_eq!(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+                .parse::<U256>(),
+            Err(ParseU256Error::Overflow)
+        );
+        assert_eq!("".parse::<U256>(), Err(ParseU256Error::Empty));
+        assert_eq!("-1".parse::<U256>(), Err(ParseU256Error::InvalidDigit));
+        assert_eq!(format!("{:>5}", U256::from(42_u8)), "   42");
+
+        assert_eq!(
+            format!("{:#x}", U256::ONE),
+            "0x0000000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(U256::from_hex("0x0a"), Ok(U256::from(10_u8)));
+        assert_eq!(U256::from_hex("FF"), Ok(U256::from(255_u8)));
+        assert_eq!(U256::from_hex("0x"), Err(ParseU256Error::Empty));
+        assert_eq!(U256::from_hex("+1"), Err(ParseU256Error::InvalidDigit));
+    }
+
+    #[test]
+    fn test_primitive_conversions() {
+        assert_eq!(U256::try_from(-1_i32), Err(TryFromU256Error));
+        assert_eq!(U256::try_from(7_i64), Ok(U256::from(7_u8)));
+        assert_eq!(u8::try_from(U256::from(256_u16)), Err(TryFromU256Error));
+        assert_eq!(u128::try_from(U256::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(u128::try_from(U256::MAX), Err(TryFromU256Error));
+        assert_eq!(i128::try_from(U256::from(u128::MAX)), Err(TryFromU256Error));
+        assert_eq!(usize::try_from(U256::from(5_usize)), Ok(5));
+    }
+
+    #[test]
+    fn test_widening_mul() {
+        let product = U256::MAX.widening_mul(U256::MAX);
+        // (2^256 - 1)^2 == 2^256 * (2^256 - 2) + 1
+        assert_eq!(product.high(), U256::MAX - U256::ONE);
+        assert_eq!(product.low(), U256::ONE);
+        assert_eq!(U256::try_from(product), Err(TryFromU256Error));
+        assert_eq!(
+            product.to_string(),
+            "13407807929942597099574024998205846127479365820592393377723561443721764030073315392623399665776056285720014482370779510884422601683867654778417822746804225"
+        );
+
+        assert_eq!(U256::MAX.checked_mul(U256::from(2_u8)), None);
+        assert_eq!(U256::MAX.saturating_mul(U256::from(2_u8)), U256::MAX);
+        assert_eq!(
+            U256::MAX.wrapping_mul(U256::from(2_u8)),
+            U256::MAX - U256::ONE
+        );
+    }
+
+    #[test]
+    fn test_overflowing_mul_low_half_carry() {
+        // (2^65 - 1) * (2^64 - 1) == 2^128 + 2^128 - 3 * 2^64 + 1, the carry into the high half
+        // comes from the sum of the partial products
+        let value = U256::new(0, (1 << 65) - 1);
+        assert_eq!(
+            value.overflowing_mul(u64::MAX),
+            (U256::new(1, u128::MAX - (3 << 64) + 2), false)
+        );
+    }
+
+    #[test]
+    fn test_saturating_ops() {
+        assert_eq!(U256::MAX.saturating_add(U256::ONE), U256::MAX);
+        assert_eq!(U256::ZERO.saturating_sub(U256::ONE), U256::ZERO);
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+        assert_eq!(U256::ONE.checked_rem(U256::ZERO), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bitcoin_work_conversion(x in u256()) {
+            let work = Work::from(x);
+            prop_assert_eq!(U256::from(work), x);
+            prop_assert_eq!(x.to_string(), work.to_string());
+            prop_assert_eq!(format!("{x:x}"), format!("{work:x}"));
+            prop_assert_eq!(format!("{x:#X}"), format!("{work:#X}"));
+            prop_assert_eq!(U256::from(Target::from(x)), x);
+        }
+
+        #[test]
+        fn prop_parse_round_trip(x in u256()) {
+            prop_assert_eq!(x.to_string().parse::<U256>(), Ok(x));
+            prop_assert_eq!(U256::from_hex(&format!("{x:#x}")), Ok(x));
+            prop_assert_eq!(
+                U256::from(Work::from_unprefixed_hex(&format!("{x:x}")).unwrap()),
+                x
+            );
+        }
+
+        #[test]
+        fn prop_add_sub_match_bitcoin(x in u256(), y in u256()) {
+            let (sum, overflow) = x.overflowing_add(y);
+            prop_assert_eq!(x.checked_add(y).is_none(), overflow);
+            if !overflow {
+                prop_assert_eq!(Work::from(sum), Work::from(x) + Work::from(y));
+            }
+
+            let (big, small) = if x > y { (x, y) } else { (y, x) };
+            prop_assert_eq!(Work::from(big - small), Work::from(big) - Work::from(small));
+            prop_assert_eq!(small.checked_sub(big).is_none(), small != big);
+            prop_assert_eq!(small.wrapping_sub(big).wrapping_add(big), small);
+        }
+
+        #[test]
+        fn prop_inverse_matches_bitcoin(x in u256()) {
+            prop_assert_eq!(Work::from(x.inverse()), Target::from(x).to_work());
+        }
+
+        #[test]
+        fn prop_target_to_bits_matches_bitcoin(x in u256()) {
+            prop_assert_eq!(
+                x.target_to_bits(),
+                Target::from(x).to_compact_lossy().to_consensus()
+            );
+        }
+
+        #[test]
+        fn prop_mul(x in u256(), y in u256(), z in any::<u64>()) {
+            let product = x.widening_mul(y);
+            prop_assert_eq!(product, y.widening_mul(x));
+            prop_assert_eq!(x.checked_mul(y).is_some(), product.high().is_zero());
+
+            if let Some(product) = x.checked_mul(y) {
+                if !y.is_zero() {
+                    prop_assert_eq!(product / y, x);
+                    prop_assert_eq!(product % y, U256::ZERO);
+                }
+            }
+
+            let (wrapped, overflow) = x.overflowing_mul(z);
+            prop_assert_eq!(wrapped, x.wrapping_mul(U256::from(z)));
+            prop_assert_eq!(overflow, x.checked_mul(U256::from(z)).is_none());
+        }
+
+        #[test]
+        fn prop_mul_small_matches_u128(x in any::<u64>(), y in any::<u64>()) {
+            let product = U256::from(x).widening_mul(U256::from(y));
+            prop_assert_eq!(product, U512::from(U256::from(u128::from(x) * u128::from(y))));
+            prop_assert_eq!(
+                product.to_string(),
+                (u128::from(x) * u128::from(y)).to_string()
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::aux::AuxDataError;
 use crate::u256::U256;
 
 pub mod serd_u32_hex {
@@ -18,10 +19,142 @@ pub mod serd_u32_hex {
     }
 }
 
-#[derive(Debug)]
+/// Serializes an optional `U256` as a big-endian hex string, e.g. `chainwork` of `getblockheader`.
+pub mod serd_u256_hex_option {
+    use crate::u256::U256;
+
+    pub fn serialize<S>(num: &Option<U256>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match num {
+            Some(num) => serializer.serialize_some(&hex::encode(num.to_be_bytes())),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let hex_str: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+        hex_str
+            .map(|hex_str| {
+                let mut bytes = [0u8; 32];
+                hex::decode_to_slice(format!("{hex_str:0>64}"), &mut bytes)
+                    .map_err(serde::de::Error::custom)?;
+                Ok(U256::from_be_bytes(&bytes))
+            })
+            .transpose()
+    }
+}
+
+/// Serializes optional bytes as a hex string, e.g. a script.
+pub mod serd_bytes_hex_option {
+    pub fn serialize<S>(bytes: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match bytes {
+            Some(bytes) => serializer.serialize_some(&hex::encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let hex_str: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+        hex_str
+            .map(|hex_str| hex::decode(hex_str).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Opt-in serialization of a header as its consensus hex, e.g.
+/// `#[serde(with = "btc_types::utils::serd_consensus_hex")]`
+pub mod serd_consensus_hex {
+    use crate::header::ConsensusHex;
+
+    pub fn serialize<T, S>(header: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ConsensusHex,
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&header.to_consensus_hex())
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: ConsensusHex,
+        D: serde::Deserializer<'de>,
+    {
+        let hex_str: String = serde::Deserialize::deserialize(deserializer)?;
+        T::from_consensus_hex(&hex_str).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Opt-in serialization of an `ExtendedHeader` with the header as its consensus hex.
+/// Zcash headers are stored without the solution, so they have no such representation.
+#[cfg(not(feature = "zcash_header"))]
+pub mod serd_extended_header_consensus_hex {
+    use crate::hash::H256;
+    use crate::header::{ExtendedHeader, LightHeader, Work};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    struct ExtendedHeaderHex {
+        #[serde(with = "super::serd_consensus_hex")]
+        block_header: LightHeader,
+        block_hash: H256,
+        chain_work: Work,
+        block_height: u64,
+        #[serde(default)]
+        recent_timestamps: Vec<u32>,
+    }
+
+    pub fn serialize<S>(header: &ExtendedHeader, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        ExtendedHeaderHex {
+            block_header: header.block_header.clone(),
+            block_hash: header.block_hash.clone(),
+            chain_work: header.chain_work,
+            block_height: header.block_height,
+            recent_timestamps: header.recent_timestamps.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ExtendedHeader, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let header = ExtendedHeaderHex::deserialize(deserializer)?;
+        Ok(ExtendedHeader {
+            block_header: header.block_header,
+            block_hash: header.block_hash,
+            chain_work: header.chain_work,
+            block_height: header.block_height,
+            recent_timestamps: header.recent_timestamps,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DecodeHeaderError {
     InvalidLength,
     IntParseError,
+    InvalidAuxPow(AuxDataError),
+    InvalidHex,
+}
+
+impl From<AuxDataError> for DecodeHeaderError {
+    fn from(err: AuxDataError) -> Self {
+        DecodeHeaderError::InvalidAuxPow(err)
+    }
 }
 
 impl std::fmt::Display for DecodeHeaderError {
@@ -29,6 +162,8 @@ impl std::fmt::Display for DecodeHeaderError {
         match self {
             DecodeHeaderError::InvalidLength => write!(f, "Invalid length"),
             DecodeHeaderError::IntParseError => write!(f, "Integer parse error"),
+            DecodeHeaderError::InvalidAuxPow(err) => write!(f, "Invalid AuxPoW: {err}"),
+            DecodeHeaderError::InvalidHex => write!(f, "Invalid hex"),
         }
     }
 }
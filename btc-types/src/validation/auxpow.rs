@@ -0,0 +1,411 @@
+use super::{check_pow_hash, ErrorCode, ValidationError};
+use crate::aux::AuxData;
+use crate::hash::{double_sha256, H256};
+use crate::header::Header;
+use bitcoin::hashes::Hash;
+
+//https://github.com/dogecoin/dogecoin/blob/2c513d0172e8bc86fe9a337693b26f2fdf68a013/src/auxpow.h#L24
+const MERGED_MINING_HEADER: &str = "fabe6d6d";
+
+// The merge-mined block must have the AuxPoW flag set (bit 8) when AuxPoW data is present.
+// https://github.com/dogecoin/dogecoin/blob/master/src/auxpow.h
+const BLOCK_VERSION_AUXPOW: i32 = 0x100;
+
+const MAX_CHAIN_MERKLE_BRANCH_LEN: usize = 30;
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuxPowError {
+    ChainMerkleBranchTooLong,
+    MissingAuxPowFlag,
+    WrongChainId { actual: i32, expected: i32 },
+    ParentHasOurChainId,
+    InvalidCoinbaseTx,
+    WrongCoinbaseMerkleRoot,
+    MissingChainMerkleRoot,
+    MultipleMergedMiningHeaders,
+    MergedMiningHeaderNotBeforeChainMerkleRoot,
+    ChainMerkleRootTooLate,
+    MissingChainMerkleTreeSizeAndNonce,
+    WrongChainMerkleBranchSize,
+    WrongIndex,
+    InsufficientParentPow,
+}
+
+impl std::fmt::Display for AuxPowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuxPowError::ChainMerkleBranchTooLong => {
+                write!(f, "Aux POW chain merkle branch too long")
+            }
+            AuxPowError::MissingAuxPowFlag => {
+                write!(f, "Aux POW block does not have AuxPoW flag set in version")
+            }
+            AuxPowError::WrongChainId { actual, expected } => write!(
+                f,
+                "block does not have our chain ID (got {actual}, expected {expected})"
+            ),
+            AuxPowError::ParentHasOurChainId => write!(f, "Aux POW parent has our chain ID"),
+            AuxPowError::InvalidCoinbaseTx => write!(f, "Aux POW parent coinbase is invalid"),
+            AuxPowError::WrongCoinbaseMerkleRoot => write!(f, "Aux POW merkle root incorrect"),
+            AuxPowError::MissingChainMerkleRoot => {
+                write!(f, "Aux POW missing chain merkle root in parent coinbase")
+            }
+            AuxPowError::MultipleMergedMiningHeaders => {
+                write!(f, "Multiple merged mining headers in coinbase")
+            }
+            AuxPowError::MergedMiningHeaderNotBeforeChainMerkleRoot => write!(
+                f,
+                "Merged mining header is not just before chain merkle root"
+            ),
+            AuxPowError::ChainMerkleRootTooLate => write!(
+                f,
+                "Aux POW chain merkle root must start in the first 20 bytes of the parent coinbase"
+            ),
+            AuxPowError::MissingChainMerkleTreeSizeAndNonce => write!(
+                f,
+                "Aux POW missing chain merkle tree size and nonce in parent coinbase"
+            ),
+            AuxPowError::WrongChainMerkleBranchSize => write!(
+                f,
+                "Aux POW merkle branch size does not match parent coinbase"
+            ),
+            AuxPowError::WrongIndex => write!(f, "Aux POW wrong index"),
+            AuxPowError::InsufficientParentPow => {
+                write!(f, "Aux POW parent proof of work does not meet the target")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuxPowError {}
+
+impl AuxPowError {
+    /// The code at the start of the error message
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AuxPowError::ChainMerkleBranchTooLong => ErrorCode::AuxPowChainMerkleBranchTooLong,
+            AuxPowError::MissingAuxPowFlag => ErrorCode::AuxPowMissingFlag,
+            AuxPowError::WrongChainId { .. } => ErrorCode::AuxPowChainId,
+            AuxPowError::ParentHasOurChainId => ErrorCode::AuxPowParentChainId,
+            AuxPowError::InvalidCoinbaseTx => ErrorCode::AuxPowCoinbase,
+            AuxPowError::WrongCoinbaseMerkleRoot => ErrorCode::AuxPowMerkleRoot,
+            AuxPowError::MissingChainMerkleRoot => ErrorCode::AuxPowMissingChainMerkleRoot,
+            AuxPowError::MultipleMergedMiningHeaders => {
+                ErrorCode::AuxPowMultipleMergedMiningHeaders
+            }
+            AuxPowError::MergedMiningHeaderNotBeforeChainMerkleRoot => {
+                ErrorCode::AuxPowMergedMiningHeaderPosition
+            }
+            AuxPowError::ChainMerkleRootTooLate => ErrorCode::AuxPowChainMerkleRootTooLate,
+            AuxPowError::MissingChainMerkleTreeSizeAndNonce => ErrorCode::AuxPowMissingSizeAndNonce,
+            AuxPowError::WrongChainMerkleBranchSize => ErrorCode::AuxPowChainMerkleBranchSize,
+            AuxPowError::WrongIndex => ErrorCode::AuxPowIndex,
+            AuxPowError::InsufficientParentPow => ErrorCode::AuxPowParentPow,
+        }
+    }
+}
+
+impl From<AuxPowError> for ValidationError {
+    fn from(err: AuxPowError) -> Self {
+        ValidationError::AuxPow(err)
+    }
+}
+
+/// Checks the AuxPoW of a merge-mined header: the parent block commits to the header,
+/// and its `parent_pow_hash`, computed with the PoW function of the merge-mined chain,
+/// meets the target of the header.
+///
+/// # Errors
+/// If the parent block does not commit to the header or its proof of work is insufficient
+pub fn check_aux(
+    block_header: &Header,
+    aux_data: &AuxData,
+    aux_chain_id: i32,
+    parent_pow_hash: &H256,
+) -> Result<(), ValidationError> {
+    check_aux_structure(block_header, aux_data, aux_chain_id)?;
+    check_pow_hash(parent_pow_hash, block_header.bits)
+        .map_err(|_| AuxPowError::InsufficientParentPow.into())
+}
+
+/// Checks that the parent block commits to the merge-mined block, independently of the chain.
+/// The proof of work of the parent block is not checked here.
+///
+/// # Errors
+/// If the AuxPoW is malformed or commits to another block
+/// # Panics
+/// If the chain ID of the header is negative
+// https://github.com/dogecoin/dogecoin/blob/2c513d0172e8bc86fe9a337693b26f2fdf68a013/src/auxpow.cpp#L72
+pub fn check_aux_structure(
+    block_header: &Header,
+    aux_data: &AuxData,
+    aux_chain_id: i32,
+) -> Result<(), AuxPowError> {
+    if aux_data.chain_merkle_proof.len() > MAX_CHAIN_MERKLE_BRANCH_LEN {
+        return Err(AuxPowError::ChainMerkleBranchTooLong);
+    }
+    if block_header.version & BLOCK_VERSION_AUXPOW == 0 {
+        return Err(AuxPowError::MissingAuxPowFlag);
+    }
+
+    if aux_chain_id != block_header.get_chain_id() {
+        return Err(AuxPowError::WrongChainId {
+            actual: block_header.get_chain_id(),
+            expected: aux_chain_id,
+        });
+    }
+
+    if aux_chain_id == aux_data.parent_block.get_chain_id() {
+        return Err(AuxPowError::ParentHasOurChainId);
+    }
+
+    let chain_root = compute_root_from_merkle_proof(
+        block_header.block_hash(),
+        aux_data.chain_id,
+        &aux_data.chain_merkle_proof,
+    );
+
+    let coinbase_tx = aux_data
+        .get_coinbase_tx()
+        .map_err(|_| AuxPowError::InvalidCoinbaseTx)?;
+    let coinbase_tx_hash = coinbase_tx.compute_txid();
+
+    if compute_root_from_merkle_proof(
+        H256::from(coinbase_tx_hash.to_raw_hash().to_byte_array()),
+        0,
+        &aux_data.merkle_proof,
+    ) != aux_data.parent_block.merkle_root
+    {
+        return Err(AuxPowError::WrongCoinbaseMerkleRoot);
+    }
+
+    let script_sig = coinbase_tx
+        .input
+        .first()
+        .ok_or(AuxPowError::InvalidCoinbaseTx)?
+        .script_sig
+        .to_hex_string();
+    let pos_merged_mining_header = script_sig.find(MERGED_MINING_HEADER);
+    let mut pos_chain_root = script_sig
+        .find(&chain_root.to_string())
+        .ok_or(AuxPowError::MissingChainMerkleRoot)?;
+
+    match pos_merged_mining_header {
+        Some(pos_merged_mining_header) => {
+            if script_sig[pos_merged_mining_header + MERGED_MINING_HEADER.len()..]
+                .contains(MERGED_MINING_HEADER)
+            {
+                return Err(AuxPowError::MultipleMergedMiningHeaders);
+            }
+
+            if pos_merged_mining_header + MERGED_MINING_HEADER.len() != pos_chain_root {
+                return Err(AuxPowError::MergedMiningHeaderNotBeforeChainMerkleRoot);
+            }
+        }
+        None => {
+            if pos_chain_root > 40 {
+                return Err(AuxPowError::ChainMerkleRootTooLate);
+            }
+        }
+    }
+
+    pos_chain_root += chain_root.to_string().len();
+    if script_sig.len() - pos_chain_root < 16 {
+        return Err(AuxPowError::MissingChainMerkleTreeSizeAndNonce);
+    }
+
+    let bytes = hex::decode(&script_sig[pos_chain_root..pos_chain_root + 8]).unwrap();
+    let n_size = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    if n_size != (1u32 << aux_data.chain_merkle_proof.len()) {
+        return Err(AuxPowError::WrongChainMerkleBranchSize);
+    }
+
+    let bytes = hex::decode(&script_sig[pos_chain_root + 8..pos_chain_root + 16]).unwrap();
+    let n_nonce = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    let expected_index = get_expected_index(
+        n_nonce,
+        block_header.get_chain_id(),
+        aux_data.chain_merkle_proof.len(),
+    );
+
+    if u32::try_from(aux_data.chain_id).ok() != Some(expected_index) {
+        return Err(AuxPowError::WrongIndex);
+    }
+
+    Ok(())
+}
+
+fn get_expected_index(nonce: u32, chain_id: i32, merkle_height: usize) -> u32 {
+    let mut rand = nonce;
+    rand = rand.wrapping_mul(1_103_515_245).wrapping_add(12345);
+    rand = rand.wrapping_add(u32::try_from(chain_id).unwrap());
+    rand = rand.wrapping_mul(1_103_515_245).wrapping_add(12345);
+
+    rand.wrapping_rem(1u32 << merkle_height)
+}
+
+/// Same as `merkle_tools::compute_root_from_merkle_proof`, which depends on this crate
+fn compute_root_from_merkle_proof(leaf: H256, mut position: usize, merkle_proof: &[H256]) -> H256 {
+    merkle_proof.iter().fold(leaf, |current_hash, sibling| {
+        let mut concat = Vec::with_capacity(64);
+        if position % 2 == 0 {
+            concat.extend(current_hash.0);
+            concat.extend(sibling.0);
+        } else {
+            concat.extend(sibling.0);
+            concat.extend(current_hash.0);
+        }
+        position /= 2;
+        double_sha256(&concat)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::absolute::LockTime;
+    use bitcoin::consensus::serialize;
+    use bitcoin::transaction::Version;
+    use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+
+    const CHAIN_ID: i32 = 0x0001;
+
+    fn block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 0x0001_0104,
+            "prev_block_hash": "000000000062b72c5e2ceb45fbc8587e807c155b0da735e6483dfba2f0a9c770",
+            "merkle_root": "41c62dbd9068c89a449525e3cd5ac61b20ece28c3c38b3f35b2161f0e6d3cb0d",
+            "time": 1_303_000_601,
+            "bits": 0x1d00_ffff,
+            "nonce": 0,
+        }))
+        .unwrap()
+    }
+
+    /// Builds the AuxPoW of a parent block whose coinbase commits to `script_sig_data`.
+    fn aux_data(script_sig_data: &[u8]) -> AuxData {
+        let coinbase_tx = Transaction {
+            version: Version::ONE,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::from_bytes(script_sig_data.to_vec()),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(5_000_000_000),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+
+        let parent_block = Header {
+            version: 4,
+            merkle_root: H256::from(coinbase_tx.compute_txid().to_raw_hash().to_byte_array()),
+            ..block_header()
+        };
+
+        AuxData {
+            coinbase_tx: serialize(&coinbase_tx),
+            parent_block_hash: parent_block.block_hash(),
+            merkle_proof: vec![],
+            chain_merkle_proof: vec![],
+            chain_id: 0,
+            parent_block,
+        }
+    }
+
+    /// Merged mining header, chain merkle root, tree size and nonce
+    fn merged_mining_commitment(block_header: &Header) -> Vec<u8> {
+        let mut commitment = hex::decode(MERGED_MINING_HEADER).unwrap();
+        // The chain merkle root is committed in the display (reversed) byte order
+        commitment.extend(block_header.block_hash().0.iter().rev());
+        commitment.extend(1u32.to_le_bytes());
+        commitment.extend(0u32.to_le_bytes());
+        commitment
+    }
+
+    #[test]
+    fn test_check_aux_structure() {
+        let block_header = block_header();
+        let aux_data = aux_data(&merged_mining_commitment(&block_header));
+
+        assert_eq!(
+            check_aux_structure(&block_header, &aux_data, CHAIN_ID),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_check_aux_structure_wrong_chain_id() {
+        let block_header = block_header();
+        let aux_data = aux_data(&merged_mining_commitment(&block_header));
+
+        assert_eq!(
+            check_aux_structure(&block_header, &aux_data, 0x0062),
+            Err(AuxPowError::WrongChainId {
+                actual: CHAIN_ID,
+                expected: 0x0062
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_aux_structure_without_auxpow_flag() {
+        let block_header = Header {
+            version: 0x0001_0004,
+            ..block_header()
+        };
+        let aux_data = aux_data(&merged_mining_commitment(&block_header));
+
+        assert_eq!(
+            check_aux_structure(&block_header, &aux_data, CHAIN_ID),
+            Err(AuxPowError::MissingAuxPowFlag)
+        );
+    }
+
+    #[test]
+    fn test_check_aux_structure_multiple_merged_mining_headers() {
+        let block_header = block_header();
+        let mut commitment = merged_mining_commitment(&block_header);
+        commitment.extend(hex::decode(MERGED_MINING_HEADER).unwrap());
+        let aux_data = aux_data(&commitment);
+
+        assert_eq!(
+            check_aux_structure(&block_header, &aux_data, CHAIN_ID),
+            Err(AuxPowError::MultipleMergedMiningHeaders)
+        );
+    }
+
+    #[test]
+    fn test_check_aux_structure_other_block_committed() {
+        let block_header = block_header();
+        let other_block_header = Header {
+            nonce: 1,
+            ..block_header.clone()
+        };
+        let aux_data = aux_data(&merged_mining_commitment(&other_block_header));
+
+        assert_eq!(
+            check_aux_structure(&block_header, &aux_data, CHAIN_ID),
+            Err(AuxPowError::MissingChainMerkleRoot)
+        );
+    }
+
+    #[test]
+    fn test_check_aux_parent_pow() {
+        let block_header = block_header();
+        let aux_data = aux_data(&merged_mining_commitment(&block_header));
+
+        assert_eq!(
+            check_aux(&block_header, &aux_data, CHAIN_ID, &H256::default()),
+            Ok(())
+        );
+        assert_eq!(
+            check_aux(&block_header, &aux_data, CHAIN_ID, &H256::from([0xff; 32])),
+            Err(ValidationError::AuxPow(AuxPowError::InsufficientParentPow))
+        );
+    }
+}
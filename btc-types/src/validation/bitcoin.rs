@@ -0,0 +1,381 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, check_version, get_median_time_past,
+    BlocksGetter, ValidationError,
+};
+use crate::header::{ExtendedHeader, Header};
+use crate::network::{NetworkConfig, MAX_TIMEWARP};
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Checks the header against the previous one: the difficulty, the timestamps and the version.
+/// The proof of work hash is checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+// reference implementation: https://github.com/bitcoin/bitcoin/blob/ae024137bda9fe189f4e7ccf26dbaffd44cbbeb6/src/validation.cpp#L4200
+pub fn check_header(
+    config: &NetworkConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let expected_bits =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+    check_bits(expected_bits, block_header.bits)?;
+
+    // Check timestamp against prev
+    check_median_time_past(
+        block_header.time,
+        get_median_time_past(prev_block_header.clone(), blocks_getter)?,
+    )?;
+
+    check_timewarp(config, block_header, prev_block_header)?;
+
+    // Check timestamp
+    check_future_time(block_header.time, current_time)?;
+
+    // Reject blocks with outdated version
+    check_version(block_header.version, 4)
+}
+
+/// # Errors
+/// If a required previous header is unknown or the new target overflows
+//https://github.com/bitcoin/bitcoin/blob/ae024137bda9fe189f4e7ccf26dbaffd44cbbeb6/src/pow.cpp#L14
+pub fn get_next_work_required(
+    config: &NetworkConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    if (prev_block_header.block_height + 1) % config.difficulty_adjustment_interval != 0 {
+        if config.pow_allow_min_difficulty_blocks {
+            if block_header.time
+                > prev_block_header.block_header.time + 2 * config.pow_target_spacing
+            {
+                return Ok(config.proof_of_work_limit_bits);
+            }
+
+            let mut current_block_header = prev_block_header.clone();
+            while current_block_header.block_header.bits == config.proof_of_work_limit_bits
+                && current_block_header.block_height % config.difficulty_adjustment_interval != 0
+            {
+                current_block_header =
+                    blocks_getter.get_prev_header(&current_block_header.block_header)?;
+            }
+
+            let last_bits = current_block_header.block_header.bits;
+            return Ok(last_bits);
+        }
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    let first_block_height =
+        prev_block_header.block_height - (config.difficulty_adjustment_interval - 1);
+
+    let interval_tail_extend_header = blocks_getter.get_header_by_height(first_block_height)?;
+    calculate_next_work_required(config, prev_block_header, &interval_tail_extend_header)
+}
+
+/// # Errors
+/// If the new target overflows
+/// # Panics
+/// If the target timespan of the config is negative
+//https://github.com/bitcoin/bitcoin/blob/ae024137bda9fe189f4e7ccf26dbaffd44cbbeb6/src/pow.cpp#L50
+pub fn calculate_next_work_required(
+    config: &NetworkConfig,
+    prev_block_header: &ExtendedHeader,
+    first_block_header: &ExtendedHeader,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    let prev_block_time: i64 = prev_block_header.block_header.time.into();
+    let first_block_time: i64 = first_block_header.block_header.time.into();
+
+    let mut actual_time_taken: i64 = prev_block_time - first_block_time;
+    if actual_time_taken < config.pow_target_timespan / 4 {
+        actual_time_taken = config.pow_target_timespan / 4;
+    }
+    if actual_time_taken > config.pow_target_timespan * 4 {
+        actual_time_taken = config.pow_target_timespan * 4;
+    }
+
+    // Special difficulty rule for Testnet4: the first block of the period is used,
+    // since it is not allowed to be a min-difficulty block and preserves the real difficulty
+    let new_target = if config.enforce_bip94 {
+        target_from_bits(first_block_header.block_header.bits)
+    } else {
+        target_from_bits(prev_block_header.block_header.bits)
+    };
+
+    let (mut new_target, new_target_overflow) =
+        new_target.overflowing_mul(<i64 as TryInto<u64>>::try_into(actual_time_taken).unwrap());
+    if new_target_overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+    new_target = new_target
+        / U256::from(<i64 as TryInto<u64>>::try_into(config.pow_target_timespan).unwrap());
+
+    if new_target > config.pow_limit {
+        new_target = config.pow_limit;
+    }
+
+    Ok(new_target.target_to_bits())
+}
+
+/// # Errors
+/// If the first block of a period is more than `MAX_TIMEWARP` older than the previous one
+// BIP94 timewarp fix: https://github.com/bitcoin/bitcoin/blob/v28.0/src/validation.cpp
+pub fn check_timewarp(
+    config: &NetworkConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+) -> Result<(), ValidationError> {
+    if config.enforce_bip94
+        && (prev_block_header.block_height + 1) % config.difficulty_adjustment_interval == 0
+        && block_header.time
+            < prev_block_header
+                .block_header
+                .time
+                .saturating_sub(MAX_TIMEWARP)
+    {
+        return Err(ValidationError::TimewarpAttack);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{get_bitcoin_config, Network};
+    use crate::validation::check_pow_hash;
+    use crate::validation::test_utils::{extend_header, extended_header, MockBlocksGetter};
+
+    // Testnet4 genesis block
+    fn testnet4_genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "7aa0a7ae1e223414cb807e40cd57e667b718e42aaf9306db9102fe28912b7b4e",
+            "time": 1_714_777_860,
+            "bits": 0x1d00_ffff,
+            "nonce": 393_743_547,
+        }))
+        .unwrap()
+    }
+
+    // Bitcoin mainnet blocks 685_440 and 687_455, the first and the last blocks of a period,
+    // and the block 687_456 mined with the new difficulty
+    fn mainnet_retarget_block_headers() -> [Header; 3] {
+        serde_json::from_value(serde_json::json!([
+            {
+                "version": 805_298_180,
+                "prev_block_hash": "00000000000000000006248c28751a176336f5c070f901dc86df190c391d761d",
+                "merkle_root": "534e13aa090e6615a2a6610f49b42ca9caa93f3ce2ca33735ca11444d6705424",
+                "time": 1_622_337_521,
+                "bits": 0x170d_5f7b,
+                "nonce": 1_876_340_370,
+            },
+            {
+                "version": 671_080_452,
+                "prev_block_hash": "0000000000000000000af20ea7974658356688563a5c9c734a3aa6205c8eebff",
+                "merkle_root": "00d2be9b9ef248699c41f6674890d07b4a19aba32195682e64fd39671c98ee4f",
+                "time": 1_623_614_781,
+                "bits": 0x170d_5f7b,
+                "nonce": 603_238_388,
+            },
+            {
+                "version": 536_870_916,
+                "prev_block_hash": "00000000000000000001464428893b618817bff3128a6e17a2c043de53ca4673",
+                "merkle_root": "2ef9ff36a518a37cc3a5a4525efb38b2bcbc9823a2482019b8411c29ec96c018",
+                "time": 1_623_614_836,
+                "bits": 0x170e_1ef9,
+                "nonce": 669_092_191,
+            },
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mainnet_retarget() {
+        let config = get_bitcoin_config(Network::Mainnet);
+        let [first_block_header, prev_block_header, block_header] =
+            mainnet_retarget_block_headers();
+        // Block hashes are only Bitcoin hashes when the header is hashed with double SHA-256
+        let bitcoin_hash = !cfg!(feature = "x11_hash");
+        if bitcoin_hash {
+            assert_eq!(
+                block_header.block_hash().to_string(),
+                "0000000000000000000a8efe0a1e90a140eba888af012c95571367b2ceea50ac"
+            );
+        }
+
+        let first_block_header = extend_header(first_block_header, 685_440, vec![]);
+        // The timestamps of the blocks 687_445 to 687_455
+        let prev_block_header = extend_header(
+            prev_block_header,
+            687_455,
+            vec![
+                1_623_614_781,
+                1_623_613_911,
+                1_623_613_863,
+                1_623_613_853,
+                1_623_613_511,
+                1_623_610_246,
+                1_623_609_946,
+                1_623_609_167,
+                1_623_609_056,
+                1_623_608_171,
+                1_623_607_526,
+            ],
+        );
+        let getter: MockBlocksGetter = [first_block_header].into_iter().collect();
+
+        assert_eq!(
+            check_header(
+                &config,
+                &block_header,
+                &prev_block_header,
+                block_header.time,
+                &getter
+            ),
+            Ok(())
+        );
+        if bitcoin_hash {
+            assert_eq!(
+                check_pow_hash(&block_header.block_hash(), block_header.bits),
+                Ok(())
+            );
+        }
+
+        // The retarget needs the first block of the period
+        assert_eq!(
+            check_header(
+                &config,
+                &block_header,
+                &prev_block_header,
+                block_header.time,
+                &MockBlocksGetter::default()
+            ),
+            Err(ValidationError::HeaderNotFound { height: 685_440 })
+        );
+    }
+
+    #[test]
+    fn test_regtest_no_retargeting() {
+        let config = get_bitcoin_config(Network::Regtest);
+        let genesis_time = testnet4_genesis_block_header().time;
+        let first_block_header = extended_header(genesis_time, 0x207f_ffff, 0);
+        // The period is mined 4 times faster than the target timespan
+        let prev_block_header = extended_header(genesis_time + 2016 * 150, 0x207f_ffff, 2015);
+
+        assert_eq!(
+            calculate_next_work_required(&config, &prev_block_header, &first_block_header),
+            Ok(0x207f_ffff)
+        );
+        assert_eq!(config.pow_limit.target_to_bits(), 0x207f_ffff);
+    }
+
+    #[test]
+    fn test_regtest_no_retargeting_without_ancestors() {
+        let config = get_bitcoin_config(Network::Regtest);
+        let prev_block_header = extended_header(1_714_777_860, 0x207f_ffff, 100);
+        let block_header = Header {
+            time: prev_block_header.block_header.time + 600,
+            ..testnet4_genesis_block_header()
+        };
+
+        // The min-difficulty rule does not walk back the chain on regtest
+        assert_eq!(
+            get_next_work_required(
+                &config,
+                &block_header,
+                &prev_block_header,
+                &MockBlocksGetter::default(),
+            ),
+            Ok(0x207f_ffff)
+        );
+    }
+
+    #[test]
+    fn test_bip94_retarget_uses_first_block_of_period() {
+        let genesis_time = testnet4_genesis_block_header().time;
+        let first_block_header = extended_header(genesis_time, 0x1c00_ffff, 2016);
+        // The last block of the period is a min-difficulty block
+        let prev_block_header = extended_header(genesis_time + 2016 * 600, 0x1d00_ffff, 4031);
+
+        let config = get_bitcoin_config(Network::Testnet4);
+        assert_eq!(
+            calculate_next_work_required(&config, &prev_block_header, &first_block_header),
+            Ok(0x1c00_ffff)
+        );
+
+        // Testnet3 retargets from the last block of the period
+        let config = get_bitcoin_config(Network::Testnet);
+        assert_eq!(
+            calculate_next_work_required(&config, &prev_block_header, &first_block_header),
+            Ok(0x1d00_ffff)
+        );
+    }
+
+    #[test]
+    fn test_bip94_timewarp() {
+        let genesis = testnet4_genesis_block_header();
+        let config = get_bitcoin_config(Network::Testnet4);
+        let prev_block_header = extended_header(genesis.time, 0x1d00_ffff, 2015);
+
+        let block_header = Header {
+            time: genesis.time - MAX_TIMEWARP,
+            ..genesis.clone()
+        };
+        assert_eq!(
+            check_timewarp(&config, &block_header, &prev_block_header),
+            Ok(())
+        );
+
+        // The rule applies to the first block of a period only
+        let block_header = Header {
+            time: genesis.time - MAX_TIMEWARP - 1,
+            ..genesis
+        };
+        assert_eq!(
+            check_timewarp(
+                &config,
+                &block_header,
+                &extended_header(prev_block_header.block_header.time, 0x1d00_ffff, 2014),
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_timewarp(
+                &get_bitcoin_config(Network::Testnet),
+                &block_header,
+                &prev_block_header,
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_bip94_timewarp_attack() {
+        let genesis = testnet4_genesis_block_header();
+        let config = get_bitcoin_config(Network::Testnet4);
+        let prev_block_header = extended_header(genesis.time, 0x1d00_ffff, 2015);
+
+        let block_header = Header {
+            time: genesis.time - MAX_TIMEWARP - 1,
+            ..genesis
+        };
+        assert_eq!(
+            check_timewarp(&config, &block_header, &prev_block_header),
+            Err(ValidationError::TimewarpAttack)
+        );
+    }
+}
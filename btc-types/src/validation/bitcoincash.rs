@@ -0,0 +1,384 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, check_version, get_median_time_past,
+    BlocksGetter, ValidationError,
+};
+use crate::header::{ExtendedHeader, Header};
+use crate::network::BitcoinCashConfig;
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Number of blocks in the cw-144 difficulty adjustment window
+const DAA_WINDOW: u64 = 144;
+
+/// Checks the header against the previous one: the difficulty, the timestamps and the version.
+/// The proof of work hash is checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+// Reference implementation: https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/validation.cpp#L3640
+pub fn check_header(
+    config: &BitcoinCashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let expected_bits =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+    check_bits(expected_bits, block_header.bits)?;
+
+    // Check timestamp against prev
+    check_median_time_past(
+        block_header.time,
+        get_median_time_past(prev_block_header.clone(), blocks_getter)?,
+    )?;
+
+    // Check timestamp
+    check_future_time(block_header.time, current_time)?;
+
+    // Reject blocks with outdated version
+    check_version(block_header.version, 4)
+}
+
+/// # Errors
+/// If the previous headers predate the supported rules or are unknown, or the new target overflows
+//https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/pow.cpp#L39
+pub fn get_next_work_required(
+    config: &BitcoinCashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    // Special difficulty rule for Testnet: if the new block's timestamp is more than
+    // 2 * 10 minutes then allow mining of a min-difficulty block.
+    if config.pow_allow_min_difficulty_blocks
+        && i64::from(block_header.time)
+            > i64::from(prev_block_header.block_header.time) + 2 * config.pow_target_spacing
+    {
+        return Ok(config.proof_of_work_limit_bits);
+    }
+
+    if prev_block_header.block_height >= config.asert_activation_height {
+        return get_next_asert_work_required(config, prev_block_header);
+    }
+
+    if prev_block_header.block_height < config.daa_height {
+        return Err(ValidationError::BeforeDaaActivation);
+    }
+    get_next_cash_work_required(config, prev_block_header, blocks_getter)
+}
+
+//https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/pow.cpp#L247
+fn get_next_asert_work_required(
+    config: &BitcoinCashConfig,
+    prev_block_header: &ExtendedHeader,
+) -> Result<u32, ValidationError> {
+    let anchor = &config.asert_anchor;
+    if prev_block_header.block_height < anchor.height {
+        return Err(ValidationError::BeforeAsertAnchor);
+    }
+
+    let time_diff = i64::from(prev_block_header.block_header.time) - anchor.prev_block_time;
+    let height_diff = i64::try_from(prev_block_header.block_height - anchor.height).unwrap();
+
+    Ok(calculate_asert(
+        config,
+        target_from_bits(anchor.bits),
+        time_diff,
+        height_diff,
+    )?
+    .target_to_bits())
+}
+
+// aserti3-2d: https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/pow.cpp#L184
+fn calculate_asert(
+    config: &BitcoinCashConfig,
+    ref_target: U256,
+    time_diff: i64,
+    height_diff: i64,
+) -> Result<U256, ValidationError> {
+    // Fixed-point exponent with 16 fractional bits, the division truncates towards zero
+    let exponent = ((time_diff - config.pow_target_spacing * (height_diff + 1)) * 65536)
+        / config.asert_half_life;
+
+    // The integer part of the exponent is applied as a shift, the fractional part
+    // is approximated by a cubic polynomial: 2^x ~= 1 + 0.695502049*x + 0.2262698*x^2 + 0.0782318*x^3
+    let mut shifts = exponent >> 16;
+    let frac = u64::try_from(exponent & 0xffff).unwrap();
+    let factor = 65536
+        + ((195_766_423_245_049 * frac
+            + 971_821_376 * frac * frac
+            + 5127 * frac * frac * frac
+            + (1 << 47))
+            >> 48);
+
+    let (mut next_target, overflow) = ref_target.overflowing_mul(factor);
+    if overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+
+    // Divide by 65536, the factor is a 16.16 fixed-point number
+    shifts -= 16;
+    if shifts <= 0 {
+        let shifts = u32::try_from(-shifts).unwrap_or(u32::MAX);
+        next_target = if shifts >= 256 {
+            U256::ZERO
+        } else {
+            next_target >> shifts
+        };
+    } else {
+        let shifts = u32::try_from(shifts).unwrap_or(u32::MAX);
+        if shifts >= 256 || next_target.bits() + shifts > 256 {
+            // The target overflowed, use the minimal difficulty
+            return Ok(config.pow_limit);
+        }
+        next_target = next_target << shifts;
+    }
+
+    Ok(if next_target == U256::ZERO {
+        // The target can not be lower than 1
+        U256::ONE
+    } else if next_target > config.pow_limit {
+        config.pow_limit
+    } else {
+        next_target
+    })
+}
+
+//https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/pow.cpp#L156
+fn get_next_cash_work_required(
+    config: &BitcoinCashConfig,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    let last_block_header = get_suitable_block(prev_block_header, blocks_getter)?;
+    let first_block_header = get_suitable_block(
+        &blocks_getter.get_header_by_height(prev_block_header.block_height - DAA_WINDOW)?,
+        blocks_getter,
+    )?;
+
+    Ok(compute_target(config, &first_block_header, &last_block_header)?.target_to_bits())
+}
+
+/// Returns the block with the median timestamp among the given block and its two ancestors,
+/// to reduce the impact of timestamp manipulation.
+//https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/pow.cpp#L121
+fn get_suitable_block(
+    block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<ExtendedHeader, ValidationError> {
+    let prev_block_header = blocks_getter.get_prev_header(&block_header.block_header)?;
+    let prev_prev_block_header = blocks_getter.get_prev_header(&prev_block_header.block_header)?;
+
+    let mut blocks = [
+        prev_prev_block_header,
+        prev_block_header,
+        block_header.clone(),
+    ];
+    // Sorting network from the reference, it defines which block is picked on equal timestamps
+    if blocks[0].block_header.time > blocks[2].block_header.time {
+        blocks.swap(0, 2);
+    }
+    if blocks[0].block_header.time > blocks[1].block_header.time {
+        blocks.swap(0, 1);
+    }
+    if blocks[1].block_header.time > blocks[2].block_header.time {
+        blocks.swap(1, 2);
+    }
+
+    let [_, median_block, _] = blocks;
+    Ok(median_block)
+}
+
+//https://github.com/bitcoin-cash-node/bitcoin-cash-node/blob/v28.0.0/src/pow.cpp#L89
+fn compute_target(
+    config: &BitcoinCashConfig,
+    first_block_header: &ExtendedHeader,
+    last_block_header: &ExtendedHeader,
+) -> Result<U256, ValidationError> {
+    if last_block_header.block_height <= first_block_header.block_height {
+        return Err(ValidationError::InvalidDaaWindow);
+    }
+
+    let (work, work_underflow) = last_block_header
+        .chain_work
+        .overflowing_sub(first_block_header.chain_work);
+    if work_underflow {
+        return Err(ValidationError::ChainWorkUnderflow);
+    }
+
+    let spacing = u64::try_from(config.pow_target_spacing).unwrap();
+    let (mut work, work_overflow) = work.overflowing_mul(spacing);
+    if work_overflow {
+        return Err(ValidationError::ChainWorkOverflow);
+    }
+
+    // Limit the adjustment to a factor of 2 in either direction
+    let actual_timespan = (i64::from(last_block_header.block_header.time)
+        - i64::from(first_block_header.block_header.time))
+    .clamp(
+        72 * config.pow_target_spacing,
+        288 * config.pow_target_spacing,
+    );
+    work = work / U256::from(u64::try_from(actual_timespan).unwrap());
+
+    // The target is (2^256 - work) / work, as the work is computed as 2^256 / (target + 1)
+    let (negated_work, _) = (!work).overflowing_add(U256::ONE);
+    let next_target = negated_work / work;
+
+    Ok(if next_target > config.pow_limit {
+        config.pow_limit
+    } else {
+        next_target
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{get_bitcoincash_config, Network};
+    use crate::utils::work_from_bits;
+    use crate::validation::test_utils::{
+        chain, genesis_block_header, spaced_times, MockBlocksGetter,
+    };
+
+    const ANCHOR_BITS: u32 = 0x1804_dafe;
+
+    fn asert_bits(time_diff: i64, height_diff: i64) -> u32 {
+        let config = get_bitcoincash_config(Network::Mainnet);
+        calculate_asert(
+            &config,
+            target_from_bits(ANCHOR_BITS),
+            time_diff,
+            height_diff,
+        )
+        .unwrap()
+        .target_to_bits()
+    }
+
+    #[test]
+    fn test_asert_on_schedule() {
+        // The difficulty is kept when blocks are mined exactly on schedule
+        assert_eq!(asert_bits(600, 0), ANCHOR_BITS);
+        assert_eq!(asert_bits(600 * 101, 100), ANCHOR_BITS);
+    }
+
+    #[test]
+    fn test_asert_half_life() {
+        // The target doubles for every half-life behind the schedule and halves for every
+        // half-life ahead of it
+        assert_eq!(asert_bits(600 + 172_800, 0), 0x1809_b5fc);
+        assert_eq!(asert_bits(600 - 172_800, 0), 0x1802_6d7f);
+    }
+
+    #[test]
+    fn test_asert_fractional_exponent() {
+        assert_eq!(asert_bits(600 + 3600, 5), 0x1804_ddfd);
+        assert_eq!(asert_bits(6900, 10), 0x1804_dc7d);
+    }
+
+    #[test]
+    fn test_asert_limits() {
+        // Clamped to the proof of work limit
+        assert_eq!(asert_bits(600 + 172_800 * 40, 0), 0x1d00_ffff);
+        assert_eq!(asert_bits(600 + 172_800 * 300, 0), 0x1d00_ffff);
+        // The target can not be lower than 1
+        assert_eq!(asert_bits(600 - 172_800 * 300, 0), 0x0101_0000);
+    }
+
+    #[test]
+    fn test_asert_from_anchor() {
+        let config = get_bitcoincash_config(Network::Mainnet);
+        let block_header = Header {
+            time: u32::try_from(config.asert_anchor.prev_block_time + 600 * 2).unwrap(),
+            bits: ANCHOR_BITS,
+            ..genesis_block_header()
+        };
+        let prev_block_header = ExtendedHeader {
+            block_hash: block_header.block_hash(),
+            block_header: Header {
+                time: u32::try_from(config.asert_anchor.prev_block_time + 600).unwrap(),
+                ..block_header.clone()
+            }
+            .into_light(),
+            chain_work: work_from_bits(ANCHOR_BITS),
+            block_height: config.asert_anchor.height,
+            recent_timestamps: vec![],
+        };
+
+        assert_eq!(
+            get_next_work_required(
+                &config,
+                &block_header,
+                &prev_block_header,
+                &MockBlocksGetter::default(),
+            ),
+            Ok(ANCHOR_BITS)
+        );
+    }
+
+    #[test]
+    fn test_cw144() {
+        let config = get_bitcoincash_config(Network::Mainnet);
+        let first_height = config.daa_height;
+
+        for (spacing, expected_bits) in [
+            (600, 0x1d00_ffff),
+            // Blocks mined twice as fast double the difficulty
+            (300, 0x1c7f_ff80),
+            // The adjustment is limited to a factor of 2
+            (100, 0x1c7f_ff80),
+            // Clamped to the proof of work limit
+            (1200, 0x1d00_ffff),
+        ] {
+            let (getter, prev_block_header) =
+                chain(first_height, 0x1d00_ffff, spaced_times(150, spacing));
+            let block_header = Header {
+                time: prev_block_header.block_header.time + spacing,
+                ..prev_block_header.block_header.clone()
+            };
+
+            assert_eq!(
+                get_next_work_required(&config, &block_header, &prev_block_header, &getter),
+                Ok(expected_bits)
+            );
+        }
+    }
+
+    #[test]
+    fn test_testnet_min_difficulty() {
+        let config = get_bitcoincash_config(Network::Testnet);
+        let (getter, prev_block_header) =
+            chain(config.daa_height, 0x1c7f_ff80, spaced_times(150, 300));
+
+        let block_header = Header {
+            time: prev_block_header.block_header.time + 1201,
+            ..prev_block_header.block_header.clone()
+        };
+        assert_eq!(
+            get_next_work_required(&config, &block_header, &prev_block_header, &getter),
+            Ok(config.proof_of_work_limit_bits)
+        );
+    }
+
+    #[test]
+    fn test_pre_daa_blocks() {
+        let config = get_bitcoincash_config(Network::Mainnet);
+        let (getter, prev_block_header) =
+            chain(config.daa_height - 150, 0x1d00_ffff, spaced_times(150, 600));
+
+        assert_eq!(
+            get_next_work_required(
+                &config,
+                &prev_block_header.block_header,
+                &prev_block_header,
+                &getter,
+            ),
+            Err(ValidationError::BeforeDaaActivation)
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, check_version, get_median_time_past,
+    BlocksGetter, ValidationError,
+};
+use crate::header::{ExtendedHeader, Header};
+use crate::network::DashConfig;
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Checks the header against the previous one: the difficulty, the timestamps and the version.
+/// The proof of work hash is checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+// Reference implementation: https://github.com/dashpay/dash/blob/v22.0.0/src/validation.cpp
+pub fn check_header(
+    config: &DashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let expected_bits =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+    check_bits(expected_bits, block_header.bits)?;
+
+    // Check timestamp against prev
+    check_median_time_past(
+        block_header.time,
+        get_median_time_past(prev_block_header.clone(), blocks_getter)?,
+    )?;
+
+    // Check timestamp
+    check_future_time(block_header.time, current_time)?;
+
+    // Reject blocks with outdated version
+    check_version(block_header.version, 4)
+}
+
+/// # Errors
+/// If the previous headers predate Dark Gravity Wave or are unknown, or the new target overflows
+//https://github.com/dashpay/dash/blob/v22.0.0/src/pow.cpp#L201
+pub fn get_next_work_required(
+    config: &DashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    if prev_block_header.block_height + 1 < config.dgw_height {
+        return Err(ValidationError::BeforeDgwActivation);
+    }
+
+    dark_gravity_wave(config, block_header, prev_block_header, blocks_getter)
+}
+
+// Dark Gravity Wave v3: https://github.com/dashpay/dash/blob/v22.0.0/src/pow.cpp#L82
+fn dark_gravity_wave(
+    config: &DashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    let past_blocks = config.dgw_past_blocks;
+
+    // Make sure we have at least (past_blocks + 1) blocks, otherwise just return the limit
+    if prev_block_header.block_height < past_blocks {
+        return Ok(config.proof_of_work_limit_bits);
+    }
+
+    if config.pow_allow_min_difficulty_blocks {
+        let block_time = i64::from(block_header.time);
+        let prev_block_time = i64::from(prev_block_header.block_header.time);
+
+        // Recent block is more than 2 hours old
+        if block_time > prev_block_time + 2 * 60 * 60 {
+            return Ok(config.proof_of_work_limit_bits);
+        }
+
+        // Recent block is more than 10 minutes old
+        if block_time > prev_block_time + config.pow_target_spacing * 4 {
+            let (new_target, overflow) =
+                target_from_bits(prev_block_header.block_header.bits).overflowing_mul(10);
+            if overflow || new_target > config.pow_limit {
+                return Ok(config.proof_of_work_limit_bits);
+            }
+            return Ok(new_target.target_to_bits());
+        }
+    }
+
+    let mut current_block_header = prev_block_header.clone();
+    let mut past_target_avg = U256::ZERO;
+
+    for count_blocks in 1..=past_blocks {
+        let target = target_from_bits(current_block_header.block_header.bits);
+        if count_blocks == 1 {
+            past_target_avg = target;
+        } else {
+            // Not really an average, but it is what the reference implementation computes
+            let (weighted_avg, overflow) = past_target_avg.overflowing_mul(count_blocks);
+            if overflow {
+                return Err(ValidationError::TargetOverflow);
+            }
+            let (weighted_sum, overflow) = weighted_avg.overflowing_add(target);
+            if overflow {
+                return Err(ValidationError::TargetOverflow);
+            }
+            past_target_avg = weighted_sum / U256::from(count_blocks + 1);
+        }
+
+        if count_blocks != past_blocks {
+            current_block_header =
+                blocks_getter.get_prev_header(&current_block_header.block_header)?;
+        }
+    }
+
+    // The timespan covers (past_blocks - 1) intervals only, as in the reference implementation
+    let target_timespan = i64::try_from(past_blocks).unwrap() * config.pow_target_spacing;
+    let actual_timespan = (i64::from(prev_block_header.block_header.time)
+        - i64::from(current_block_header.block_header.time))
+    .clamp(target_timespan / 3, target_timespan * 3);
+
+    let (mut new_target, overflow) =
+        past_target_avg.overflowing_mul(u64::try_from(actual_timespan).unwrap());
+    if overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+    new_target = new_target / U256::from(u64::try_from(target_timespan).unwrap());
+
+    if new_target > config.pow_limit {
+        new_target = config.pow_limit;
+    }
+
+    Ok(new_target.target_to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{get_dash_config, Network};
+    use crate::validation::test_utils::{chain, spaced_times};
+
+    const BITS: u32 = 0x1b04_04cb;
+
+    fn next_bits(config: &DashConfig, spacing: u32, block_time_delta: u32) -> u32 {
+        let (getter, prev_block_header) = chain(100_000 - 23, BITS, spaced_times(24, spacing));
+        let block_header = Header {
+            time: prev_block_header.block_header.time + block_time_delta,
+            ..prev_block_header.block_header.clone()
+        };
+
+        get_next_work_required(config, &block_header, &prev_block_header, &getter).unwrap()
+    }
+
+    #[test]
+    fn test_dark_gravity_wave() {
+        let config = get_dash_config(Network::Mainnet);
+
+        // The timespan covers 23 intervals of the 24 blocks window
+        assert_eq!(next_bits(&config, 150, 150), 0x1b03_d9ed);
+        // The adjustment is limited to a factor of 3 in either direction
+        assert_eq!(next_bits(&config, 10, 10), 0x1b01_56ee);
+        assert_eq!(next_bits(&config, 1000, 1000), 0x1b0c_0e61);
+    }
+
+    #[test]
+    fn test_testnet_min_difficulty() {
+        let config = get_dash_config(Network::Testnet);
+
+        // The target is increased tenfold if the block is more than 10 minutes late
+        assert_eq!(next_bits(&config, 150, 601), 0x1b28_2fee);
+        // The minimal difficulty is allowed if the block is more than 2 hours late
+        assert_eq!(
+            next_bits(&config, 150, 2 * 60 * 60 + 1),
+            config.proof_of_work_limit_bits
+        );
+        // Mainnet does not have the special rules
+        assert_eq!(
+            next_bits(&get_dash_config(Network::Mainnet), 150, 601),
+            0x1b03_d9ed
+        );
+    }
+
+    #[test]
+    fn test_pre_dgw_blocks() {
+        let config = get_dash_config(Network::Mainnet);
+        let (getter, prev_block_header) =
+            chain(config.dgw_height - 25, BITS, spaced_times(24, 150));
+
+        assert_eq!(
+            get_next_work_required(
+                &config,
+                &prev_block_header.block_header,
+                &prev_block_header,
+                &getter,
+            ),
+            Err(ValidationError::BeforeDgwActivation)
+        );
+    }
+}
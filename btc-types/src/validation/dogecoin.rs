@@ -0,0 +1,174 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, get_median_time_past, BlocksGetter,
+    ValidationError,
+};
+use crate::header::{ExtendedHeader, Header};
+use crate::network::AuxPowConfig;
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Checks the header against the previous one: the difficulty and the timestamps.
+/// The proof of work hash and the AuxPoW are checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+pub fn check_header(
+    config: &AuxPowConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let expected_bits =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+    check_bits(expected_bits, block_header.bits)?;
+
+    // Check timestamp against median time past of the previous 11 blocks
+    check_median_time_past(
+        block_header.time,
+        get_median_time_past(prev_block_header.clone(), blocks_getter)?,
+    )?;
+
+    // Reject blocks whose timestamp is more than 2 hours ahead of local time
+    check_future_time(block_header.time, current_time)
+}
+
+// source https://github.com/dogecoin/dogecoin/blob/2c513d0172e8bc86fe9a337693b26f2fdf68a013/src/pow.cpp#L17
+fn allow_min_difficulty_for_block(
+    config: &AuxPowConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+) -> bool {
+    // check if the chain allows minimum difficulty blocks
+    if !config.pow_allow_min_difficulty_blocks {
+        return false;
+    }
+
+    // Dogecoin: Magic number at which reset protocol switches
+    // check if we allow minimum difficulty at this block-height
+    if prev_block_header.block_height < 157_500 {
+        return false;
+    }
+
+    // Allow for a minimum block time if the elapsed time > 2*nTargetSpacing
+    block_header.time > prev_block_header.block_header.time + config.pow_target_spacing * 2
+}
+
+/// # Errors
+/// If a required previous header is unknown or the new target overflows
+// source https://github.com/dogecoin/dogecoin/blob/2c513d0172e8bc86fe9a337693b26f2fdf68a013/src/pow.cpp#L17
+pub fn get_next_work_required(
+    config: &AuxPowConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    // Dogecoin: Special rules for minimum difficulty blocks with Digishield
+    if allow_min_difficulty_for_block(config, block_header, prev_block_header) {
+        // Special difficulty rule for testnet:
+        // If the new block's timestamp is more than 2* nTargetSpacing minutes
+        // then allow mining of a min-difficulty block.
+        return Ok(config.proof_of_work_limit_bits);
+    }
+
+    // Only change once per difficulty adjustment interval
+    let new_difficulty_protocol = prev_block_header.block_height >= 145_000;
+    let difficulty_adjustment_interval = if new_difficulty_protocol {
+        1
+    } else {
+        config.difficulty_adjustment_interval
+    };
+
+    if (prev_block_header.block_height + 1) % difficulty_adjustment_interval != 0 {
+        if config.pow_allow_min_difficulty_blocks {
+            // Special difficulty rule for testnet:
+            // If the new block's timestamp is more than 2* 10 minutes
+            // then allow mining of a min-difficulty block.
+            if block_header.time
+                > prev_block_header.block_header.time + config.pow_target_spacing * 2
+            {
+                return Ok(config.proof_of_work_limit_bits);
+            }
+
+            // Return the last non-special-min-difficulty-rules-block
+            let mut current_block_header = prev_block_header.clone();
+
+            while current_block_header.block_header.bits == config.proof_of_work_limit_bits
+                && current_block_header.block_height % config.difficulty_adjustment_interval != 0
+            {
+                current_block_header =
+                    blocks_getter.get_prev_header(&current_block_header.block_header)?;
+            }
+
+            return Ok(current_block_header.block_header.bits);
+        }
+
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    // Litecoin: This fixes an issue where a 51% attack can change difficulty at will.
+    // Go back the full period unless it's the first retarget after genesis. Code courtesy of Art Forz
+    let mut blocks_to_go_back = difficulty_adjustment_interval - 1;
+    if prev_block_header.block_height + 1 != difficulty_adjustment_interval {
+        blocks_to_go_back = difficulty_adjustment_interval;
+    }
+
+    // Go back by what we want to be 14 days worth of blocks
+    let height_first = prev_block_header
+        .block_height
+        .checked_sub(blocks_to_go_back)
+        .ok_or(ValidationError::HeightUnderflow)?;
+
+    // TODO: check if it is correct to get block header by height from mainchain without looping to find the ancestor
+    let first_block_time = blocks_getter
+        .get_header_by_height(height_first)?
+        .block_header
+        .time;
+
+    calculate_next_work_required(config, prev_block_header, i64::from(first_block_time))
+}
+
+/// # Errors
+/// If the new target overflows
+/// # Panics
+/// If the target timespan of the config is negative
+// source https://github.com/dogecoin/dogecoin/blob/2c513d0172e8bc86fe9a337693b26f2fdf68a013/src/dogecoin.cpp#L41
+pub fn calculate_next_work_required(
+    config: &AuxPowConfig,
+    prev_block_header: &ExtendedHeader,
+    first_block_time: i64,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    let retarget_timespan = config.pow_target_timespan;
+    let modulated_timespan = i64::from(prev_block_header.block_header.time) - first_block_time;
+
+    let mut modulated_timespan = retarget_timespan + (modulated_timespan - retarget_timespan) / 8;
+
+    let min_timespan = retarget_timespan - (retarget_timespan / 4);
+    let max_timespan = retarget_timespan + (retarget_timespan / 2);
+
+    if modulated_timespan < min_timespan {
+        modulated_timespan = min_timespan;
+    } else if modulated_timespan > max_timespan {
+        modulated_timespan = max_timespan;
+    }
+
+    let new_target = target_from_bits(prev_block_header.block_header.bits);
+
+    let (mut new_target, new_target_overflow) =
+        new_target.overflowing_mul(<i64 as TryInto<u64>>::try_into(modulated_timespan).unwrap());
+    if new_target_overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+    new_target =
+        new_target / U256::from(<i64 as TryInto<u64>>::try_into(retarget_timespan).unwrap());
+
+    if new_target > config.pow_limit {
+        new_target = config.pow_limit;
+    }
+
+    Ok(new_target.target_to_bits())
+}
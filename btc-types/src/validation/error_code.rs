@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+/// Prefix added by the NEAR runtime to the message of a contract panic
+const PANIC_PREFIX: &str = "Smart contract panicked: ";
+
+/// Stable code at the start of every validation panic message of the contract,
+/// formatted as `<code>: <description>`.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    BadDiffBits,
+    TimeTooOld,
+    TimeTooNew,
+    TimeTooFarAheadOfMtp,
+    TimewarpAttack,
+    BadVersion,
+    BadPow,
+    PrevNotFound,
+    HeaderNotFound,
+    DifficultyCalculation,
+    UnsupportedHeight,
+    BadHeartwoodRoot,
+    AuxPowChainMerkleBranchTooLong,
+    AuxPowMissingFlag,
+    AuxPowChainId,
+    AuxPowParentChainId,
+    AuxPowCoinbase,
+    AuxPowMerkleRoot,
+    AuxPowMissingChainMerkleRoot,
+    AuxPowMultipleMergedMiningHeaders,
+    AuxPowMergedMiningHeaderPosition,
+    AuxPowChainMerkleRootTooLate,
+    AuxPowMissingSizeAndNonce,
+    AuxPowChainMerkleBranchSize,
+    AuxPowIndex,
+    AuxPowParentPow,
+    Equihash,
+    SignetBlockSignature,
+    ReorgLimit,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 29] = [
+        ErrorCode::BadDiffBits,
+        ErrorCode::TimeTooOld,
+        ErrorCode::TimeTooNew,
+        ErrorCode::TimeTooFarAheadOfMtp,
+        ErrorCode::TimewarpAttack,
+        ErrorCode::BadVersion,
+        ErrorCode::BadPow,
+        ErrorCode::PrevNotFound,
+        ErrorCode::HeaderNotFound,
+        ErrorCode::DifficultyCalculation,
+        ErrorCode::UnsupportedHeight,
+        ErrorCode::BadHeartwoodRoot,
+        ErrorCode::AuxPowChainMerkleBranchTooLong,
+        ErrorCode::AuxPowMissingFlag,
+        ErrorCode::AuxPowChainId,
+        ErrorCode::AuxPowParentChainId,
+        ErrorCode::AuxPowCoinbase,
+        ErrorCode::AuxPowMerkleRoot,
+        ErrorCode::AuxPowMissingChainMerkleRoot,
+        ErrorCode::AuxPowMultipleMergedMiningHeaders,
+        ErrorCode::AuxPowMergedMiningHeaderPosition,
+        ErrorCode::AuxPowChainMerkleRootTooLate,
+        ErrorCode::AuxPowMissingSizeAndNonce,
+        ErrorCode::AuxPowChainMerkleBranchSize,
+        ErrorCode::AuxPowIndex,
+        ErrorCode::AuxPowParentPow,
+        ErrorCode::Equihash,
+        ErrorCode::SignetBlockSignature,
+        ErrorCode::ReorgLimit,
+    ];
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::BadDiffBits => "bad-diffbits",
+            ErrorCode::TimeTooOld => "time-too-old",
+            ErrorCode::TimeTooNew => "time-too-new",
+            ErrorCode::TimeTooFarAheadOfMtp => "time-too-far-ahead-of-mtp",
+            ErrorCode::TimewarpAttack => "time-timewarp-attack",
+            ErrorCode::BadVersion => "bad-version",
+            ErrorCode::BadPow => "bad-pow",
+            ErrorCode::PrevNotFound => "prev-not-found",
+            ErrorCode::HeaderNotFound => "header-not-found",
+            ErrorCode::DifficultyCalculation => "bad-diff-calculation",
+            ErrorCode::UnsupportedHeight => "unsupported-height",
+            ErrorCode::BadHeartwoodRoot => "bad-heartwood-root-in-block",
+            ErrorCode::AuxPowChainMerkleBranchTooLong => "bad-auxpow-chain-merkle-branch-too-long",
+            ErrorCode::AuxPowMissingFlag => "bad-auxpow-missing-flag",
+            ErrorCode::AuxPowChainId => "bad-auxpow-chain-id",
+            ErrorCode::AuxPowParentChainId => "bad-auxpow-parent-chain-id",
+            ErrorCode::AuxPowCoinbase => "bad-auxpow-coinbase",
+            ErrorCode::AuxPowMerkleRoot => "bad-auxpow-merkle-root",
+            ErrorCode::AuxPowMissingChainMerkleRoot => "bad-auxpow-missing-chain-merkle-root",
+            ErrorCode::AuxPowMultipleMergedMiningHeaders => "bad-auxpow-multiple-mm-headers",
+            ErrorCode::AuxPowMergedMiningHeaderPosition => "bad-auxpow-mm-header-position",
+            ErrorCode::AuxPowChainMerkleRootTooLate => "bad-auxpow-chain-merkle-root-too-late",
+            ErrorCode::AuxPowMissingSizeAndNonce => "bad-auxpow-missing-size-and-nonce",
+            ErrorCode::AuxPowChainMerkleBranchSize => "bad-auxpow-chain-merkle-branch-size",
+            ErrorCode::AuxPowIndex => "bad-auxpow-index",
+            ErrorCode::AuxPowParentPow => "bad-auxpow-parent-pow",
+            ErrorCode::Equihash => "bad-equihash",
+            ErrorCode::SignetBlockSignature => "bad-signet-blksig",
+            ErrorCode::ReorgLimit => "reorg-limit",
+        }
+    }
+
+    /// Extracts the code from a contract panic message. The message may be wrapped
+    /// in the debug output of the transaction failure, as returned by the RPC.
+    pub fn parse(message: &str) -> Option<Self> {
+        let message = message
+            .rsplit_once(PANIC_PREFIX)
+            .map_or(message, |(_, panic_message)| panic_message);
+        let (code, _) = message.split_once(':')?;
+        code.trim().parse().ok()
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownErrorCode(pub String);
+
+impl std::fmt::Display for UnknownErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown error code: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownErrorCode {}
+
+impl FromStr for ErrorCode {
+    type Err = UnknownErrorCode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ErrorCode::ALL
+            .into_iter()
+            .find(|code| code.as_str() == s)
+            .ok_or_else(|| UnknownErrorCode(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::ValidationError;
+
+    #[test]
+    fn test_code_round_trip() {
+        for code in ErrorCode::ALL {
+            assert_eq!(code.as_str().parse(), Ok(code));
+        }
+        assert_eq!(
+            "bad-code".parse::<ErrorCode>(),
+            Err(UnknownErrorCode("bad-code".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_validation_error_message_starts_with_code() {
+        let errors = [
+            ValidationError::BadDiffBits {
+                expected: 0x1d00_ffff,
+                actual: 0x207f_ffff,
+            },
+            ValidationError::TimeTooOld,
+            ValidationError::BadPow,
+            ValidationError::PrevBlockNotFound,
+            ValidationError::HeaderNotFound { height: 10 },
+            ValidationError::TargetOverflow,
+            ValidationError::InvalidEquihashSolution("invalid".to_owned()),
+        ];
+
+        for err in errors {
+            assert_eq!(ErrorCode::parse(&err.to_string()), Some(err.code()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "auxpow_header")]
+    fn test_auxpow_error_codes() {
+        use crate::validation::auxpow::AuxPowError;
+
+        let errors = [
+            AuxPowError::ChainMerkleBranchTooLong,
+            AuxPowError::MissingAuxPowFlag,
+            AuxPowError::WrongChainId {
+                actual: 1,
+                expected: 98,
+            },
+            AuxPowError::ParentHasOurChainId,
+            AuxPowError::InvalidCoinbaseTx,
+            AuxPowError::WrongCoinbaseMerkleRoot,
+            AuxPowError::MissingChainMerkleRoot,
+            AuxPowError::MultipleMergedMiningHeaders,
+            AuxPowError::MergedMiningHeaderNotBeforeChainMerkleRoot,
+            AuxPowError::ChainMerkleRootTooLate,
+            AuxPowError::MissingChainMerkleTreeSizeAndNonce,
+            AuxPowError::WrongChainMerkleBranchSize,
+            AuxPowError::WrongIndex,
+            AuxPowError::InsufficientParentPow,
+        ];
+
+        let mut codes = Vec::new();
+        for err in errors {
+            let code = err.code();
+            assert!(ErrorCode::ALL.contains(&code));
+            assert!(!codes.contains(&code), "{code} is shared by several errors");
+            assert_eq!(
+                ErrorCode::parse(&ValidationError::AuxPow(err).to_string()),
+                Some(code)
+            );
+            codes.push(code);
+        }
+    }
+
+    #[test]
+    fn test_parse_transaction_failure() {
+        let failure = r#"ActionError(ActionError { index: Some(0), kind: FunctionCallError(ExecutionError("Smart contract panicked: prev-not-found: PrevBlockNotFound")) })"#;
+        assert_eq!(ErrorCode::parse(failure), Some(ErrorCode::PrevNotFound));
+
+        assert_eq!(
+            ErrorCode::parse("reorg-limit: reorg depth 5 exceeds 3"),
+            Some(ErrorCode::ReorgLimit)
+        );
+        assert_eq!(
+            ErrorCode::parse("Smart contract panicked: Relayer is not active"),
+            None
+        );
+        assert_eq!(
+            ErrorCode::parse("Exceeded the maximum amount of gas allowed to burn per contract"),
+            None
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, check_version, get_median_time_past,
+    BlocksGetter, ValidationError,
+};
+use crate::header::{ExtendedHeader, Header};
+use crate::network::NetworkConfig;
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Checks the header against the previous one: the difficulty, the timestamps and the version.
+/// The proof of work hash is checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+// Reference implementation: https://github.com/litecoin-project/litecoin/blob/09a67c25495e2398437d6a388ee96fb6a266460e/src/validation.cpp#L3630
+pub fn check_header(
+    config: &NetworkConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let expected_bits =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+
+    // Check proof of work
+    check_bits(expected_bits, block_header.bits)?;
+
+    // Check timestamp against prev
+    check_median_time_past(
+        block_header.time,
+        get_median_time_past(prev_block_header.clone(), blocks_getter)?,
+    )?;
+
+    // Check timestamp
+    check_future_time(block_header.time, current_time)?;
+
+    // Reject blocks with outdated version
+    check_version(block_header.version, 4)
+}
+
+/// # Errors
+/// If a required previous header is unknown or the new target overflows
+//https://github.com/litecoin-project/litecoin/blob/09a67c25495e2398437d6a388ee96fb6a266460e/src/pow.cpp#L13
+pub fn get_next_work_required(
+    config: &NetworkConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    if (prev_block_header.block_height + 1) % config.difficulty_adjustment_interval != 0 {
+        if config.pow_allow_min_difficulty_blocks {
+            if block_header.time
+                > prev_block_header.block_header.time + 2 * config.pow_target_spacing
+            {
+                return Ok(config.proof_of_work_limit_bits);
+            }
+
+            let mut current_block_header = prev_block_header.clone();
+            while current_block_header.block_header.bits == config.proof_of_work_limit_bits
+                && current_block_header.block_height % config.difficulty_adjustment_interval != 0
+            {
+                current_block_header =
+                    blocks_getter.get_prev_header(&current_block_header.block_header)?;
+            }
+
+            let last_bits = current_block_header.block_header.bits;
+            return Ok(last_bits);
+        }
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    // Litecoin: This fixes an issue where a 51% attack can change difficulty at will.
+    // Go back the full period unless it's the first retarget after genesis. Code courtesy of Art Forz
+    let mut blocks_to_go_back = config.difficulty_adjustment_interval - 1;
+    if prev_block_header.block_height + 1 != config.difficulty_adjustment_interval {
+        blocks_to_go_back = config.difficulty_adjustment_interval;
+    }
+
+    let first_block_height = prev_block_header.block_height - blocks_to_go_back;
+
+    let interval_tail_extend_header = blocks_getter.get_header_by_height(first_block_height)?;
+    calculate_next_work_required(
+        config,
+        prev_block_header,
+        interval_tail_extend_header.block_header.time.into(),
+    )
+}
+
+/// # Errors
+/// If the new target overflows
+/// # Panics
+/// If the target timespan of the config is negative
+//https://github.com/litecoin-project/litecoin/blob/09a67c25495e2398437d6a388ee96fb6a266460e/src/pow.cpp#L57
+pub fn calculate_next_work_required(
+    config: &NetworkConfig,
+    prev_block_header: &ExtendedHeader,
+    first_block_time: i64,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    let prev_block_time: i64 = prev_block_header.block_header.time.into();
+
+    let mut actual_time_taken: i64 = prev_block_time - first_block_time;
+    if actual_time_taken < config.pow_target_timespan / 4 {
+        actual_time_taken = config.pow_target_timespan / 4;
+    }
+    if actual_time_taken > config.pow_target_timespan * 4 {
+        actual_time_taken = config.pow_target_timespan * 4;
+    }
+
+    let mut new_target = target_from_bits(prev_block_header.block_header.bits);
+
+    let shift: bool = new_target.bits() > config.pow_limit.bits() - 1;
+    if shift {
+        new_target = new_target >> 1;
+    }
+
+    let (mut new_target, new_target_overflow) =
+        new_target.overflowing_mul(<i64 as TryInto<u64>>::try_into(actual_time_taken).unwrap());
+    if new_target_overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+    new_target = new_target
+        / U256::from(<i64 as TryInto<u64>>::try_into(config.pow_target_timespan).unwrap());
+
+    if shift {
+        new_target = new_target << 1;
+    }
+
+    if new_target > config.pow_limit {
+        new_target = config.pow_limit;
+    }
+
+    Ok(new_target.target_to_bits())
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "zcash_header"))]
+mod tests {
+    use super::*;
+    use crate::network::{get_litecoin_config, Network};
+    use crate::validation::test_utils::{chain, genesis_block_header, MockBlocksGetter};
+
+    #[test]
+    fn test_regtest_no_retargeting_without_ancestors() {
+        let config = get_litecoin_config(Network::Regtest);
+        let genesis_time = genesis_block_header().time;
+
+        // Neither the min-difficulty rule nor the retarget walk back the chain on regtest
+        for block_height in [100, 2015] {
+            let (_, prev_block_header) = chain(block_height, 0x207f_ffff, [genesis_time]);
+            let block_header = Header {
+                time: genesis_time + 150,
+                ..genesis_block_header()
+            };
+
+            assert_eq!(
+                get_next_work_required(
+                    &config,
+                    &block_header,
+                    &prev_block_header,
+                    &MockBlocksGetter::default(),
+                ),
+                Ok(0x207f_ffff)
+            );
+        }
+        assert_eq!(config.pow_limit.target_to_bits(), 0x207f_ffff);
+    }
+}
@@ -0,0 +1,476 @@
+//! Consensus rules of the header chains, shared by the contract and the off-chain clients.
+//! The checks read the previous headers through [`BlocksGetter`] and return a
+//! [`ValidationError`] instead of panicking.
+
+use crate::hash::H256;
+use crate::header::{ExtendedHeader, LightHeader};
+use crate::network::{MAX_FUTURE_BLOCK_TIME_LOCAL, MEDIAN_TIME_SPAN};
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+#[cfg(feature = "auxpow_header")]
+pub mod auxpow;
+#[cfg(not(feature = "zcash_header"))]
+pub mod bitcoin;
+#[cfg(not(feature = "zcash_header"))]
+pub mod bitcoincash;
+#[cfg(not(feature = "zcash_header"))]
+pub mod dash;
+#[cfg(not(feature = "zcash_header"))]
+pub mod dogecoin;
+mod error_code;
+#[cfg(not(feature = "zcash_header"))]
+pub mod litecoin;
+#[cfg(not(feature = "zcash_header"))]
+pub mod namecoin;
+#[cfg(feature = "zcash_header")]
+pub mod zcash;
+
+#[cfg(feature = "auxpow_header")]
+pub use auxpow::AuxPowError;
+pub use error_code::{ErrorCode, UnknownErrorCode};
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    BadDiffBits {
+        expected: u32,
+        actual: u32,
+    },
+    TimeTooOld,
+    TimeTooNew,
+    TimeTooFarAheadOfMtp,
+    TimewarpAttack,
+    BadVersion {
+        min_version: i32,
+    },
+    BadPow,
+    PrevBlockNotFound,
+    HeaderNotFound {
+        height: u64,
+    },
+    TargetOverflow,
+    HeightUnderflow,
+    BeforeDaaActivation,
+    BeforeAsertAnchor,
+    BeforeDgwActivation,
+    InvalidDaaWindow,
+    ChainWorkUnderflow,
+    ChainWorkOverflow,
+    BadHeartwoodRoot,
+    InvalidEquihashSolution(String),
+    #[cfg(feature = "auxpow_header")]
+    AuxPow(AuxPowError),
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::BadDiffBits { expected, actual } => write!(
+                f,
+                "bad-diffbits: incorrect proof of work (expected bits {expected:#010x}, got {actual:#010x})"
+            ),
+            ValidationError::TimeTooOld => {
+                write!(f, "time-too-old: block's timestamp is too early")
+            }
+            ValidationError::TimeTooNew => {
+                write!(f, "time-too-new: block timestamp too far in the future")
+            }
+            ValidationError::TimeTooFarAheadOfMtp => write!(
+                f,
+                "time-too-far-ahead-of-mtp: block timestamp is too far ahead of median-time-past"
+            ),
+            ValidationError::TimewarpAttack => write!(
+                f,
+                "time-timewarp-attack: block's timestamp is too early on diff adjustment block"
+            ),
+            ValidationError::BadVersion { min_version } => write!(
+                f,
+                "bad-version: block version must be at least {min_version}"
+            ),
+            ValidationError::BadPow => write!(f, "bad-pow: block should have correct pow"),
+            ValidationError::PrevBlockNotFound => write!(f, "prev-not-found: PrevBlockNotFound"),
+            ValidationError::HeaderNotFound { height } => {
+                write!(f, "header-not-found: no main chain header at height {height}")
+            }
+            ValidationError::TargetOverflow => write!(f, "bad-diff-calculation: new target overflow"),
+            ValidationError::HeightUnderflow => {
+                write!(f, "bad-diff-calculation: height underflow when calculating first block height")
+            }
+            ValidationError::BeforeDaaActivation => write!(
+                f,
+                "unsupported-height: blocks before the cw-144 DAA activation are not supported"
+            ),
+            ValidationError::BeforeAsertAnchor => {
+                write!(f, "unsupported-height: blocks before the ASERT anchor block are not supported")
+            }
+            ValidationError::BeforeDgwActivation => write!(
+                f,
+                "unsupported-height: blocks before the Dark Gravity Wave activation are not supported"
+            ),
+            ValidationError::InvalidDaaWindow => write!(f, "bad-diff-calculation: invalid DAA window"),
+            ValidationError::ChainWorkUnderflow => write!(f, "bad-diff-calculation: chain work underflow"),
+            ValidationError::ChainWorkOverflow => write!(f, "bad-diff-calculation: chain work overflow"),
+            ValidationError::BadHeartwoodRoot => write!(
+                f,
+                "bad-heartwood-root-in-block: hashLightClientRoot must be null at Heartwood activation"
+            ),
+            ValidationError::InvalidEquihashSolution(err) => {
+                write!(f, "bad-equihash: invalid Equihash solution: {err}")
+            }
+            #[cfg(feature = "auxpow_header")]
+            ValidationError::AuxPow(err) => write!(f, "{}: {err}", err.code()),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl ValidationError {
+    /// The code at the start of the error message
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ValidationError::BadDiffBits { .. } => ErrorCode::BadDiffBits,
+            ValidationError::TimeTooOld => ErrorCode::TimeTooOld,
+            ValidationError::TimeTooNew => ErrorCode::TimeTooNew,
+            ValidationError::TimeTooFarAheadOfMtp => ErrorCode::TimeTooFarAheadOfMtp,
+            ValidationError::TimewarpAttack => ErrorCode::TimewarpAttack,
+            ValidationError::BadVersion { .. } => ErrorCode::BadVersion,
+            ValidationError::BadPow => ErrorCode::BadPow,
+            ValidationError::PrevBlockNotFound => ErrorCode::PrevNotFound,
+            ValidationError::HeaderNotFound { .. } => ErrorCode::HeaderNotFound,
+            ValidationError::TargetOverflow
+            | ValidationError::HeightUnderflow
+            | ValidationError::InvalidDaaWindow
+            | ValidationError::ChainWorkUnderflow
+            | ValidationError::ChainWorkOverflow => ErrorCode::DifficultyCalculation,
+            ValidationError::BeforeDaaActivation
+            | ValidationError::BeforeAsertAnchor
+            | ValidationError::BeforeDgwActivation => ErrorCode::UnsupportedHeight,
+            ValidationError::BadHeartwoodRoot => ErrorCode::BadHeartwoodRoot,
+            ValidationError::InvalidEquihashSolution(_) => ErrorCode::Equihash,
+            #[cfg(feature = "auxpow_header")]
+            ValidationError::AuxPow(err) => err.code(),
+        }
+    }
+}
+
+/// Access to the headers preceding the validated one
+pub trait BlocksGetter {
+    fn find_prev_header(&self, current_header: &LightHeader) -> Option<ExtendedHeader>;
+
+    /// Returns the main chain header at the given height
+    fn find_header_by_height(&self, height: u64) -> Option<ExtendedHeader>;
+
+    /// # Errors
+    /// If the previous header is unknown
+    fn get_prev_header(
+        &self,
+        current_header: &LightHeader,
+    ) -> Result<ExtendedHeader, ValidationError> {
+        self.find_prev_header(current_header)
+            .ok_or(ValidationError::PrevBlockNotFound)
+    }
+
+    /// # Errors
+    /// If there is no main chain header at the given height
+    fn get_header_by_height(&self, height: u64) -> Result<ExtendedHeader, ValidationError> {
+        self.find_header_by_height(height)
+            .ok_or(ValidationError::HeaderNotFound { height })
+    }
+}
+
+/// Returns the timestamps to cache in the header of the block following `prev_block_header`.
+/// If they are not cached in `prev_block_header`, the ancestors are read from the getter,
+/// stopping at the oldest known one.
+pub fn get_recent_timestamps(
+    block_time: u32,
+    prev_block_header: &ExtendedHeader,
+    prev_block_getter: &impl BlocksGetter,
+) -> Vec<u32> {
+    let mut recent_timestamps = Vec::with_capacity(MEDIAN_TIME_SPAN);
+    recent_timestamps.push(block_time);
+
+    if let Some(prev_timestamps) = prev_block_header
+        .recent_timestamps
+        .get(..MEDIAN_TIME_SPAN - 1)
+    {
+        recent_timestamps.extend_from_slice(prev_timestamps);
+        return recent_timestamps;
+    }
+
+    let mut current_header = prev_block_header.clone();
+    loop {
+        recent_timestamps.push(current_header.block_header.time);
+        if recent_timestamps.len() == MEDIAN_TIME_SPAN {
+            break;
+        }
+
+        match prev_block_getter.find_prev_header(&current_header.block_header) {
+            Some(prev_header) => current_header = prev_header,
+            None => break,
+        }
+    }
+
+    recent_timestamps
+}
+
+/// Median timestamp of the block and its `MEDIAN_TIME_SPAN - 1` ancestors
+///
+/// # Errors
+/// If the timestamps are not cached in the header and some ancestor is unknown
+pub fn get_median_time_past(
+    block_header: ExtendedHeader,
+    prev_block_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    if let Some(median_time_past) = block_header.median_time_past() {
+        return Ok(median_time_past);
+    }
+
+    let mut median_time = [0u32; MEDIAN_TIME_SPAN];
+    let mut current_header = block_header;
+
+    for slot in &mut median_time {
+        *slot = current_header.block_header.time;
+        current_header = prev_block_getter.get_prev_header(&current_header.block_header)?;
+    }
+
+    median_time.sort_unstable();
+    Ok(median_time[median_time.len() / 2])
+}
+
+/// # Errors
+/// If the header bits do not match the expected ones
+pub fn check_bits(expected_bits: u32, block_bits: u32) -> Result<(), ValidationError> {
+    if expected_bits == block_bits {
+        Ok(())
+    } else {
+        Err(ValidationError::BadDiffBits {
+            expected: expected_bits,
+            actual: block_bits,
+        })
+    }
+}
+
+/// # Errors
+/// If the timestamp is not after the median time past of the previous block
+pub fn check_median_time_past(
+    block_time: u32,
+    prev_block_median_time_past: u32,
+) -> Result<(), ValidationError> {
+    if block_time > prev_block_median_time_past {
+        Ok(())
+    } else {
+        Err(ValidationError::TimeTooOld)
+    }
+}
+
+/// Checks the timestamp against the local time in seconds
+///
+/// # Errors
+/// If the timestamp is more than `MAX_FUTURE_BLOCK_TIME_LOCAL` ahead of the local time
+pub fn check_future_time(block_time: u32, current_time: u32) -> Result<(), ValidationError> {
+    if block_time <= current_time.saturating_add(MAX_FUTURE_BLOCK_TIME_LOCAL) {
+        Ok(())
+    } else {
+        Err(ValidationError::TimeTooNew)
+    }
+}
+
+/// # Errors
+/// If the header has an outdated version
+pub fn check_version(version: i32, min_version: i32) -> Result<(), ValidationError> {
+    if version >= min_version {
+        Ok(())
+    } else {
+        Err(ValidationError::BadVersion { min_version })
+    }
+}
+
+/// Checks the proof of work hash against the target encoded by the header bits
+///
+/// # Errors
+/// If the hash exceeds the target
+pub fn check_pow_hash(pow_hash: &H256, bits: u32) -> Result<(), ValidationError> {
+    if U256::from_le_bytes(&pow_hash.0) <= target_from_bits(bits) {
+        Ok(())
+    } else {
+        Err(ValidationError::BadPow)
+    }
+}
+
+/// Helpers shared by the tests of the chain rules
+#[cfg(test)]
+#[cfg(not(feature = "zcash_header"))]
+pub(crate) mod test_utils {
+    use super::BlocksGetter;
+    use crate::hash::H256;
+    use crate::header::{ExtendedHeader, Header, LightHeader};
+    use crate::u256::U256;
+    use crate::utils::work_from_bits;
+    use std::collections::BTreeMap;
+
+    // Bitcoin genesis block, shared by Bitcoin Cash
+    pub(crate) fn genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+            "time": 1_231_006_505,
+            "bits": 0x1d00_ffff,
+            "nonce": 2_083_236_893,
+        }))
+        .unwrap()
+    }
+
+    #[derive(Default)]
+    pub(crate) struct MockBlocksGetter {
+        headers: BTreeMap<H256, ExtendedHeader>,
+    }
+
+    impl BlocksGetter for MockBlocksGetter {
+        fn find_prev_header(&self, current_header: &LightHeader) -> Option<ExtendedHeader> {
+            self.headers.get(&current_header.prev_block_hash).cloned()
+        }
+
+        fn find_header_by_height(&self, height: u64) -> Option<ExtendedHeader> {
+            self.headers
+                .values()
+                .find(|h| h.block_height == height)
+                .cloned()
+        }
+    }
+
+    impl FromIterator<ExtendedHeader> for MockBlocksGetter {
+        fn from_iter<I: IntoIterator<Item = ExtendedHeader>>(headers: I) -> Self {
+            Self {
+                headers: headers
+                    .into_iter()
+                    .map(|header| (header.block_hash.clone(), header))
+                    .collect(),
+            }
+        }
+    }
+
+    /// Stored header of the given block, with the timestamps cached from `recent_timestamps`
+    pub(crate) fn extend_header(
+        block_header: Header,
+        block_height: u64,
+        recent_timestamps: Vec<u32>,
+    ) -> ExtendedHeader {
+        ExtendedHeader {
+            block_hash: block_header.block_hash(),
+            chain_work: work_from_bits(block_header.bits),
+            block_header: block_header.into_light(),
+            block_height,
+            recent_timestamps,
+        }
+    }
+
+    /// Stored header of a block with the given timestamp and bits, built from the genesis block
+    pub(crate) fn extended_header(time: u32, bits: u32, block_height: u64) -> ExtendedHeader {
+        let block_header = Header {
+            time,
+            bits,
+            ..genesis_block_header()
+        };
+        extend_header(block_header, block_height, vec![time])
+    }
+
+    /// Builds a chain of blocks with the given bits and timestamps following the genesis
+    /// block, the first one at `first_height`, and returns it with its tip
+    pub(crate) fn chain(
+        first_height: u64,
+        bits: u32,
+        times: impl IntoIterator<Item = u32>,
+    ) -> (MockBlocksGetter, ExtendedHeader) {
+        let mut headers = BTreeMap::new();
+        let mut prev_block_hash = genesis_block_header().block_hash();
+        let mut chain_work = U256::ZERO;
+        let mut tip = None;
+
+        for (block_height, time) in (first_height..).zip(times) {
+            let block_header = Header {
+                prev_block_hash,
+                time,
+                bits,
+                ..genesis_block_header()
+            };
+            prev_block_hash = block_header.block_hash();
+            chain_work = chain_work.overflowing_add(work_from_bits(bits)).0;
+
+            let header = ExtendedHeader {
+                block_hash: block_header.block_hash(),
+                block_header: block_header.into_light(),
+                chain_work,
+                block_height,
+                recent_timestamps: vec![],
+            };
+            headers.insert(header.block_hash.clone(), header.clone());
+            tip = Some(header);
+        }
+
+        (MockBlocksGetter { headers }, tip.unwrap())
+    }
+
+    /// Timestamps of `len` blocks mined every `spacing` seconds after the genesis block
+    pub(crate) fn spaced_times(len: u32, spacing: u32) -> impl Iterator<Item = u32> {
+        let genesis_time = genesis_block_header().time;
+        (1..=len).map(move |i| genesis_time + i * spacing)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "zcash_header"))]
+mod tests {
+    use super::test_utils::chain;
+    use super::*;
+
+    #[test]
+    fn test_median_time_past() {
+        let times: Vec<u32> = (0..12).map(|i| 1_000 + (i * 7) % 12).collect();
+        let (getter, tip) = chain(0, 0x1d00_ffff, times.iter().copied());
+
+        let mut last_times = times[1..].to_vec();
+        last_times.sort_unstable();
+        assert_eq!(get_median_time_past(tip, &getter), Ok(last_times[5]));
+    }
+
+    #[test]
+    fn test_median_time_past_without_ancestors() {
+        let (getter, tip) = chain(0, 0x1d00_ffff, [1_000; 10]);
+
+        assert_eq!(
+            get_median_time_past(tip, &getter),
+            Err(ValidationError::PrevBlockNotFound)
+        );
+    }
+
+    #[test]
+    fn test_header_checks() {
+        assert_eq!(check_bits(0x1d00_ffff, 0x1d00_ffff), Ok(()));
+        assert_eq!(
+            check_bits(0x1d00_ffff, 0x1c00_ffff)
+                .unwrap_err()
+                .to_string(),
+            "bad-diffbits: incorrect proof of work (expected bits 0x1d00ffff, got 0x1c00ffff)"
+        );
+        assert_eq!(
+            check_median_time_past(1_000, 1_000),
+            Err(ValidationError::TimeTooOld)
+        );
+        assert_eq!(
+            check_future_time(1_000 + MAX_FUTURE_BLOCK_TIME_LOCAL + 1, 1_000),
+            Err(ValidationError::TimeTooNew)
+        );
+        assert_eq!(check_future_time(u32::MAX, u32::MAX), Ok(()));
+        assert_eq!(
+            check_version(3, 4),
+            Err(ValidationError::BadVersion { min_version: 4 })
+        );
+        assert_eq!(
+            check_pow_hash(&H256::from([0xff; 32]), 0x1d00_ffff),
+            Err(ValidationError::BadPow)
+        );
+    }
+}
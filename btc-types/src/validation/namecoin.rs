@@ -0,0 +1,207 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, get_median_time_past, BlocksGetter,
+    ValidationError,
+};
+use crate::header::{ExtendedHeader, Header};
+use crate::network::AuxPowConfig;
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Checks the header against the previous one: the difficulty and the timestamps.
+/// The proof of work hash and the AuxPoW are checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+// Reference implementation: https://github.com/namecoin/namecoin-core/blob/nc28.0/src/validation.cpp
+pub fn check_header(
+    config: &AuxPowConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let expected_bits =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+    check_bits(expected_bits, block_header.bits)?;
+
+    // Check timestamp against prev
+    check_median_time_past(
+        block_header.time,
+        get_median_time_past(prev_block_header.clone(), blocks_getter)?,
+    )?;
+
+    // Check timestamp
+    check_future_time(block_header.time, current_time)
+}
+
+/// # Errors
+/// If a required previous header is unknown or the new target overflows
+// Namecoin keeps the Bitcoin retargeting with the Art Forz lookback fix: https://github.com/namecoin/namecoin-core/blob/nc28.0/src/pow.cpp
+pub fn get_next_work_required(
+    config: &AuxPowConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    if (prev_block_header.block_height + 1) % config.difficulty_adjustment_interval != 0 {
+        if config.pow_allow_min_difficulty_blocks {
+            if block_header.time
+                > prev_block_header.block_header.time + 2 * config.pow_target_spacing
+            {
+                return Ok(config.proof_of_work_limit_bits);
+            }
+
+            let mut current_block_header = prev_block_header.clone();
+            while current_block_header.block_header.bits == config.proof_of_work_limit_bits
+                && current_block_header.block_height % config.difficulty_adjustment_interval != 0
+            {
+                current_block_header =
+                    blocks_getter.get_prev_header(&current_block_header.block_header)?;
+            }
+
+            return Ok(current_block_header.block_header.bits);
+        }
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    // Go back the full period unless it's the first retarget after genesis. Code courtesy of Art Forz
+    let mut blocks_to_go_back = config.difficulty_adjustment_interval - 1;
+    if prev_block_header.block_height + 1 != config.difficulty_adjustment_interval {
+        blocks_to_go_back = config.difficulty_adjustment_interval;
+    }
+
+    let first_block_height = prev_block_header.block_height - blocks_to_go_back;
+
+    let interval_tail_extend_header = blocks_getter.get_header_by_height(first_block_height)?;
+    calculate_next_work_required(
+        config,
+        prev_block_header,
+        interval_tail_extend_header.block_header.time.into(),
+    )
+}
+
+/// # Errors
+/// If the new target overflows
+/// # Panics
+/// If the target timespan of the config is negative
+pub fn calculate_next_work_required(
+    config: &AuxPowConfig,
+    prev_block_header: &ExtendedHeader,
+    first_block_time: i64,
+) -> Result<u32, ValidationError> {
+    if config.pow_no_retargeting {
+        return Ok(prev_block_header.block_header.bits);
+    }
+
+    let prev_block_time: i64 = prev_block_header.block_header.time.into();
+
+    let actual_time_taken = (prev_block_time - first_block_time).clamp(
+        config.pow_target_timespan / 4,
+        config.pow_target_timespan * 4,
+    );
+
+    let (mut new_target, new_target_overflow) =
+        target_from_bits(prev_block_header.block_header.bits)
+            .overflowing_mul(<i64 as TryInto<u64>>::try_into(actual_time_taken).unwrap());
+    if new_target_overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+    new_target = new_target
+        / U256::from(<i64 as TryInto<u64>>::try_into(config.pow_target_timespan).unwrap());
+
+    if new_target > config.pow_limit {
+        new_target = config.pow_limit;
+    }
+
+    Ok(new_target.target_to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{get_namecoin_config, Network};
+    use crate::validation::test_utils::{
+        chain, extended_header, genesis_block_header, MockBlocksGetter,
+    };
+
+    #[test]
+    fn test_retarget() {
+        let config = get_namecoin_config(Network::Mainnet);
+        let first_time = genesis_block_header().time;
+
+        // The period is mined twice faster than the target timespan
+        let prev_block_header = extended_header(first_time + 2016 * 300, 0x1d00_ffff, 2015);
+        assert_eq!(
+            calculate_next_work_required(&config, &prev_block_header, first_time.into()),
+            Ok(0x1c7f_ff80)
+        );
+
+        // The adjustment is clamped to the proof of work limit
+        let prev_block_header = extended_header(first_time + 2016 * 4800, 0x1d00_ffff, 2015);
+        assert_eq!(
+            calculate_next_work_required(&config, &prev_block_header, first_time.into()),
+            Ok(0x1d00_ffff)
+        );
+    }
+
+    #[test]
+    fn test_retarget_goes_back_full_period() {
+        let config = get_namecoin_config(Network::Mainnet);
+        let genesis_time = genesis_block_header().time;
+        let times = (0..=2016).map(|i| genesis_time + i * 300);
+        let block_header = genesis_block_header();
+
+        // The first retarget after genesis spans the blocks 0..=2015
+        let (getter, _) = chain(0, 0x1d00_ffff, times.clone());
+        let prev_block_header = getter.get_header_by_height(2015).unwrap();
+        assert_eq!(
+            get_next_work_required(&config, &block_header, &prev_block_header, &getter),
+            calculate_next_work_required(&config, &prev_block_header, genesis_time.into())
+        );
+
+        // The later ones include the last block of the previous period
+        let (getter, prev_block_header) = chain(2015, 0x1d00_ffff, times);
+        assert_eq!(prev_block_header.block_height, 4031);
+        assert_eq!(
+            get_next_work_required(&config, &block_header, &prev_block_header, &getter),
+            calculate_next_work_required(&config, &prev_block_header, genesis_time.into())
+        );
+    }
+
+    #[test]
+    fn test_regtest_no_retargeting() {
+        let config = get_namecoin_config(Network::Regtest);
+        let first_time = genesis_block_header().time;
+        let prev_block_header = extended_header(first_time + 2016 * 150, 0x207f_ffff, 2015);
+
+        assert_eq!(
+            calculate_next_work_required(&config, &prev_block_header, first_time.into()),
+            Ok(0x207f_ffff)
+        );
+        assert_eq!(config.pow_limit.target_to_bits(), 0x207f_ffff);
+    }
+
+    #[test]
+    fn test_regtest_no_retargeting_without_ancestors() {
+        let config = get_namecoin_config(Network::Regtest);
+        let prev_block_header = extended_header(genesis_block_header().time, 0x207f_ffff, 100);
+        let block_header = Header {
+            time: prev_block_header.block_header.time + 600,
+            ..genesis_block_header()
+        };
+
+        assert_eq!(
+            get_next_work_required(
+                &config,
+                &block_header,
+                &prev_block_header,
+                &MockBlocksGetter::default(),
+            ),
+            Ok(0x207f_ffff)
+        );
+    }
+}
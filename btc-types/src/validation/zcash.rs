@@ -0,0 +1,376 @@
+use super::{
+    check_bits, check_future_time, check_median_time_past, check_version, get_median_time_past,
+    BlocksGetter, ValidationError,
+};
+use crate::hash::H256;
+use crate::header::{ExtendedHeader, Header};
+use crate::network::{ZcashConfig, ZcashNetworkUpgrade, MAX_FUTURE_BLOCK_TIME_MTP};
+use crate::u256::U256;
+use crate::utils::target_from_bits;
+
+/// Checks the header against the previous one: the difficulty, the timestamps, the version,
+/// the Heartwood activation commitments and the Equihash solution.
+/// The proof of work hash is checked separately.
+///
+/// # Errors
+/// If the header breaks a rule or a required previous header is unknown
+// Reference implementation: https://github.com/zcash/zcash/blob/v6.2.0/src/main.cpp#L5019
+pub fn check_header(
+    config: &ZcashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &impl BlocksGetter,
+) -> Result<(), ValidationError> {
+    let next_work_result =
+        get_next_work_required(config, block_header, prev_block_header, blocks_getter)?;
+    check_bits(next_work_result.expected_bits, block_header.bits)?;
+
+    // Check timestamp against prev
+    check_median_time_past(
+        block_header.time,
+        next_work_result.prev_block_median_time_past,
+    )?;
+
+    // Check future timestamp soft fork rule introduced in v2.1.1-1.
+    // This retrospectively activates at block height 2 for mainnet and regtest,
+    // and 6 blocks after Blossom activation for testnet.
+    //
+    // MAX_FUTURE_BLOCK_TIME_MTP is typically 129600 seconds (36 hours) in Zcash
+    if block_header.time > next_work_result.prev_block_median_time_past + MAX_FUTURE_BLOCK_TIME_MTP
+    {
+        return Err(ValidationError::TimeTooFarAheadOfMtp);
+    }
+
+    // Check timestamp
+    check_future_time(block_header.time, current_time)?;
+
+    check_version(block_header.version, 4)?;
+
+    // The chain history tree starts with the Heartwood activation block, which commits
+    // to the empty history, see ZIP-221
+    let block_height = prev_block_header.block_height + 1;
+    if config.is_activation_height(ZcashNetworkUpgrade::Heartwood, block_height)
+        && block_header.block_commitments != H256::default()
+    {
+        return Err(ValidationError::BadHeartwoodRoot);
+    }
+
+    check_equihash_solution(config, block_header)
+}
+
+/// # Errors
+/// If the Equihash solution of the header is invalid
+pub fn check_equihash_solution(
+    config: &ZcashConfig,
+    block_header: &Header,
+) -> Result<(), ValidationError> {
+    let input = block_header.get_block_header_vec_for_equihash();
+
+    equihash::is_valid_solution(
+        config.equihash_n,
+        config.equihash_k,
+        &input,
+        &block_header.nonce.0,
+        &block_header.solution,
+    )
+    .map_err(|e| ValidationError::InvalidEquihashSolution(e.to_string()))
+}
+
+pub struct NextWorkResult {
+    pub expected_bits: u32,
+    pub prev_block_median_time_past: u32,
+}
+
+/// # Errors
+/// If a required previous header is unknown or the new target overflows
+/// # Panics
+/// If the averaging window of the config is negative
+// Reference implementation: https://github.com/zcash/zcash/blob/v6.2.0/src/pow.cpp#L20
+pub fn get_next_work_required(
+    config: &ZcashConfig,
+    block_header: &Header,
+    prev_block_header: &ExtendedHeader,
+    prev_block_getter: &impl BlocksGetter,
+) -> Result<NextWorkResult, ValidationError> {
+    let prev_block_median_time_past =
+        get_median_time_past(prev_block_header.clone(), prev_block_getter)?;
+
+    // Regtest
+    if config.pow_no_retargeting {
+        return Ok(NextWorkResult {
+            expected_bits: prev_block_header.block_header.bits,
+            prev_block_median_time_past,
+        });
+    }
+
+    // Find the first block in the averaging interval
+    // and the median time past for the first block in the interval
+    let mut current_header = prev_block_header.clone();
+    let mut total_target = U256::ZERO;
+
+    for _ in 0..config.pow_averaging_window {
+        let (sum, overflow) =
+            total_target.overflowing_add(target_from_bits(current_header.block_header.bits));
+        if overflow {
+            return Err(ValidationError::TargetOverflow);
+        }
+        total_target = sum;
+
+        current_header = prev_block_getter.get_prev_header(&current_header.block_header)?;
+    }
+
+    let first_block_in_interval_median_time_past =
+        get_median_time_past(current_header, prev_block_getter)?;
+
+    if let Some(pow_allow_min_difficulty_blocks_after_height) =
+        config.pow_allow_min_difficulty_blocks_after_height
+    {
+        // Comparing with >= because this function returns the work required for the block after prev_block_header
+        if prev_block_header.block_height >= pow_allow_min_difficulty_blocks_after_height {
+            // Special difficulty rule for testnet:
+            // If the new block's timestamp is more than 6 * block interval minutes
+            // then allow mining of a min-difficulty block.
+            if i64::from(block_header.time)
+                > i64::from(prev_block_header.block_header.time)
+                    + config.pow_target_spacing(prev_block_header.block_height + 1) * 6
+            {
+                return Ok(NextWorkResult {
+                    expected_bits: config.proof_of_work_limit_bits,
+                    prev_block_median_time_past,
+                });
+            }
+        }
+    }
+
+    // The protocol specification leaves MeanTarget(height) as a rational, and takes the floor
+    // only after dividing by AveragingWindowTimespan in the computation of Threshold(height):
+    // <https://zips.z.cash/protocol/protocol.pdf#diffadjustment>
+    //
+    // Here we take the floor of MeanTarget(height) immediately, but that is equivalent to doing
+    // so only after a further division, as proven in <https://math.stackexchange.com/a/147832/185422>.
+    let average_target = total_target
+        / U256::from(<i64 as TryInto<u64>>::try_into(config.pow_averaging_window).unwrap());
+
+    let expected_bits = calculate_next_work_required(
+        config,
+        prev_block_header.block_height + 1,
+        average_target,
+        prev_block_median_time_past,
+        first_block_in_interval_median_time_past,
+    )?;
+
+    Ok(NextWorkResult {
+        expected_bits,
+        prev_block_median_time_past,
+    })
+}
+
+/// # Errors
+/// If the new target overflows
+/// # Panics
+/// If the averaging window timespan of the config is negative
+pub fn calculate_next_work_required(
+    config: &ZcashConfig,
+    next_height: u64,
+    average_target: U256,
+    last_interval_block_median_time_past: u32,
+    first_interval_block_median_time_past: u32,
+) -> Result<u32, ValidationError> {
+    let averaging_window_timespan = config.averaging_window_timespan(next_height);
+    let min_actual_timespan = config.min_actual_timespan(next_height);
+    let max_actual_timespan = config.max_actual_timespan(next_height);
+
+    // Limit adjustment step
+    // Use medians to prevent time-warp attacks
+    let mut actual_timespan = i64::from(last_interval_block_median_time_past)
+        - i64::from(first_interval_block_median_time_past);
+
+    actual_timespan = averaging_window_timespan + (actual_timespan - averaging_window_timespan) / 4;
+
+    if actual_timespan < min_actual_timespan {
+        actual_timespan = min_actual_timespan;
+    }
+    if actual_timespan > max_actual_timespan {
+        actual_timespan = max_actual_timespan;
+    }
+
+    // Retarget
+    let new_target = average_target
+        / U256::from(<i64 as TryInto<u64>>::try_into(averaging_window_timespan).unwrap());
+    let (mut new_target, new_target_overflow) =
+        new_target.overflowing_mul(<i64 as TryInto<u64>>::try_into(actual_timespan).unwrap());
+    if new_target_overflow {
+        return Err(ValidationError::TargetOverflow);
+    }
+
+    if new_target > config.pow_limit {
+        new_target = config.pow_limit;
+    }
+
+    Ok(new_target.target_to_bits())
+}
+
+// Tests ported from:
+// https://github.com/zcash/zcash/blob/fe3e645ca9f1de4ff7feaaa1ddb763ae714c93c6/src/test/pow_tests.cpp
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{get_zcash_config, Network};
+
+    // Mainnet Blossom activation height
+    const BLOSSOM_HEIGHT: u64 = 653_600;
+    const PRE_BLOSSOM_HEIGHT: u64 = BLOSSOM_HEIGHT - 1;
+
+    #[test]
+    fn test_calculate_next_work_pre_blossom() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1d00ffff);
+        let first_time = 1000000000;
+        let last_time = 1000003570;
+
+        let result = calculate_next_work_required(
+            &config,
+            PRE_BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1d011998));
+    }
+
+    #[test]
+    fn test_calculate_next_work() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1d00ffff);
+        let first_time = 1000000000;
+        let last_time = 1000001445;
+
+        let result = calculate_next_work_required(
+            &config,
+            BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert!(result.unwrap() < 0x1d011998);
+    }
+
+    #[test]
+    fn test_calculate_next_work_pow_limit_pre_blossom() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1f07ffff);
+        let first_time = 1231006505;
+        let last_time = 1233061996;
+
+        let result = calculate_next_work_required(
+            &config,
+            PRE_BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1f07ffff));
+    }
+
+    #[test]
+    fn test_calculate_next_work_pow_limit() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1f07ffff);
+        let first_time = 1231006505;
+        let last_time = 1233061996;
+
+        let result = calculate_next_work_required(
+            &config,
+            BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1f07ffff));
+    }
+
+    #[test]
+    fn test_calculate_next_work_lower_limit_actual_pre_blossom() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1c05a3f4);
+        let first_time = 1000000000;
+        let last_time = 100000917;
+
+        let result = calculate_next_work_required(
+            &config,
+            PRE_BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1c04bceb));
+    }
+
+    #[test]
+    fn test_calculate_next_work_lower_limit_actual() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1c05a3f4);
+        let first_time = 1000000000;
+        let last_time = 1000000458;
+
+        let result = calculate_next_work_required(
+            &config,
+            BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1c04bceb));
+    }
+
+    #[test]
+    fn test_calculate_next_work_upper_limit_actual_pre_blossom() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1c387f6f);
+        let first_time = 1000000000;
+        let last_time = 1000005815;
+
+        let result = calculate_next_work_required(
+            &config,
+            PRE_BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1c4a93bb));
+    }
+
+    #[test]
+    fn test_calculate_next_work_upper_limit_actual() {
+        let config = get_zcash_config(Network::Mainnet);
+
+        let average_target = target_from_bits(0x1c387f6f);
+        let first_time = 1000000000;
+        let last_time = 1000002908;
+
+        let result = calculate_next_work_required(
+            &config,
+            BLOSSOM_HEIGHT,
+            average_target,
+            last_time,
+            first_time,
+        );
+
+        assert_eq!(result, Ok(0x1c4a93bb));
+    }
+}
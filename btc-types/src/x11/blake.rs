@@ -0,0 +1,130 @@
+//! BLAKE-512
+
+const IV: [u64; 8] = [
+    0x6a09_e667_f3bc_c908,
+    0xbb67_ae85_84ca_a73b,
+    0x3c6e_f372_fe94_f82b,
+    0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1,
+    0x9b05_688c_2b3e_6c1f,
+    0x1f83_d9ab_fb41_bd6b,
+    0x5be0_cd19_137e_2179,
+];
+
+const C: [u64; 16] = [
+    0x243f_6a88_85a3_08d3,
+    0x1319_8a2e_0370_7344,
+    0xa409_3822_299f_31d0,
+    0x082e_fa98_ec4e_6c89,
+    0x4528_21e6_38d0_1377,
+    0xbe54_66cf_34e9_0c6c,
+    0xc0ac_29b7_c97c_50dd,
+    0x3f84_d5b5_b547_0917,
+    0x9216_d5d9_8979_fb1b,
+    0xd131_0ba6_98df_b5ac,
+    0x2ffd_72db_d01a_dfb7,
+    0xb8e1_afed_6a26_7e96,
+    0xba7c_9045_f12c_7f99,
+    0x24a1_9947_b391_6cf7,
+    0x0801_f2e2_858e_fc16,
+    0x6369_20d8_7157_4e69,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLOCK_SIZE: usize = 128;
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    let mut counter: u128 = 0;
+    for block in blocks.by_ref() {
+        counter += 1024;
+        compress(&mut state, block, counter);
+    }
+
+    // The counter of a block holding only the padding is zero
+    let remainder = blocks.remainder();
+    let bit_len = u128::try_from(data.len()).unwrap() * 8;
+    let mut last_blocks = [0u8; 2 * BLOCK_SIZE];
+    last_blocks[..remainder.len()].copy_from_slice(remainder);
+    last_blocks[remainder.len()] = 0x80;
+    let padded_len = if remainder.len() < BLOCK_SIZE - 16 {
+        BLOCK_SIZE
+    } else {
+        2 * BLOCK_SIZE
+    };
+    last_blocks[padded_len - 17] |= 1;
+    last_blocks[padded_len - 16..padded_len].copy_from_slice(&bit_len.to_be_bytes());
+
+    let remainder_counter = if remainder.is_empty() { 0 } else { bit_len };
+    compress(&mut state, &last_blocks[..BLOCK_SIZE], remainder_counter);
+    if padded_len > BLOCK_SIZE {
+        compress(&mut state, &last_blocks[BLOCK_SIZE..], 0);
+    }
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(8).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    output
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn compress(state: &mut [u64; 8], block: &[u8], counter: u128) {
+    let mut m = [0u64; 16];
+    for (word, chunk) in m.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    let (t0, t1) = (counter as u64, (counter >> 64) as u64);
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(state);
+    v[8..12].copy_from_slice(&C[..4]);
+    v[12] = t0 ^ C[4];
+    v[13] = t0 ^ C[5];
+    v[14] = t1 ^ C[6];
+    v[15] = t1 ^ C[7];
+
+    for round in 0..16 {
+        let sigma = &SIGMA[round % 10];
+        for (i, [a, b, c, d]) in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (x, y) = (sigma[2 * i], sigma[2 * i + 1]);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[x] ^ C[y]);
+            v[d] = (v[d] ^ v[a]).rotate_right(32);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(25);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[y] ^ C[x]);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(11);
+        }
+    }
+
+    for i in 0..8 {
+        state[i] ^= v[i] ^ v[i + 8];
+    }
+}
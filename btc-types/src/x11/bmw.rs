@@ -0,0 +1,188 @@
+//! Blue Midnight Wish 512
+
+const BLOCK_SIZE: usize = 128;
+
+const IV: [u64; 16] = [
+    0x8081_8283_8485_8687,
+    0x8889_8a8b_8c8d_8e8f,
+    0x9091_9293_9495_9697,
+    0x9899_9a9b_9c9d_9e9f,
+    0xa0a1_a2a3_a4a5_a6a7,
+    0xa8a9_aaab_acad_aeaf,
+    0xb0b1_b2b3_b4b5_b6b7,
+    0xb8b9_babb_bcbd_bebf,
+    0xc0c1_c2c3_c4c5_c6c7,
+    0xc8c9_cacb_cccd_cecf,
+    0xd0d1_d2d3_d4d5_d6d7,
+    0xd8d9_dadb_dcdd_dedf,
+    0xe0e1_e2e3_e4e5_e6e7,
+    0xe8e9_eaeb_eced_eeef,
+    0xf0f1_f2f3_f4f5_f6f7,
+    0xf8f9_fafb_fcfd_feff,
+];
+
+/// Chaining value of the final compression
+const FINAL: [u64; 16] = [
+    0xaaaa_aaaa_aaaa_aaa0,
+    0xaaaa_aaaa_aaaa_aaa1,
+    0xaaaa_aaaa_aaaa_aaa2,
+    0xaaaa_aaaa_aaaa_aaa3,
+    0xaaaa_aaaa_aaaa_aaa4,
+    0xaaaa_aaaa_aaaa_aaa5,
+    0xaaaa_aaaa_aaaa_aaa6,
+    0xaaaa_aaaa_aaaa_aaa7,
+    0xaaaa_aaaa_aaaa_aaa8,
+    0xaaaa_aaaa_aaaa_aaa9,
+    0xaaaa_aaaa_aaaa_aaaa,
+    0xaaaa_aaaa_aaaa_aaab,
+    0xaaaa_aaaa_aaaa_aaac,
+    0xaaaa_aaaa_aaaa_aaad,
+    0xaaaa_aaaa_aaaa_aaae,
+    0xaaaa_aaaa_aaaa_aaaf,
+];
+
+/// The terms `M[i] ^ H[i]` of each `W` word, the first one added and the others added
+/// or subtracted as marked
+const W: [(usize, [(usize, bool); 4]); 16] = [
+    (5, [(7, false), (10, true), (13, true), (14, true)]),
+    (6, [(8, false), (11, true), (14, true), (15, false)]),
+    (0, [(7, true), (9, true), (12, false), (15, true)]),
+    (0, [(1, false), (8, true), (10, false), (13, true)]),
+    (1, [(2, true), (9, true), (11, false), (14, false)]),
+    (3, [(2, false), (10, true), (12, false), (15, true)]),
+    (4, [(0, false), (3, false), (11, false), (13, true)]),
+    (1, [(4, false), (5, false), (12, false), (14, false)]),
+    (2, [(5, false), (6, false), (13, true), (15, false)]),
+    (0, [(3, false), (6, true), (7, false), (14, true)]),
+    (8, [(1, false), (4, false), (7, false), (15, true)]),
+    (8, [(0, false), (2, false), (5, false), (9, true)]),
+    (1, [(3, true), (6, false), (9, false), (10, true)]),
+    (2, [(4, true), (7, true), (10, true), (11, true)]),
+    (3, [(5, false), (8, true), (11, false), (12, false)]),
+    (12, [(4, false), (6, false), (9, false), (13, true)]),
+];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    for block in blocks.by_ref() {
+        state = compress(&state, &words(block));
+    }
+
+    let remainder = blocks.remainder();
+    let mut last_blocks = [0u8; 2 * BLOCK_SIZE];
+    last_blocks[..remainder.len()].copy_from_slice(remainder);
+    last_blocks[remainder.len()] = 0x80;
+    let padded_len = if remainder.len() < BLOCK_SIZE - 8 {
+        BLOCK_SIZE
+    } else {
+        2 * BLOCK_SIZE
+    };
+    let bit_len = u64::try_from(data.len()).unwrap().wrapping_mul(8);
+    last_blocks[padded_len - 8..padded_len].copy_from_slice(&bit_len.to_le_bytes());
+    for block in last_blocks[..padded_len].chunks_exact(BLOCK_SIZE) {
+        state = compress(&state, &words(block));
+    }
+
+    let state = compress(&FINAL, &state);
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(8).zip(&state[8..]) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+fn words(block: &[u8]) -> [u64; 16] {
+    let mut words = [0u64; 16];
+    for (word, chunk) in words.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+fn s(i: usize, x: u64) -> u64 {
+    match i {
+        0 => (x >> 1) ^ (x << 3) ^ x.rotate_left(4) ^ x.rotate_left(37),
+        1 => (x >> 1) ^ (x << 2) ^ x.rotate_left(13) ^ x.rotate_left(43),
+        2 => (x >> 2) ^ (x << 1) ^ x.rotate_left(19) ^ x.rotate_left(53),
+        3 => (x >> 2) ^ (x << 2) ^ x.rotate_left(28) ^ x.rotate_left(59),
+        4 => (x >> 1) ^ x,
+        _ => (x >> 2) ^ x,
+    }
+}
+
+#[allow(clippy::many_single_char_names)]
+fn compress(h: &[u64; 16], m: &[u64; 16]) -> [u64; 16] {
+    let mut q = [0u64; 32];
+    for (i, (first, terms)) in W.iter().enumerate() {
+        let w = terms.iter().fold(m[*first] ^ h[*first], |w, &(j, add)| {
+            if add {
+                w.wrapping_add(m[j] ^ h[j])
+            } else {
+                w.wrapping_sub(m[j] ^ h[j])
+            }
+        });
+        q[i] = s(i % 5, w).wrapping_add(h[(i + 1) % 16]);
+    }
+
+    let rotated_m = |i: usize| m[i % 16].rotate_left(u32::try_from(i % 16).unwrap() + 1);
+    for j in 16..32 {
+        let add_element = (rotated_m(j - 16)
+            .wrapping_add(rotated_m(j - 13))
+            .wrapping_sub(rotated_m(j - 6))
+            .wrapping_add(
+                u64::try_from(j)
+                    .unwrap()
+                    .wrapping_mul(0x0555_5555_5555_5555),
+            ))
+            ^ h[(j - 9) % 16];
+        let expansion = if j < 18 {
+            (0..16).fold(0u64, |sum, k| {
+                sum.wrapping_add(s((k + 1) % 4, q[j - 16 + k]))
+            })
+        } else {
+            let mut sum = 0u64;
+            for (k, rotation) in [0, 5, 0, 11, 0, 27, 0, 32, 0, 37, 0, 43, 0, 53]
+                .into_iter()
+                .enumerate()
+            {
+                sum = sum.wrapping_add(q[j - 16 + k].rotate_left(rotation));
+            }
+            sum.wrapping_add(s(4, q[j - 2]))
+                .wrapping_add(s(5, q[j - 1]))
+        };
+        q[j] = expansion.wrapping_add(add_element);
+    }
+
+    let xl = q[16..24].iter().fold(0, |x, q| x ^ q);
+    let xh = q[24..32].iter().fold(xl, |x, q| x ^ q);
+    let mut dh = [0u64; 16];
+    dh[0] = ((xh << 5) ^ (q[16] >> 5) ^ m[0]).wrapping_add(xl ^ q[24] ^ q[0]);
+    dh[1] = ((xh >> 7) ^ (q[17] << 8) ^ m[1]).wrapping_add(xl ^ q[25] ^ q[1]);
+    dh[2] = ((xh >> 5) ^ (q[18] << 5) ^ m[2]).wrapping_add(xl ^ q[26] ^ q[2]);
+    dh[3] = ((xh >> 1) ^ (q[19] << 5) ^ m[3]).wrapping_add(xl ^ q[27] ^ q[3]);
+    dh[4] = ((xh >> 3) ^ q[20] ^ m[4]).wrapping_add(xl ^ q[28] ^ q[4]);
+    dh[5] = ((xh << 6) ^ (q[21] >> 6) ^ m[5]).wrapping_add(xl ^ q[29] ^ q[5]);
+    dh[6] = ((xh >> 4) ^ (q[22] << 6) ^ m[6]).wrapping_add(xl ^ q[30] ^ q[6]);
+    dh[7] = ((xh >> 11) ^ (q[23] << 2) ^ m[7]).wrapping_add(xl ^ q[31] ^ q[7]);
+    for (i, shifted_xl) in [
+        xl << 8,
+        xl >> 6,
+        xl << 6,
+        xl << 4,
+        xl >> 3,
+        xl >> 4,
+        xl >> 7,
+        xl >> 2,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let rotation = u32::try_from(i).unwrap() + 9;
+        dh[i + 8] = dh[(i + 4) % 8]
+            .rotate_left(rotation)
+            .wrapping_add(xh ^ q[i + 24] ^ m[i + 8])
+            .wrapping_add(shifted_xl ^ q[(i + 7) % 8 + 16] ^ q[i + 8]);
+    }
+    dh
+}
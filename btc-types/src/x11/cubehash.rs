@@ -0,0 +1,92 @@
+//! CubeHash16/32-512
+
+const BLOCK_SIZE: usize = 32;
+
+const IV: [u32; 32] = [
+    0x2aea_2a61,
+    0x50f4_94d4,
+    0x2d53_8b8b,
+    0x4167_d83e,
+    0x3fee_2313,
+    0xc701_cf8c,
+    0xcc39_968e,
+    0x50ac_5695,
+    0x4d42_c787,
+    0xa647_a8b3,
+    0x97cf_0bef,
+    0x825b_4537,
+    0xeef8_64d2,
+    0xf220_90c4,
+    0xd0e5_cd33,
+    0xa239_11ae,
+    0xfcd3_98d9,
+    0x148f_e485,
+    0x1b01_7bef,
+    0xb644_4532,
+    0x6a53_6159,
+    0x2ff5_781c,
+    0x91fa_7934,
+    0x0dba_dea9,
+    0xd65c_8a2b,
+    0xa5a7_0e75,
+    0xb1c6_2456,
+    0xbc79_6576,
+    0x1921_c8f7,
+    0xe798_9af1,
+    0x7795_d246,
+    0xd43e_3b44,
+];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    for block in blocks.by_ref() {
+        input_block(&mut state, block);
+        rounds(&mut state);
+    }
+
+    let remainder = blocks.remainder();
+    let mut last_block = [0u8; BLOCK_SIZE];
+    last_block[..remainder.len()].copy_from_slice(remainder);
+    last_block[remainder.len()] = 0x80;
+    input_block(&mut state, &last_block);
+    rounds(&mut state);
+
+    state[31] ^= 1;
+    for _ in 0..10 {
+        rounds(&mut state);
+    }
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+fn input_block(state: &mut [u32; 32], block: &[u8]) {
+    for (word, chunk) in state.iter_mut().zip(block.chunks_exact(4)) {
+        *word ^= u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+}
+
+/// The sixteen rounds applied to each block
+fn rounds(x: &mut [u32; 32]) {
+    for _ in 0..16 {
+        for (rotation, swap_low, swap_high) in [(7, 8, 2), (11, 4, 1)] {
+            for i in 0..16 {
+                x[i + 16] = x[i + 16].wrapping_add(x[i]);
+                x[i] = x[i].rotate_left(rotation);
+            }
+            for i in (0..16).filter(|i| i & swap_low == 0) {
+                x.swap(i, i ^ swap_low);
+            }
+            for i in 0..16 {
+                x[i] ^= x[i + 16];
+            }
+            for i in (16..32).filter(|i| i & swap_high == 0) {
+                x.swap(i, i ^ swap_high);
+            }
+        }
+    }
+}
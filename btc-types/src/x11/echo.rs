@@ -0,0 +1,95 @@
+//! ECHO-512
+
+use super::aes;
+
+const BLOCK_SIZE: usize = 128;
+/// Offset of the output size and bit count in the last block
+const LENGTH_OFFSET: usize = BLOCK_SIZE - 18;
+
+/// The state as sixteen 128-bit words
+type State = [[u8; 16]; 16];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    // The chaining value, eight words initialized to the output size in bits
+    let mut chaining = [512u128.to_le_bytes(); 8];
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    let mut bit_count = 0u128;
+    for block in blocks.by_ref() {
+        bit_count += 1024;
+        compress(&mut chaining, block, bit_count);
+    }
+
+    let remainder = blocks.remainder();
+    let total_bits = u128::try_from(data.len()).unwrap() * 8;
+    let mut last_block = [0u8; BLOCK_SIZE];
+    last_block[..remainder.len()].copy_from_slice(remainder);
+    last_block[remainder.len()] = 0x80;
+    // The block carrying no message bits is compressed with a zero count
+    let mut last_count = if remainder.is_empty() { 0 } else { total_bits };
+    if remainder.len() >= LENGTH_OFFSET {
+        compress(&mut chaining, &last_block, last_count);
+        last_block = [0; BLOCK_SIZE];
+        last_count = 0;
+    }
+    last_block[LENGTH_OFFSET..LENGTH_OFFSET + 2].copy_from_slice(&512u16.to_le_bytes());
+    last_block[LENGTH_OFFSET + 2..].copy_from_slice(&total_bits.to_le_bytes());
+    compress(&mut chaining, &last_block, last_count);
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(16).zip(chaining) {
+        chunk.copy_from_slice(&word);
+    }
+    output
+}
+
+fn compress(chaining: &mut [[u8; 16]; 8], block: &[u8], bit_count: u128) {
+    let mut w: State = [[0; 16]; 16];
+    w[..8].copy_from_slice(chaining);
+    for (word, chunk) in w[8..].iter_mut().zip(block.chunks_exact(16)) {
+        word.copy_from_slice(chunk);
+    }
+
+    let mut key = bit_count;
+    for _ in 0..10 {
+        // Sub words
+        for word in &mut w {
+            let x = words(word);
+            let x = aes::round(x, words(&key.to_le_bytes()));
+            let x = aes::round(x, [0; 4]);
+            for (chunk, x) in word.chunks_exact_mut(4).zip(x) {
+                chunk.copy_from_slice(&x.to_le_bytes());
+            }
+            key = key.wrapping_add(1);
+        }
+
+        // Shift rows
+        let shifted = w;
+        for (i, word) in w.iter_mut().enumerate() {
+            let (row, column) = (i % 4, i / 4);
+            *word = shifted[row + 4 * ((column + row) % 4)];
+        }
+
+        // Mix columns
+        for column in 0..4 {
+            for byte in 0..16 {
+                let [a, b, c, d] = [0, 1, 2, 3].map(|row| w[4 * column + row][byte]);
+                let (ab, bc, cd) = (a ^ b, b ^ c, c ^ d);
+                let (abx, bcx, cdx) = (aes::mul2(ab), aes::mul2(bc), aes::mul2(cd));
+                w[4 * column][byte] = abx ^ bc ^ d;
+                w[4 * column + 1][byte] = bcx ^ a ^ cd;
+                w[4 * column + 2][byte] = cdx ^ ab ^ d;
+                w[4 * column + 3][byte] = abx ^ bcx ^ cdx ^ ab ^ c;
+            }
+        }
+    }
+
+    for (i, (v, m)) in chaining.iter_mut().zip(block.chunks_exact(16)).enumerate() {
+        for (j, v) in v.iter_mut().enumerate() {
+            *v ^= m[j] ^ w[i][j] ^ w[i + 8][j];
+        }
+    }
+}
+
+fn words(bytes: &[u8; 16]) -> [u32; 4] {
+    core::array::from_fn(|i| u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap()))
+}
@@ -0,0 +1,101 @@
+//! Groestl-512
+
+use super::aes::{mul2, SBOX};
+
+const BLOCK_SIZE: usize = 128;
+const ROUNDS: u8 = 14;
+
+/// First row of the circulant `MixBytes` matrix
+const MIX: [u8; 8] = [2, 2, 3, 4, 5, 3, 5, 7];
+const SHIFT_P: [usize; 8] = [0, 1, 2, 3, 4, 5, 6, 11];
+const SHIFT_Q: [usize; 8] = [1, 3, 5, 11, 0, 2, 4, 6];
+
+/// The state is 8 rows of 16 columns, stored column by column
+type State = [u8; BLOCK_SIZE];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = [0u8; BLOCK_SIZE];
+    // The output size in bits
+    state[BLOCK_SIZE - 2] = 0x02;
+
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    for block in blocks.by_ref() {
+        compress(&mut state, block.try_into().unwrap());
+    }
+
+    let remainder = blocks.remainder();
+    let mut last_blocks = [0u8; 2 * BLOCK_SIZE];
+    last_blocks[..remainder.len()].copy_from_slice(remainder);
+    last_blocks[remainder.len()] = 0x80;
+    let padded_len = if remainder.len() < BLOCK_SIZE - 8 {
+        BLOCK_SIZE
+    } else {
+        2 * BLOCK_SIZE
+    };
+    let block_count = u64::try_from(data.len() / BLOCK_SIZE + padded_len / BLOCK_SIZE).unwrap();
+    last_blocks[padded_len - 8..padded_len].copy_from_slice(&block_count.to_be_bytes());
+    for block in last_blocks[..padded_len].chunks_exact(BLOCK_SIZE) {
+        compress(&mut state, block.try_into().unwrap());
+    }
+
+    let mut output_state = state;
+    permute(&mut output_state, false);
+    let mut output = [0u8; 64];
+    for (i, byte) in output.iter_mut().enumerate() {
+        *byte = output_state[i + 64] ^ state[i + 64];
+    }
+    output
+}
+
+fn compress(state: &mut State, block: &State) {
+    let mut p: State = core::array::from_fn(|i| state[i] ^ block[i]);
+    let mut q = *block;
+    permute(&mut p, false);
+    permute(&mut q, true);
+    for (i, byte) in state.iter_mut().enumerate() {
+        *byte ^= p[i] ^ q[i];
+    }
+}
+
+fn mul(x: u8, factor: u8) -> u8 {
+    let x2 = mul2(x);
+    let x4 = mul2(x2);
+    match factor {
+        2 => x2,
+        3 => x2 ^ x,
+        4 => x4,
+        5 => x4 ^ x,
+        _ => x4 ^ x2 ^ x,
+    }
+}
+
+/// The `P` permutation, or the `Q` one if `is_q`
+fn permute(state: &mut State, is_q: bool) {
+    let shift = if is_q { SHIFT_Q } else { SHIFT_P };
+    for round in 0..ROUNDS {
+        for column in 0..16 {
+            let constant = (u8::try_from(column).unwrap() << 4) ^ round;
+            if is_q {
+                for row in 0..7 {
+                    state[column * 8 + row] ^= 0xff;
+                }
+                state[column * 8 + 7] ^= 0xff ^ constant;
+            } else {
+                state[column * 8] ^= constant;
+            }
+        }
+
+        let shifted: State = core::array::from_fn(|i| {
+            let (column, row) = (i / 8, i % 8);
+            SBOX[state[(column + shift[row]) % 16 * 8 + row] as usize]
+        });
+
+        for column in 0..16 {
+            let a = &shifted[column * 8..column * 8 + 8];
+            for row in 0..8 {
+                state[column * 8 + row] =
+                    (0..8).fold(0, |b, j| b ^ mul(a[j], MIX[(j + 8 - row) % 8]));
+            }
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! JH-512
+
+const BLOCK_SIZE: usize = 64;
+
+/// The state as eight 128-bit words, each one split into its high and low halves
+type State = [[u64; 2]; 8];
+
+const IV: State = [
+    [0x6fd1_4b96_3e00_aa17, 0x636a_2e05_7a15_d543],
+    [0x8a22_5e8d_0c97_ef0b, 0xe934_1259_f2b3_c361],
+    [0x891d_a0c1_536f_801e, 0x2aa9_056b_ea2b_6d80],
+    [0x588e_ccdb_2075_baa6, 0xa90f_3a76_baf8_3bf7],
+    [0x0169_e605_41e3_4a69, 0x46b5_8a8e_2e6f_e65a],
+    [0x1047_a7d0_c184_3c24, 0x3b6e_71b1_2d5a_c199],
+    [0xcf57_f6ec_9db1_f856, 0xa706_887c_5716_b156],
+    [0xe3c2_fcdf_e685_17fb, 0x545a_4678_cc8c_dd4b],
+];
+
+/// Round constants: the even high and low halves, then the odd ones
+const ROUND_CONSTANTS: [[u64; 4]; 42] = [
+    [
+        0x72d5_dea2_df15_f867,
+        0x7b84_150a_b723_1557,
+        0x81ab_d690_4d5a_87f6,
+        0x4e9f_4fc5_c3d1_2b40,
+    ],
+    [
+        0xea98_3ae0_5c45_fa9c,
+        0x03c5_d299_66b2_999a,
+        0x6602_96b4_f2bb_538a,
+        0xb556_141a_88db_a231,
+    ],
+    [
+        0x03a3_5a5c_9a19_0edb,
+        0x403f_b20a_87c1_4410,
+        0x1c05_1980_849e_951d,
+        0x6f33_ebad_5ee7_cddc,
+    ],
+    [
+        0x10ba_1392_02bf_6b41,
+        0xdc78_6515_f7bb_27d0,
+        0x0a2c_8139_37aa_7850,
+        0x3f1a_bfd2_4100_91d3,
+    ],
+    [
+        0x422d_5a0d_f6cc_7e90,
+        0xdd62_9f9c_92c0_97ce,
+        0x185c_a70b_c72b_44ac,
+        0xd1df_65d6_63c6_fc23,
+    ],
+    [
+        0x976e_6c03_9ee0_b81a,
+        0x2105_457e_446c_eca8,
+        0xeef1_03bb_5d8e_61fa,
+        0xfd96_97b2_9483_8197,
+    ],
+    [
+        0x4a8e_8537_db03_302f,
+        0x2a67_8d2d_fb9f_6a95,
+        0x8afe_7381_f8b8_696c,
+        0x8ac7_7246_c07f_4214,
+    ],
+    [
+        0xc5f4_158f_bdc7_5ec4,
+        0x7544_6fa7_8f11_bb80,
+        0x52de_75b7_aee4_88bc,
+        0x82b8_001e_98a6_a3f4,
+    ],
+    [
+        0x8ef4_8f33_a9a3_6315,
+        0xaa5f_5624_d5b7_f989,
+        0xb6f1_ed20_7c5a_e0fd,
+        0x36ca_e95a_0642_2c36,
+    ],
+    [
+        0xce29_3543_4efe_983d,
+        0x533a_f974_739a_4ba7,
+        0xd0f5_1f59_6f4e_8186,
+        0x0e9d_ad81_afd8_5a9f,
+    ],
+    [
+        0xa705_0667_ee34_626a,
+        0x8b0b_28be_6eb9_1727,
+        0x4774_0726_c680_103f,
+        0xe0a0_7e6f_c67e_487b,
+    ],
+    [
+        0x0d55_0aa5_4af8_a4c0,
+        0x91e3_e79f_978e_f19e,
+        0x8676_7281_5060_8dd4,
+        0x7e9e_5a41_f3e5_b062,
+    ],
+    [
+        0xfc9f_1fec_4054_207a,
+        0xe3e4_1a00_cef4_c984,
+        0x4fd7_94f5_9dfa_95d8,
+        0x552e_7e11_24c3_54a5,
+    ],
+    [
+        0x5bdf_7228_bdfe_6e28,
+        0x78f5_7fe2_0fa5_c4b2,
+        0x0589_7cef_ee49_d32e,
+        0x447e_9385_eb28_597f,
+    ],
+    [
+        0x705f_6937_b324_314a,
+        0x5e86_28f1_1dd6_e465,
+        0xc71b_7704_51b9_20e7,
+        0x74fe_43e8_23d4_878a,
+    ],
+    [
+        0x7d29_e8a3_9276_94f2,
+        0xddcb_7a09_9b30_d9c1,
+        0x1d1b_30fb_5bdc_1be0,
+        0xda24_494f_f29c_82bf,
+    ],
+    [
+        0xa4e7_ba31_b470_bfff,
+        0x0d32_4405_def8_bc48,
+        0x3bae_fc32_53bb_d339,
+        0x459f_c3c1_e029_8ba0,
+    ],
+    [
+        0xe5c9_05fd_f7ae_090f,
+        0x9470_3412_4290_f134,
+        0xa271_b701_e344_ed95,
+        0xe93b_8e36_4f2f_984a,
+    ],
+    [
+        0x8840_1d63_a06c_f615,
+        0x47c1_444b_8752_afff,
+        0x7ebb_4af1_e20a_c630,
+        0x4670_b6c5_cc6e_8ce6,
+    ],
+    [
+        0xa4d5_a456_bd4f_ca00,
+        0xda9d_844b_c83e_18ae,
+        0x7357_ce45_3064_d1ad,
+        0xe8a6_ce68_145c_2567,
+    ],
+    [
+        0xa3da_8cf2_cb0e_e116,
+        0x33e9_0658_9a94_999a,
+        0x1f60_b220_c26f_847b,
+        0xd1ce_ac7f_a0d1_8518,
+    ],
+    [
+        0x3259_5ba1_8ddd_19d3,
+        0x509a_1cc0_aaa5_b446,
+        0x9f3d_6367_e404_6bba,
+        0xf6ca_19ab_0b56_ee7e,
+    ],
+    [
+        0x1fb1_79ea_a928_2174,
+        0xe9bd_f735_3b36_51ee,
+        0x1d57_ac5a_7550_d376,
+        0x3a46_c2fe_a37d_7001,
+    ],
+    [
+        0xf735_c1af_98a4_d842,
+        0x78ed_ec20_9e6b_6779,
+        0x4183_6315_ea3a_dba8,
+        0xfac3_3b4d_3283_2c83,
+    ],
+    [
+        0xa740_3b1f_1c27_47f3,
+        0x5940_f034_b72d_769a,
+        0xe73e_4e6c_d221_4ffd,
+        0xb8fd_8d39_dc57_59ef,
+    ],
+    [
+        0x8d9b_0c49_2b49_ebda,
+        0x5ba2_d749_68f3_700d,
+        0x7d3b_aed0_7a8d_5584,
+        0xf5a5_e9f0_e4f8_8e65,
+    ],
+    [
+        0xa0b8_a2f4_3610_3b53,
+        0x0ca8_079e_753e_ec5a,
+        0x9168_9492_56e8_884f,
+        0x5bb0_5c55_f8ba_bc4c,
+    ],
+    [
+        0xe3bb_3b99_f387_947b,
+        0x75da_f4d6_726b_1c5d,
+        0x64ae_ac28_dc34_b36d,
+        0x6c34_a550_b828_db71,
+    ],
+    [
+        0xf861_e2f2_108d_512a,
+        0xe3db_6433_59dd_75fc,
+        0x1cac_bcf1_43ce_3fa2,
+        0x67bb_d13c_02e8_43b0,
+    ],
+    [
+        0x330a_5bca_8829_a175,
+        0x7f34_194d_b416_535c,
+        0x923b_94c3_0e79_4d1e,
+        0x7974_75d7_b6ee_af3f,
+    ],
+    [
+        0xeaa8_d4f7_be1a_3921,
+        0x5cf4_7e09_4c23_2751,
+        0x26a3_2453_ba32_3cd2,
+        0x44a3_174a_6da6_d5ad,
+    ],
+    [
+        0xb51d_3ea6_aff2_c908,
+        0x8359_3d98_916b_3c56,
+        0x4cf8_7ca1_7286_604d,
+        0x46e2_3ecc_086e_c7f6,
+    ],
+    [
+        0x2f98_33b3_b1bc_765e,
+        0x2bd6_66a5_efc4_e62a,
+        0x06f4_b6e8_bec1_d436,
+        0x74ee_8215_bcef_2163,
+    ],
+    [
+        0xfdc1_4e0d_f453_c969,
+        0xa77d_5ac4_0658_5826,
+        0x7ec1_1416_06e0_fa16,
+        0x7e90_af3d_2863_9d3f,
+    ],
+    [
+        0xd2c9_f2e3_009b_d20c,
+        0x5faa_ce30_b7d4_0c30,
+        0x742a_5116_f2e0_3298,
+        0x0deb_30d8_e3ce_f89a,
+    ],
+    [
+        0x4bc5_9e7b_b5f1_7992,
+        0xff51_e66e_0486_68d3,
+        0x9b23_4d57_e696_6731,
+        0xcce6_a6f3_170a_7505,
+    ],
+    [
+        0xb176_81d9_1332_6cce,
+        0x3c17_5284_f805_a262,
+        0xf42b_cbb3_7847_1547,
+        0xff46_5482_2393_6a48,
+    ],
+    [
+        0x38df_5807_4e5e_6565,
+        0xf2fc_7c89_fc86_508e,
+        0x3170_2e44_d00b_ca86,
+        0xf040_09a2_3078_474e,
+    ],
+    [
+        0x65a0_ee39_d1f7_3883,
+        0xf75e_e937_e42c_3abd,
+        0x2197_b226_0113_f86f,
+        0xa344_edd1_ef9f_dee7,
+    ],
+    [
+        0x8ba0_df15_7625_92d9,
+        0x3c85_f7f6_12dc_42be,
+        0xd8a7_ec7c_ab27_b07e,
+        0x538d_7dda_aa3e_a8de,
+    ],
+    [
+        0xaa25_ce93_bd02_69d8,
+        0x5af6_43fd_1a73_08f9,
+        0xc05f_efda_174a_19a5,
+        0x974d_6633_4cfd_216a,
+    ],
+    [
+        0x35b4_9831_db41_1570,
+        0xea1e_0fbb_edcd_549b,
+        0x9ad0_63a1_5197_4072,
+        0xf675_9dbf_9147_6fe2,
+    ],
+];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    for block in blocks.by_ref() {
+        compress(&mut state, block);
+    }
+
+    let remainder = blocks.remainder();
+    let mut last_blocks = [0u8; 2 * BLOCK_SIZE];
+    last_blocks[..remainder.len()].copy_from_slice(remainder);
+    last_blocks[remainder.len()] = 0x80;
+    let padded_len = if remainder.is_empty() {
+        BLOCK_SIZE
+    } else {
+        2 * BLOCK_SIZE
+    };
+    let bit_len = u128::try_from(data.len()).unwrap() * 8;
+    last_blocks[padded_len - 16..padded_len].copy_from_slice(&bit_len.to_be_bytes());
+    for block in last_blocks[..padded_len].chunks_exact(BLOCK_SIZE) {
+        compress(&mut state, block);
+    }
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(8).zip(state[4..].iter().flatten()) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    output
+}
+
+fn compress(state: &mut State, block: &[u8]) {
+    let mut message = [[0u64; 2]; 4];
+    for (word, chunk) in message.iter_mut().flatten().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for (h, m) in state[..4].iter_mut().zip(&message) {
+        h[0] ^= m[0];
+        h[1] ^= m[1];
+    }
+    e8(state);
+    for (h, m) in state[4..].iter_mut().zip(&message) {
+        h[0] ^= m[0];
+        h[1] ^= m[1];
+    }
+}
+
+fn e8(state: &mut State) {
+    for (round, constants) in ROUND_CONSTANTS.iter().enumerate() {
+        for half in 0..2 {
+            let [h0, h1, h2, h3, h4, h5, h6, h7] = state.each_mut().map(|h| &mut h[half]);
+            sbox([h0, h2, h4, h6], constants[half]);
+            sbox([h1, h3, h5, h7], constants[half + 2]);
+            linear([h0, h2, h4, h6], [h1, h3, h5, h7]);
+        }
+        for h in state.iter_mut().skip(1).step_by(2) {
+            swap_bits(h, round % 7);
+        }
+    }
+}
+
+fn sbox([x0, x1, x2, x3]: [&mut u64; 4], c: u64) {
+    *x3 = !*x3;
+    *x0 ^= c & !*x2;
+    let tmp = c ^ (*x0 & *x1);
+    *x0 ^= *x2 & *x3;
+    *x3 ^= !*x1 & *x2;
+    *x1 ^= *x0 & *x2;
+    *x2 ^= *x0 & !*x3;
+    *x0 ^= *x1 | *x3;
+    *x3 ^= *x1 & *x2;
+    *x1 ^= tmp & *x0;
+    *x2 ^= tmp;
+}
+
+fn linear([x0, x1, x2, x3]: [&mut u64; 4], [x4, x5, x6, x7]: [&mut u64; 4]) {
+    *x4 ^= *x1;
+    *x5 ^= *x2;
+    *x6 ^= *x3 ^ *x0;
+    *x7 ^= *x0;
+    *x0 ^= *x5;
+    *x1 ^= *x6;
+    *x2 ^= *x7 ^ *x4;
+    *x3 ^= *x4;
+}
+
+/// Swaps the adjacent groups of `2^n` bits of a 128-bit word
+fn swap_bits(h: &mut [u64; 2], n: usize) {
+    const MASKS: [u64; 6] = [
+        0x5555_5555_5555_5555,
+        0x3333_3333_3333_3333,
+        0x0f0f_0f0f_0f0f_0f0f,
+        0x00ff_00ff_00ff_00ff,
+        0x0000_ffff_0000_ffff,
+        0x0000_0000_ffff_ffff,
+    ];
+    if n == 6 {
+        h.swap(0, 1);
+    } else {
+        for word in h {
+            *word = ((*word >> (1 << n)) & MASKS[n]) | ((*word & MASKS[n]) << (1 << n));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Luffa-512
+
+const BLOCK_SIZE: usize = 32;
+
+type Word = [u32; 8];
+
+const IV: [Word; 5] = [
+    [
+        0x6d25_1e69,
+        0x44b0_51e0,
+        0x4eaa_6fb4,
+        0xdbf7_8465,
+        0x6e29_2011,
+        0x9015_2df4,
+        0xee05_8139,
+        0xdef6_10bb,
+    ],
+    [
+        0xc3b4_4b95,
+        0xd9d2_f256,
+        0x70ee_e9a0,
+        0xde09_9fa3,
+        0x5d9b_0557,
+        0x8fc9_44b3,
+        0xcf1c_cf0e,
+        0x746c_d581,
+    ],
+    [
+        0xf7ef_c89d,
+        0x5dba_5781,
+        0x0401_6ce5,
+        0xad65_9c05,
+        0x0306_194f,
+        0x666d_1836,
+        0x24aa_230a,
+        0x8b26_4ae7,
+    ],
+    [
+        0x8580_75d5,
+        0x36d7_9cce,
+        0xe571_f7d7,
+        0x204b_1f67,
+        0x3587_0c6a,
+        0x57e9_e923,
+        0x14bc_b808,
+        0x7cde_72ce,
+    ],
+    [
+        0x6c68_e9be,
+        0x5ec4_1e22,
+        0xc825_b7c7,
+        0xaffb_4363,
+        0xf5df_3999,
+        0x0fc6_88f1,
+        0xb072_24cc,
+        0x03e8_6cea,
+    ],
+];
+
+/// Constants of the words 0 and 4 of each step, for each of the five permutations
+const ROUND_CONSTANTS: [[Word; 2]; 5] = [
+    [
+        [
+            0x3039_94a6,
+            0xc0e6_5299,
+            0x6cc3_3a12,
+            0xdc56_983e,
+            0x1e00_108f,
+            0x7800_423d,
+            0x8f5b_7882,
+            0x96e1_db12,
+        ],
+        [
+            0xe033_7818,
+            0x441b_a90d,
+            0x7f34_d442,
+            0x9389_217f,
+            0xe5a8_bce6,
+            0x5274_baf4,
+            0x2688_9ba7,
+            0x9a22_6e9d,
+        ],
+    ],
+    [
+        [
+            0xb6de_10ed,
+            0x70f4_7aae,
+            0x0707_a3d4,
+            0x1c1e_8f51,
+            0x707a_3d45,
+            0xaeb2_8562,
+            0xbaca_1589,
+            0x40a4_6f3e,
+        ],
+        [
+            0x0168_5f3d,
+            0x05a1_7cf4,
+            0xbd09_caca,
+            0xf427_2b28,
+            0x144a_e5cc,
+            0xfaa7_ae2b,
+            0x2e48_f1c1,
+            0xb923_c704,
+        ],
+    ],
+    [
+        [
+            0xfc20_d9d2,
+            0x3455_2e25,
+            0x7ad8_818f,
+            0x8438_764a,
+            0xbb6d_e032,
+            0xedb7_80c8,
+            0xd984_7356,
+            0xa2c7_8434,
+        ],
+        [
+            0xe25e_72c1,
+            0xe623_bb72,
+            0x5c58_a4a4,
+            0x1e38_e2e7,
+            0x78e3_8b9d,
+            0x2758_6719,
+            0x36ed_a57f,
+            0x703a_ace7,
+        ],
+    ],
+    [
+        [
+            0xb213_afa5,
+            0xc84e_be95,
+            0x4e60_8a22,
+            0x56d8_58fe,
+            0x343b_138f,
+            0xd0ec_4e3d,
+            0x2ceb_4882,
+            0xb3ad_2208,
+        ],
+        [
+            0xe028_c9bf,
+            0x4475_6f91,
+            0x7e8f_ce32,
+            0x9565_48be,
+            0xfe19_1be2,
+            0x3cb2_26e5,
+            0x5944_a28e,
+            0xa1c4_c355,
+        ],
+    ],
+    [
+        [
+            0xf0d2_e9e3,
+            0xac11_d7fa,
+            0x1bcb_66f2,
+            0x6f2d_9bc9,
+            0x7860_2649,
+            0x8eda_e952,
+            0x3b6b_a548,
+            0xedae_9520,
+        ],
+        [
+            0x5090_d577,
+            0x2d19_25ab,
+            0xb464_96ac,
+            0xd192_5ab0,
+            0x2913_1ab6,
+            0x0fc0_53c3,
+            0x3f01_4f0c,
+            0xfc05_3c31,
+        ],
+    ],
+];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    for block in blocks.by_ref() {
+        round(&mut state, block);
+    }
+
+    let remainder = blocks.remainder();
+    let mut last_block = [0u8; BLOCK_SIZE];
+    last_block[..remainder.len()].copy_from_slice(remainder);
+    last_block[remainder.len()] = 0x80;
+    round(&mut state, &last_block);
+
+    let mut output = [0u8; 64];
+    for half in output.chunks_exact_mut(BLOCK_SIZE) {
+        round(&mut state, &[0; BLOCK_SIZE]);
+        for (i, chunk) in half.chunks_exact_mut(4).enumerate() {
+            let word = state.iter().fold(0, |word, v| word ^ v[i]);
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+    output
+}
+
+fn xor(a: &Word, b: &Word) -> Word {
+    core::array::from_fn(|i| a[i] ^ b[i])
+}
+
+/// Multiplication by 2 in the ring of the message injection
+fn mul2(s: &Word) -> Word {
+    let t = s[7];
+    [t, s[0] ^ t, s[1], s[2] ^ t, s[3] ^ t, s[4], s[5], s[6]]
+}
+
+fn round(v: &mut [Word; 5], block: &[u8]) {
+    let mut m = [0u32; 8];
+    for (word, chunk) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    // Message injection
+    let a = mul2(&v.iter().fold([0; 8], |a, v| xor(&a, v)));
+    for v in v.iter_mut() {
+        *v = xor(v, &a);
+    }
+    let b = xor(&mul2(&v[0]), &v[1]);
+    for j in 1..5 {
+        v[j] = xor(&mul2(&v[j]), &v[(j + 1) % 5]);
+    }
+    v[0] = xor(&mul2(&b), &v[4]);
+    for j in (2..5).rev() {
+        v[j] = xor(&mul2(&v[j]), &v[j - 1]);
+    }
+    v[1] = xor(&mul2(&v[1]), &b);
+    for v in v.iter_mut() {
+        *v = xor(v, &m);
+        m = mul2(&m);
+    }
+
+    // Permutations, after the tweak
+    for (j, (v, [constants0, constants4])) in v.iter_mut().zip(ROUND_CONSTANTS).enumerate() {
+        for word in &mut v[4..] {
+            *word = word.rotate_left(u32::try_from(j).unwrap());
+        }
+        for (constant0, constant4) in constants0.into_iter().zip(constants4) {
+            sub_crumb(v, [0, 1, 2, 3]);
+            sub_crumb(v, [5, 6, 7, 4]);
+            for k in 0..4 {
+                mix_word(v, k, k + 4);
+            }
+            v[0] ^= constant0;
+            v[4] ^= constant4;
+        }
+    }
+}
+
+fn sub_crumb(v: &mut Word, [i0, i1, i2, i3]: [usize; 4]) {
+    let (mut a0, mut a1, mut a2, mut a3) = (v[i0], v[i1], v[i2], v[i3]);
+    let mut tmp = a0;
+    a0 |= a1;
+    a2 ^= a3;
+    a1 = !a1;
+    a0 ^= a3;
+    a3 &= tmp;
+    a1 ^= a3;
+    a3 ^= a2;
+    a2 &= a0;
+    a0 = !a0;
+    a2 ^= a1;
+    a1 |= a3;
+    tmp ^= a1;
+    a3 ^= a2;
+    a2 &= a1;
+    a1 ^= a0;
+    a0 = tmp;
+    (v[i0], v[i1], v[i2], v[i3]) = (a0, a1, a2, a3);
+}
+
+fn mix_word(v: &mut Word, i: usize, j: usize) {
+    v[j] ^= v[i];
+    v[i] = v[i].rotate_left(2) ^ v[j];
+    v[j] = v[j].rotate_left(14) ^ v[i];
+    v[i] = v[i].rotate_left(10) ^ v[j];
+    v[j] = v[j].rotate_left(1);
+}
@@ -0,0 +1,75 @@
+//! The X11 hash of Dash: eleven 512-bit hashes of the SHA-3 competition applied in sequence,
+//! as implemented by sphlib.
+
+mod aes;
+mod blake;
+mod bmw;
+mod cubehash;
+mod echo;
+mod groestl;
+mod jh;
+mod keccak;
+mod luffa;
+mod shavite;
+mod simd;
+mod skein;
+
+/// The X11 hash, the first half of the output of the last stage.
+#[must_use]
+pub fn x11_hash(data: &[u8]) -> [u8; 32] {
+    let hash = [
+        bmw::hash,
+        groestl::hash,
+        skein::hash,
+        jh::hash,
+        keccak::hash,
+        luffa::hash,
+        cubehash::hash,
+        shavite::hash,
+        simd::hash,
+        echo::hash,
+    ]
+    .iter()
+    .fold(blake::hash(data), |hash, stage| stage(&hash));
+    let mut x11 = [0; 32];
+    x11.copy_from_slice(&hash[..32]);
+    x11
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btc_header::Header;
+
+    type Stage = fn(&[u8]) -> [u8; 64];
+
+    #[test]
+    fn test_empty_input() {
+        let stages: [(Stage, &str); 11] = [
+            (blake::hash, "a8cfbbd73726062df0c6864dda65defe58ef0cc52a5625090fa17601e1eecd1b628e94f396ae402a00acc9eab77b4d4c2e852aaaa25a636d80af3fc7913ef5b8"),
+            (bmw::hash, "6a725655c42bc8a2a20549dd5a233a6a2beb01616975851fd122504e604b46af7d96697d0b6333db1d1709d6df328d2a6c786551b0cce2255e8c7332b4819c0e"),
+            (groestl::hash, "6d3ad29d279110eef3adbd66de2a0345a77baede1557f5d099fce0c03d6dc2ba8e6d4a6633dfbd66053c20faa87d1a11f39a7fbe4a6c2f009801370308fc4ad8"),
+            (skein::hash, "bc5b4c50925519c290cc634277ae3d6257212395cba733bbad37a4af0fa06af41fca7903d06564fea7a2d3730dbdb80c1f85562dfcc070334ea4d1d9e72cba7a"),
+            (jh::hash, "90ecf2f76f9d2c8017d979ad5ab96b87d58fc8fc4b83060f3f900774faa2c8fabe69c5f4ff1ec2b61d6b316941cedee117fb04b1f4c5bc1b919ae841c50eec4f"),
+            (keccak::hash, "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e"),
+            (luffa::hash, "6e7de4501189b3ca58f3ac114916654bbcd4922024b4cc1cd764acfe8ab4b7805df133eab345ffdb1c414564c924f48e0a301824e2ac4c34bd4efde2e43da90e"),
+            (cubehash::hash, "4a1d00bbcfcb5a9562fb981e7f7db3350fe2658639d948b9d57452c22328bb32f468b072208450bad5ee178271408be0b16e5633ac8a1e3cf9864cfbfc8e043a"),
+            (shavite::hash, "a485c1b2578459d1efc5dddd840bb0b4a650ac82fe68f58c4442ccda747da006b2d1dc6b4a4eb7d84ff91e1f466fef429d259acd995dddcad16fa545c7a6e5ba"),
+            (simd::hash, "51a5af7e243cd9a5989f7792c880c4c3168c3d60c4518725fe5757d1f7a69c6366977eaba7905ce2da5d7cfd07773725f0935b55f3efb954996689a49b6d29e0"),
+            (echo::hash, "158f58cc79d300a9aa292515049275d051a28ab931726d0ec44bdd9faef4a702c36db9e7922fff077402236465833c5cc76af4efc352b4b44c7fa15aa0ef234e"),
+        ];
+        for (hash, expected) in stages {
+            assert_eq!(hex::encode(hash(&[])), expected);
+        }
+    }
+
+    #[test]
+    fn test_dash_genesis_block_hash() {
+        let genesis = Header::from_block_header_vec(&hex::decode("010000000000000000000000000000000000000000000000000000000000000000000000c762a6567f3cc092f0684bb62b7e00a84890b990f07cc71a6bb58d64b98e02e0022ddb52f0ff0f1ec23fb901").unwrap()).unwrap();
+
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "00000ffd590b1485b3caadc19b22e6379c733355108f107a430458cdf3407ab6"
+        );
+    }
+}
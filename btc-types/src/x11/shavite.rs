@@ -0,0 +1,121 @@
+//! SHAvite-3-512
+
+use super::aes;
+
+const BLOCK_SIZE: usize = 128;
+/// Offset of the bit count in the last block
+const COUNT_OFFSET: usize = 110;
+
+const IV: [u32; 16] = [
+    0x72fc_cdd8,
+    0x79ca_4727,
+    0x128a_077b,
+    0x40d5_5aec,
+    0xd190_1a06,
+    0x430a_e307,
+    0xb29f_5cd1,
+    0xdf07_fbfc,
+    0x8e45_d73d,
+    0x681a_b538,
+    0xbde8_6578,
+    0xdd57_7e47,
+    0xe275_eade,
+    0x502d_9fcd,
+    0xb935_7178,
+    0x022a_4b9a,
+];
+
+/// Positions where the bit count is mixed into the round keys, with the order of its words
+const COUNT_POSITIONS: [(usize, [usize; 4]); 4] = [
+    (32, [0, 1, 2, 3]),
+    (164, [3, 2, 1, 0]),
+    (316, [2, 3, 0, 1]),
+    (440, [1, 0, 3, 2]),
+];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    let mut bit_count = 0u128;
+    for block in blocks.by_ref() {
+        bit_count += 1024;
+        compress(&mut state, block, bit_count);
+    }
+
+    let remainder = blocks.remainder();
+    let total_bits = u128::try_from(data.len()).unwrap() * 8;
+    let mut last_block = [0u8; BLOCK_SIZE];
+    last_block[..remainder.len()].copy_from_slice(remainder);
+    last_block[remainder.len()] = 0x80;
+    // The block carrying no message bits is compressed with a zero count
+    let mut last_count = if remainder.is_empty() { 0 } else { total_bits };
+    if remainder.len() >= COUNT_OFFSET {
+        compress(&mut state, &last_block, last_count);
+        last_block = [0; BLOCK_SIZE];
+        last_count = 0;
+    }
+    last_block[COUNT_OFFSET..COUNT_OFFSET + 16].copy_from_slice(&total_bits.to_le_bytes());
+    // The output size in bits
+    last_block[BLOCK_SIZE - 2..].copy_from_slice(&512u16.to_le_bytes());
+    compress(&mut state, &last_block, last_count);
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+fn compress(state: &mut [u32; 16], block: &[u8], bit_count: u128) {
+    let count: [u32; 4] =
+        core::array::from_fn(|i| u32::try_from((bit_count >> (32 * i)) & 0xffff_ffff).unwrap());
+
+    let mut rk = [0u32; 448];
+    for (key, chunk) in rk.iter_mut().zip(block.chunks_exact(4)) {
+        *key = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    let mut u = 32;
+    loop {
+        for _ in 0..8 {
+            let x = aes::round([rk[u - 31], rk[u - 30], rk[u - 29], rk[u - 32]], [0; 4]);
+            for i in 0..4 {
+                rk[u + i] = x[i] ^ rk[u - 4 + i];
+            }
+            if let Some((_, order)) = COUNT_POSITIONS.iter().find(|(position, _)| *position == u) {
+                for i in 0..4 {
+                    rk[u + i] ^= count[order[i]];
+                }
+                rk[u + 3] = !rk[u + 3];
+            }
+            u += 4;
+        }
+        if u == rk.len() {
+            break;
+        }
+        for _ in 0..8 {
+            for i in 0..4 {
+                rk[u + i] = rk[u - 32 + i] ^ rk[u - 7 + i];
+            }
+            u += 4;
+        }
+    }
+
+    let mut p = *state;
+    for round_keys in rk.chunks_exact(32) {
+        for (half, keys) in round_keys.chunks_exact(16).enumerate() {
+            let right: [u32; 4] = p[8 * half + 4..8 * half + 8].try_into().unwrap();
+            let mut x = core::array::from_fn(|i| right[i] ^ keys[i]);
+            for key in keys[4..].chunks_exact(4) {
+                x = aes::round(x, key.try_into().unwrap());
+            }
+            x = aes::round(x, [0; 4]);
+            for i in 0..4 {
+                p[8 * half + i] ^= x[i];
+            }
+        }
+        p.rotate_right(4);
+    }
+    for (h, p) in state.iter_mut().zip(p) {
+        *h ^= p;
+    }
+}
@@ -0,0 +1,209 @@
+//! SIMD-512
+
+const BLOCK_SIZE: usize = 128;
+const MODULUS: i32 = 257;
+
+const IV: [u32; 32] = [
+    0x0ba1_6b95,
+    0x72f9_99ad,
+    0x9fec_c2ae,
+    0xba32_64fc,
+    0x5e89_4929,
+    0x8e9f_30e5,
+    0x2f1d_aa37,
+    0xf0f2_c558,
+    0xac50_6643,
+    0xa906_35a5,
+    0xe25b_878b,
+    0xaab7_878f,
+    0x8881_7f7a,
+    0x0a02_892b,
+    0x559a_7550,
+    0x598f_657e,
+    0x7eef_60a1,
+    0x6b70_e3e8,
+    0x9c17_14d1,
+    0xb958_e2a8,
+    0xab02_675e,
+    0xed1c_014f,
+    0xcd8d_65bb,
+    0xfdb7_a257,
+    0x0925_4899,
+    0xd699_c7bc,
+    0x9019_b6dc,
+    0x2b90_22e4,
+    0x8fa1_4956,
+    0x21bf_9bd3,
+    0xb94d_0943,
+    0x6ffd_dc22,
+];
+
+/// The powers of 41, a 256th root of unity modulo 257
+const ALPHA: [i32; 256] = {
+    let mut alpha = [1; 256];
+    let mut i = 1;
+    while i < 256 {
+        alpha[i] = alpha[i - 1] * 41 % MODULUS;
+        i += 1;
+    }
+    alpha
+};
+
+/// For each round: the first message word group, the offsets of the two transform
+/// values combined into a word, their multiplier and the rotations of the steps
+const ROUNDS: [(usize, [isize; 2], i32, [u32; 4]); 4] = [
+    (0, [0, 1], 185, [3, 23, 17, 27]),
+    (8, [0, 1], 185, [28, 19, 22, 7]),
+    (16, [-256, -128], 233, [29, 9, 15, 5]),
+    (24, [-383, -255], 233, [4, 13, 10, 25]),
+];
+
+/// Order in which the groups of 16 transform values are read into the message words
+const WORD_GROUPS: [usize; 32] = [
+    4, 6, 0, 2, 7, 5, 3, 1, 15, 11, 12, 8, 9, 13, 10, 14, 17, 18, 23, 20, 22, 21, 16, 19, 30, 24,
+    25, 31, 27, 29, 28, 26,
+];
+
+/// Masks of the permutations mixing the eight parallel Feistel ladders between steps
+const PERMUTATIONS: [usize; 11] = [1, 6, 2, 3, 5, 7, 4, 1, 6, 2, 3];
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let mut blocks = data.chunks_exact(BLOCK_SIZE);
+    for block in blocks.by_ref() {
+        compress(&mut state, block, false);
+    }
+
+    let remainder = blocks.remainder();
+    if !remainder.is_empty() {
+        let mut last_block = [0u8; BLOCK_SIZE];
+        last_block[..remainder.len()].copy_from_slice(remainder);
+        compress(&mut state, &last_block, false);
+    }
+
+    let mut length_block = [0u8; BLOCK_SIZE];
+    let bit_len = u64::try_from(data.len()).unwrap().wrapping_mul(8);
+    length_block[..8].copy_from_slice(&bit_len.to_le_bytes());
+    compress(&mut state, &length_block, true);
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+fn compress(chaining: &mut [u32; 32], block: &[u8], is_last: bool) {
+    let y = transform(block, is_last);
+
+    let mut state = *chaining;
+    for (word, chunk) in state.iter_mut().zip(block.chunks_exact(4)) {
+        *word ^= u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    for (round, (first_group, [offset_low, offset_high], multiplier, rotations)) in
+        ROUNDS.into_iter().enumerate()
+    {
+        let mut w = [0u32; 64];
+        for (words, group) in w.chunks_exact_mut(8).zip(&WORD_GROUPS[first_group..]) {
+            for (k, word) in words.iter_mut().enumerate() {
+                let index = group * 16 + 2 * k;
+                let low = y[index.wrapping_add_signed(offset_low)] * multiplier;
+                let high = y[index.wrapping_add_signed(offset_high)] * multiplier;
+                *word = low16(low) | (low16(high) << 16);
+            }
+        }
+        for (step, words) in w.chunks_exact(8).enumerate() {
+            step_big(
+                &mut state,
+                words.try_into().unwrap(),
+                step >= 4,
+                [rotations[step % 4], rotations[(step + 1) % 4]],
+                PERMUTATIONS[round + step],
+            );
+        }
+    }
+
+    // Feed forward of the chaining value through four more steps
+    for (step, (rotations, permutation)) in
+        [([4, 13], 4), ([13, 10], 5), ([10, 25], 6), ([25, 4], 0)]
+            .into_iter()
+            .enumerate()
+    {
+        let words = chaining[8 * step..8 * step + 8].try_into().unwrap();
+        step_big(
+            &mut state,
+            words,
+            false,
+            rotations,
+            PERMUTATIONS[permutation],
+        );
+    }
+
+    *chaining = state;
+}
+
+/// The number theoretic transform of the block, with its tweak, as values in `-128..=128`
+fn transform(block: &[u8], is_last: bool) -> [i32; 256] {
+    let mut y = [0i32; 256];
+    for (i, y) in y.iter_mut().enumerate() {
+        // The input is `block` padded with zeros to 256 values, in bit-reversed order
+        let j = usize::from(u8::try_from(i).unwrap().reverse_bits());
+        if j < BLOCK_SIZE {
+            *y = i32::from(block[j]);
+        }
+    }
+    let mut half = 1;
+    while half < 256 {
+        let stride = 128 / half;
+        for start in (0..256).step_by(2 * half) {
+            for k in 0..half {
+                let even = y[start + k];
+                let odd = y[start + k + half] * ALPHA[k * stride] % MODULUS;
+                y[start + k] = (even + odd) % MODULUS;
+                y[start + k + half] = (even - odd).rem_euclid(MODULUS);
+            }
+        }
+        half *= 2;
+    }
+
+    for (i, y) in y.iter_mut().enumerate() {
+        let mut tweak = ALPHA[(255 * i) % 256];
+        if is_last {
+            tweak += ALPHA[(253 * i) % 256];
+        }
+        let value = (*y + tweak) % MODULUS;
+        *y = if value <= 128 { value } else { value - MODULUS };
+    }
+    y
+}
+
+fn low16(x: i32) -> u32 {
+    u32::try_from(x.rem_euclid(1 << 16)).unwrap()
+}
+
+/// One step of the eight Feistel ladders, with the IF function, or MAJ if `is_majority`
+fn step_big(
+    state: &mut [u32; 32],
+    w: &[u32; 8],
+    is_majority: bool,
+    [r, s]: [u32; 2],
+    permutation: usize,
+) {
+    let (a, rest) = state.split_at_mut(8);
+    let (b, rest) = rest.split_at_mut(8);
+    let (c, d) = rest.split_at_mut(8);
+    let rotated_a: [u32; 8] = core::array::from_fn(|n| a[n].rotate_left(r));
+    for n in 0..8 {
+        let f = if is_majority {
+            (a[n] & b[n]) | ((a[n] | b[n]) & c[n])
+        } else {
+            ((b[n] ^ c[n]) & a[n]) ^ c[n]
+        };
+        let t = d[n].wrapping_add(w[n]).wrapping_add(f);
+        a[n] = t.rotate_left(s).wrapping_add(rotated_a[permutation ^ n]);
+        d[n] = c[n];
+        c[n] = b[n];
+        b[n] = rotated_a[n];
+    }
+}
@@ -0,0 +1,114 @@
+//! Skein-512-512
+
+const BLOCK_SIZE: usize = 64;
+
+const IV: [u64; 8] = [
+    0x4903_adff_749c_51ce,
+    0x0d95_de39_9746_df03,
+    0x8fd1_9341_27c7_9bce,
+    0x9a25_5629_ff35_2cb1,
+    0x5db6_2599_df6c_a7b0,
+    0xeabe_394c_a9d5_c3f4,
+    0x9911_12c7_1a75_b523,
+    0xae18_a40b_660f_cc33,
+];
+
+/// Rotations of the four `MIX8` of the even and odd Threefish round groups
+const ROTATIONS: [[[u32; 4]; 4]; 2] = [
+    [
+        [46, 36, 19, 37],
+        [33, 27, 14, 42],
+        [17, 49, 36, 39],
+        [44, 9, 54, 56],
+    ],
+    [
+        [39, 30, 34, 24],
+        [13, 50, 10, 17],
+        [25, 29, 39, 43],
+        [8, 35, 56, 22],
+    ],
+];
+/// Word permutation of the four `MIX8`
+const PERMUTATIONS: [[usize; 8]; 4] = [
+    [0, 1, 2, 3, 4, 5, 6, 7],
+    [2, 1, 4, 7, 6, 5, 0, 3],
+    [4, 1, 6, 3, 0, 5, 2, 7],
+    [6, 1, 0, 7, 2, 5, 4, 3],
+];
+
+/// Tweak types, already shifted by one bit: message, final message and output
+const TYPE_MESSAGE: u64 = 96;
+const TYPE_FINAL: u64 = 352;
+const TYPE_OUTPUT: u64 = 510;
+const TYPE_FIRST: u64 = 128;
+
+pub(super) fn hash(data: &[u8]) -> [u8; 64] {
+    let mut state = IV;
+    let block_count = data.len().div_ceil(BLOCK_SIZE).max(1);
+    for (i, block) in data.chunks(BLOCK_SIZE).take(block_count - 1).enumerate() {
+        let first = if i == 0 { TYPE_FIRST } else { 0 };
+        ubi(
+            &mut state,
+            block,
+            (i + 1) * BLOCK_SIZE,
+            TYPE_MESSAGE + first,
+        );
+    }
+    let first = if block_count == 1 { TYPE_FIRST } else { 0 };
+    ubi(
+        &mut state,
+        &data[(block_count - 1) * BLOCK_SIZE..],
+        data.len(),
+        TYPE_FINAL + first,
+    );
+    ubi(&mut state, &[0; 8], 8, TYPE_OUTPUT);
+
+    let mut output = [0u8; 64];
+    for (chunk, word) in output.chunks_exact_mut(8).zip(state) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    output
+}
+
+/// Processes a block, zero padded, with the number of bytes processed so far
+fn ubi(state: &mut [u64; 8], block: &[u8], position: usize, block_type: u64) {
+    let mut padded = [0u8; BLOCK_SIZE];
+    padded[..block.len()].copy_from_slice(block);
+    let mut message = [0u64; 8];
+    for (word, chunk) in message.iter_mut().zip(padded.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let mut key = [0u64; 9];
+    key[..8].copy_from_slice(state);
+    key[8] = state.iter().fold(0x1bd1_1bda_a9fc_1a22, |k, h| k ^ h);
+    let tweak0 = u64::try_from(position).unwrap();
+    let tweak1 = block_type << 55;
+    let tweak = [tweak0, tweak1, tweak0 ^ tweak1];
+
+    let mut p = message;
+    for s in 0..18 {
+        add_key(&mut p, &key, &tweak, s);
+        for (rotations, permutation) in ROTATIONS[s % 2].iter().zip(PERMUTATIONS) {
+            for (pair, rotation) in rotations.iter().enumerate() {
+                let (x0, x1) = (permutation[2 * pair], permutation[2 * pair + 1]);
+                p[x0] = p[x0].wrapping_add(p[x1]);
+                p[x1] = p[x1].rotate_left(*rotation) ^ p[x0];
+            }
+        }
+    }
+    add_key(&mut p, &key, &tweak, 18);
+
+    for (h, (m, p)) in state.iter_mut().zip(message.iter().zip(p)) {
+        *h = m ^ p;
+    }
+}
+
+fn add_key(p: &mut [u64; 8], key: &[u64; 9], tweak: &[u64; 3], s: usize) {
+    for (i, word) in p.iter_mut().enumerate() {
+        *word = word.wrapping_add(key[(s + i) % 9]);
+    }
+    p[5] = p[5].wrapping_add(tweak[s % 3]);
+    p[6] = p[6].wrapping_add(tweak[(s + 1) % 3]);
+    p[7] = p[7].wrapping_add(u64::try_from(s).unwrap());
+}
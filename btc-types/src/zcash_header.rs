@@ -1,3 +1,4 @@
+use bitcoin::consensus::encode::{deserialize_partial, serialize, VarInt};
 use near_sdk::near;
 
 use crate::hash::{double_sha256, H256};
@@ -36,16 +37,18 @@ impl Header {
 
     #[must_use]
     pub fn block_hash(&self) -> H256 {
-        let block_header = self.get_block_header_vec();
+        let block_header = self.to_block_header_vec();
         double_sha256(&block_header)
     }
 
     pub fn block_hash_pow(&self) -> H256 {
-        let block_header = self.get_block_header_vec();
+        let block_header = self.to_block_header_vec();
         double_sha256(&block_header)
     }
 
-    fn get_block_header_vec(&self) -> Vec<u8> {
+    /// Consensus serialization of the header, the counterpart of [`Self::from_block_header_vec`]
+    #[must_use]
+    pub fn to_block_header_vec(&self) -> Vec<u8> {
         let mut block_header = Vec::with_capacity(Self::SIZE);
         block_header.extend_from_slice(&self.version.to_le_bytes());
         block_header.extend(self.prev_block_hash.0);
@@ -54,7 +57,8 @@ impl Header {
         block_header.extend_from_slice(&self.time.to_le_bytes());
         block_header.extend_from_slice(&self.bits.to_le_bytes());
         block_header.extend_from_slice(&self.nonce.0);
-        block_header.extend_from_slice(&[0xfd, 0x40, 0x05]); // The compact size of an Equihash solution in bytes (always 1344).
+        // The compact size of an Equihash solution in bytes (1344 except for regtest).
+        block_header.extend(serialize(&VarInt::from(self.solution.len())));
         block_header.extend_from_slice(&self.solution);
 
         block_header
@@ -73,8 +77,27 @@ impl Header {
         block_header
     }
 
+    /// Size of the consensus-serialized header at the start of `bytes`, read from the compact
+    /// size of the Equihash solution. [`Self::SIZE`] is the size for mainnet and testnet only.
+    ///
+    /// # Errors
+    /// If `bytes` is too short to hold the compact size of the solution
+    pub fn consensus_size(bytes: &[u8]) -> Result<usize, Error> {
+        let (solution_len, solution_len_size) =
+            deserialize_partial::<VarInt>(bytes.get(140..).ok_or(Error::InvalidLength)?)
+                .map_err(|_| Error::InvalidLength)?;
+        usize::try_from(solution_len.0)
+            .ok()
+            .and_then(|solution_len| (140 + solution_len_size).checked_add(solution_len))
+            .ok_or(Error::InvalidLength)
+    }
+
     pub fn from_block_header_vec(block_header: &[u8]) -> Result<Self, Error> {
-        if block_header.len() != Self::SIZE {
+        let (solution_len, solution_len_size) =
+            deserialize_partial::<VarInt>(block_header.get(140..).ok_or(Error::InvalidLength)?)
+                .map_err(|_| Error::InvalidLength)?;
+        let solution_start = 140 + solution_len_size;
+        if u64::try_from(block_header.len() - solution_start) != Ok(solution_len.0) {
             return Err(Error::InvalidLength);
         }
 
@@ -101,7 +124,7 @@ impl Header {
                 .map_err(|_| Error::IntParseError)?,
         );
         let nonce = H256::try_from(&block_header[108..140]).map_err(|_| Error::InvalidLength)?;
-        let solution = block_header[143..].to_vec();
+        let solution = block_header[solution_start..].to_vec();
 
         Ok(Self {
             version,
@@ -120,6 +143,21 @@ impl Header {
     }
 }
 
+/// Formats the header as its consensus hex, as returned by `getblockheader <hash> false`
+impl std::fmt::Display for Header {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&hex::encode(self.to_block_header_vec()))
+    }
+}
+
+impl std::str::FromStr for Header {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_block_header_vec(&hex::decode(s).map_err(|_| Error::InvalidHex)?)
+    }
+}
+
 #[near(serializers = [borsh, json])]
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
@@ -195,4 +233,90 @@ mod tests {
             "00000000012860b68fc02b728ef20b64c2b15714c988d0664c54e0ace815b1bd"
         );
     }
+
+    #[test]
+    fn test_consensus_hex() {
+        let mut block_header_bytes = vec![0u8; 140];
+        block_header_bytes.extend([3, 1, 2, 3]);
+        let block_header_hex = hex::encode(&block_header_bytes);
+
+        let header: Header = block_header_hex.parse().unwrap();
+        assert_eq!(header.solution, vec![1, 2, 3]);
+        assert_eq!(header.to_string(), block_header_hex);
+        assert_eq!(
+            block_header_hex[..block_header_hex.len() - 2].parse::<Header>(),
+            Err(crate::utils::DecodeHeaderError::InvalidLength)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zcash_header")]
+    fn test_compact_headers_with_regtest_solution() {
+        // The Equihash solution of regtest has 36 bytes instead of 1344
+        let mut first_bytes = vec![4, 0, 0, 0];
+        first_bytes.extend([0u8; 136]);
+        first_bytes.push(36);
+        first_bytes.extend([1u8; 36]);
+        let first = Header::from_block_header_vec(&first_bytes).unwrap();
+        let second = Header {
+            prev_block_hash: first.block_hash(),
+            time: 1,
+            solution: vec![2; 36],
+            ..first.clone()
+        };
+        assert_eq!(Header::consensus_size(&first_bytes), Ok(first_bytes.len()));
+
+        let second_bytes = second.to_block_header_vec();
+        let mut compact_bytes = first_bytes;
+        compact_bytes.extend(&second_bytes[..4]);
+        compact_bytes.extend(&second_bytes[36..]);
+
+        assert_eq!(
+            crate::header::block_headers_from_compact_vec(&compact_bytes),
+            Ok(vec![first, second])
+        );
+        assert_eq!(
+            crate::header::block_headers_from_compact_vec(
+                &compact_bytes[..compact_bytes.len() - 1]
+            ),
+            Err(crate::utils::DecodeHeaderError::InvalidLength)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "zcash_header")]
+    fn test_decode_compact_mainnet_headers() {
+        // Zcash mainnet blocks 2940821 and 2940822
+        let first_bytes = hex::decode("04000000b1bd910969e61849582162baeac38f53d26757a76f54999f31bd610000000000f4c3d4610d8c7a1ddf2e66ca4b1b13e8da182a6dcdfd7a09625641ca50dbf3abf24a9c22fbc00d6eb26caee33f4035edb74055612c3c399eefa429469567b78cc55638684fe8011ca879683d0000000000000000000000000000010000000000000000000008b12dfd40050012c102a64945b7ff3ad9c75cfa678aa4a59c72843fc614be61a61db5c2b6f5e14168880e949eb817500db2b6f1c1e3d83578f900dc298d5bac399df6feda37dbebec5f5e9746fdb29463f94015320166b4f8d21335b2c7f159d26b821f530d7b270ed0e9e02f6bee1fc62e2b5bf31fdbfbb2220d34e3131a8ca6d4717320d1db0b6cdf8025d1e6b216b1d21f5b2fd4fd925427a0f6e49e55ce9ab035831d65dc9684d09951b41f0075b0d7f008e333e42f70345dfb47a421d14d0ade2e8282212ece07c071cb038802bb0fe5ad98bb39bc142901d5c752d3c76e4346fcf6e0ef81e526bac13a1fd26c7ce0cb07cb8f36c5f2f9ead5e6ac3c9d34ae07702092b102d831a729e1a2efb3f68d48ccd6de947fc32c303ff7c167f648aa372a5acba74194bba998091381dcfa5ea26559bb40f862bd8478779d7a575131c60fb6036d07ebb4b1a3b89aa37721c87331b0cf0086dd8602969f56e4247222c97f50e92ad4f03795387fd3802117a245e19ff3beb4a2f6cb6475dcf5f90f10dc40a9c6f9fd2d64d191e57e5441c20313886c48ff33e84898fa691e21c5f4b07f92f19a883c3ab802d5322f128617a494f045774cdb701da63bfe1a710a7daac42053190ce6ad43dd41a9015d0f815557522364aed888d334c2be9eb43ff3c004e9b77cd78cf3426d335d97d69770f7cfb53e884a1c338f96dd0414035013f6ad706c19b1eca27f54affd12433f3ac5b11201b72ef0c9f90b4368d561777c76aae8415b0f4b0c09f702bfd6e0099603ea24cf71365af3afbd06ca72a6fdac59ec8513adf9f77574c4853687e9bc33271e254165ad489fbe8ab9686a66d66fdfa024fe0b09242151c705e151694351c51c187eef05664174e57026a3f9699d0bbb89d0c0848b38c83052c855fb88ad61780bc5825887c9ccb047fd82c311c715acbfb262010bc091a18a0b29f884302fb405c1ca4c4db627a124bb2627b48dd9a4d5e5c27a28172bf56edb3a56550a3262375643ff15e5a58210e61a545538f3921ffc22b5b509358b86a7d31e55752847dfa2030117befc0159b8d4144813f0b641c5db6a333295b9c26df73c1e7948f56b14207d228d449e5c410c05db009f1e7907ac816f0711c033f9a6518afd34d7e681b4dd40af125c820e86667ad53c6761c9d18e88d48a3c0732a01009ee607b565b04cdec9540007e6b55c2e84f3be11f5ac7a69c23c8a1546982a763f65a5cdb41d40768142c613aa80f277ca75ca53370dd15bf0cd5bf1b301dbce78fdfd80e7ce2b164f867d99c4e9347bf3968134ea1e5bc688cc7dc51433c73f4f9c685ecf792252046617b81d0c284ab03655129c2a641a586cedd62244ac5d19499fc4591cce32de9eb6234f8e76a901c7a69ddbd6125e39ba0670981224ea756a0c1f78f9c030d0b393a924fb34caf2118d35bbf88eca388fc321dde97b2178c3bc567a2f2e1dda60b723de8796b1a1e62af294ee90bbdf32523124d6a293d916b4fcc13475b4c81b430be41fd9136e78d392f7369de1ce27116be12d11aa0afa587d1c19da8c26c36b50917f96a1d23465d366901ddb763228b225d2329a716d6deb718d72710f9e9566bf9715c3c8deebab31f2fba81a12f86ab05956f62b3d884a3beb6b3cbdd115f3ce62c05f89410ed0b067485152133b2fc89215348cda084151b3f294947d336bf76429d06a8353e31b35b56a207b15dff485b06fcf265397acf6b1f0344257c288914b96bc35d0f71d8c19772c8335df04936467e603909dc8bff139e92cf629ca15cca2ffb29798ed75c1a1b9b219b91e35c67dfffc6430a5ad9d5ad90bc8b871918adb50cb6057ff5faa3ee0243f5a3464c3a3a53375aa722509af3d396dcb5dadcc1b81a40a49c6836").unwrap();
+        let second_bytes = hex::decode("0400000078b0f3950da0e303b9427089a37c3375ddccd98b6026380bf3201c00000000003d9b4c9dc7f60a5e06fc9c7e47903b5597c22fe34937f47f3237cb5f50f08f7ad0143ca9bf9eb20a6aa58acdb1b77eda32781756962620bbf5f1c2078932fa3f6157386813d0011c26c06a7c00000000000000000000000000005200000000000000000080009771fd4005002a18686ef31e53d78c8078a0a4d7707eb5fc510020127d127f1a6e871709549152b303e93d1439a5ef122431e7e627600b858d3166a2976f2ed118dfc6c925940995d56a76516586761107f1d8fa01253488a4038ce92b181b9a711fa4711eed2fd26523b0fce30007777f6aee2d1f0dda54d0a8cdb5c6ac47bede01ea23ddb6047c9fdd955166c56493c5d2ed9ef04f4e25383dcef98a5b36571dbde3aac45a7756687a35692400cfd1b9f90f10d968dd400e9f587e7243879cf6e90a723e2aef5dd5bbb9fec409f243bb9d5b89b69b46055c190b418bca5b912c9491cb30fdf61e6a3d2ae919e2cfebc954be614eea31fabe48b455ed04f79a5609813b202d2128ffa6e532f61b31f65dd430b3596122aefc2dcfe9d6c1c76ab62225f7e415b935182cba12c9fa3c190a96425708d535595cb1a1f96bfa5c242cdb7adc95980dfbee8924faecb88ab30b9b986ec600415956c051ad195120544081c07d9557aed692c82f51eac8cf96ad6debecc8aba6fdf6ea51105fedff0071c54f19644149c892d23382fa982b230ffcb8ef06e774a012cc7762c91c52ffe34ac84a549254b48813ec6a2330dc3209a47eb27b592f7cef20dbdc74ff175136db6126ce43518f297965fbef2b1b903bf1a415b5e8d8ad5a479fa99b220e4116c6eee446796d0416c7f10ab3462f0eabf7f5dc7751f3b5fa63716b3905b125f2f19083a5535eb45c4ad3eb2652f1dce1ea08453d8f23d2b76dec5cba5a907e3ef74c1c9dd0e910900f84e4184c7ba6a8812fd57eebe13a28f860fb375bec6efbd1d7bab15e75594268f5c637ad5ab7460b0a431aeeef6e3d8eeee0d39bb7431475662d71a94f5cd692b0db352519249a0ac3d20892a9d6f96b62212e0598f13df28ff74752c21bfd613cc55abb0ea6472d0fdb5cd720458443d4b0ffbcdc9548aff500fa00eeddfbdf179f9914be75e2a76ed9ab17ca9e01ff049288e9f1d0db0cdbf02146aad43b88a53e313ab813f2a7f8ef54f3113a4ef394b8db9b354a08dbdcf039281d8d6aa633336b7075dceb3b84bbc6b99ffb9b050bf237e547fd4ff4338251a850d865b48aba090619280bb3eb4e2a789372c52de0c1571372c3ff3cbb0f7fc54a31c6d4a1cac0969b905496c6a83d5e32a71b85f189f65143b509c9129b3df89c7f00d4586f4605e56c3e2cee441f99de16a4f66cf7a6b1d6fb01130f0b6216e21df01d551771f45145513286f6ba18c206a26eee761e6f2f866e33bb41671104ef0259c2f72714614412a842e1f44c338de3b05ac9901e593aa20fa489a90ccbdc0b7a099976a255163f451bfe04114237fce9ad18e2132c76b4c6c628345dc3a253742c265e1a6b7ba278699ad8a5977d4f56f5aa774e9c7227bcb793aa9ddef994de43a383e7dc0b19711da77f027fa5c05f056c1997aa76d1566b518ae1857bb00b122ab4bdb615ba8d4578d484a45b38336ea37cabfc20087145b55abfd187eee37d7144e3fa2057b34ca43985dc03e0aa882be25b9750b57ae7d2927115c76205c81aa128dbd44f9cb9a16c18b7062cdfdb4b15bc1e5e06c07a1b97152f5e75f257d38e15af0990bbd01b403618bf5a233f708471d9af79a16abcec5f977b49726e5491a13e67f401a57e37d270ce694314747b051979e0264b943ac68471bf67dd1dae3af03c562741418d1dccb513dde627385ce6e1b4a33dab7dfc810cf3ad5c14d3e74d416d3158564385f5ac7afb98f574a73f72d62f86afb8b23839b14b437b0c5f5db1de06b246d6da17e892c63a46ad7e7903f6d4601c5a3a22022ec8e529050d604dd2d33544561e344b3ae8361d1c098c0d4dcd4d0d66761f9ab9c2d65aa396d71f4c0b1700a9686a0b71751535b9e9deb65672f4d8d7").unwrap();
+        assert_eq!(Header::consensus_size(&first_bytes), Ok(Header::SIZE));
+
+        // The second header omits `prev_block_hash`
+        let mut compact_bytes = first_bytes.clone();
+        compact_bytes.extend(&second_bytes[..4]);
+        compact_bytes.extend(&second_bytes[36..]);
+
+        let headers = crate::header::block_headers_from_compact_vec(&compact_bytes).unwrap();
+        assert_eq!(
+            headers,
+            vec![
+                Header::from_block_header_vec(&first_bytes).unwrap(),
+                Header::from_block_header_vec(&second_bytes).unwrap(),
+            ]
+        );
+        assert_eq!(
+            headers[0].block_hash().to_string(),
+            "00000000001c20f30b3826608bd9ccdd75337ca3897042b903e3a00d95f3b078"
+        );
+        assert_eq!(
+            headers[1].block_hash().to_string(),
+            "0000000000288972f82cb0da164cb5c921c274a5ea6fa640a4fc184f67c0c4d5"
+        );
+        assert_eq!(
+            crate::header::block_headers_from_compact_vec(
+                &compact_bytes[..compact_bytes.len() - 1]
+            ),
+            Err(crate::utils::DecodeHeaderError::InvalidLength)
+        );
+    }
 }
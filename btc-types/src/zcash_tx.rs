@@ -0,0 +1,347 @@
+use crate::hash::H256;
+use bitcoin::consensus::encode::{serialize, Decodable, VarInt};
+use bitcoin::{TxIn, TxOut};
+use blake2b_simd::Params;
+use near_sdk::near;
+
+/// Header of a v5 transaction, the version with the `fOverwintered` flag set
+const TX_V5_HEADER: u32 = 5 | (1 << 31);
+
+/// Version group id of the v5 transactions, see ZIP-225
+const TX_V5_VERSION_GROUP_ID: u32 = 0x26A7_270A;
+
+/// Leaf of the auth data tree that pads it to a power of two, see ZIP-244
+const AUTH_DATA_PADDING_LEAF: [u8; 32] = [0; 32];
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ZcashTxError {
+    InvalidTransaction,
+    UnsupportedVersion,
+    ShieldedDataNotSupported,
+}
+
+impl std::fmt::Display for ZcashTxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZcashTxError::InvalidTransaction => write!(f, "Invalid Zcash transaction"),
+            ZcashTxError::UnsupportedVersion => write!(f, "Only v5 transactions are supported"),
+            ZcashTxError::ShieldedDataNotSupported => {
+                write!(f, "Transactions with shielded data are not supported")
+            }
+        }
+    }
+}
+
+/// A transparent output of a Zcash transaction
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransparentOutput {
+    pub value: u64,
+    #[serde(with = "hex::serde")]
+    pub script_pubkey: Vec<u8>,
+}
+
+/// A v5 Zcash transaction with transparent components only, see ZIP-225
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug)]
+pub struct ZcashTransaction {
+    pub consensus_branch_id: u32,
+    pub lock_time: u32,
+    pub expiry_height: u32,
+    pub inputs: Vec<TxIn>,
+    pub outputs: Vec<TxOut>,
+}
+
+impl ZcashTransaction {
+    /// Parses a consensus-serialized v5 transaction.
+    ///
+    /// # Errors
+    /// If the bytes are not a valid v5 transaction or the transaction has Sapling or Orchard data
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ZcashTxError> {
+        fn read<T: Decodable>(bytes: &mut &[u8]) -> Result<T, ZcashTxError> {
+            T::consensus_decode(bytes).map_err(|_| ZcashTxError::InvalidTransaction)
+        }
+
+        let header: u32 = read(&mut bytes)?;
+        let version_group_id: u32 = read(&mut bytes)?;
+        if header != TX_V5_HEADER || version_group_id != TX_V5_VERSION_GROUP_ID {
+            return Err(ZcashTxError::UnsupportedVersion);
+        }
+
+        let consensus_branch_id: u32 = read(&mut bytes)?;
+        let lock_time: u32 = read(&mut bytes)?;
+        let expiry_height: u32 = read(&mut bytes)?;
+
+        let inputs: Vec<TxIn> = read(&mut bytes)?;
+        let outputs: Vec<TxOut> = read(&mut bytes)?;
+
+        // nSpendsSapling, nOutputsSapling and nActionsOrchard, the rest of the shielded
+        // data is only serialized if they are not zero
+        for _ in 0..3 {
+            if read::<VarInt>(&mut bytes)?.0 != 0 {
+                return Err(ZcashTxError::ShieldedDataNotSupported);
+            }
+        }
+
+        if !bytes.is_empty() {
+            return Err(ZcashTxError::InvalidTransaction);
+        }
+
+        Ok(Self {
+            consensus_branch_id,
+            lock_time,
+            expiry_height,
+            inputs,
+            outputs,
+        })
+    }
+
+    /// The transaction id, the leaf of the block merkle tree, see ZIP-244
+    #[must_use]
+    pub fn txid(&self) -> H256 {
+        let mut header_digest = Vec::with_capacity(20);
+        header_digest.extend(TX_V5_HEADER.to_le_bytes());
+        header_digest.extend(TX_V5_VERSION_GROUP_ID.to_le_bytes());
+        header_digest.extend(self.consensus_branch_id.to_le_bytes());
+        header_digest.extend(self.lock_time.to_le_bytes());
+        header_digest.extend(self.expiry_height.to_le_bytes());
+
+        let transparent_digest = if self.inputs.is_empty() && self.outputs.is_empty() {
+            blake2b_256(b"ZTxIdTranspaHash", &[])
+        } else {
+            let mut prevouts = Vec::with_capacity(self.inputs.len() * 36);
+            let mut sequences = Vec::with_capacity(self.inputs.len() * 4);
+            for input in &self.inputs {
+                prevouts.extend(serialize(&input.previous_output));
+                sequences.extend(serialize(&input.sequence));
+            }
+            let outputs: Vec<u8> = self.outputs.iter().flat_map(serialize).collect();
+
+            let mut digest = Vec::with_capacity(96);
+            digest.extend(blake2b_256(b"ZTxIdPrevoutHash", &prevouts).0);
+            digest.extend(blake2b_256(b"ZTxIdSequencHash", &sequences).0);
+            digest.extend(blake2b_256(b"ZTxIdOutputsHash", &outputs).0);
+            blake2b_256(b"ZTxIdTranspaHash", &digest)
+        };
+
+        let mut digest = Vec::with_capacity(128);
+        digest.extend(blake2b_256(b"ZTxIdHeadersHash", &header_digest).0);
+        digest.extend(transparent_digest.0);
+        digest.extend(blake2b_256(b"ZTxIdSaplingHash", &[]).0);
+        digest.extend(blake2b_256(b"ZTxIdOrchardHash", &[]).0);
+
+        blake2b_256(&self.personalization(b"ZcashTxHash_"), &digest)
+    }
+
+    /// Digest of the authorizing data of the transaction, the leaf of the auth data tree, see ZIP-244
+    #[must_use]
+    pub fn auth_digest(&self) -> H256 {
+        let script_sigs: Vec<u8> = self
+            .inputs
+            .iter()
+            .flat_map(|input| serialize(&input.script_sig))
+            .collect();
+
+        let mut digest = Vec::with_capacity(96);
+        digest.extend(blake2b_256(b"ZTxAuthTransHash", &script_sigs).0);
+        digest.extend(blake2b_256(b"ZTxAuthSapliHash", &[]).0);
+        digest.extend(blake2b_256(b"ZTxAuthOrchaHash", &[]).0);
+
+        blake2b_256(&self.personalization(b"ZTxAuthHash_"), &digest)
+    }
+
+    #[must_use]
+    pub fn transparent_outputs(&self) -> Vec<TransparentOutput> {
+        self.outputs
+            .iter()
+            .map(|output| TransparentOutput {
+                value: output.value.to_sat(),
+                script_pubkey: output.script_pubkey.to_bytes(),
+            })
+            .collect()
+    }
+
+    fn personalization(&self, prefix: &[u8; 12]) -> [u8; 16] {
+        let mut personalization = [0; 16];
+        personalization[..12].copy_from_slice(prefix);
+        personalization[12..].copy_from_slice(&self.consensus_branch_id.to_le_bytes());
+        personalization
+    }
+}
+
+/// Computes the root of the auth data tree, padded with zero leaves to a power of two,
+/// from the auth digest at `index` and its merkle proof.
+#[must_use]
+pub fn compute_auth_data_root_from_merkle_proof(
+    auth_digest: H256,
+    index: usize,
+    merkle_proof: &[H256],
+) -> H256 {
+    let mut current_hash = auth_digest;
+    let mut current_index = index;
+
+    for proof_hash in merkle_proof {
+        current_hash = if current_index % 2 == 0 {
+            auth_data_node(&current_hash, proof_hash)
+        } else {
+            auth_data_node(proof_hash, &current_hash)
+        };
+        current_index /= 2;
+    }
+
+    current_hash
+}
+
+/// Computes the merkle proof of the auth digest at `index` in the auth data tree of a block.
+#[must_use]
+pub fn auth_data_merkle_proof_calculator(auth_digests: Vec<H256>, index: usize) -> Vec<H256> {
+    let mut current_index = index;
+    let mut merkle_proof = Vec::new();
+    let mut current_hashes = auth_digests;
+    current_hashes.resize(
+        current_hashes.len().max(1).next_power_of_two(),
+        H256::from(AUTH_DATA_PADDING_LEAF),
+    );
+
+    while current_hashes.len() > 1 {
+        merkle_proof.push(current_hashes[current_index ^ 1].clone());
+        current_hashes = current_hashes
+            .chunks(2)
+            .map(|pair| auth_data_node(&pair[0], &pair[1]))
+            .collect();
+        current_index /= 2;
+    }
+
+    merkle_proof
+}
+
+/// The `hashBlockCommitments` of the header of the blocks since NU5, see ZIP-244
+#[must_use]
+pub fn block_commitments(chain_history_root: &H256, auth_data_root: &H256) -> H256 {
+    let mut commitments = Vec::with_capacity(96);
+    commitments.extend(chain_history_root.0);
+    commitments.extend(auth_data_root.0);
+    commitments.extend([0; 32]);
+
+    blake2b_256(b"ZcashBlockCommit", &commitments)
+}
+
+fn auth_data_node(left: &H256, right: &H256) -> H256 {
+    let mut node = Vec::with_capacity(64);
+    node.extend(left.0);
+    node.extend(right.0);
+
+    blake2b_256(b"ZcashAuthDatHash", &node)
+}
+
+fn blake2b_256(personalization: &[u8; 16], data: &[u8]) -> H256 {
+    let hash = Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .hash(data);
+
+    H256::try_from(hash.as_bytes()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRANCH_ID_NU5: u32 = 0xC2D6_D0B4;
+
+    // A v5 transaction with one transparent input and two transparent outputs
+    const TX_HEX: &str = "050000800a27a726b4d0d6c20000000080841e0001111111111111111111111111111111111111111111111111111111111111111101000000025152feffffff02a0860100000000001976a914222222222222222222222222222222222222222288ac50c3000000000000066a0401020304000000";
+
+    fn transaction() -> ZcashTransaction {
+        ZcashTransaction::from_bytes(&hex::decode(TX_HEX).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_transaction() {
+        let tx = transaction();
+
+        assert_eq!(tx.consensus_branch_id, BRANCH_ID_NU5);
+        assert_eq!(tx.expiry_height, 2_000_000);
+        assert_eq!(tx.inputs.len(), 1);
+        assert_eq!(
+            tx.transparent_outputs(),
+            vec![
+                TransparentOutput {
+                    value: 100_000,
+                    script_pubkey: hex::decode(
+                        "76a914222222222222222222222222222222222222222288ac"
+                    )
+                    .unwrap(),
+                },
+                TransparentOutput {
+                    value: 50_000,
+                    script_pubkey: hex::decode("6a0401020304").unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_txid_and_auth_digest() {
+        let tx = transaction();
+
+        assert_eq!(
+            tx.txid().to_string(),
+            "9ef7ee8c7885c3e100453568ee8f50f76fe548f5a8061816e8415bfa0584eb0c"
+        );
+        assert_eq!(
+            tx.auth_digest().to_string(),
+            "c2da7d72f84ef53d658d05bd45a9fc4ae6112238db7fca04f6bcdbe41836eb99"
+        );
+    }
+
+    #[test]
+    fn test_block_commitments() {
+        let auth_digests = vec![
+            H256::from([0xaa; 32]),
+            transaction().auth_digest(),
+            H256::from([0xbb; 32]),
+        ];
+        let merkle_proof = auth_data_merkle_proof_calculator(auth_digests.clone(), 1);
+        assert_eq!(merkle_proof.len(), 2);
+
+        let auth_data_root =
+            compute_auth_data_root_from_merkle_proof(auth_digests[1].clone(), 1, &merkle_proof);
+        assert_eq!(
+            auth_data_root.to_string(),
+            "fbacc393ee0ebbca9cb9a689e4d20014f9a048018d7ee0923c17186af481523e"
+        );
+        assert_eq!(
+            block_commitments(&H256::from([0xcc; 32]), &auth_data_root).to_string(),
+            "dacd5c7f73b9d555ee9e22e3cbae3c759819f683d305fa2b4347fb5b45f39a84"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_transactions() {
+        let bytes = hex::decode(TX_HEX).unwrap();
+
+        // v4 transaction
+        let mut v4 = bytes.clone();
+        v4[0] = 4;
+        assert_eq!(
+            ZcashTransaction::from_bytes(&v4).unwrap_err(),
+            ZcashTxError::UnsupportedVersion
+        );
+
+        // One Orchard action
+        let mut shielded = bytes.clone();
+        *shielded.last_mut().unwrap() = 1;
+        assert_eq!(
+            ZcashTransaction::from_bytes(&shielded).unwrap_err(),
+            ZcashTxError::ShieldedDataNotSupported
+        );
+
+        // Truncated
+        assert_eq!(
+            ZcashTransaction::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            ZcashTxError::InvalidTransaction
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use crate::chain::require_valid;
+use crate::{BtcLightClient, Header};
+use btc_types::aux::AuxData;
+use btc_types::header::ExtendedHeader;
+use btc_types::utils::work_from_bits;
+use btc_types::validation::auxpow::{check_aux, check_aux_structure};
+use btc_types::validation::{check_pow_hash, get_recent_timestamps, BlocksGetter};
+use near_sdk::{env, require};
+
+impl BtcLightClient {
+    pub(crate) fn check_aux(&self, block_header: &Header, aux_data: &AuxData) {
+        let rules = self.chain_rules();
+        let aux_chain_id = rules.aux_chain_id(self.network).unwrap_or_else(|| {
+            env::panic_str(&format!("AuxPoW is not supported by {}", rules.name()))
+        });
+
+        if self.skip_pow_verification {
+            require_valid(
+                check_aux_structure(block_header, aux_data, aux_chain_id).map_err(Into::into),
+            );
+        } else {
+            // The parent block is hashed with the PoW function of the merge-mined chain
+            require_valid(check_aux(
+                block_header,
+                aux_data,
+                aux_chain_id,
+                &rules.pow_hash(&aux_data.parent_block),
+            ));
+        }
+    }
+
+    pub(crate) fn submit_block_header(
+        &mut self,
+        header: (Header, Option<AuxData>),
+        skip_pow_verification: bool,
+    ) {
+        let (block_header, aux_data) = header;
+
+        let prev_block_header = require_valid(self.get_prev_header(&block_header));
+        let current_block_hash = block_header.block_hash();
+
+        if !skip_pow_verification {
+            self.check_target(&block_header, &prev_block_header);
+
+            if let Some(ref aux_data) = aux_data {
+                self.check_aux(&block_header, aux_data);
+            } else {
+                // Check if the block hash is less than or equal to the target
+                require_valid(check_pow_hash(
+                    &self.chain_rules().pow_hash(&block_header),
+                    block_header.bits,
+                ));
+            }
+        }
+
+        let (current_block_computed_chain_work, overflow) = prev_block_header
+            .chain_work
+            .overflowing_add(work_from_bits(block_header.bits));
+        require!(!overflow, "Addition of U256 values overflowed");
+
+        let current_header = ExtendedHeader {
+            block_header: block_header.clone().into_light(),
+            block_hash: current_block_hash,
+            chain_work: current_block_computed_chain_work,
+            block_height: 1 + prev_block_header.block_height,
+            recent_timestamps: get_recent_timestamps(block_header.time, &prev_block_header, self),
+        };
+
+        self.submit_block_header_inner(current_header, &prev_block_header);
+    }
+}
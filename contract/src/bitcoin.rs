@@ -1,118 +1,71 @@
-use crate::utils::{get_median_time_past, BlocksGetter};
-use crate::{BtcLightClient, BtcLightClientExt, Header, U256};
+use crate::chain::{check_init_median_time_past, current_timestamp, require_valid, ChainRules};
+use crate::{BtcLightClient, Header};
 use btc_types::header::ExtendedHeader;
-use btc_types::network::{Network, NetworkConfig, MAX_FUTURE_BLOCK_TIME_LOCAL};
-use btc_types::utils::target_from_bits;
-use near_sdk::{env, near, require};
+use btc_types::network::{get_bitcoin_config, ChainConfig, Network};
+use btc_types::validation::bitcoin::check_header;
+use near_sdk::require;
 
-#[near]
-impl BtcLightClient {
-    pub fn get_config(&self) -> btc_types::network::NetworkConfig {
-        btc_types::network::get_bitcoin_config(self.network)
-    }
+pub(crate) struct Bitcoin;
 
-    pub fn get_network(&self) -> (String, Network) {
-        ("Bitcoin".to_owned(), self.network)
+impl ChainRules for Bitcoin {
+    fn name(&self) -> &'static str {
+        "Bitcoin"
     }
 
-    // reference implementation: https://github.com/bitcoin/bitcoin/blob/ae024137bda9fe189f4e7ccf26dbaffd44cbbeb6/src/validation.cpp#L4200
-    pub(crate) fn check_pow(&self, block_header: &Header, prev_block_header: &ExtendedHeader) {
-        let config = self.get_config();
-        let expected_bits = get_next_work_required(&config, block_header, prev_block_header, self);
-
-        require!(
-            expected_bits == block_header.bits,
-            "bad-diffbits: incorrect proof of work"
-        );
-
-        // Check timestamp against prev
-        require!(
-            block_header.time > get_median_time_past(prev_block_header.clone(), self),
-            "time-too-old: block's timestamp is too early"
-        );
-
-        // Check timestamp
-        let current_timestamp = u32::try_from(env::block_timestamp_ms() / 1000).unwrap(); // Convert to seconds
-        require!(
-            block_header.time <= current_timestamp + MAX_FUTURE_BLOCK_TIME_LOCAL,
-            "time-too-new: block timestamp too far in the future"
-        );
-
-        // Reject blocks with outdated version
-        require!(
-            block_header.version >= 4,
-            "bad-version: block version must be at least 4"
-        );
+    fn config(&self, network: Network) -> ChainConfig {
+        get_bitcoin_config(network).into()
     }
-}
 
-//https://github.com/bitcoin/bitcoin/blob/ae024137bda9fe189f4e7ccf26dbaffd44cbbeb6/src/pow.cpp#L14
-fn get_next_work_required(
-    config: &NetworkConfig,
-    block_header: &Header,
-    prev_block_header: &ExtendedHeader,
-    blocks_getter: &impl BlocksGetter,
-) -> u32 {
-    if (prev_block_header.block_height + 1) % config.difficulty_adjustment_interval != 0 {
-        if config.pow_allow_min_difficulty_blocks {
-            if block_header.time
-                > prev_block_header.block_header.time + 2 * config.pow_target_spacing
-            {
-                return config.proof_of_work_limit_bits;
-            }
-
-            let mut current_block_header = prev_block_header.clone();
-            while current_block_header.block_header.bits == config.proof_of_work_limit_bits
-                && current_block_header.block_height % config.difficulty_adjustment_interval != 0
-            {
-                current_block_header =
-                    blocks_getter.get_prev_header(&current_block_header.block_header);
-            }
-
-            let last_bits = current_block_header.block_header.bits;
-            return last_bits;
-        }
-        return prev_block_header.block_header.bits;
+    fn check_init(&self, network: Network, block_height: u64, submit_blocks: usize) {
+        let config = get_bitcoin_config(network);
+        require!(block_height % config.difficulty_adjustment_interval == 0, format!("Error: The initial block height must be divisible by {} to ensure proper alignment with difficulty adjustment periods.", config.difficulty_adjustment_interval));
+        check_init_median_time_past(submit_blocks);
     }
 
-    let first_block_height =
-        prev_block_header.block_height - (config.difficulty_adjustment_interval - 1);
-
-    let interval_tail_extend_header = blocks_getter.get_header_by_height(first_block_height);
-    calculate_next_work_required(
-        config,
-        prev_block_header,
-        interval_tail_extend_header.block_header.time.into(),
-    )
+    fn check_pow(
+        &self,
+        contract: &BtcLightClient,
+        block_header: &Header,
+        prev_block_header: &ExtendedHeader,
+    ) {
+        require_valid(check_header(
+            &get_bitcoin_config(contract.network),
+            block_header,
+            prev_block_header,
+            current_timestamp(),
+            contract,
+        ));
+    }
 }
 
-//https://github.com/bitcoin/bitcoin/blob/ae024137bda9fe189f4e7ccf26dbaffd44cbbeb6/src/pow.cpp#L50
-fn calculate_next_work_required(
-    config: &NetworkConfig,
-    prev_block_header: &ExtendedHeader,
-    first_block_time: i64,
-) -> u32 {
-    let prev_block_time: i64 = prev_block_header.block_header.time.into();
-
-    let mut actual_time_taken: i64 = prev_block_time - first_block_time;
-    if actual_time_taken < config.pow_target_timespan / 4 {
-        actual_time_taken = config.pow_target_timespan / 4;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::U256;
+
+    // Testnet4 genesis block
+    fn testnet4_genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "7aa0a7ae1e223414cb807e40cd57e667b718e42aaf9306db9102fe28912b7b4e",
+            "time": 1_714_777_860,
+            "bits": 0x1d00_ffff,
+            "nonce": 393_743_547,
+        }))
+        .unwrap()
     }
-    if actual_time_taken > config.pow_target_timespan * 4 {
-        actual_time_taken = config.pow_target_timespan * 4;
-    }
-
-    let new_target = target_from_bits(prev_block_header.block_header.bits);
 
-    let (mut new_target, new_target_overflow) =
-        new_target.overflowing_mul(<i64 as TryInto<u64>>::try_into(actual_time_taken).unwrap());
-    require!(!new_target_overflow, "new target overflow");
-    new_target = new_target
-        / U256::from(<i64 as TryInto<u64>>::try_into(config.pow_target_timespan).unwrap());
+    #[test]
+    fn test_testnet4_genesis_block() {
+        let config = get_bitcoin_config(Network::Testnet4);
+        let genesis = testnet4_genesis_block_header();
 
-    if new_target > config.pow_limit {
-        new_target = config.pow_limit;
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "00000000da84f2bafbbc53dee25a72ae507ff4914b867c565be350b0da8bf043"
+        );
+        assert_eq!(genesis.bits, config.proof_of_work_limit_bits);
+        assert!(U256::from_le_bytes(&Bitcoin.pow_hash(&genesis).0) <= config.pow_limit);
     }
-
-    new_target.target_to_bits()
 }
@@ -0,0 +1,76 @@
+use crate::chain::{check_init_median_time_past, current_timestamp, require_valid, ChainRules};
+use crate::{BtcLightClient, Header};
+use btc_types::header::ExtendedHeader;
+use btc_types::network::{get_bitcoincash_config, ChainConfig, Network};
+use btc_types::validation::bitcoincash::check_header;
+use near_sdk::require;
+
+pub(crate) struct BitcoinCash;
+
+impl ChainRules for BitcoinCash {
+    fn name(&self) -> &'static str {
+        "BitcoinCash"
+    }
+
+    fn config(&self, network: Network) -> ChainConfig {
+        get_bitcoincash_config(network).into()
+    }
+
+    fn check_init(&self, network: Network, block_height: u64, submit_blocks: usize) {
+        check_init_median_time_past(submit_blocks);
+        // cw-144 reads the two ancestors of the block 144 blocks below the previous one
+        require!(
+            block_height >= get_bitcoincash_config(network).asert_activation_height
+                || submit_blocks > 146,
+            "At least 147 initial blocks must be submitted to support cw-144 DAA computation"
+        );
+    }
+
+    fn check_pow(
+        &self,
+        contract: &BtcLightClient,
+        block_header: &Header,
+        prev_block_header: &ExtendedHeader,
+    ) {
+        require_valid(check_header(
+            &get_bitcoincash_config(contract.network),
+            block_header,
+            prev_block_header,
+            current_timestamp(),
+            contract,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::U256;
+
+    // Bitcoin Cash shares the genesis block with Bitcoin
+    fn genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+            "time": 1_231_006_505,
+            "bits": 0x1d00_ffff,
+            "nonce": 2_083_236_893,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_genesis_block() {
+        let config = get_bitcoincash_config(Network::Mainnet);
+        let genesis = genesis_block_header();
+
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert_eq!(genesis.bits, config.proof_of_work_limit_bits);
+        assert_eq!(config.pow_limit.target_to_bits(), genesis.bits);
+        assert!(U256::from_le_bytes(&BitcoinCash.pow_hash(&genesis).0) <= config.pow_limit);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{BtcLightClient, Header, H256};
+use btc_types::header::ExtendedHeader;
+use btc_types::network::{Chain, ChainConfig, Network};
+use btc_types::validation::ValidationError;
+use near_sdk::{env, require};
+
+#[cfg(all(
+    feature = "zcash",
+    any(
+        feature = "bitcoin",
+        feature = "auxpow",
+        feature = "litecoin",
+        feature = "bitcoincash",
+        feature = "dash"
+    )
+))]
+compile_error!("Zcash has its own header format and cannot be built together with other chains");
+
+#[cfg(all(
+    feature = "dash",
+    any(
+        feature = "bitcoin",
+        feature = "auxpow",
+        feature = "litecoin",
+        feature = "bitcoincash"
+    )
+))]
+compile_error!(
+    "Dash identifies the blocks by the X11 hash and cannot be built together with other chains"
+);
+
+/// Chains whose rules are compiled into the contract
+pub(crate) const COMPILED_CHAINS: &[Chain] = &[
+    #[cfg(feature = "bitcoin")]
+    Chain::Bitcoin,
+    #[cfg(feature = "litecoin")]
+    Chain::Litecoin,
+    #[cfg(feature = "dogecoin")]
+    Chain::Dogecoin,
+    #[cfg(feature = "namecoin")]
+    Chain::Namecoin,
+    #[cfg(feature = "bitcoincash")]
+    Chain::BitcoinCash,
+    #[cfg(feature = "dash")]
+    Chain::Dash,
+    #[cfg(feature = "zcash")]
+    Chain::Zcash,
+];
+
+/// Consensus rules of a chain the light client follows.
+/// The rules are selected at the initialization by the chain stored in the contract state.
+pub(crate) trait ChainRules {
+    /// Name of the chain, as returned by `get_network`
+    fn name(&self) -> &'static str;
+
+    fn config(&self, network: Network) -> ChainConfig;
+
+    /// Checks that the initial headers are aligned and numerous enough to validate
+    /// the headers following them
+    fn check_init(&self, network: Network, block_height: u64, submit_blocks: usize);
+
+    /// Checks the header against the previous one: the difficulty, the timestamp and
+    /// the chain specific rules. The proof of work hash is checked separately.
+    fn check_pow(
+        &self,
+        contract: &BtcLightClient,
+        block_header: &Header,
+        prev_block_header: &ExtendedHeader,
+    );
+
+    /// The hash of the header which must not exceed the target
+    fn pow_hash(&self, block_header: &Header) -> H256 {
+        block_header.block_hash()
+    }
+
+    /// Chain ID of the merge-mined headers, `None` if the chain is not merge-mined
+    fn aux_chain_id(&self, _network: Network) -> Option<i32> {
+        None
+    }
+}
+
+/// Returns the rules of the chain
+/// # Panics
+/// If the chain is not compiled into the contract
+pub(crate) fn chain_rules(chain: Chain) -> &'static dyn ChainRules {
+    match chain {
+        #[cfg(feature = "bitcoin")]
+        Chain::Bitcoin => &crate::bitcoin::Bitcoin,
+        #[cfg(feature = "litecoin")]
+        Chain::Litecoin => &crate::litecoin::Litecoin,
+        #[cfg(feature = "dogecoin")]
+        Chain::Dogecoin => &crate::dogecoin::Dogecoin,
+        #[cfg(feature = "namecoin")]
+        Chain::Namecoin => &crate::namecoin::Namecoin,
+        #[cfg(feature = "bitcoincash")]
+        Chain::BitcoinCash => &crate::bitcoincash::BitcoinCash,
+        #[cfg(feature = "dash")]
+        Chain::Dash => &crate::dash::Dash,
+        #[cfg(feature = "zcash")]
+        Chain::Zcash => &crate::zcash::Zcash,
+        #[allow(unreachable_patterns)]
+        _ => env::panic_str(&format!("{chain:?} is not supported by this build")),
+    }
+}
+
+/// Resolves the chain of a new contract: the requested one, or the only compiled one
+/// # Panics
+/// If the chain is not compiled into the contract, or is not set while several are
+pub(crate) fn resolve_chain(chain: Option<Chain>) -> Chain {
+    let chain = match (chain, COMPILED_CHAINS) {
+        (Some(chain), _) => chain,
+        (None, [chain]) => *chain,
+        (None, _) => {
+            env::panic_str("The chain must be set for a contract built with several chains")
+        }
+    };
+    require!(
+        COMPILED_CHAINS.contains(&chain),
+        format!("{chain:?} is not supported by this build")
+    );
+    chain
+}
+
+/// Checks that enough initial headers are submitted to compute the median time past
+#[cfg(not(feature = "zcash"))]
+pub(crate) fn check_init_median_time_past(submit_blocks: usize) {
+    use btc_types::network::MEDIAN_TIME_SPAN;
+
+    require!(
+        submit_blocks > MEDIAN_TIME_SPAN,
+        format!(
+            "At least {} initial blocks must be submitted to support MTP computation",
+            MEDIAN_TIME_SPAN + 1
+        )
+    );
+}
+
+/// Current time in seconds, the header timestamps are checked against
+pub(crate) fn current_timestamp() -> u32 {
+    u32::try_from(env::block_timestamp_ms() / 1000).unwrap()
+}
+
+/// Returns the result of a validation
+/// # Panics
+/// With the message of the validation error
+pub(crate) fn require_valid<T>(result: Result<T, ValidationError>) -> T {
+    result.unwrap_or_else(|err| env::panic_str(&err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_compiled_chain() {
+        for chain in COMPILED_CHAINS {
+            assert_eq!(resolve_chain(Some(*chain)), *chain);
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "zcash"))]
+    #[should_panic(expected = "Zcash is not supported by this build")]
+    fn test_resolve_not_compiled_chain() {
+        resolve_chain(Some(Chain::Zcash));
+    }
+
+    #[test]
+    #[cfg(feature = "multichain")]
+    fn test_multichain_compiled_chains() {
+        assert_eq!(
+            COMPILED_CHAINS,
+            [Chain::Bitcoin, Chain::Litecoin, Chain::Dogecoin]
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "bitcoin", feature = "litecoin"))]
+    fn test_pow_hash_of_chain() {
+        let header: Header = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+            "time": 1_231_006_505,
+            "bits": 486_604_799,
+            "nonce": 2_083_236_893
+        }))
+        .unwrap();
+
+        assert_eq!(
+            chain_rules(Chain::Bitcoin).pow_hash(&header).to_string(),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert_ne!(
+            chain_rules(Chain::Litecoin).pow_hash(&header),
+            header.block_hash()
+        );
+        assert_eq!(resolve_chain(Some(Chain::Litecoin)), Chain::Litecoin);
+    }
+}
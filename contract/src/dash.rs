@@ -0,0 +1,94 @@
+use crate::chain::{check_init_median_time_past, current_timestamp, require_valid, ChainRules};
+use crate::{BtcLightClient, Header};
+use btc_types::header::ExtendedHeader;
+use btc_types::network::{get_dash_config, ChainConfig, Network};
+use btc_types::validation::dash::check_header;
+use near_sdk::require;
+
+pub(crate) struct Dash;
+
+impl ChainRules for Dash {
+    fn name(&self) -> &'static str {
+        "Dash"
+    }
+
+    fn config(&self, network: Network) -> ChainConfig {
+        get_dash_config(network).into()
+    }
+
+    fn check_init(&self, network: Network, _block_height: u64, submit_blocks: usize) {
+        check_init_median_time_past(submit_blocks);
+        let config = get_dash_config(network);
+        require!(
+            submit_blocks >= usize::try_from(config.dgw_past_blocks).unwrap(),
+            format!(
+                "At least {} initial blocks must be submitted to support Dark Gravity Wave computation",
+                config.dgw_past_blocks
+            )
+        );
+    }
+
+    fn check_pow(
+        &self,
+        contract: &BtcLightClient,
+        block_header: &Header,
+        prev_block_header: &ExtendedHeader,
+    ) {
+        require_valid(check_header(
+            &get_dash_config(contract.network),
+            block_header,
+            prev_block_header,
+            current_timestamp(),
+            contract,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::U256;
+
+    fn genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "e0028eb9648db56b1ac77cf090b99048a8007e2bb64b68f092c03c7f56a662c7",
+            "time": 1_390_095_618,
+            "bits": 0x1e0f_fff0,
+            "nonce": 28_917_698,
+        }))
+        .unwrap()
+    }
+
+    fn testnet_genesis_block_header() -> Header {
+        Header {
+            time: 1_390_666_206,
+            nonce: 3_861_367_235,
+            ..genesis_block_header()
+        }
+    }
+
+    #[test]
+    fn test_genesis_block() {
+        let config = get_dash_config(Network::Mainnet);
+        let genesis = genesis_block_header();
+
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "00000ffd590b1485b3caadc19b22e6379c733355108f107a430458cdf3407ab6"
+        );
+        assert_eq!(genesis.block_hash_pow(), genesis.block_hash());
+        assert!(U256::from_le_bytes(&genesis.block_hash_pow().0) <= config.pow_limit);
+    }
+
+    #[test]
+    fn test_testnet_genesis_block() {
+        let genesis = testnet_genesis_block_header();
+
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "00000bafbc94add76cb75e2ec92894837288a481e5c005f6563d91623bf8bc2c"
+        );
+    }
+}
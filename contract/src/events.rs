@@ -0,0 +1,24 @@
+use near_sdk::{near, NearToken};
+
+/// Events emitted by the contract in the NEP-297 format.
+#[allow(clippy::enum_variant_names)]
+#[near(event_json(standard = "btc-light-client"))]
+pub enum ContractEvent {
+    #[event_version("1.0.0")]
+    GcThresholdUpdated { old_value: u64, new_value: u64 },
+    #[event_version("1.0.0")]
+    MaxReorgDepthUpdated {
+        old_value: Option<u64>,
+        new_value: Option<u64>,
+    },
+    #[event_version("1.0.0")]
+    MaxHeadersPerSubmitUpdated {
+        old_value: Option<u64>,
+        new_value: Option<u64>,
+    },
+    #[event_version("1.0.0")]
+    MinSubmitDepositUpdated {
+        old_value: NearToken,
+        new_value: NearToken,
+    },
+}
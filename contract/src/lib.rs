@@ -1,13 +1,21 @@
-use btc_types::contract_args::{InitArgs, ProofArgs, ProofArgsV2};
+#[cfg(feature = "signet")]
+use btc_types::contract_args::SignetBlockArgs;
+use btc_types::contract_args::{
+    InitArgs, MerkleBlockProofArgs, ProofArgs, ProofArgsV2, ProofArgsV3,
+};
 use btc_types::hash::H256;
-use btc_types::header::{BlockHeader, ExtendedHeader, Header, LightHeader};
-use btc_types::network::Network;
+use btc_types::header::{
+    block_headers_from_compact_vec_with_auxpow, BlockHeader, ExtendedHeader, Header, LightHeader,
+};
+use btc_types::network::{Chain, ChainConfig, Network};
 use btc_types::u256::U256;
-#[cfg(not(feature = "dogecoin"))]
-use btc_types::utils::target_from_bits;
 use btc_types::utils::work_from_bits;
+#[cfg(not(feature = "auxpow"))]
+use btc_types::validation::{check_pow_hash, get_recent_timestamps};
+use btc_types::validation::{BlocksGetter, ErrorCode};
 use near_plugins::{
-    access_control, pause, AccessControlRole, AccessControllable, Pausable, Upgradable,
+    access_control, access_control_any, pause, AccessControlRole, AccessControllable, Pausable,
+    Upgradable,
 };
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::LookupMap;
@@ -15,24 +23,43 @@ use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, log, near, require, NearToken, PanicOnDefault, Promise, PromiseOrValue};
 use omni_utils::macros::trusted_relayer;
 
-use crate::utils::BlocksGetter;
+#[cfg(not(feature = "auxpow"))]
+use crate::chain::require_valid;
+use crate::chain::ChainRules;
+use crate::events::ContractEvent;
 
 pub(crate) const ERR_KEY_NOT_EXIST: &str = "ERR_KEY_NOT_EXIST";
 
-mod utils;
+mod chain;
+mod events;
 
 #[cfg(feature = "zcash")]
 mod zcash;
 
+#[cfg(feature = "auxpow")]
+mod auxpow;
+
 #[cfg(feature = "dogecoin")]
 mod dogecoin;
 
+#[cfg(feature = "namecoin")]
+mod namecoin;
+
 #[cfg(feature = "bitcoin")]
 mod bitcoin;
 
+#[cfg(feature = "signet")]
+mod signet;
+
 #[cfg(feature = "litecoin")]
 mod litecoin;
 
+#[cfg(feature = "bitcoincash")]
+mod bitcoincash;
+
+#[cfg(feature = "dash")]
+mod dash;
+
 /// Define roles for access control of `Pausable` features. Accounts which are
 /// granted a role are authorized to execute the corresponding action.
 #[derive(AccessControlRole, Deserialize, Serialize, Copy, Clone)]
@@ -76,6 +103,7 @@ enum StorageKey {
     MainchainHeightToHeader,
     MainchainHeaderToHeight,
     HeadersPool,
+    HeadersPoolV2,
 }
 
 /// Contract implementing Bitcoin light client.
@@ -107,6 +135,10 @@ pub struct BtcLightClient {
     // Mapping of block hashes to block headers (ALL ever submitted, i.e., incl. forks)
     headers_pool: LookupMap<H256, ExtendedHeader>,
 
+    // Headers stored before the recent timestamps were cached in `ExtendedHeader`,
+    // they are read as a fallback for `headers_pool` until collected by the GC
+    legacy_headers_pool: LookupMap<H256, migrate::ExtendedHeaderV1>,
+
     // If we should run all the block checks or not
     skip_pow_verification: bool,
 
@@ -115,6 +147,24 @@ pub struct BtcLightClient {
 
     // Network type Mainnet/Testnet
     network: Network,
+
+    // The deepest chain reorganization we accept, unlimited if not set
+    max_reorg_depth: Option<u64>,
+
+    // How many headers could be submitted in a single `submit_blocks` call, unlimited if not set
+    max_headers_per_submit: Option<u64>,
+
+    // Minimum deposit that should be attached to `submit_blocks`
+    min_submit_deposit: NearToken,
+
+    // Mainchain height of the next header to move from `legacy_headers_pool`, if any left
+    legacy_headers_migration_height: Option<u64>,
+
+    // Challenge script that the signet block solutions must satisfy, set only for Signet
+    signet_challenge: Option<Vec<u8>>,
+
+    // Chain whose consensus rules are followed
+    chain: Chain,
 }
 
 #[trusted_relayer(
@@ -129,19 +179,40 @@ impl BtcLightClient {
     /// * The `genesis_block` must be at least 144 blocks earlier than the last block. 144 is the approximate number of blocks generated in one day.
     /// * `skip_pow_verification = false`: Should be set to `false` for standard use. Set to `true` only for testing purposes.
     /// * `gc_threshold = 52704`: This is the approximate number of blocks generated in a year.
+    /// * `initial_chain_work`: The `chainwork` of the genesis block as reported by `getblockheader`,
+    ///   so that the stored chain work matches the one of the full nodes.
+    /// * `min_chain_work`: The minimum chain work of the initial chain tip, e.g. `nMinimumChainWork`
+    ///   of Bitcoin Core. Makes sense only together with `initial_chain_work`.
+    /// * `signet_challenge`: The challenge script of a custom signet, the default signet is
+    ///   assumed if not set. Requires the contract built with the `signet` feature. The script
+    ///   must use only the opcodes implemented by the light client interpreter.
+    /// * `chain`: The chain to follow, one of the chains the contract is built with.
+    ///   Could be omitted if the contract is built with a single chain.
     #[init]
     #[private]
     #[must_use]
     pub fn init(args: InitArgs) -> Self {
+        let chain = chain::resolve_chain(args.chain);
+        require!(
+            chain.supports(args.network),
+            format!("{:?} is not supported for {chain:?}", args.network)
+        );
         let mut contract = Self {
             mainchain_height_to_header: LookupMap::new(StorageKey::MainchainHeightToHeader),
             mainchain_header_to_height: LookupMap::new(StorageKey::MainchainHeaderToHeight),
-            headers_pool: LookupMap::new(StorageKey::HeadersPool),
+            headers_pool: LookupMap::new(StorageKey::HeadersPoolV2),
+            legacy_headers_pool: LookupMap::new(StorageKey::HeadersPool),
             mainchain_initial_blockhash: H256::default(),
             mainchain_tip_blockhash: H256::default(),
             skip_pow_verification: args.skip_pow_verification,
             gc_threshold: args.gc_threshold,
             network: args.network,
+            max_reorg_depth: None,
+            max_headers_per_submit: None,
+            min_submit_deposit: NearToken::from_yoctonear(0),
+            legacy_headers_migration_height: None,
+            signet_challenge: Self::init_signet_challenge(args.network, args.signet_challenge),
+            chain,
         };
 
         // Make the contract itself super admin. This allows us to grant any role in the
@@ -155,6 +226,8 @@ impl BtcLightClient {
             &args.genesis_block_hash,
             args.genesis_block_height,
             args.submit_blocks,
+            args.initial_chain_work,
+            args.min_chain_work,
         );
 
         contract
@@ -170,42 +243,68 @@ impl BtcLightClient {
         &mut self,
         #[serializer(borsh)] headers: Vec<BlockHeader>,
     ) -> PromiseOrValue<()> {
-        let amount = env::attached_deposit();
-        let initial_storage = env::storage_usage();
-        let num_of_headers = headers.len().try_into().unwrap();
+        self.require_headers_only_submission();
+        self.submit_headers(headers)
+    }
 
-        for header in headers {
-            self.submit_block_header(header, self.skip_pow_verification);
+    /// Submits headers in the compact raw format: a contiguous blob of consensus-serialized
+    /// headers, where every header but the first one omits `prev_block_hash`. For the merge-mined
+    /// chains, the headers with the AuxPoW version bit set are followed by the serialized AuxPoW.
+    /// # Panics
+    /// The headers cannot be parsed
+    #[payable]
+    #[pause]
+    #[trusted_relayer]
+    pub fn submit_raw_blocks(
+        &mut self,
+        #[serializer(borsh)] raw_headers: Vec<u8>,
+    ) -> PromiseOrValue<()> {
+        self.require_headers_only_submission();
+        let auxpow = self.chain_rules().aux_chain_id(self.network).is_some();
+        let headers = block_headers_from_compact_vec_with_auxpow(&raw_headers, auxpow)
+            .unwrap_or_else(|err| env::panic_str(&format!("Invalid raw headers: {err}")));
+        self.submit_headers(headers)
+    }
+
+    /// Submits signet headers together with the coinbase transaction and its merkle proof,
+    /// so that the block solution is checked against the signet challenge (BIP325).
+    /// # Panics
+    /// The network is not Signet, or a block solution is invalid
+    #[cfg(feature = "signet")]
+    #[payable]
+    #[pause]
+    #[trusted_relayer]
+    pub fn submit_signet_blocks(
+        &mut self,
+        #[serializer(borsh)] blocks: Vec<SignetBlockArgs>,
+    ) -> PromiseOrValue<()> {
+        if !self.skip_pow_verification {
+            for block in &blocks {
+                self.check_signet_solution(block);
+            }
         }
+        self.submit_headers(blocks.into_iter().map(|block| block.header).collect())
+    }
 
-        self.run_mainchain_gc(num_of_headers);
-        let diff_storage_usage = env::storage_usage().saturating_sub(initial_storage);
-        let required_deposit = env::storage_byte_cost().saturating_mul(diff_storage_usage.into());
+    pub fn get_config(&self) -> ChainConfig {
+        self.chain_rules().config(self.network)
+    }
 
-        require!(
-            amount >= required_deposit,
-            format!("Required deposit {}", required_deposit)
-        );
+    pub fn get_network(&self) -> (String, Network) {
+        (self.chain_rules().name().to_owned(), self.network)
+    }
 
-        let refund = amount.saturating_sub(required_deposit);
-        if refund > NearToken::from_near(0) {
-            Promise::new(env::predecessor_account_id())
-                .transfer(refund)
-                .into()
-        } else {
-            PromiseOrValue::Value(())
-        }
+    pub fn get_chain(&self) -> Chain {
+        self.chain
     }
 
     pub fn get_last_block_header(&self) -> ExtendedHeader {
-        self.headers_pool
-            .get(&self.mainchain_tip_blockhash)
+        self.get_header(&self.mainchain_tip_blockhash)
             .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST))
     }
 
     pub fn get_last_block_height(&self) -> u64 {
-        self.headers_pool
-            .get(&self.mainchain_tip_blockhash)
+        self.get_header(&self.mainchain_tip_blockhash)
             .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST))
             .block_height
     }
@@ -221,16 +320,114 @@ impl BtcLightClient {
 
     pub fn get_mainchain_size(&self) -> u64 {
         let tail = self
-            .headers_pool
-            .get(&self.mainchain_initial_blockhash)
+            .get_header(&self.mainchain_initial_blockhash)
             .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
         let tip = self
-            .headers_pool
-            .get(&self.mainchain_tip_blockhash)
+            .get_header(&self.mainchain_tip_blockhash)
             .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
         tip.block_height - tail.block_height + 1
     }
 
+    pub fn get_gc_threshold(&self) -> u64 {
+        self.gc_threshold
+    }
+
+    pub fn get_max_reorg_depth(&self) -> Option<u64> {
+        self.max_reorg_depth
+    }
+
+    pub fn get_max_headers_per_submit(&self) -> Option<u64> {
+        self.max_headers_per_submit
+    }
+
+    pub fn get_min_submit_deposit(&self) -> NearToken {
+        self.min_submit_deposit
+    }
+
+    /// Updates how many mainchain headers we keep in storage.
+    ///
+    /// Growing the threshold only postpones the GC. Shrinking it never removes headers in bulk:
+    /// the surplus is collected by the following `submit_blocks` calls, or right away if
+    /// `gc_batch_size` is provided, in which case at most `gc_batch_size` headers are removed.
+    ///
+    /// # Panics
+    /// If `gc_threshold` is zero or does not exceed `max_reorg_depth`
+    #[access_control_any(roles(Role::DAO))]
+    pub fn set_gc_threshold(&mut self, gc_threshold: u64, gc_batch_size: Option<u64>) {
+        require!(gc_threshold > 0, "GC threshold should be positive");
+        if let Some(max_reorg_depth) = self.max_reorg_depth {
+            require!(
+                gc_threshold > max_reorg_depth,
+                "GC threshold should exceed the max reorg depth"
+            );
+        }
+
+        let old_value = std::mem::replace(&mut self.gc_threshold, gc_threshold);
+        ContractEvent::GcThresholdUpdated {
+            old_value,
+            new_value: gc_threshold,
+        }
+        .emit();
+
+        if let Some(gc_batch_size) = gc_batch_size {
+            self.mainchain_gc(gc_batch_size);
+        }
+    }
+
+    /// Limits how deep a chain reorganization could be. `None` removes the limit.
+    ///
+    /// # Panics
+    /// If `max_reorg_depth` is zero or is not below `gc_threshold`
+    #[access_control_any(roles(Role::DAO))]
+    pub fn set_max_reorg_depth(&mut self, max_reorg_depth: Option<u64>) {
+        if let Some(max_reorg_depth) = max_reorg_depth {
+            require!(max_reorg_depth > 0, "Max reorg depth should be positive");
+            require!(
+                max_reorg_depth < self.gc_threshold,
+                "Max reorg depth should be below the GC threshold"
+            );
+        }
+
+        let old_value = std::mem::replace(&mut self.max_reorg_depth, max_reorg_depth);
+        ContractEvent::MaxReorgDepthUpdated {
+            old_value,
+            new_value: max_reorg_depth,
+        }
+        .emit();
+    }
+
+    /// Limits how many headers could be submitted in a single `submit_blocks` call.
+    /// `None` removes the limit.
+    ///
+    /// # Panics
+    /// If `max_headers_per_submit` is zero
+    #[access_control_any(roles(Role::DAO))]
+    pub fn set_max_headers_per_submit(&mut self, max_headers_per_submit: Option<u64>) {
+        require!(
+            max_headers_per_submit != Some(0),
+            "Max headers per submit should be positive"
+        );
+
+        let old_value = std::mem::replace(&mut self.max_headers_per_submit, max_headers_per_submit);
+        ContractEvent::MaxHeadersPerSubmitUpdated {
+            old_value,
+            new_value: max_headers_per_submit,
+        }
+        .emit();
+    }
+
+    /// Sets the minimum deposit that should be attached to `submit_blocks`.
+    /// The part of the deposit not spent on storage is still refunded.
+    #[access_control_any(roles(Role::DAO))]
+    pub fn set_min_submit_deposit(&mut self, min_submit_deposit: NearToken) {
+        let old_value = std::mem::replace(&mut self.min_submit_deposit, min_submit_deposit);
+        ContractEvent::MinSubmitDepositUpdated {
+            old_value,
+            new_value: min_submit_deposit,
+        }
+        .emit();
+    }
+
     /// This method return n last blocks from the mainchain
     /// # Panics
     /// Cannot find a tip of main chain in a pool
@@ -238,13 +435,11 @@ impl BtcLightClient {
         let mut block_hashes = vec![];
         let tip_hash = &self.mainchain_tip_blockhash;
         let tip = self
-            .headers_pool
-            .get(tip_hash)
+            .get_header(tip_hash)
             .unwrap_or_else(|| env::panic_str("heaviest block should be recorded"));
 
         let min_block_height = self
-            .headers_pool
-            .get(&self.mainchain_initial_blockhash)
+            .get_header(&self.mainchain_initial_blockhash)
             .unwrap_or_else(|| env::panic_str("initial block should be recorded"))
             .block_height;
 
@@ -286,31 +481,8 @@ impl BtcLightClient {
     )]
     #[pause]
     pub fn verify_transaction_inclusion(&self, #[serializer(borsh)] args: ProofArgs) -> bool {
-        require!(
-            args.confirmations <= self.gc_threshold,
-            "The required number of confirmations exceeds the number of blocks stored in memory"
-        );
-
-        let heaviest_block_header = self
-            .headers_pool
-            .get(&self.mainchain_tip_blockhash)
-            .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
-        let target_block_height = self
-            .mainchain_header_to_height
-            .get(&args.tx_block_blockhash)
-            .unwrap_or_else(|| env::panic_str("block does not belong to the current main chain"));
-
-        // Check requested confirmations. No need to compute proof if insufficient confirmations.
-        require!(
-            (heaviest_block_header.block_height).saturating_sub(target_block_height) + 1
-                >= args.confirmations,
-            "Not enough blocks confirmed"
-        );
-
-        let header = self
-            .headers_pool
-            .get(&args.tx_block_blockhash)
-            .unwrap_or_else(|| env::panic_str("cannot find requested transaction block"));
+        // No need to compute proof if insufficient confirmations.
+        let header = self.get_confirmed_header(&args.tx_block_blockhash, args.confirmations);
 
         require!(!args.merkle_proof.is_empty(), "Merkle proof is empty");
 
@@ -351,8 +523,7 @@ impl BtcLightClient {
         );
 
         let header = self
-            .headers_pool
-            .get(&args.tx_block_blockhash)
+            .get_header(&args.tx_block_blockhash)
             .unwrap_or_else(|| env::panic_str("cannot find requested transaction block"));
 
         require!(
@@ -368,6 +539,94 @@ impl BtcLightClient {
         self.verify_transaction_inclusion(args.into())
     }
 
+    /// Verifies that a transaction is included in a block at a given block height, as
+    /// [`Self::verify_transaction_inclusion_v2`] does, and also checks both merkle proofs
+    /// against the shape of the merkle tree of a block with `tx_count` transactions.
+    /// This rejects the proofs of mutated merkle trees with duplicated transactions (CVE-2012-2459).
+    ///
+    /// @param tx_id transaction identifier
+    /// @param tx_block_blockhash block hash at which transaction is supposedly included
+    /// @param tx_index index of transaction in the block's tx merkle tree
+    /// @param merkle_proof merkle tree path (concatenated LE sha256 hashes) (does not contain initial transaction_hash and merkle_root)
+    /// @param coinbase_tx_id coinbase transaction hash
+    /// @param coinbase_merkle_proof merkle proof for the coinbase transaction
+    /// @param tx_count number of transactions in the block
+    /// @param confirmations how many confirmed blocks we want to have before the transaction is valid
+    /// @return True if tx_id is at the claimed position in the block at the given blockhash, False otherwise
+    ///
+    /// # Panics
+    /// - If a merkle proof does not match the tree of `tx_count` transactions
+    /// - If coinbase merkle proof does not match the block's merkle root
+    /// - If the required number of confirmations exceeds the number of stored blocks
+    /// - If the block does not belong to the current main chain
+    /// - If there are not enough confirmed blocks
+    #[pause]
+    pub fn verify_transaction_inclusion_v3(&self, #[serializer(borsh)] args: ProofArgsV3) -> bool {
+        let header = self.get_confirmed_header(&args.tx_block_blockhash, args.confirmations);
+
+        require!(
+            merkle_tools::compute_root_from_checked_merkle_proof(
+                args.coinbase_tx_id,
+                0,
+                args.tx_count,
+                &args.coinbase_merkle_proof,
+            )
+            .unwrap_or_else(|err| env::panic_str(&err.to_string()))
+                == header.block_header.merkle_root,
+            "Incorrect coinbase merkle proof"
+        );
+
+        merkle_tools::compute_root_from_checked_merkle_proof(
+            args.tx_id,
+            args.tx_index,
+            args.tx_count,
+            &args.merkle_proof,
+        )
+        .unwrap_or_else(|err| env::panic_str(&err.to_string()))
+            == header.block_header.merkle_root
+    }
+
+    /// Verifies that a transaction is included in a block using a BIP37 merkle block,
+    /// as returned by `gettxoutproof`. The header of the merkle block must be stored
+    /// in the current main chain. For the merge-mined chains, the AuxPoW following the header
+    /// is skipped.
+    ///
+    /// As in [`Self::verify_transaction_inclusion_v2`], the coinbase transaction must also be
+    /// matched by the partial merkle tree, so that the transaction is proven at the depth of
+    /// the tree. Such a merkle block is returned by `gettxoutproof '["<coinbase_txid>", "<txid>"]'`.
+    ///
+    /// @param `merkle_block` consensus-serialized merkle block
+    /// @param `tx_id` transaction identifier
+    /// @param confirmations how many confirmed blocks we want to have before the transaction is valid
+    /// @return True if `tx_id` is matched by the merkle block, False otherwise
+    ///
+    /// # Panics
+    /// - If the merkle block cannot be decoded or its partial merkle tree is malformed
+    /// - If the partial merkle tree does not match the coinbase transaction
+    /// - If the block does not belong to the current main chain
+    /// - If there are not enough confirmed blocks
+    #[pause]
+    pub fn verify_merkle_block_inclusion(
+        &self,
+        #[serializer(borsh)] args: MerkleBlockProofArgs,
+    ) -> bool {
+        let auxpow = self.chain_rules().aux_chain_id(self.network).is_some();
+        let merkle_block =
+            merkle_tools::MerkleBlock::from_bytes_with_auxpow(&args.merkle_block, auxpow)
+                .unwrap_or_else(|err| env::panic_str(&err.to_string()));
+        let matches = merkle_block
+            .extract_matches()
+            .unwrap_or_else(|err| env::panic_str(&err.to_string()));
+        require!(
+            matches.first().is_some_and(|(index, _)| *index == 0),
+            "Merkle block must match the coinbase transaction"
+        );
+
+        self.get_confirmed_header(&merkle_block.header.block_hash(), args.confirmations);
+
+        matches.iter().any(|(_, tx_id)| tx_id == &args.tx_id)
+    }
+
     /// Public call to run GC on a mainchain.
     /// `batch_size` is how many block headers should be removed in the execution
     ///
@@ -375,53 +634,124 @@ impl BtcLightClient {
     /// If initial blockheader or tip blockheader are not in a header pool
     #[pause(except(roles(Role::UnrestrictedRunGC)))]
     pub fn run_mainchain_gc(&mut self, batch_size: u64) {
-        let initial_blockheader = self
-            .headers_pool
-            .get(&self.mainchain_initial_blockhash)
-            .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+        self.mainchain_gc(batch_size);
+    }
+}
 
-        let tip_blockheader = self
-            .headers_pool
-            .get(&self.mainchain_tip_blockhash)
+impl BtcLightClient {
+    /// Returns the consensus rules of the chain the contract follows
+    fn chain_rules(&self) -> &'static dyn ChainRules {
+        chain::chain_rules(self.chain)
+    }
+
+    /// Returns the header of a main chain block with at least `confirmations` confirmations
+    fn get_confirmed_header(&self, block_hash: &H256, confirmations: u64) -> ExtendedHeader {
+        require!(
+            confirmations <= self.gc_threshold,
+            "The required number of confirmations exceeds the number of blocks stored in memory"
+        );
+
+        let heaviest_block_header = self
+            .get_header(&self.mainchain_tip_blockhash)
             .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+        let target_block_height = self
+            .mainchain_header_to_height
+            .get(block_hash)
+            .unwrap_or_else(|| env::panic_str("block does not belong to the current main chain"));
 
-        let amount_of_headers_we_store =
-            tip_blockheader.block_height - initial_blockheader.block_height + 1;
+        require!(
+            (heaviest_block_header.block_height).saturating_sub(target_block_height) + 1
+                >= confirmations,
+            "Not enough blocks confirmed"
+        );
 
-        if amount_of_headers_we_store > self.gc_threshold {
-            let total_amount_to_remove = amount_of_headers_we_store - self.gc_threshold;
-            let selected_amount_to_remove = std::cmp::min(total_amount_to_remove, batch_size);
+        self.get_header(block_hash)
+            .unwrap_or_else(|| env::panic_str("cannot find requested transaction block"))
+    }
 
-            let start_removal_height = initial_blockheader.block_height;
-            let end_removal_height = initial_blockheader.block_height + selected_amount_to_remove;
-            env::log_str(&format!(
-                "Num of blocks to remove {selected_amount_to_remove}"
-            ));
+    #[allow(clippy::needless_pass_by_value)]
+    fn init_signet_challenge(
+        network: Network,
+        signet_challenge: Option<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        if !matches!(network, Network::Signet) {
+            require!(
+                signet_challenge.is_none(),
+                "Signet challenge is set for a network other than Signet"
+            );
+            return None;
+        }
 
-            for height in start_removal_height..end_removal_height {
-                let blockhash = &self
-                    .mainchain_height_to_header
-                    .get(&height)
-                    .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+        #[cfg(not(feature = "signet"))]
+        env::panic_str("Signet requires the contract built with the signet feature");
+        #[cfg(feature = "signet")]
+        {
+            let signet_challenge = signet_challenge.unwrap_or_else(|| {
+                hex::decode(btc_types::signet::DEFAULT_SIGNET_CHALLENGE).unwrap()
+            });
+            btc_types::script::check_supported_opcodes(::bitcoin::Script::from_bytes(
+                &signet_challenge,
+            ))
+            .unwrap_or_else(|err| env::panic_str(&format!("Invalid signet challenge: {err}")));
+            Some(signet_challenge)
+        }
+    }
 
-                self.remove_block_header(blockhash);
-                self.mainchain_height_to_header.remove(&height);
-            }
+    /// Signet headers could be submitted without the block solution only when
+    /// the proof of work verification is skipped
+    fn require_headers_only_submission(&self) {
+        require!(
+            self.skip_pow_verification || self.signet_challenge.is_none(),
+            "Signet headers must be submitted with the block solution, use submit_signet_blocks"
+        );
+    }
 
-            self.mainchain_initial_blockhash = self
-                .mainchain_height_to_header
-                .get(&end_removal_height)
-                .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+    fn submit_headers(&mut self, headers: Vec<BlockHeader>) -> PromiseOrValue<()> {
+        let amount = env::attached_deposit();
+        let initial_storage = env::storage_usage();
+        let num_of_headers = headers.len().try_into().unwrap();
+
+        if let Some(max_headers_per_submit) = self.max_headers_per_submit {
+            require!(
+                num_of_headers <= max_headers_per_submit,
+                format!("Too many headers, at most {max_headers_per_submit} could be submitted")
+            );
+        }
+        require!(
+            amount >= self.min_submit_deposit,
+            format!("Required minimum deposit {}", self.min_submit_deposit)
+        );
+
+        for header in headers {
+            self.submit_block_header(header, self.skip_pow_verification);
+        }
+
+        self.run_mainchain_gc(num_of_headers);
+        let diff_storage_usage = env::storage_usage().saturating_sub(initial_storage);
+        let required_deposit = env::storage_byte_cost().saturating_mul(diff_storage_usage.into());
+
+        require!(
+            amount >= required_deposit,
+            format!("Required deposit {}", required_deposit)
+        );
+
+        let refund = amount.saturating_sub(required_deposit);
+        if refund > NearToken::from_near(0) {
+            Promise::new(env::predecessor_account_id())
+                .transfer(refund)
+                .into()
+        } else {
+            PromiseOrValue::Value(())
         }
     }
-}
 
-impl BtcLightClient {
     fn init_genesis(
         &mut self,
         block_hash: &H256,
         block_height: u64,
         mut submit_blocks: Vec<Header>,
+        initial_chain_work: Option<U256>,
+        min_chain_work: Option<U256>,
     ) {
         env::log_str(&format!(
             "Init with block hash {block_hash} at height {block_height}"
@@ -431,41 +761,21 @@ impl BtcLightClient {
             "At least one block header must be submitted"
         );
 
-        let config = self.get_config();
-        #[cfg(feature = "bitcoin")]
-        {
-            require!(block_height % config.difficulty_adjustment_interval == 0, format!("Error: The initial block height must be divisible by {} to ensure proper alignment with difficulty adjustment periods.", config.difficulty_adjustment_interval));
-        }
-        #[cfg(any(feature = "litecoin", feature = "dogecoin"))]
-        {
-            require!((block_height + 1) % config.difficulty_adjustment_interval == 0, format!("Error: The initial block height  + 1 must be divisible by {} to ensure proper alignment with difficulty adjustment periods.", config.difficulty_adjustment_interval));
-        }
-        #[cfg(any(feature = "litecoin", feature = "dogecoin", feature = "bitcoin"))]
-        {
-            require!(
-                submit_blocks.len() > btc_types::network::MEDIAN_TIME_SPAN,
-                format!(
-                    "At least {} initial blocks must be submitted to support MTP computation",
-                    btc_types::network::MEDIAN_TIME_SPAN + 1
-                )
-            );
-        }
-        #[cfg(feature = "zcash")]
-        {
-            require!(
-                btc_types::network::MEDIAN_TIME_SPAN
-                    + usize::try_from(config.pow_averaging_window).unwrap()
-                    == submit_blocks.len() - 1,
-                "ERR_NOT_ENOUGH_BLOCKS_FOR_ZCASH"
-            );
-        }
+        self.chain_rules()
+            .check_init(self.network, block_height, submit_blocks.len());
 
         let block_header = submit_blocks.remove(0);
         let current_block_hash = block_header.block_hash();
         require!(&current_block_hash == block_hash, "Invalid block hash");
-        let chain_work = work_from_bits(block_header.bits);
+        let block_work = work_from_bits(block_header.bits);
+        let chain_work = initial_chain_work.unwrap_or(block_work);
+        require!(
+            chain_work >= block_work,
+            "Initial chain work is less than the work of the genesis block"
+        );
 
         let header = ExtendedHeader {
+            recent_timestamps: vec![block_header.time],
             block_header: block_header.into_light(),
             block_height,
             block_hash: current_block_hash.clone(),
@@ -478,14 +788,21 @@ impl BtcLightClient {
         self.mainchain_tip_blockhash = current_block_hash;
 
         for block_header in submit_blocks {
-            #[cfg(feature = "dogecoin")]
+            #[cfg(feature = "auxpow")]
             self.submit_block_header((block_header, None), true);
-            #[cfg(not(feature = "dogecoin"))]
+            #[cfg(not(feature = "auxpow"))]
             self.submit_block_header(block_header, true);
         }
+
+        if let Some(min_chain_work) = min_chain_work {
+            require!(
+                self.get_last_block_header().chain_work >= min_chain_work,
+                "Chain work of the initial chain is below the minimum chain work"
+            );
+        }
     }
 
-    #[cfg(not(feature = "dogecoin"))]
+    #[cfg(not(feature = "auxpow"))]
     #[allow(clippy::needless_pass_by_value)]
     fn submit_block_header(&mut self, header: Header, skip_pow_verification: bool) {
         // We do not have a previous block in the headers_pool, there is a high probability
@@ -499,7 +816,7 @@ impl BtcLightClient {
         // It means we found an initial fork position.
         // We are starting to gather new fork from this initial position.
         #[allow(clippy::useless_conversion)]
-        let prev_block_header = self.get_prev_header(&header.clone().into());
+        let prev_block_header = require_valid(self.get_prev_header(&header.clone().into()));
         let current_block_hash = header.block_hash();
 
         let (current_block_computed_chain_work, overflow) = prev_block_header
@@ -512,17 +829,17 @@ impl BtcLightClient {
             block_hash: current_block_hash,
             chain_work: current_block_computed_chain_work,
             block_height: 1 + prev_block_header.block_height,
+            recent_timestamps: get_recent_timestamps(header.time, &prev_block_header, self),
         };
 
         if !skip_pow_verification {
             self.check_target(&header, &prev_block_header);
 
-            let pow_hash = header.block_hash_pow();
             // Check if the block hash is less than or equal to the target
-            require!(
-                U256::from_le_bytes(&pow_hash.0) <= target_from_bits(header.bits),
-                format!("block should have correct pow")
-            );
+            require_valid(check_pow_hash(
+                &self.chain_rules().pow_hash(&header),
+                header.bits,
+            ));
         }
 
         self.submit_block_header_inner(current_header, &prev_block_header);
@@ -550,8 +867,7 @@ impl BtcLightClient {
             log!("Block {}: saving to fork", current_header.block_hash);
             // Fork submission
             let main_chain_tip_header = self
-                .headers_pool
-                .get(&self.mainchain_tip_blockhash)
+                .get_header(&self.mainchain_tip_blockhash)
                 .unwrap_or_else(|| env::panic_str("tip should be in a header pool"));
 
             let last_main_chain_block_height = main_chain_tip_header.block_height;
@@ -565,15 +881,51 @@ impl BtcLightClient {
                 self.reorg_chain(current_header, last_main_chain_block_height);
             }
         }
-    }
+    }
+
+    fn check_target(&self, block_header: &Header, prev_block_header: &ExtendedHeader) {
+        self.chain_rules()
+            .check_pow(self, block_header, prev_block_header);
+    }
+
+    /// The most expensive operation which reorganizes the chain, based on fork weight
+    fn reorg_chain(&mut self, fork_tip_header: ExtendedHeader, last_main_chain_block_height: u64) {
+        let fork_tip_height = fork_tip_header.block_height;
+        let fork_tip_hash = fork_tip_header.block_hash.clone();
+
+        // Walk back to the last block shared by both chains before touching the main chain,
+        // so that a reorg deeper than `max_reorg_depth` is rejected without rewriting it
+        let mut fork_blocks = Vec::new();
+        let mut fork_header_cursor = fork_tip_header;
+
+        while !self
+            .mainchain_header_to_height
+            .contains_key(&fork_header_cursor.block_hash)
+        {
+            let prev_block_hash = fork_header_cursor.block_header.prev_block_hash;
+            fork_blocks.push((
+                fork_header_cursor.block_height,
+                fork_header_cursor.block_hash,
+            ));
+
+            // Switch iterator cursor to the previous block in fork
+            fork_header_cursor = self
+                .get_header(&prev_block_hash)
+                .unwrap_or_else(|| env::panic_str("previous fork block should be there"));
+        }
 
-    fn check_target(&self, block_header: &Header, prev_block_header: &ExtendedHeader) {
-        self.check_pow(block_header, prev_block_header);
-    }
+        // The cursor stopped at the last block shared by both chains
+        if let Some(max_reorg_depth) = self.max_reorg_depth {
+            let reorg_depth = last_main_chain_block_height - fork_header_cursor.block_height;
+            require!(
+                reorg_depth <= max_reorg_depth,
+                format!(
+                    "{}: reorg depth {reorg_depth} exceeds {max_reorg_depth}",
+                    ErrorCode::ReorgLimit
+                )
+            );
+        }
 
-    /// The most expensive operation which reorganizes the chain, based on fork weight
-    fn reorg_chain(&mut self, fork_tip_header: ExtendedHeader, last_main_chain_block_height: u64) {
-        let fork_tip_height = fork_tip_header.block_height;
         if last_main_chain_block_height > fork_tip_height {
             // If we see that main chain is longer than fork we first garbage collect
             // outstanding main chain blocks:
@@ -610,17 +962,7 @@ impl BtcLightClient {
         //     \
         //      [f1] - [f2] - [f3] - [f4] <- fork tip
 
-        let fork_tip_hash = fork_tip_header.block_hash.clone();
-        let mut fork_header_cursor = fork_tip_header;
-
-        while !self
-            .mainchain_header_to_height
-            .contains_key(&fork_header_cursor.block_hash)
-        {
-            let prev_block_hash = fork_header_cursor.block_header.prev_block_hash;
-            let current_block_hash = fork_header_cursor.block_hash;
-            let current_height = fork_header_cursor.block_height;
-
+        for (current_height, current_block_hash) in fork_blocks {
             // Inserting the fork block into the main chain, if some mainchain block is occupying
             // this height let's save its hashcode
             let main_chain_block = self
@@ -634,18 +976,52 @@ impl BtcLightClient {
             if let Some(current_main_chain_blockhash) = main_chain_block {
                 self.remove_block_header(&current_main_chain_blockhash);
             }
-
-            // Switch iterator cursor to the previous block in fork
-            fork_header_cursor = self
-                .headers_pool
-                .get(&prev_block_hash)
-                .unwrap_or_else(|| env::panic_str("previous fork block should be there"));
         }
 
         // Updating tip of the new main chain
         self.mainchain_tip_blockhash = fork_tip_hash;
     }
 
+    /// Removes up to `batch_size` of the oldest mainchain headers above `gc_threshold`
+    fn mainchain_gc(&mut self, batch_size: u64) {
+        let initial_blockheader = self
+            .get_header(&self.mainchain_initial_blockhash)
+            .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+
+        let tip_blockheader = self
+            .get_header(&self.mainchain_tip_blockhash)
+            .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+
+        let amount_of_headers_we_store =
+            tip_blockheader.block_height - initial_blockheader.block_height + 1;
+
+        if amount_of_headers_we_store > self.gc_threshold {
+            let total_amount_to_remove = amount_of_headers_we_store - self.gc_threshold;
+            let selected_amount_to_remove = std::cmp::min(total_amount_to_remove, batch_size);
+
+            let start_removal_height = initial_blockheader.block_height;
+            let end_removal_height = initial_blockheader.block_height + selected_amount_to_remove;
+            env::log_str(&format!(
+                "Num of blocks to remove {selected_amount_to_remove}"
+            ));
+
+            for height in start_removal_height..end_removal_height {
+                let blockhash = &self
+                    .mainchain_height_to_header
+                    .get(&height)
+                    .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+
+                self.remove_block_header(blockhash);
+                self.mainchain_height_to_header.remove(&height);
+            }
+
+            self.mainchain_initial_blockhash = self
+                .mainchain_height_to_header
+                .get(&end_removal_height)
+                .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST));
+        }
+    }
+
     /// Stores parsed block header and meta information
     fn store_block_header(&mut self, header: &ExtendedHeader) {
         self.mainchain_height_to_header
@@ -658,7 +1034,16 @@ impl BtcLightClient {
     /// Remove block header and meta information
     fn remove_block_header(&mut self, header_block_hash: &H256) {
         self.mainchain_header_to_height.remove(header_block_hash);
-        self.headers_pool.remove(header_block_hash);
+        if self.headers_pool.remove(header_block_hash).is_none() {
+            self.legacy_headers_pool.remove(header_block_hash);
+        }
+    }
+
+    /// Reads a header from the headers pool, falling back to the legacy one
+    fn get_header(&self, block_hash: &H256) -> Option<ExtendedHeader> {
+        self.headers_pool
+            .get(block_hash)
+            .or_else(|| self.legacy_headers_pool.get(block_hash).map(Into::into))
     }
 
     /// Stores and handles fork submissions
@@ -668,25 +1053,75 @@ impl BtcLightClient {
 }
 
 impl BlocksGetter for BtcLightClient {
-    fn get_prev_header(&self, current_header: &LightHeader) -> ExtendedHeader {
-        self.headers_pool
-            .get(&current_header.prev_block_hash)
-            .unwrap_or_else(|| env::panic_str("PrevBlockNotFound"))
+    fn find_prev_header(&self, current_header: &LightHeader) -> Option<ExtendedHeader> {
+        self.get_header(&current_header.prev_block_hash)
     }
 
-    fn get_header_by_height(&self, height: u64) -> ExtendedHeader {
+    fn find_header_by_height(&self, height: u64) -> Option<ExtendedHeader> {
         self.mainchain_height_to_header
             .get(&height)
-            .and_then(|hash| self.headers_pool.get(&hash))
-            .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST))
+            .and_then(|hash| self.get_header(&hash))
     }
 }
 
 mod migrate {
+    use crate::chain::resolve_chain;
     use crate::{
-        borsh, env, log, near, BorshDeserialize, BorshSerialize, BtcLightClient, BtcLightClientExt,
-        ExtendedHeader, LookupMap, Network, PanicOnDefault, H256,
+        access_control_any, borsh, env, log, near, require, AccessControllable, BorshDeserialize,
+        BorshSerialize, BtcLightClient, BtcLightClientExt, Chain, ExtendedHeader, LightHeader,
+        LookupMap, NearToken, Network, PanicOnDefault, Role, StorageKey, ERR_KEY_NOT_EXIST, H256,
     };
+    use btc_types::header::Work;
+    use btc_types::validation::{get_recent_timestamps, BlocksGetter};
+
+    /// Version of the current state layout, see `VersionedState`
+    pub const STATE_VERSION: u32 = 4;
+
+    /// Header layout stored in the headers pool before `recent_timestamps` were cached.
+    #[derive(BorshDeserialize, BorshSerialize)]
+    pub struct ExtendedHeaderV1 {
+        pub(crate) block_header: LightHeader,
+        pub(crate) block_hash: H256,
+        pub(crate) chain_work: Work,
+        pub(crate) block_height: u64,
+    }
+
+    /// Lazy migration, used when a legacy header is read: the fields introduced since
+    /// the header was stored are left empty.
+    impl From<ExtendedHeaderV1> for ExtendedHeader {
+        fn from(header: ExtendedHeaderV1) -> Self {
+            Self {
+                block_header: header.block_header,
+                block_hash: header.block_hash,
+                chain_work: header.chain_work,
+                block_height: header.block_height,
+                recent_timestamps: vec![],
+            }
+        }
+    }
+
+    impl ExtendedHeaderV1 {
+        /// Full migration, used when a legacy header is moved to the headers pool:
+        /// the fields introduced since the header was stored are computed.
+        fn migrate(
+            self,
+            prev_block_header: Option<&ExtendedHeader>,
+            prev_block_getter: &impl BlocksGetter,
+        ) -> ExtendedHeader {
+            let block_time = self.block_header.time;
+            let recent_timestamps = match prev_block_header {
+                Some(prev_block_header) => {
+                    get_recent_timestamps(block_time, prev_block_header, prev_block_getter)
+                }
+                None => vec![block_time],
+            };
+
+            ExtendedHeader {
+                recent_timestamps,
+                ..ExtendedHeader::from(self)
+            }
+        }
+    }
 
     /// State layout used between #101 and #116, which contained the
     /// `used_aux_parent_blocks` field in all chain builds.
@@ -696,22 +1131,128 @@ mod migrate {
         mainchain_header_to_height: LookupMap<H256, u64>,
         mainchain_tip_blockhash: H256,
         mainchain_initial_blockhash: H256,
-        headers_pool: LookupMap<H256, ExtendedHeader>,
+        headers_pool: LookupMap<H256, ExtendedHeaderV1>,
         skip_pow_verification: bool,
         gc_threshold: u64,
         used_aux_parent_blocks: near_sdk::collections::LookupSet<H256>,
         network: Network,
     }
 
+    /// State layout used since #116, before the runtime parameters, the recent timestamps,
+    /// the signet challenge and the chain were added.
+    #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+    pub struct BtcLightClientV3 {
+        mainchain_height_to_header: LookupMap<u64, H256>,
+        mainchain_header_to_height: LookupMap<H256, u64>,
+        mainchain_tip_blockhash: H256,
+        mainchain_initial_blockhash: H256,
+        headers_pool: LookupMap<H256, ExtendedHeaderV1>,
+        skip_pow_verification: bool,
+        gc_threshold: u64,
+        network: Network,
+    }
+
+    impl From<BtcLightClientV2> for BtcLightClientV3 {
+        fn from(old_state: BtcLightClientV2) -> Self {
+            Self {
+                mainchain_height_to_header: old_state.mainchain_height_to_header,
+                mainchain_header_to_height: old_state.mainchain_header_to_height,
+                mainchain_tip_blockhash: old_state.mainchain_tip_blockhash,
+                mainchain_initial_blockhash: old_state.mainchain_initial_blockhash,
+                headers_pool: old_state.headers_pool,
+                skip_pow_verification: old_state.skip_pow_verification,
+                gc_threshold: old_state.gc_threshold,
+                network: old_state.network,
+            }
+        }
+    }
+
+    impl BtcLightClientV3 {
+        /// The chain was not stored, so it is set to the one the contract follows
+        fn migrate(self, chain: Chain) -> BtcLightClient {
+            require!(
+                chain.supports(self.network),
+                format!("{:?} is not supported for {chain:?}", self.network)
+            );
+
+            BtcLightClient {
+                mainchain_height_to_header: self.mainchain_height_to_header,
+                mainchain_header_to_height: self.mainchain_header_to_height,
+                mainchain_tip_blockhash: self.mainchain_tip_blockhash,
+                mainchain_initial_blockhash: self.mainchain_initial_blockhash,
+                headers_pool: LookupMap::new(StorageKey::HeadersPoolV2),
+                legacy_headers_pool: self.headers_pool,
+                skip_pow_verification: self.skip_pow_verification,
+                gc_threshold: self.gc_threshold,
+                network: self.network,
+                max_reorg_depth: None,
+                max_headers_per_submit: None,
+                min_submit_deposit: NearToken::from_yoctonear(0),
+                legacy_headers_migration_height: Some(0),
+                signet_challenge: None,
+                chain,
+            }
+        }
+    }
+
+    /// All the known layouts of the contract state, oldest first.
+    ///
+    /// To change the layout, add the previous one as a new variant, implement its migration
+    /// to the next layout, extend `from_raw_state` and `migrate_step`, and bump `STATE_VERSION`.
+    enum VersionedState {
+        V2(BtcLightClientV2),
+        V3(BtcLightClientV3),
+        Current(BtcLightClient),
+    }
+
+    impl VersionedState {
+        /// Detects the layout of the stored state. Borsh requires the whole buffer
+        /// to be consumed, so exactly one of the layouts can parse.
+        fn from_raw_state(raw_state: &[u8]) -> Option<Self> {
+            if let Ok(state) = <BtcLightClient as BorshDeserialize>::try_from_slice(raw_state) {
+                return Some(Self::Current(state));
+            }
+
+            BtcLightClientV3::try_from_slice(raw_state)
+                .map(Self::V3)
+                .or_else(|_| BtcLightClientV2::try_from_slice(raw_state).map(Self::V2))
+                .ok()
+        }
+
+        const fn version(&self) -> u32 {
+            match self {
+                Self::V2(_) => 2,
+                Self::V3(_) => 3,
+                Self::Current(_) => STATE_VERSION,
+            }
+        }
+
+        fn migrate_step(self, chain: Option<Chain>) -> Self {
+            match self {
+                Self::V2(state) => Self::V3(state.into()),
+                Self::V3(state) => Self::Current(state.migrate(resolve_chain(chain))),
+                Self::Current(state) => Self::Current(state),
+            }
+        }
+    }
+
     #[near]
     impl BtcLightClient {
         /// Migrates the contract state to the current `BtcLightClient` version.
         ///
-        /// The stored state variant is detected automatically. Borsh requires the
-        /// whole buffer to be consumed, so exactly one of the layouts can parse:
+        /// The stored state version is detected automatically, then the migration
+        /// steps run one by one up to the current version:
         /// * current layout: returned unchanged (re-running `migrate` is a no-op)
-        /// * `BtcLightClientV2` (#101..#116): drops `used_aux_parent_blocks`;
-        ///   `network` is carried over from the old state
+        /// * V2 (#101..#116) to V3: drops `used_aux_parent_blocks`
+        /// * V3 (#116..) to V4, the current layout:
+        ///   - the runtime parameters are added with their defaults, i.e. no limits
+        ///     and no minimum deposit
+        ///   - the stored headers are kept under their prefix as the legacy headers pool,
+        ///     to be moved to the headers pool by `migrate_legacy_headers`. New headers are
+        ///     stored with the recent timestamps.
+        ///   - `signet_challenge` is unset, since Signet was not supported
+        ///   - `chain` is set to the given one or to the only chain the contract is built
+        ///     with. It must be given to a contract built with several chains.
         ///
         /// Note: any entries stored under the dropped `LookupSet` prefix are left
         /// orphaned in storage. They are only present on Dogecoin deployments;
@@ -719,34 +1260,81 @@ mod migrate {
         ///
         /// # Panics
         /// This function will panic if no state is found in storage, or it
-        /// matches none of the known layouts.
+        /// matches none of the known layouts, or if the chain of a V3 state cannot be resolved.
         #[private]
         #[init(ignore_state)]
         #[must_use]
-        pub fn migrate() -> Self {
+        pub fn migrate(chain: Option<Chain>) -> Self {
             let raw_state = env::storage_read(b"STATE")
                 .unwrap_or_else(|| env::panic_str("contract state not found"));
 
-            if let Ok(state) = <Self as BorshDeserialize>::try_from_slice(&raw_state) {
-                log!("state is already in the current layout");
-                return state;
+            let mut state = VersionedState::from_raw_state(&raw_state)
+                .unwrap_or_else(|| env::panic_str("contract state matches no known layout"));
+            log!("state version {}", state.version());
+
+            loop {
+                match state {
+                    VersionedState::Current(state) => return state,
+                    old_state => {
+                        state = old_state.migrate_step(chain);
+                        log!("migrated state to version {}", state.version());
+                    }
+                }
             }
+        }
 
-            if let Ok(old_state) = BtcLightClientV2::try_from_slice(&raw_state) {
-                log!("migrating state from the V2 layout");
-                return Self {
-                    mainchain_height_to_header: old_state.mainchain_height_to_header,
-                    mainchain_header_to_height: old_state.mainchain_header_to_height,
-                    mainchain_tip_blockhash: old_state.mainchain_tip_blockhash,
-                    mainchain_initial_blockhash: old_state.mainchain_initial_blockhash,
-                    headers_pool: old_state.headers_pool,
-                    skip_pow_verification: old_state.skip_pow_verification,
-                    gc_threshold: old_state.gc_threshold,
-                    network: old_state.network,
+        pub fn get_state_version(&self) -> u32 {
+            STATE_VERSION
+        }
+
+        /// Returns the mainchain height from which the legacy headers are still to be
+        /// migrated, `None` if there are none
+        pub fn get_legacy_headers_migration_height(&self) -> Option<u64> {
+            self.legacy_headers_migration_height
+        }
+
+        /// Moves up to `batch_size` mainchain headers from the legacy headers pool to the
+        /// headers pool, oldest first, computing the fields introduced since they were stored.
+        /// Legacy headers are a prefix of any chain, so the migration is over once a migrated
+        /// header is met. Fork headers are never moved, they stay readable from the legacy pool.
+        ///
+        /// # Panics
+        /// If the mainchain headers are not in the headers pool
+        #[access_control_any(roles(Role::DAO))]
+        pub fn migrate_legacy_headers(&mut self, batch_size: u64) {
+            let Some(migration_height) = self.legacy_headers_migration_height else {
+                return;
+            };
+
+            let initial_height = self
+                .get_header(&self.mainchain_initial_blockhash)
+                .unwrap_or_else(|| env::panic_str(ERR_KEY_NOT_EXIST))
+                .block_height;
+            let mut height = std::cmp::max(migration_height, initial_height);
+            let mut prev_block_header = None;
+
+            for _ in 0..batch_size {
+                let Some(block_hash) = self.mainchain_height_to_header.get(&height) else {
+                    self.legacy_headers_migration_height = None;
+                    return;
                 };
+                let Some(legacy_header) = self.legacy_headers_pool.remove(&block_hash) else {
+                    self.legacy_headers_migration_height = None;
+                    return;
+                };
+
+                if prev_block_header.is_none() {
+                    prev_block_header = self.find_prev_header(&legacy_header.block_header);
+                }
+                let header = legacy_header.migrate(prev_block_header.as_ref(), self);
+                self.headers_pool.insert(&header.block_hash, &header);
+
+                prev_block_header = Some(header);
+                height += 1;
             }
 
-            env::panic_str("contract state matches no known layout")
+            log!("Legacy headers are migrated up to height {height}");
+            self.legacy_headers_migration_height = Some(height);
         }
     }
 }
@@ -756,9 +1344,12 @@ mod migrate {
  * Learn more about Rust tests: https://doc.rust-lang.org/book/ch11-01-writing-tests.html
  */
 #[cfg(test)]
-#[cfg(feature = "bitcoin")]
+#[cfg(all(feature = "bitcoin", not(feature = "auxpow")))]
 mod tests {
     use super::*;
+    use btc_types::network::MEDIAN_TIME_SPAN;
+    use btc_types::utils::target_from_bits;
+    use btc_types::validation::get_median_time_past;
 
     fn decode_hex(hex: &str) -> H256 {
         hex.parse().unwrap()
@@ -950,6 +1541,10 @@ mod tests {
             skip_pow_verification: false,
             gc_threshold: 1000,
             submit_blocks: blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         }
     }
 
@@ -986,6 +1581,10 @@ mod tests {
             skip_pow_verification: false,
             gc_threshold: 3,
             submit_blocks: make_default_submit_blocks(),
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         }
     }
 
@@ -998,6 +1597,10 @@ mod tests {
             skip_pow_verification: true,
             gc_threshold: 3,
             submit_blocks: make_default_submit_blocks(),
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         }
     }
 
@@ -1024,6 +1627,15 @@ mod tests {
 
         let received_header = contract.get_last_block_header();
 
+        let mut recent_timestamps = vec![header.time];
+        recent_timestamps.extend(
+            real_block_headers_685440_to_685451()
+                .iter()
+                .rev()
+                .take(MEDIAN_TIME_SPAN - 1)
+                .map(|header| header.time),
+        );
+
         let w = work_from_bits(386752379);
         let mut expected_chain_work = w;
         for _ in 0..12 {
@@ -1040,10 +1652,191 @@ mod tests {
                 ),
                 chain_work: expected_chain_work,
                 block_height: 685452,
+                recent_timestamps,
             }
         );
     }
 
+    // The retarget multiplies the target of the period by its timespan with `U256::overflowing_mul`,
+    // which dropped the carry of its partial products. A target decoded from compact bits has
+    // a zero low half, so the retarget at 687456 was not affected.
+    #[test]
+    fn test_retarget_on_mainnet_headers() {
+        let headers: Vec<Vec<Header>> = serde_json::from_str(include_str!(
+            "../tests/data/blocks_headers_685440-687456_mainnet.json"
+        ))
+        .unwrap();
+        let headers: Vec<Header> = headers.into_iter().flatten().collect();
+        let last_header = headers.last().unwrap();
+        // Every submission runs in a fresh context, as it would in its own transaction,
+        // so the logs of the whole period do not add up against the per-call log limit
+        let set_context = || {
+            near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+                .block_timestamp(u64::from(last_header.time) * 1_000_000_000)
+                .build());
+        };
+        set_context();
+
+        let mut contract = BtcLightClient::init(InitArgs {
+            gc_threshold: 2017,
+            ..get_init_args_with_real_blocks()
+        });
+        for header in &headers[12..] {
+            set_context();
+            contract.submit_block_header(header.clone(), false);
+        }
+
+        assert_eq!(headers[2015].bits, 0x170d_5f7b);
+        assert_eq!(
+            contract.get_block_hash_by_height(687_456),
+            Some(headers[2016].block_hash())
+        );
+        assert_eq!(headers[2016].bits, 0x170e_1ef9);
+        assert_eq!(
+            contract.get_last_block_header().block_hash,
+            last_header.block_hash()
+        );
+    }
+
+    #[test]
+    fn test_median_time_past_is_cached() {
+        let contract = BtcLightClient::init(get_init_args_with_real_blocks());
+        let tip = contract.get_last_block_header();
+        assert_eq!(tip.recent_timestamps.len(), MEDIAN_TIME_SPAN);
+
+        let uncached_tip = ExtendedHeader {
+            recent_timestamps: vec![],
+            ..tip.clone()
+        };
+        assert_eq!(
+            tip.median_time_past(),
+            get_median_time_past(uncached_tip, &contract).ok()
+        );
+    }
+
+    // Bitcoin regtest genesis block
+    fn regtest_genesis_block_header() -> Header {
+        Header {
+            time: 1_296_688_602,
+            bits: 0x207f_ffff,
+            nonce: 2,
+            ..genesis_block_header()
+        }
+    }
+
+    // Mines a regtest block on top of `prev_block_header`
+    fn mine_regtest_block_header(prev_block_header: &Header, time: u32) -> Header {
+        let mut header = Header {
+            version: 4,
+            prev_block_hash: prev_block_header.block_hash(),
+            time,
+            bits: 0x207f_ffff,
+            nonce: 0,
+            ..prev_block_header.clone()
+        };
+        while U256::from_le_bytes(&header.block_hash_pow().0) > target_from_bits(header.bits) {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    // Initializes with the regtest genesis and MEDIAN_TIME_SPAN mined blocks, skip_pow=false.
+    // Returns the contract and the tip of the chain.
+    fn init_regtest() -> (BtcLightClient, Header) {
+        let genesis = regtest_genesis_block_header();
+        let mut blocks = vec![genesis.clone()];
+        for i in 1..=MEDIAN_TIME_SPAN {
+            let time = genesis.time + u32::try_from(i).unwrap();
+            blocks.push(mine_regtest_block_header(blocks.last().unwrap(), time));
+        }
+        let tip = blocks.last().unwrap().clone();
+
+        near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+            .block_timestamp(u64::from(tip.time) * 1_000_000_000)
+            .build());
+        let contract = BtcLightClient::init(InitArgs {
+            network: Network::Regtest,
+            genesis_block_hash: genesis.block_hash(),
+            genesis_block_height: 0,
+            skip_pow_verification: false,
+            gc_threshold: 1000,
+            submit_blocks: blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
+        });
+
+        (contract, tip)
+    }
+
+    #[test]
+    fn test_regtest_full_validation() {
+        let (mut contract, tip) = init_regtest();
+        assert_eq!(
+            regtest_genesis_block_header().block_hash().to_string(),
+            "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206"
+        );
+
+        let header = mine_regtest_block_header(&tip, tip.time + 1);
+        contract.submit_block_header(header.clone(), contract.skip_pow_verification);
+        assert_eq!(
+            contract.get_last_block_header().block_hash,
+            header.block_hash()
+        );
+        assert_eq!(
+            contract.get_last_block_header().block_height,
+            u64::try_from(MEDIAN_TIME_SPAN).unwrap() + 1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "time-too-old")]
+    fn test_regtest_time_too_old() {
+        let (mut contract, tip) = init_regtest();
+
+        let header = mine_regtest_block_header(&tip, regtest_genesis_block_header().time);
+        contract.submit_block_header(header, contract.skip_pow_verification);
+    }
+
+    #[test]
+    fn test_migrate_legacy_headers() {
+        let mut contract = init_as_dao(get_init_args_with_real_blocks());
+        let tip = contract.get_last_block_header();
+
+        // Move the headers to the legacy pool, as if they were stored before
+        // the recent timestamps were cached
+        for height in 685440..=685451 {
+            let block_hash = contract.get_block_hash_by_height(height).unwrap();
+            let header = contract.headers_pool.remove(&block_hash).unwrap();
+            contract.legacy_headers_pool.insert(
+                &block_hash,
+                &migrate::ExtendedHeaderV1 {
+                    block_header: header.block_header,
+                    block_hash: header.block_hash,
+                    chain_work: header.chain_work,
+                    block_height: header.block_height,
+                },
+            );
+        }
+        contract.legacy_headers_migration_height = Some(0);
+        assert!(contract
+            .get_last_block_header()
+            .recent_timestamps
+            .is_empty());
+
+        contract.migrate_legacy_headers(5);
+        assert_eq!(contract.get_legacy_headers_migration_height(), Some(685445));
+        assert!(contract
+            .get_last_block_header()
+            .recent_timestamps
+            .is_empty());
+
+        contract.migrate_legacy_headers(100);
+        assert_eq!(contract.get_legacy_headers_migration_height(), None);
+        assert_eq!(contract.get_last_block_header(), tip);
+    }
+
     #[test]
     fn test_saving_mainchain_block_header() {
         let header = block_header_example();
@@ -1065,6 +1858,7 @@ mod tests {
                     0, 2, 0, 2, 0, 2
                 ]),
                 block_height: 1,
+                recent_timestamps: vec![1_231_006_506, 1_231_006_505],
             }
         );
     }
@@ -1092,6 +1886,7 @@ mod tests {
                     0, 2, 0, 2, 0, 2
                 ]),
                 block_height: 1,
+                recent_timestamps: vec![1_231_006_506, 1_231_006_505],
             }
         );
     }
@@ -1157,6 +1952,7 @@ mod tests {
                     0, 3, 0, 3, 0, 3
                 ]),
                 block_height: 2,
+                recent_timestamps: vec![1_231_469_744, 1_231_469_665, 1_231_006_505],
             }
         );
     }
@@ -1177,4 +1973,151 @@ mod tests {
 
         contract.submit_block_header(fork_block_header_example_2(), false);
     }
+
+    #[test]
+    fn test_init_with_initial_chain_work() {
+        let initial_chain_work = U256::new(0, 0x1_0000_0000_0000);
+        let mut args = get_default_init_args_with_skip_pow();
+        args.initial_chain_work = Some(initial_chain_work);
+        args.min_chain_work = Some(initial_chain_work);
+        let contract = BtcLightClient::init(args);
+
+        let genesis = contract
+            .headers_pool
+            .get(&genesis_block_header().block_hash())
+            .unwrap();
+        assert_eq!(genesis.chain_work, initial_chain_work);
+        assert!(contract.get_last_block_header().chain_work > initial_chain_work);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain work of the initial chain is below the minimum chain work")]
+    fn test_init_below_min_chain_work() {
+        let mut args = get_default_init_args_with_skip_pow();
+        args.min_chain_work = Some(U256::new(0, 0x1_0000_0000_0000));
+        let _ = BtcLightClient::init(args);
+    }
+
+    #[test]
+    #[should_panic(expected = "Initial chain work is less than the work of the genesis block")]
+    fn test_init_with_initial_chain_work_below_block_work() {
+        let mut args = get_default_init_args_with_skip_pow();
+        args.initial_chain_work = Some(U256::ONE);
+        let _ = BtcLightClient::init(args);
+    }
+
+    // Initializes the contract on behalf of its own account and grants that account the DAO role.
+    fn init_as_dao(args: InitArgs) -> BtcLightClient {
+        let account_id = near_sdk::test_utils::accounts(0);
+        near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+            .current_account_id(account_id.clone())
+            .predecessor_account_id(account_id.clone())
+            .build());
+
+        let mut contract = BtcLightClient::init(args);
+        assert_eq!(
+            contract.acl_grant_role("DAO".to_owned(), account_id),
+            Some(true)
+        );
+        contract
+    }
+
+    #[test]
+    fn test_set_gc_threshold() {
+        let mut contract = init_as_dao(get_default_init_args_with_skip_pow());
+        contract.submit_block_header(block_header_example(), contract.skip_pow_verification);
+        contract.submit_block_header(fork_block_header_example(), contract.skip_pow_verification);
+        contract.submit_block_header(
+            fork_block_header_example_2(),
+            contract.skip_pow_verification,
+        );
+        assert_eq!(contract.get_mainchain_size(), 3);
+
+        // Shrinking without a GC batch keeps the headers until the next GC run
+        contract.set_gc_threshold(2, None);
+        assert_eq!(contract.get_gc_threshold(), 2);
+        assert_eq!(contract.get_mainchain_size(), 3);
+
+        contract.set_gc_threshold(1, Some(100));
+        assert_eq!(contract.get_gc_threshold(), 1);
+        assert_eq!(contract.get_mainchain_size(), 1);
+        assert_eq!(
+            contract.get_last_block_header().block_hash,
+            fork_block_header_example_2().block_hash()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "GC threshold should exceed the max reorg depth")]
+    fn test_gc_threshold_should_exceed_max_reorg_depth() {
+        let mut contract = init_as_dao(get_default_init_args_with_skip_pow());
+        contract.set_max_reorg_depth(Some(2));
+        assert_eq!(contract.get_max_reorg_depth(), Some(2));
+
+        contract.set_gc_threshold(2, None);
+    }
+
+    #[test]
+    fn test_reorg_over_max_reorg_depth_keeps_main_chain() {
+        let mut contract = init_as_dao(get_default_init_args_with_skip_pow());
+        contract.set_max_reorg_depth(Some(1));
+
+        let main_block_2 = Header {
+            prev_block_hash: block_header_example().block_hash(),
+            ..block_header_example()
+        };
+        contract.submit_block_header(block_header_example(), contract.skip_pow_verification);
+        contract.submit_block_header(main_block_2.clone(), contract.skip_pow_verification);
+
+        contract.submit_block_header(fork_block_header_example(), contract.skip_pow_verification);
+        contract.submit_block_header(
+            fork_block_header_example_2(),
+            contract.skip_pow_verification,
+        );
+        let fork_block_3 = Header {
+            prev_block_hash: fork_block_header_example_2().block_hash(),
+            ..fork_block_header_example_2()
+        };
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.submit_block_header(fork_block_3, true);
+        }));
+        assert!(result.is_err());
+
+        // The rejected reorg does not rewrite the main chain
+        assert_eq!(
+            contract.get_last_block_header().block_hash,
+            main_block_2.block_hash()
+        );
+        assert_eq!(
+            contract.get_block_hash_by_height(1),
+            Some(block_header_example().block_hash())
+        );
+        assert_eq!(
+            contract.get_height_by_block_hash(fork_block_header_example().block_hash()),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many headers, at most 1 could be submitted")]
+    fn test_submit_blocks_over_max_headers_per_submit() {
+        let mut contract = init_as_dao(get_default_init_args_with_skip_pow());
+        contract.set_max_headers_per_submit(Some(1));
+        assert_eq!(contract.get_max_headers_per_submit(), Some(1));
+
+        let _ = contract.submit_blocks(vec![block_header_example(), fork_block_header_example()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Required minimum deposit")]
+    fn test_submit_blocks_below_min_submit_deposit() {
+        let mut contract = init_as_dao(get_default_init_args_with_skip_pow());
+        contract.set_min_submit_deposit(NearToken::from_millinear(1));
+        assert_eq!(
+            contract.get_min_submit_deposit(),
+            NearToken::from_millinear(1)
+        );
+
+        let _ = contract.submit_blocks(vec![block_header_example()]);
+    }
 }
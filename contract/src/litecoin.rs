@@ -1,134 +1,290 @@
-use crate::utils::{get_median_time_past, BlocksGetter};
-use crate::{BtcLightClient, BtcLightClientExt, Header, U256};
+use crate::chain::{check_init_median_time_past, current_timestamp, require_valid, ChainRules};
+use crate::{BtcLightClient, BtcLightClientExt, Header, H256};
+use btc_types::contract_args::MwebProofArgs;
 use btc_types::header::ExtendedHeader;
-use btc_types::network::{Network, NetworkConfig, MAX_FUTURE_BLOCK_TIME_LOCAL};
-use btc_types::utils::target_from_bits;
+use btc_types::mweb::LitecoinTransaction;
+use btc_types::network::{get_litecoin_config, Chain, ChainConfig, Network};
+use btc_types::validation::litecoin::check_header;
+use merkle_tools::merkle_tree_depth;
+use near_plugins::{pause, Pausable};
 use near_sdk::{env, near, require};
 
-#[near]
-impl BtcLightClient {
-    pub fn get_config(&self) -> btc_types::network::NetworkConfig {
-        btc_types::network::get_litecoin_config(self.network)
+pub(crate) struct Litecoin;
+
+impl ChainRules for Litecoin {
+    fn name(&self) -> &'static str {
+        "Litecoin"
+    }
+
+    fn config(&self, network: Network) -> ChainConfig {
+        get_litecoin_config(network).into()
+    }
+
+    fn check_init(&self, network: Network, block_height: u64, submit_blocks: usize) {
+        let config = get_litecoin_config(network);
+        require!((block_height + 1) % config.difficulty_adjustment_interval == 0, format!("Error: The initial block height  + 1 must be divisible by {} to ensure proper alignment with difficulty adjustment periods.", config.difficulty_adjustment_interval));
+        check_init_median_time_past(submit_blocks);
     }
 
-    pub fn get_network(&self) -> (String, Network) {
-        ("Litecoin".to_owned(), self.network)
+    fn check_pow(
+        &self,
+        contract: &BtcLightClient,
+        block_header: &Header,
+        prev_block_header: &ExtendedHeader,
+    ) {
+        require_valid(check_header(
+            &get_litecoin_config(contract.network),
+            block_header,
+            prev_block_header,
+            current_timestamp(),
+            contract,
+        ));
     }
 
-    // Reference implementation: https://github.com/litecoin-project/litecoin/blob/09a67c25495e2398437d6a388ee96fb6a266460e/src/validation.cpp#L3630
-    pub(crate) fn check_pow(&self, block_header: &Header, prev_block_header: &ExtendedHeader) {
-        let config = self.get_config();
-        let expected_bits = get_next_work_required(&config, block_header, prev_block_header, self);
+    fn pow_hash(&self, block_header: &Header) -> H256 {
+        block_header.block_hash_scrypt()
+    }
+}
 
-        // Check proof of work
+#[near]
+impl BtcLightClient {
+    /// Verifies that a transaction is included in a Litecoin block with MWEB, see LIP-0003.
+    /// In addition to the checks of [`Self::verify_transaction_inclusion_v2`], the `HogEx` transaction
+    /// is proven to be the last leaf of the block merkle tree, so the number of transactions
+    /// and the depth of the tree are known. If `mweb_header_hash` is set, it is checked against
+    /// the MWEB header commitment of the `HogEx`.
+    ///
+    /// Only the position of the `HogEx` and the number of transactions are proven: the MWEB flag
+    /// is not part of the txid, so it is a sanity check of the submitted bytes. The `HogEx` is
+    /// identified as the last transaction, which the Litecoin consensus requires for MWEB blocks.
+    ///
+    /// # Panics
+    /// Multiple cases
+    #[pause]
+    pub fn verify_mweb_transaction_inclusion(
+        &self,
+        #[serializer(borsh)] args: MwebProofArgs,
+    ) -> bool {
         require!(
-            expected_bits == block_header.bits,
-            "bad-diffbits: incorrect proof of work"
+            self.chain == Chain::Litecoin,
+            "MWEB is supported by Litecoin only"
         );
+        let header = self
+            .get_header(&args.proof.tx_block_blockhash)
+            .unwrap_or_else(|| env::panic_str("cannot find requested transaction block"));
 
-        // Check timestamp against prev
         require!(
-            block_header.time > get_median_time_past(prev_block_header.clone(), self),
-            "time-too-old: block's timestamp is too early"
+            args.proof.tx_index < args.tx_count,
+            "Transaction index exceeds the number of transactions in the block"
         );
-
-        // Check timestamp
-        let current_timestamp = u32::try_from(env::block_timestamp_ms() / 1000).unwrap(); // Convert to seconds
+        let depth = merkle_tree_depth(args.tx_count);
         require!(
-            block_header.time <= current_timestamp + MAX_FUTURE_BLOCK_TIME_LOCAL,
-            "time-too-new: block timestamp too far in the future"
+            args.hogex_merkle_proof.len() == depth && args.proof.merkle_proof.len() == depth,
+            "Merkle proof length does not match the number of transactions in the block"
         );
 
-        // Reject blocks with outdated version
+        let hogex = LitecoinTransaction::from_bytes(&args.hogex_tx)
+            .unwrap_or_else(|err| env::panic_str(&err.to_string()));
+        // Not committed by the txid, the merkle proof below is what binds the transaction
         require!(
-            block_header.version >= 4,
-            "bad-version: block version must be at least 4"
+            hogex.is_hogex,
+            "HogEx transaction must have the MWEB flag set"
         );
+        // The HogEx is the last transaction of the block
+        require!(
+            merkle_tools::compute_root_from_checked_merkle_proof(
+                hogex.txid(),
+                args.tx_count - 1,
+                args.tx_count,
+                &args.hogex_merkle_proof,
+            )
+            .unwrap_or_else(|err| env::panic_str(&err.to_string()))
+                == header.block_header.merkle_root,
+            "Incorrect HogEx merkle proof"
+        );
+
+        if let Some(mweb_header_hash) = args.mweb_header_hash {
+            let committed_mweb_header_hash = hogex
+                .mweb_header_hash()
+                .unwrap_or_else(|err| env::panic_str(&err.to_string()));
+            require!(
+                committed_mweb_header_hash == mweb_header_hash,
+                "MWEB header hash does not match the HogEx commitment"
+            );
+        }
+
+        self.verify_transaction_inclusion_v2(args.proof)
     }
 }
 
-//https://github.com/litecoin-project/litecoin/blob/09a67c25495e2398437d6a388ee96fb6a266460e/src/pow.cpp#L13
-fn get_next_work_required(
-    config: &NetworkConfig,
-    block_header: &Header,
-    prev_block_header: &ExtendedHeader,
-    blocks_getter: &impl BlocksGetter,
-) -> u32 {
-    if (prev_block_header.block_height + 1) % config.difficulty_adjustment_interval != 0 {
-        if config.pow_allow_min_difficulty_blocks {
-            if block_header.time
-                > prev_block_header.block_header.time + 2 * config.pow_target_spacing
-            {
-                return config.proof_of_work_limit_bits;
-            }
-
-            let mut current_block_header = prev_block_header.clone();
-            while current_block_header.block_header.bits == config.proof_of_work_limit_bits
-                && current_block_header.block_height % config.difficulty_adjustment_interval != 0
-            {
-                current_block_header =
-                    blocks_getter.get_prev_header(&current_block_header.block_header);
-            }
-
-            let last_bits = current_block_header.block_header.bits;
-            return last_bits;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InitArgs;
+    use btc_types::contract_args::ProofArgsV2;
+    use merkle_tools::test_utils::leaves;
+    use merkle_tools::MerkleTree;
+
+    const MWEB_HEADER_HASH: [u8; 32] = [0x42; 32];
+
+    /// Serializes a `HogEx` paying to the `HogAddr` of `MWEB_HEADER_HASH`, with or without
+    /// the MWEB flag and the null MWEB transaction
+    fn hogex_tx(mweb: bool) -> Vec<u8> {
+        let mut bytes = vec![2, 0, 0, 0];
+        if mweb {
+            bytes.extend([0, 0x08]);
+        }
+        // A single input spending the null outpoint
+        bytes.push(1);
+        bytes.extend([0; 36]);
+        bytes.push(0);
+        bytes.extend([0xff; 4]);
+        // The HogAddr output
+        bytes.push(1);
+        bytes.extend(100_000u64.to_le_bytes());
+        bytes.extend([34, 0x58, 0x20]);
+        bytes.extend(MWEB_HEADER_HASH);
+        if mweb {
+            bytes.push(0);
         }
-        return prev_block_header.block_header.bits;
+        bytes.extend([0; 4]);
+        bytes
     }
 
-    // Litecoin: This fixes an issue where a 51% attack can change difficulty at will.
-    // Go back the full period unless it's the first retarget after genesis. Code courtesy of Art Forz
-    let mut blocks_to_go_back = config.difficulty_adjustment_interval - 1;
-    if prev_block_header.block_height + 1 != config.difficulty_adjustment_interval {
-        blocks_to_go_back = config.difficulty_adjustment_interval;
-    }
+    /// Initializes the contract of `chain` with a block of the transactions `tx_ids`
+    fn init_with_block(
+        chain: Chain,
+        block_height: u64,
+        tx_ids: Vec<H256>,
+    ) -> (BtcLightClient, H256) {
+        near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+            .block_timestamp(1_700_000_100_000_000_000)
+            .build());
+        let block: Header = serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": MerkleTree::new(tx_ids).unwrap().root(),
+            "time": 1_700_000_000,
+            "bits": 0x207f_ffff,
+            "nonce": 0,
+        }))
+        .unwrap();
+        let block_hash = block.block_hash();
+        let mut submit_blocks = vec![block];
+        for nonce in 1..=11 {
+            submit_blocks.push(Header {
+                prev_block_hash: block_hash.clone(),
+                time: 1_700_000_000 + nonce,
+                nonce,
+                ..submit_blocks[0].clone()
+            });
+        }
 
-    let first_block_height = prev_block_header.block_height - blocks_to_go_back;
+        let contract = BtcLightClient::init(InitArgs {
+            network: Network::Mainnet,
+            genesis_block_hash: block_hash.clone(),
+            genesis_block_height: block_height,
+            skip_pow_verification: true,
+            gc_threshold: 1000,
+            submit_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: Some(chain),
+        });
+        (contract, block_hash)
+    }
 
-    let interval_tail_extend_header = blocks_getter.get_header_by_height(first_block_height);
-    calculate_next_work_required(
-        config,
-        prev_block_header,
-        interval_tail_extend_header.block_header.time.into(),
-    )
-}
+    /// The transactions of a block whose last transaction is the `HogEx`
+    fn block_tx_ids(tx_count: u8) -> Vec<H256> {
+        let mut tx_ids = leaves(tx_count - 1);
+        tx_ids.push(
+            LitecoinTransaction::from_bytes(&hogex_tx(true))
+                .unwrap()
+                .txid(),
+        );
+        tx_ids
+    }
 
-//https://github.com/litecoin-project/litecoin/blob/09a67c25495e2398437d6a388ee96fb6a266460e/src/pow.cpp#L57
-fn calculate_next_work_required(
-    config: &NetworkConfig,
-    prev_block_header: &ExtendedHeader,
-    first_block_time: i64,
-) -> u32 {
-    let prev_block_time: i64 = prev_block_header.block_header.time.into();
+    /// Proves the transaction 1 of the block `block_hash` of the transactions `tx_ids`,
+    /// with the `HogEx` at `hogex_index`
+    fn mweb_proof_args(tx_ids: &[H256], hogex_index: usize, block_hash: H256) -> MwebProofArgs {
+        let tree = MerkleTree::new(tx_ids.to_vec()).unwrap();
+        MwebProofArgs {
+            proof: ProofArgsV2 {
+                tx_id: tx_ids[1].clone(),
+                tx_block_blockhash: block_hash,
+                tx_index: 1,
+                merkle_proof: tree.proof(1).unwrap(),
+                coinbase_tx_id: tx_ids[0].clone(),
+                coinbase_merkle_proof: tree.proof(0).unwrap(),
+                confirmations: 1,
+            },
+            tx_count: tx_ids.len().try_into().unwrap(),
+            hogex_tx: hogex_tx(true),
+            hogex_merkle_proof: tree.proof(hogex_index).unwrap(),
+            mweb_header_hash: Some(H256::from(MWEB_HEADER_HASH)),
+        }
+    }
 
-    let mut actual_time_taken: i64 = prev_block_time - first_block_time;
-    if actual_time_taken < config.pow_target_timespan / 4 {
-        actual_time_taken = config.pow_target_timespan / 4;
+    /// Initializes a Litecoin contract with a block of 5 transactions, the last one being
+    /// the `HogEx`, and proves its transaction 1
+    fn init_litecoin() -> (BtcLightClient, MwebProofArgs) {
+        let tx_ids = block_tx_ids(5);
+        let (contract, block_hash) = init_with_block(Chain::Litecoin, 2015, tx_ids.clone());
+        (contract, mweb_proof_args(&tx_ids, 4, block_hash))
     }
-    if actual_time_taken > config.pow_target_timespan * 4 {
-        actual_time_taken = config.pow_target_timespan * 4;
+
+    #[test]
+    fn test_verify_mweb_transaction_inclusion() {
+        let (contract, args) = init_litecoin();
+        assert!(contract.verify_mweb_transaction_inclusion(args));
     }
 
-    let mut new_target = target_from_bits(prev_block_header.block_header.bits);
+    #[test]
+    #[should_panic(expected = "Merkle proof sibling at level 0 must duplicate the last node")]
+    fn test_hogex_not_at_last_position() {
+        let mut tx_ids = block_tx_ids(5);
+        let hogex_tx_id = tx_ids.pop().unwrap();
+        tx_ids.insert(2, hogex_tx_id);
+        let (contract, block_hash) = init_with_block(Chain::Litecoin, 2015, tx_ids.clone());
 
-    let shift: bool = new_target.bits() > config.pow_limit.bits() - 1;
-    if shift {
-        new_target = new_target >> 1;
+        contract.verify_mweb_transaction_inclusion(mweb_proof_args(&tx_ids, 2, block_hash));
     }
 
-    let (mut new_target, new_target_overflow) =
-        new_target.overflowing_mul(<i64 as TryInto<u64>>::try_into(actual_time_taken).unwrap());
-    require!(!new_target_overflow, "new target overflow");
-    new_target = new_target
-        / U256::from(<i64 as TryInto<u64>>::try_into(config.pow_target_timespan).unwrap());
+    #[test]
+    #[should_panic(
+        expected = "Merkle proof sibling at level 0 equals the node, the tree is mutated"
+    )]
+    fn test_wrong_tx_count() {
+        let (contract, mut args) = init_litecoin();
+        args.tx_count = 6;
+        contract.verify_mweb_transaction_inclusion(args);
+    }
 
-    if shift {
-        new_target = new_target << 1;
+    #[test]
+    #[should_panic(expected = "HogEx transaction must have the MWEB flag set")]
+    fn test_hogex_without_mweb_flag() {
+        let (contract, mut args) = init_litecoin();
+        args.hogex_tx = hogex_tx(false);
+        contract.verify_mweb_transaction_inclusion(args);
     }
 
-    if new_target > config.pow_limit {
-        new_target = config.pow_limit;
+    #[test]
+    #[should_panic(expected = "MWEB header hash does not match the HogEx commitment")]
+    fn test_mismatched_mweb_header_hash() {
+        let (contract, mut args) = init_litecoin();
+        args.mweb_header_hash = Some(H256::from([0x43; 32]));
+        contract.verify_mweb_transaction_inclusion(args);
     }
 
-    new_target.target_to_bits()
+    #[test]
+    #[cfg(feature = "bitcoin")]
+    #[should_panic(expected = "MWEB is supported by Litecoin only")]
+    fn test_not_litecoin_chain() {
+        let tx_ids = block_tx_ids(5);
+        let (contract, block_hash) = init_with_block(Chain::Bitcoin, 0, tx_ids.clone());
+
+        contract.verify_mweb_transaction_inclusion(mweb_proof_args(&tx_ids, 4, block_hash));
+    }
 }
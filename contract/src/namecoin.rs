@@ -0,0 +1,77 @@
+use crate::chain::{check_init_median_time_past, current_timestamp, require_valid, ChainRules};
+use crate::{BtcLightClient, Header};
+use btc_types::header::ExtendedHeader;
+use btc_types::network::{get_namecoin_config, ChainConfig, Network};
+use btc_types::validation::namecoin::check_header;
+use near_sdk::require;
+
+pub(crate) struct Namecoin;
+
+impl ChainRules for Namecoin {
+    fn name(&self) -> &'static str {
+        "Namecoin"
+    }
+
+    fn config(&self, network: Network) -> ChainConfig {
+        get_namecoin_config(network).into()
+    }
+
+    fn check_init(&self, network: Network, block_height: u64, submit_blocks: usize) {
+        let config = get_namecoin_config(network);
+        require!(block_height % config.difficulty_adjustment_interval == 0, format!("Error: The initial block height must be divisible by {} to ensure proper alignment with difficulty adjustment periods.", config.difficulty_adjustment_interval));
+        check_init_median_time_past(submit_blocks);
+    }
+
+    fn check_pow(
+        &self,
+        contract: &BtcLightClient,
+        block_header: &Header,
+        prev_block_header: &ExtendedHeader,
+    ) {
+        require_valid(check_header(
+            &get_namecoin_config(contract.network),
+            block_header,
+            prev_block_header,
+            current_timestamp(),
+            contract,
+        ));
+    }
+
+    fn aux_chain_id(&self, network: Network) -> Option<i32> {
+        Some(get_namecoin_config(network).aux_chain_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::U256;
+    use btc_types::utils::target_from_bits;
+
+    fn genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "41c62dbd9068c89a449525e3cd5ac61b20ece28c3c38b3f35b2161f0e6d3cb0d",
+            "time": 1_303_000_001,
+            "bits": 0x1c00_7fff,
+            "nonce": 2_719_916_434_u32,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_genesis_block() {
+        let config = get_namecoin_config(Network::Mainnet);
+        let genesis = genesis_block_header();
+
+        assert_eq!(
+            genesis.block_hash().to_string(),
+            "000000000062b72c5e2ceb45fbc8587e807c155b0da735e6483dfba2f0a9c770"
+        );
+        assert!(
+            U256::from_le_bytes(&Namecoin.pow_hash(&genesis).0) <= target_from_bits(genesis.bits)
+        );
+        assert!(target_from_bits(genesis.bits) <= config.pow_limit);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{BtcLightClient, BtcLightClientExt};
+use bitcoin::Script;
+use btc_types::contract_args::SignetBlockArgs;
+use btc_types::signet::SignetTxs;
+use btc_types::validation::ErrorCode;
+use near_sdk::{env, near};
+
+#[near]
+impl BtcLightClient {
+    /// Returns the challenge script of the signet as a hex string, `None` for other networks
+    pub fn get_signet_challenge(&self) -> Option<String> {
+        self.signet_challenge.as_ref().map(hex::encode)
+    }
+}
+
+impl BtcLightClient {
+    // reference implementation: https://github.com/bitcoin/bitcoin/blob/v28.0/src/signet.cpp#L125
+    pub(crate) fn check_signet_solution(&self, block: &SignetBlockArgs) {
+        let challenge = self
+            .signet_challenge
+            .as_ref()
+            .unwrap_or_else(|| env::panic_str("Signet blocks are submitted for another network"));
+
+        SignetTxs::create(
+            &block.header,
+            &block.coinbase_tx,
+            &block.coinbase_merkle_proof,
+            Script::from_bytes(challenge),
+        )
+        .and_then(|signet_txs| signet_txs.verify())
+        .unwrap_or_else(|err| {
+            env::panic_str(&format!(
+                "{}: signet block signature validation failure: {err}",
+                ErrorCode::SignetBlockSignature
+            ))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use btc_types::contract_args::InitArgs;
+    use btc_types::header::Header;
+    use btc_types::network::{Network, MEDIAN_TIME_SPAN};
+    use near_plugins::AccessControllable;
+
+    // Signet genesis block
+    fn signet_genesis_block_header() -> Header {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "prev_block_hash": "0000000000000000000000000000000000000000000000000000000000000000",
+            "merkle_root": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+            "time": 1_598_918_400,
+            "bits": 0x1e03_77ae,
+            "nonce": 52_613_770,
+        }))
+        .unwrap()
+    }
+
+    fn init_signet(signet_challenge: Option<Vec<u8>>) -> BtcLightClient {
+        let genesis = signet_genesis_block_header();
+        let mut submit_blocks = vec![genesis.clone()];
+        for i in 1..=MEDIAN_TIME_SPAN {
+            let prev_block_hash = submit_blocks.last().unwrap().block_hash();
+            submit_blocks.push(Header {
+                prev_block_hash,
+                time: genesis.time + u32::try_from(i).unwrap() * 600,
+                ..genesis.clone()
+            });
+        }
+
+        // Init as DAO to bypass the trusted relayer checks
+        let account_id = near_sdk::test_utils::accounts(0);
+        near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+            .current_account_id(account_id.clone())
+            .predecessor_account_id(account_id.clone())
+            .build());
+
+        let mut contract = BtcLightClient::init(InitArgs {
+            network: Network::Signet,
+            genesis_block_hash: genesis.block_hash(),
+            genesis_block_height: 0,
+            skip_pow_verification: false,
+            gc_threshold: 1000,
+            submit_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge,
+            chain: None,
+        });
+        assert_eq!(
+            contract.acl_grant_role("DAO".to_owned(), account_id),
+            Some(true)
+        );
+        contract
+    }
+
+    #[test]
+    fn test_signet_genesis_block() {
+        assert_eq!(
+            signet_genesis_block_header().block_hash().to_string(),
+            "00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6"
+        );
+        assert_eq!(
+            init_signet(None).get_signet_challenge().as_deref(),
+            Some(btc_types::signet::DEFAULT_SIGNET_CHALLENGE)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid signet challenge: Opcode OP_ADD is not supported")]
+    fn test_signet_challenge_with_unsupported_opcode() {
+        // OP_1 OP_1 OP_ADD OP_2 OP_EQUAL
+        init_signet(Some(vec![0x51, 0x51, 0x93, 0x52, 0x87]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Signet headers must be submitted with the block solution")]
+    fn test_signet_headers_only_submission() {
+        let mut contract = init_signet(None);
+        let tip = contract.get_last_block_header();
+        let header = Header {
+            prev_block_hash: tip.block_hash,
+            time: tip.block_header.time + 600,
+            ..signet_genesis_block_header()
+        };
+        let _ = contract.submit_blocks(vec![header]);
+    }
+
+    #[test]
+    #[should_panic(expected = "bad-signet-blksig")]
+    fn test_signet_invalid_solution() {
+        let mut contract = init_signet(Some(vec![0x51]));
+        let tip = contract.get_last_block_header();
+        let header = Header {
+            prev_block_hash: tip.block_hash,
+            time: tip.block_header.time + 600,
+            ..signet_genesis_block_header()
+        };
+        // The coinbase transaction is missing
+        let _ = contract.submit_signet_blocks(vec![SignetBlockArgs {
+            header,
+            coinbase_tx: vec![],
+            coinbase_merkle_proof: vec![],
+        }]);
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "bitcoin")]
 mod test_basics {
-    use btc_types::contract_args::{InitArgs, ProofArgs, ProofArgsV2};
+    use btc_types::contract_args::{
+        InitArgs, MerkleBlockProofArgs, ProofArgs, ProofArgsV2, ProofArgsV3,
+    };
     use btc_types::hash::H256;
     use btc_types::header::{ExtendedHeader, Header};
     use near_sdk::NearToken;
@@ -72,6 +74,10 @@ mod test_basics {
             gc_threshold: 20,
             network: btc_types::network::Network::Mainnet,
             submit_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
         // Call the init method on the contract
         let outcome = contract
@@ -116,6 +122,10 @@ mod test_basics {
             gc_threshold,
             network: btc_types::network::Network::Mainnet,
             submit_blocks: init_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
         // Call the init method on the contract
         let outcome = contract
@@ -176,9 +186,11 @@ mod test_basics {
     }
 
     /// Initializes a sandbox contract from the wasm currently deployed on
-    /// mainnet (`btc-client.bridge.near`, which is already on the current state
-    /// layout), upgrades it to the locally built wasm and verifies that
-    /// `migrate` detects the up-to-date layout and keeps the state intact.
+    /// mainnet (`btc-client.bridge.near`, which is on the V3 state layout),
+    /// upgrades it to the locally built wasm and verifies that `migrate`
+    /// keeps the state intact, adds the runtime parameters with defaults and
+    /// keeps the stored headers readable until they are moved from the legacy
+    /// headers pool by `migrate_legacy_headers`.
     #[tokio::test]
     async fn test_migration_from_mainnet_wasm() -> Result<(), Box<dyn std::error::Error>> {
         let sandbox = near_workspaces::sandbox().await?;
@@ -194,6 +206,10 @@ mod test_basics {
             gc_threshold: 20,
             network: btc_types::network::Network::Mainnet,
             submit_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
         let outcome = contract
             .call("init")
@@ -208,9 +224,8 @@ mod test_basics {
             .await?
             .json::<ExtendedHeader>()?;
 
-        // Upgrade to the current wasm and migrate. The mainnet contract was
-        // already migrated to the current layout, so this exercises the
-        // "state is already in the current layout" no-op path.
+        // Upgrade to the current wasm and migrate. The mainnet contract is on
+        // the V3 layout, so this exercises adding the runtime parameters.
         let new_wasm = near_workspaces::compile_project("./").await?;
         contract
             .as_account()
@@ -234,6 +249,65 @@ mod test_basics {
             .json::<ExtendedHeader>()?;
         assert_eq!(last_header, header_before_upgrade);
 
+        let gc_threshold = contract
+            .view("get_gc_threshold")
+            .args_json(json!({}))
+            .await?
+            .json::<u64>()?;
+        assert_eq!(gc_threshold, 20);
+
+        let max_reorg_depth = contract
+            .view("get_max_reorg_depth")
+            .args_json(json!({}))
+            .await?
+            .json::<Option<u64>>()?;
+        assert_eq!(max_reorg_depth, None);
+
+        let state_version = contract
+            .view("get_state_version")
+            .args_json(json!({}))
+            .await?
+            .json::<u32>()?;
+        assert_eq!(state_version, 4);
+
+        let (chain_name, _) = contract
+            .view("get_network")
+            .args_json(json!({}))
+            .await?
+            .json::<(String, btc_types::network::Network)>()?;
+        assert_eq!(chain_name, "Bitcoin");
+
+        // The stored headers are moved from the legacy headers pool in batches
+        let outcome = contract
+            .call("acl_grant_role")
+            .args_json(json!({ "role": "DAO", "account_id": contract.id() }))
+            .transact()
+            .await?;
+        assert!(outcome.is_success(), "{:?}", outcome.failures());
+
+        let outcome = contract
+            .call("migrate_legacy_headers")
+            .args_json(json!({ "batch_size": 100 }))
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(outcome.is_success(), "{:?}", outcome.failures());
+
+        let migration_height = contract
+            .view("get_legacy_headers_migration_height")
+            .args_json(json!({}))
+            .await?
+            .json::<Option<u64>>()?;
+        assert_eq!(migration_height, None);
+
+        let last_header = contract
+            .view("get_last_block_header")
+            .args_json(json!({}))
+            .await?
+            .json::<ExtendedHeader>()?;
+        assert_eq!(last_header.block_hash, header_before_upgrade.block_hash);
+        assert!(!last_header.recent_timestamps.is_empty());
+
         let user_account = sandbox.dev_create_account().await?;
         grant_relayer_role(&contract, &user_account).await?;
         let (main_block, _, _) = make_reorg_test_blocks();
@@ -420,6 +494,63 @@ mod test_basics {
         Ok(())
     }
 
+    // Serializes headers in the compact raw format accepted by `submit_raw_blocks`:
+    // every header but the first one omits `prev_block_hash`.
+    fn to_compact_raw_headers(headers: &[Header]) -> Vec<u8> {
+        let mut raw_headers = Vec::new();
+        for (i, header) in headers.iter().enumerate() {
+            raw_headers.extend_from_slice(&header.version.to_le_bytes());
+            if i == 0 {
+                raw_headers.extend_from_slice(&header.prev_block_hash.0);
+            }
+            raw_headers.extend_from_slice(&header.merkle_root.0);
+            raw_headers.extend_from_slice(&header.time.to_le_bytes());
+            raw_headers.extend_from_slice(&header.bits.to_le_bytes());
+            raw_headers.extend_from_slice(&header.nonce.to_le_bytes());
+        }
+        raw_headers
+    }
+
+    #[tokio::test]
+    async fn test_submit_raw_blocks() -> Result<(), Box<dyn std::error::Error>> {
+        let (contract, user_account, block_headers) = init_contract_from_file(2017).await?;
+
+        let outcome = user_account
+            .call(contract.id(), "submit_raw_blocks")
+            .args_borsh(to_compact_raw_headers(&block_headers[0]))
+            .deposit(STORAGE_DEPOSIT_PER_BLOCK)
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(outcome.is_success(), "{:?}", outcome.failures());
+
+        let last_header = contract
+            .view("get_last_block_header")
+            .args_json(json!({}))
+            .await?
+            .json::<ExtendedHeader>()?;
+        assert_eq!(
+            last_header.block_hash,
+            block_headers[0].last().unwrap().block_hash()
+        );
+
+        // A header whose implied `prev_block_hash` is not the hash of the previous one
+        // in the batch cannot be attached
+        let mut raw_headers = to_compact_raw_headers(&block_headers[1][..2]);
+        raw_headers.truncate(raw_headers.len() - 48);
+        raw_headers.extend_from_slice(&to_compact_raw_headers(&block_headers[1][2..3])[36..]);
+        let outcome = user_account
+            .call(contract.id(), "submit_raw_blocks")
+            .args_borsh(raw_headers)
+            .deposit(STORAGE_DEPOSIT_PER_BLOCK)
+            .max_gas()
+            .transact()
+            .await?;
+        assert!(outcome.is_failure());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_get_last_n_blocks() -> Result<(), Box<dyn std::error::Error>> {
         let (contract, user_account, block_headers) = init_contract_from_file(2017).await?;
@@ -664,6 +795,10 @@ mod test_basics {
             gc_threshold: 20,
             network: btc_types::network::Network::Mainnet,
             submit_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
         let outcome = contract
             .call("init")
@@ -810,6 +945,105 @@ mod test_basics {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_verify_transaction_inclusion_v3() -> Result<(), Box<dyn std::error::Error>> {
+        let (contract, user_account) = init_contract().await?;
+        let (block, coinbase_hash, tx_hash) = submit_two_tx_block(&contract, &user_account).await?;
+
+        let proof = |tx_count| ProofArgsV3 {
+            tx_id: tx_hash.clone(),
+            tx_block_blockhash: block.block_hash(),
+            tx_index: 1,
+            merkle_proof: vec![coinbase_hash.clone()],
+            coinbase_tx_id: coinbase_hash.clone(),
+            coinbase_merkle_proof: vec![tx_hash.clone()],
+            tx_count,
+            confirmations: 0,
+        };
+
+        let result: bool = user_account
+            .view(contract.id(), "verify_transaction_inclusion_v3")
+            .args_borsh(proof(2))
+            .await?
+            .json()?;
+        assert!(result, "Valid transaction inclusion should return true");
+
+        // A block with 3 transactions has a merkle tree of depth 2
+        let result = user_account
+            .view(contract.id(), "verify_transaction_inclusion_v3")
+            .args_borsh(proof(3))
+            .await;
+        assert!(
+            result.is_err(),
+            "Should fail when the proof length does not match the transaction count"
+        );
+
+        // The transaction index must be in range
+        let result = user_account
+            .view(contract.id(), "verify_transaction_inclusion_v3")
+            .args_borsh(proof(1))
+            .await;
+        assert!(
+            result.is_err(),
+            "Should fail when the transaction index exceeds the transaction count"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_merkle_block_inclusion() -> Result<(), Box<dyn std::error::Error>> {
+        let (contract, user_account) = init_contract().await?;
+        let (block, coinbase_hash, tx_hash) = submit_two_tx_block(&contract, &user_account).await?;
+        let txids = [coinbase_hash.clone(), tx_hash.clone()];
+
+        let merkle_block = |matches: &[bool]| {
+            merkle_tools::MerkleBlock {
+                header: block.clone(),
+                txn: merkle_tools::PartialMerkleTree::new(&txids, matches),
+            }
+            .to_bytes()
+        };
+
+        let result: bool = user_account
+            .view(contract.id(), "verify_merkle_block_inclusion")
+            .args_borsh(MerkleBlockProofArgs {
+                merkle_block: merkle_block(&[true, true]),
+                tx_id: tx_hash.clone(),
+                confirmations: 0,
+            })
+            .await?
+            .json()?;
+        assert!(result, "Matched transaction should return true");
+
+        let result: bool = user_account
+            .view(contract.id(), "verify_merkle_block_inclusion")
+            .args_borsh(MerkleBlockProofArgs {
+                merkle_block: merkle_block(&[true, false]),
+                tx_id: tx_hash.clone(),
+                confirmations: 0,
+            })
+            .await?
+            .json()?;
+        assert!(!result, "Unmatched transaction should return false");
+
+        // The coinbase transaction must be matched
+        let result = user_account
+            .view(contract.id(), "verify_merkle_block_inclusion")
+            .args_borsh(MerkleBlockProofArgs {
+                merkle_block: merkle_block(&[false, true]),
+                tx_id: tx_hash,
+                confirmations: 0,
+            })
+            .await;
+        assert!(
+            result.is_err(),
+            "Should fail when the coinbase transaction is not matched"
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_verify_transaction_inclusion_v2_invalid_coinbase_proof(
     ) -> Result<(), Box<dyn std::error::Error>> {
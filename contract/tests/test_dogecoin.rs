@@ -107,6 +107,10 @@ mod test_dogecoin {
             gc_threshold: 20,
             network: Network::Mainnet,
             submit_blocks: init_blocks,
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
 
         let outcome = contract
@@ -275,6 +279,7 @@ mod test_dogecoin {
                 "6547a00ec700000000",
             ))
             .unwrap(),
+            parent_block_hash: H256::default(),
             // 9-branch proof: coinbase txid → Litecoin block merkle_root.
             merkle_proof: vec![
                 h("09d1f49f9953dd1dfc107cb6afb32006864822cc4961be8bd0d2f5bd3b1b1192"),
@@ -335,6 +340,10 @@ mod test_dogecoin {
             gc_threshold: 20,
             network: Network::Mainnet,
             submit_blocks: blocks, // 5_800_000 .. 5_800_012 (13 blocks, skip=true in init loop)
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
 
         let outcome = contract
@@ -382,7 +391,7 @@ mod test_dogecoin {
     }
 
     /// Submit a Dogecoin block with wrong target bits and no AuxPoW data.
-    /// Expected: rejected with "Error: Incorrect target." before any AuxPoW checks.
+    /// Expected: rejected with "bad-diffbits" before any AuxPoW checks.
     #[tokio::test]
     async fn test_wrong_target_no_auxpow_rejected() -> Result<(), Box<dyn std::error::Error>> {
         let (contract, user_account) = init_dogecoin_contract().await?;
@@ -398,15 +407,15 @@ mod test_dogecoin {
 
         assert!(
             format!("{:?}", outcome.failures()[0].clone().into_result())
-                .contains("Error: Incorrect target."),
-            "Expected 'Error: Incorrect target.' but got: {:?}",
+                .contains("bad-diffbits: incorrect proof of work"),
+            "Expected 'bad-diffbits' but got: {:?}",
             outcome.failures()
         );
         Ok(())
     }
 
     /// Submit a Dogecoin block with wrong target bits AND AuxPoW data attached.
-    /// Expected: still rejected with "Error: Incorrect target." because the bits
+    /// Expected: still rejected with "bad-diffbits" because the bits
     /// check in check_pow fires before check_aux is ever reached.
     #[tokio::test]
     async fn test_wrong_target_with_auxpow_rejected_before_auxpow_check(
@@ -415,6 +424,7 @@ mod test_dogecoin {
 
         let aux_data = AuxData {
             coinbase_tx: vec![],
+            parent_block_hash: H256::default(),
             merkle_proof: vec![],
             chain_merkle_proof: vec![],
             chain_id: 0,
@@ -432,8 +442,8 @@ mod test_dogecoin {
 
         assert!(
             format!("{:?}", outcome.failures()[0].clone().into_result())
-                .contains("Error: Incorrect target."),
-            "Expected 'Error: Incorrect target.' but got: {:?}",
+                .contains("bad-diffbits: incorrect proof of work"),
+            "Expected 'bad-diffbits' but got: {:?}",
             outcome.failures()
         );
         Ok(())
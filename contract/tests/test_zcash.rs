@@ -66,6 +66,10 @@ mod test_zcash {
             gc_threshold: 2000,
             network: btc_types::network::Network::Mainnet,
             submit_blocks: initial_blocks[..29].to_vec(),
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
 
         let outcome = contract
@@ -142,6 +146,10 @@ mod test_zcash {
             gc_threshold: 2000,
             network: btc_types::network::Network::Mainnet,
             submit_blocks: initial_blocks[..29].to_vec(),
+            initial_chain_work: None,
+            min_chain_work: None,
+            signet_challenge: None,
+            chain: None,
         };
         let outcome = contract
             .call("init")
@@ -0,0 +1,342 @@
+use btc_types::contract_args::ProofArgsV3;
+use btc_types::header::Header;
+use btc_types::utils::DecodeHeaderError;
+
+use crate::encode::{Reader, UnexpectedEnd};
+use crate::{double_sha256, MerkleTree, H256};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockError {
+    InvalidLength,
+    TrailingBytes,
+    InvalidHeader(DecodeHeaderError),
+    NoTransactions,
+    MerkleRootMismatch,
+    TransactionNotFound,
+}
+
+impl std::fmt::Display for BlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockError::InvalidLength => write!(f, "Block is truncated"),
+            BlockError::TrailingBytes => write!(f, "Block has trailing bytes"),
+            BlockError::InvalidHeader(err) => write!(f, "Invalid block header: {err}"),
+            BlockError::NoTransactions => write!(f, "Block has no transactions"),
+            BlockError::MerkleRootMismatch => write!(
+                f,
+                "Merkle root of the transactions does not match the block header"
+            ),
+            BlockError::TransactionNotFound => write!(f, "Transaction is not in the block"),
+        }
+    }
+}
+
+impl std::error::Error for BlockError {}
+
+impl From<UnexpectedEnd> for BlockError {
+    fn from(_: UnexpectedEnd) -> Self {
+        BlockError::InvalidLength
+    }
+}
+
+impl From<DecodeHeaderError> for BlockError {
+    fn from(err: DecodeHeaderError) -> Self {
+        BlockError::InvalidHeader(err)
+    }
+}
+
+/// Merkle tree of the transactions of a block, checked against the merkle root of its header.
+/// Builds the inclusion proofs of the transactions as expected by the contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockMerkleTree {
+    header: Header,
+    tree: MerkleTree,
+}
+
+impl BlockMerkleTree {
+    /// Builds the merkle tree of the block with `txids`, in block order.
+    ///
+    /// # Errors
+    /// If there are no transactions or their merkle root does not match the header.
+    pub fn new(header: Header, txids: Vec<H256>) -> Result<Self, BlockError> {
+        let tree = MerkleTree::new(txids).ok_or(BlockError::NoTransactions)?;
+        if tree.root() != header.merkle_root {
+            return Err(BlockError::MerkleRootMismatch);
+        }
+        Ok(Self { header, tree })
+    }
+
+    /// Decodes a consensus-serialized block, as returned by `getblock <hash> 0`.
+    /// Blocks with AuxPoW are not supported.
+    ///
+    /// # Errors
+    /// If the block cannot be decoded or its merkle root does not match the header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BlockError> {
+        let header = Header::from_block_header_vec(
+            bytes.get(..Header::SIZE).ok_or(BlockError::InvalidLength)?,
+        )?;
+
+        let mut reader = Reader::new(&bytes[Header::SIZE..]);
+        let tx_count = reader.read_compact_size()?;
+        // Each transaction takes at least 10 bytes
+        if tx_count > reader.remaining() / 10 {
+            return Err(BlockError::InvalidLength);
+        }
+        let txids = (0..tx_count)
+            .map(|_| read_txid(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        if reader.remaining() != 0 {
+            return Err(BlockError::TrailingBytes);
+        }
+
+        Self::new(header, txids)
+    }
+
+    /// Builds the merkle tree of a block decoded with the `bitcoin` crate.
+    ///
+    /// # Errors
+    /// If the header cannot be converted or the merkle root does not match the header.
+    #[cfg(feature = "bitcoin")]
+    pub fn from_block(block: &bitcoin::Block) -> Result<Self, BlockError> {
+        use bitcoin::hashes::Hash;
+
+        let header = Header::from_block_header_vec(&bitcoin::consensus::serialize(&block.header))?;
+        let txids = block
+            .txdata
+            .iter()
+            .map(|tx| H256(tx.compute_txid().to_byte_array()))
+            .collect();
+
+        Self::new(header, txids)
+    }
+
+    #[must_use]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    #[must_use]
+    pub fn txids(&self) -> &[H256] {
+        self.tree.leaves()
+    }
+
+    #[must_use]
+    pub fn tx_index(&self, txid: &H256) -> Option<usize> {
+        self.txids().iter().position(|id| id == txid)
+    }
+
+    /// Arguments of `verify_transaction_inclusion_v3` for the transaction at `index`,
+    /// with the coinbase proof and the number of transactions of the block. They convert
+    /// into the `ProofArgsV2` of `verify_transaction_inclusion_v2`.
+    /// Returns `None` if `index` is out of range.
+    #[must_use]
+    pub fn proof_args_at(&self, index: usize, confirmations: u64) -> Option<ProofArgsV3> {
+        Some(ProofArgsV3 {
+            tx_id: self.txids().get(index)?.clone(),
+            tx_block_blockhash: self.header.block_hash(),
+            tx_index: index.try_into().ok()?,
+            merkle_proof: self.tree.proof(index)?,
+            coinbase_tx_id: self.txids()[0].clone(),
+            coinbase_merkle_proof: self.tree.proof(0)?,
+            tx_count: self.tree.leaf_count().try_into().ok()?,
+            confirmations,
+        })
+    }
+
+    /// Arguments of `verify_transaction_inclusion_v3` for the transaction with `txid`.
+    ///
+    /// # Errors
+    /// If the transaction is not in the block.
+    pub fn proof_args(&self, txid: &H256, confirmations: u64) -> Result<ProofArgsV3, BlockError> {
+        self.tx_index(txid)
+            .and_then(|index| self.proof_args_at(index, confirmations))
+            .ok_or(BlockError::TransactionNotFound)
+    }
+}
+
+/// Reads a consensus-serialized transaction, with or without witness, and returns its txid
+fn read_txid(reader: &mut Reader) -> Result<H256, UnexpectedEnd> {
+    let version = reader.read_array::<4>()?;
+
+    let mut segwit_reader = Reader::new(reader.peek());
+    let has_witness = segwit_reader.read_array::<2>() == Ok([0x00, 0x01]);
+    if has_witness {
+        reader.read_array::<2>()?;
+    }
+
+    let inputs_and_outputs = reader.peek();
+    let inputs_start = reader.offset();
+    let input_count = reader.read_compact_size()?;
+    for _ in 0..input_count {
+        reader.read_array::<36>()?; // previous output
+        reader.read_var_slice()?; // script_sig
+        reader.read_array::<4>()?; // sequence
+    }
+    for _ in 0..reader.read_compact_size()? {
+        reader.read_array::<8>()?; // value
+        reader.read_var_slice()?; // script_pubkey
+    }
+    let inputs_and_outputs = &inputs_and_outputs[..reader.offset() - inputs_start];
+
+    if has_witness {
+        for _ in 0..input_count {
+            for _ in 0..reader.read_compact_size()? {
+                reader.read_var_slice()?;
+            }
+        }
+    }
+    let lock_time = reader.read_array::<4>()?;
+
+    let mut tx_without_witness = Vec::with_capacity(inputs_and_outputs.len() + 8);
+    tx_without_witness.extend(version);
+    tx_without_witness.extend(inputs_and_outputs);
+    tx_without_witness.extend(lock_time);
+    Ok(double_sha256(&tx_without_witness))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_root_from_checked_merkle_proof, compute_root_from_merkle_proof};
+    use btc_types::contract_args::ProofArgsV2;
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        hex::decode(hex).unwrap()
+    }
+
+    // Mainnet block 00000000b0c5a240b2a61d2e75692224efd4cbecdf6eaf4cc2cf477ca7c270e7
+    const BLOCK: &str = concat!(
+        "010000004ddccd549d28f385ab457e98d1b11ce80bfea2c5ab93015ade4973e400000000bf4473e53794beae34e64fccc471dace6ae544180816f89591894e0f417a914cd74d6e49ffff001d323b3a7b",
+        "02",
+        "01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0804ffff001d026e04ffffffff0100f2052a0100000043410446ef0102d1ec5240f0d061a4246c1bdef63fc3dbab7733052fbbf0ecd8f41fc26bf049ebb4f9527f374280259e7cfa99c48b0e3f39c51347a19a5819651503a5ac00000000",
+        "010000000321f75f3139a013f50f315b23b0c9a2b6eac31e2bec98e5891c924664889942260000000049483045022100cb2c6b346a978ab8c61b18b5e9397755cbd17d6eb2fe0083ef32e067fa6c785a02206ce44e613f31d9a6b0517e46f3db1576e9812cc98d159bfdaf759a5014081b5c01ffffffff79cda0945903627c3da1f85fc95d0b8ee3e76ae0cfdc9a65d09744b1f8fc85430000000049483045022047957cdd957cfd0becd642f6b84d82f49b6cb4c51a91f49246908af7c3cfdf4a022100e96b46621f1bffcf5ea5982f88cef651e9354f5791602369bf5a82a6cd61a62501fffffffffe09f5fe3ffbf5ee97a54eb5e5069e9da6b4856ee86fc52938c2f979b0f38e82000000004847304402204165be9a4cbab8049e1af9723b96199bfd3e85f44c6b4c0177e3962686b26073022028f638da23fc003760861ad481ead4099312c60030d4cb57820ce4d33812a5ce01ffffffff01009d966b01000000434104ea1feff861b51fe3f5f8a3b12d0f4712db80e919548a80839fc47c6a21e66d957e9c5d8cd108c7a2d2324bad71f9904ac0ae7336507d785b17a2c115e427a32fac00000000",
+    );
+
+    const TXID: &str = "a3b0e9e7cddbbe78270fa4182a7675ff00b92872d8df7d14265a2b1e379a9d33";
+
+    #[test]
+    fn test_block_proof_args() {
+        let block = BlockMerkleTree::from_bytes(&decode_hex(BLOCK)).unwrap();
+        assert_eq!(
+            block.header().block_hash().to_string(),
+            "00000000b0c5a240b2a61d2e75692224efd4cbecdf6eaf4cc2cf477ca7c270e7"
+        );
+
+        let txid: H256 = TXID.parse().unwrap();
+        assert_eq!(block.tx_index(&txid), Some(1));
+
+        let args = block.proof_args(&txid, 6).unwrap();
+        assert_eq!(args.tx_id, txid);
+        assert_eq!(args.tx_block_blockhash, block.header().block_hash());
+        assert_eq!(args.tx_index, 1);
+        assert_eq!(args.coinbase_tx_id, block.txids()[0]);
+        assert_eq!(args.tx_count, 2);
+        assert_eq!(args.confirmations, 6);
+
+        let args_v2 = ProofArgsV2::from(args.clone());
+        assert_eq!(args_v2.tx_id, txid);
+        assert_eq!(args_v2.merkle_proof, args.merkle_proof);
+        assert_eq!(args_v2.coinbase_merkle_proof, args.coinbase_merkle_proof);
+        assert_eq!(args_v2.confirmations, 6);
+
+        assert_eq!(
+            compute_root_from_merkle_proof(args.tx_id, 1, &args.merkle_proof),
+            block.header().merkle_root
+        );
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(
+                args.coinbase_tx_id,
+                0,
+                2,
+                &args.coinbase_merkle_proof
+            ),
+            Ok(block.header().merkle_root.clone())
+        );
+
+        assert_eq!(
+            block.proof_args(&H256::default(), 6).unwrap_err(),
+            BlockError::TransactionNotFound
+        );
+        assert!(block.proof_args_at(2, 6).is_none());
+    }
+
+    #[test]
+    fn test_invalid_block() {
+        let bytes = decode_hex(BLOCK);
+        assert_eq!(
+            BlockMerkleTree::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BlockError::InvalidLength)
+        );
+        assert_eq!(
+            BlockMerkleTree::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+            Err(BlockError::TrailingBytes)
+        );
+
+        // Drops the second transaction
+        let mut one_tx = bytes[..Header::SIZE].to_vec();
+        one_tx.push(1);
+        one_tx.extend(&bytes[Header::SIZE + 1..Header::SIZE + 1 + 135]);
+        assert_eq!(
+            BlockMerkleTree::from_bytes(&one_tx),
+            Err(BlockError::MerkleRootMismatch)
+        );
+
+        let header = BlockMerkleTree::from_bytes(&bytes)
+            .unwrap()
+            .header()
+            .clone();
+        assert_eq!(
+            BlockMerkleTree::new(header, Vec::new()),
+            Err(BlockError::NoTransactions)
+        );
+    }
+
+    #[test]
+    fn test_segwit_txid() {
+        // A segwit transaction spending one input with a two-item witness
+        let tx = decode_hex(concat!(
+            "02000000",
+            "0001",
+            "01",
+            "1111111111111111111111111111111111111111111111111111111111111111",
+            "00000000",
+            "00",
+            "fdffffff",
+            "01",
+            "e803000000000000",
+            "160014",
+            "2222222222222222222222222222222222222222",
+            "02",
+            "02abcd",
+            "0133",
+            "00000000",
+        ));
+        let tx_without_witness = [&tx[..4], &tx[6..tx.len() - 10], &tx[tx.len() - 4..]].concat();
+
+        let mut reader = Reader::new(&tx);
+        assert_eq!(
+            read_txid(&mut reader),
+            Ok(double_sha256(&tx_without_witness))
+        );
+        assert_eq!(reader.remaining(), 0);
+
+        let mut reader = Reader::new(&tx_without_witness);
+        assert_eq!(
+            read_txid(&mut reader),
+            Ok(double_sha256(&tx_without_witness))
+        );
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn test_from_bitcoin_block() {
+        let bytes = decode_hex(BLOCK);
+        let block: bitcoin::Block = bitcoin::consensus::deserialize(&bytes).unwrap();
+
+        assert_eq!(
+            BlockMerkleTree::from_block(&block),
+            BlockMerkleTree::from_bytes(&bytes)
+        );
+    }
+}
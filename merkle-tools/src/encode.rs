@@ -0,0 +1,78 @@
+/// The input ends before the decoded data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct UnexpectedEnd;
+
+/// Reader of consensus-serialized data
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    /// Number of bytes read so far
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Bytes left to read
+    pub(crate) fn peek(&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    pub(crate) fn read_slice(&mut self, len: usize) -> Result<&'a [u8], UnexpectedEnd> {
+        let slice = self
+            .bytes
+            .get(self.offset..)
+            .and_then(|bytes| bytes.get(..len))
+            .ok_or(UnexpectedEnd)?;
+        self.offset += len;
+        Ok(slice)
+    }
+
+    pub(crate) fn read_array<const N: usize>(&mut self) -> Result<[u8; N], UnexpectedEnd> {
+        Ok(self.read_slice(N)?.try_into().unwrap())
+    }
+
+    /// Reads a `CompactSize` unsigned integer
+    pub(crate) fn read_compact_size(&mut self) -> Result<usize, UnexpectedEnd> {
+        let value = match self.read_array::<1>()?[0] {
+            0xfd => u64::from(u16::from_le_bytes(self.read_array()?)),
+            0xfe => u64::from(u32::from_le_bytes(self.read_array()?)),
+            0xff => u64::from_le_bytes(self.read_array()?),
+            value => u64::from(value),
+        };
+        usize::try_from(value).map_err(|_| UnexpectedEnd)
+    }
+
+    /// Reads a byte vector prefixed with its `CompactSize` length
+    pub(crate) fn read_var_slice(&mut self) -> Result<&'a [u8], UnexpectedEnd> {
+        let len = self.read_compact_size()?;
+        self.read_slice(len)
+    }
+}
+
+pub(crate) fn write_compact_size(out: &mut Vec<u8>, value: usize) {
+    match u64::try_from(value).unwrap() {
+        value @ 0..=0xfc => out.push(u8::try_from(value).unwrap()),
+        value @ 0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend(u16::try_from(value).unwrap().to_le_bytes());
+        }
+        value @ 0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend(u32::try_from(value).unwrap().to_le_bytes());
+        }
+        value => {
+            out.push(0xff);
+            out.extend(value.to_le_bytes());
+        }
+    }
+}
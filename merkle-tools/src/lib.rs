@@ -1,33 +1,41 @@
+pub use block::{BlockError, BlockMerkleTree};
 pub use btc_types::hash::{double_sha256, H256};
+pub use partial::{MerkleBlock, MerkleBlockError, MerkleMatches, PartialMerkleTree};
+pub use tree::{MerkleBranches, MerkleTree, StreamingMerkleTree};
+pub use verifier::{compute_root_from_checked_merkle_proof, merkle_tree_depth, MerkleProofError};
+pub use witness::{
+    compute_witness_commitment, extract_witness_commitment, verify_witness_commitment,
+    witness_merkle_root, witness_merkle_tree, CoinbaseWitnessCommitment, WitnessCommitmentError,
+    WITNESS_COMMITMENT_HEADER,
+};
+
+mod block;
+mod encode;
+mod partial;
+mod tree;
+mod verifier;
+mod witness;
+
+/// Helpers shared by the tests of the merkle trees
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils {
+    use crate::H256;
+
+    /// Distinct leaves `[0; 32]`, `[1; 32]`, ...
+    #[must_use]
+    pub fn leaves(count: u8) -> Vec<H256> {
+        (0..count).map(|i| H256::from([i; 32])).collect()
+    }
+}
 
+/// Computes the merkle proof of a single transaction. To generate proofs for
+/// several transactions of the same block, build a `MerkleTree` once instead.
 #[must_use]
 pub fn merkle_proof_calculator(tx_hashes: Vec<H256>, transaction_position: usize) -> Vec<H256> {
-    let mut transaction_position = transaction_position;
-    let mut merkle_proof = Vec::new();
-    let mut current_hashes = tx_hashes;
-
-    while current_hashes.len() > 1 {
-        if current_hashes.len() % 2 == 1 {
-            current_hashes.push(current_hashes[current_hashes.len() - 1].clone());
-        }
-
-        if transaction_position % 2 == 1 {
-            merkle_proof.push(current_hashes[transaction_position - 1].clone());
-        } else {
-            merkle_proof.push(current_hashes[transaction_position + 1].clone());
-        }
-
-        let mut new_hashes = Vec::new();
-
-        for i in (0..current_hashes.len() - 1).step_by(2) {
-            new_hashes.push(compute_hash(&current_hashes[i], &current_hashes[i + 1]));
-        }
-
-        current_hashes = new_hashes;
-        transaction_position /= 2;
-    }
-
-    merkle_proof
+    MerkleTree::new(tx_hashes).map_or_else(Vec::new, |tree| {
+        tree.proof(transaction_position)
+            .expect("transaction position is out of range")
+    })
 }
 
 #[must_use]
@@ -108,6 +116,10 @@ mod tests {
             decode_hex("7c8708d1f517caf3082d95cf1f6ced11a009318338e720ecee58a2b4e643d56a");
         let calculated_merkle_root = merkle_root_calculator(&tx_hashes);
         assert_eq!(calculated_merkle_root, expected_merkle_root);
+        assert_eq!(
+            MerkleTree::new(tx_hashes).unwrap().root(),
+            expected_merkle_root
+        );
     }
 
     #[test]
@@ -0,0 +1,527 @@
+use btc_types::header::{deserialize_partial_header, Header};
+use btc_types::utils::DecodeHeaderError;
+
+use crate::encode::{write_compact_size, Reader, UnexpectedEnd};
+use crate::{compute_hash, H256};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MerkleBlockError {
+    InvalidLength,
+    TrailingBytes,
+    InvalidHeader(DecodeHeaderError),
+    NoTransactions,
+    TooManyHashes,
+    NotEnoughBits,
+    /// The traversal of the tree needs more bits or hashes than provided
+    Overflow,
+    /// Some bits or hashes are not used by the traversal of the tree
+    UnusedData,
+    /// The two children of a node are equal, see CVE-2012-2459
+    MutatedTree,
+    MerkleRootMismatch,
+}
+
+impl std::fmt::Display for MerkleBlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MerkleBlockError::InvalidLength => write!(f, "Merkle block is truncated"),
+            MerkleBlockError::TrailingBytes => write!(f, "Merkle block has trailing bytes"),
+            MerkleBlockError::InvalidHeader(err) => write!(f, "Invalid merkle block header: {err}"),
+            MerkleBlockError::NoTransactions => {
+                write!(f, "Partial merkle tree has no transactions")
+            }
+            MerkleBlockError::TooManyHashes => {
+                write!(f, "Partial merkle tree has more hashes than transactions")
+            }
+            MerkleBlockError::NotEnoughBits => {
+                write!(f, "Partial merkle tree has fewer flag bits than hashes")
+            }
+            MerkleBlockError::Overflow => write!(
+                f,
+                "Partial merkle tree traversal needs more flag bits or hashes than provided"
+            ),
+            MerkleBlockError::UnusedData => write!(
+                f,
+                "Partial merkle tree has flag bits or hashes not used by the traversal"
+            ),
+            MerkleBlockError::MutatedTree => {
+                write!(f, "Partial merkle tree has a node with two equal children")
+            }
+            MerkleBlockError::MerkleRootMismatch => write!(
+                f,
+                "Partial merkle tree root does not match the merkle root of the header"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MerkleBlockError {}
+
+impl From<UnexpectedEnd> for MerkleBlockError {
+    fn from(_: UnexpectedEnd) -> Self {
+        MerkleBlockError::InvalidLength
+    }
+}
+
+impl From<DecodeHeaderError> for MerkleBlockError {
+    fn from(err: DecodeHeaderError) -> Self {
+        MerkleBlockError::InvalidHeader(err)
+    }
+}
+
+/// Transactions matched by a partial merkle tree, with the root of the tree
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleMatches {
+    pub merkle_root: H256,
+    /// Indices of the matched transactions in the block with their txids, in block order
+    pub matches: Vec<(u32, H256)>,
+}
+
+/// Partial merkle tree of BIP37, which proves the inclusion of a subset of the transactions
+/// of a block. The tree is traversed depth-first: a flag bit tells for each node whether
+/// it is the parent of a matched transaction, and the hash of each node which is not
+/// traversed further (or of each leaf) is given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialMerkleTree {
+    tx_count: u32,
+    hashes: Vec<H256>,
+    bits: Vec<bool>,
+}
+
+impl PartialMerkleTree {
+    /// Builds the partial merkle tree of the block with `txids` proving the transactions
+    /// whose `matches` flag is set.
+    ///
+    /// # Panics
+    /// If `txids` and `matches` have different lengths or there are more than `u32::MAX` transactions.
+    #[must_use]
+    pub fn new(txids: &[H256], matches: &[bool]) -> Self {
+        assert_eq!(txids.len(), matches.len(), "one match flag per transaction");
+        let mut tree = Self {
+            tx_count: u32::try_from(txids.len()).expect("transaction count fits in u32"),
+            hashes: Vec::new(),
+            bits: Vec::new(),
+        };
+
+        let height = tree.height();
+        tree.traverse_and_build(height, 0, txids, matches);
+        tree
+    }
+
+    #[must_use]
+    pub fn tx_count(&self) -> u32 {
+        self.tx_count
+    }
+
+    #[must_use]
+    pub fn hashes(&self) -> &[H256] {
+        &self.hashes
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let flag_bytes = self.bits.len().div_ceil(8);
+        let mut out = Vec::with_capacity(4 + 9 + self.hashes.len() * 32 + 9 + flag_bytes);
+        out.extend(self.tx_count.to_le_bytes());
+        write_compact_size(&mut out, self.hashes.len());
+        for hash in &self.hashes {
+            out.extend(hash.0);
+        }
+        write_compact_size(&mut out, flag_bytes);
+        let mut flags = vec![0u8; flag_bytes];
+        for (i, bit) in self.bits.iter().enumerate() {
+            flags[i / 8] |= u8::from(*bit) << (i % 8);
+        }
+        out.extend(flags);
+        out
+    }
+
+    /// Decodes a consensus-serialized partial merkle tree that spans the whole input.
+    ///
+    /// # Errors
+    /// If the input is truncated or has trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleBlockError> {
+        let (tree, size) = Self::deserialize_partial(bytes)?;
+        if size != bytes.len() {
+            return Err(MerkleBlockError::TrailingBytes);
+        }
+        Ok(tree)
+    }
+
+    /// Decodes a consensus-serialized partial merkle tree at the start of the input,
+    /// returning it with the number of bytes read.
+    ///
+    /// # Errors
+    /// If the input is truncated.
+    pub fn deserialize_partial(bytes: &[u8]) -> Result<(Self, usize), MerkleBlockError> {
+        let mut reader = Reader::new(bytes);
+
+        let tx_count = u32::from_le_bytes(reader.read_array()?);
+        let hash_count = reader.read_compact_size()?;
+        if hash_count > reader.remaining() / 32 {
+            return Err(MerkleBlockError::InvalidLength);
+        }
+        let hashes = (0..hash_count)
+            .map(|_| reader.read_array().map(H256))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let flag_bytes = reader.read_compact_size()?;
+        let bits = reader
+            .read_slice(flag_bytes)?
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| byte & (1 << i) != 0))
+            .collect();
+
+        Ok((
+            Self {
+                tx_count,
+                hashes,
+                bits,
+            },
+            reader.offset(),
+        ))
+    }
+
+    /// Checks the partial merkle tree and extracts the matched transactions with the root
+    /// of the tree, as `CPartialMerkleTree::ExtractMatches` of Bitcoin Core.
+    ///
+    /// # Errors
+    /// If the tree is malformed or mutated.
+    pub fn extract_matches(&self) -> Result<MerkleMatches, MerkleBlockError> {
+        if self.tx_count == 0 {
+            return Err(MerkleBlockError::NoTransactions);
+        }
+        if u64::try_from(self.hashes.len()).unwrap() > u64::from(self.tx_count) {
+            return Err(MerkleBlockError::TooManyHashes);
+        }
+        if self.bits.len() < self.hashes.len() {
+            return Err(MerkleBlockError::NotEnoughBits);
+        }
+
+        let mut traversal = Traversal {
+            bits_used: 0,
+            hashes_used: 0,
+            matches: Vec::new(),
+        };
+        let merkle_root = self.traverse_and_extract(self.height(), 0, &mut traversal)?;
+
+        // All the hashes and all the flag bytes must be used
+        if traversal.bits_used.div_ceil(8) != self.bits.len().div_ceil(8)
+            || traversal.hashes_used != self.hashes.len()
+        {
+            return Err(MerkleBlockError::UnusedData);
+        }
+
+        Ok(MerkleMatches {
+            merkle_root,
+            matches: traversal.matches,
+        })
+    }
+
+    /// Number of nodes at the given height, the leaves being at height 0
+    fn width(&self, height: u32) -> u64 {
+        (u64::from(self.tx_count) + (1 << height) - 1) >> height
+    }
+
+    fn height(&self) -> u32 {
+        let mut height = 0;
+        while self.width(height) > 1 {
+            height += 1;
+        }
+        height
+    }
+
+    fn has_right_child(&self, height: u32, position: u64) -> bool {
+        position * 2 + 1 < self.width(height - 1)
+    }
+
+    fn calc_hash(&self, height: u32, position: u64, txids: &[H256]) -> H256 {
+        if height == 0 {
+            return txids[usize::try_from(position).unwrap()].clone();
+        }
+
+        let left = self.calc_hash(height - 1, position * 2, txids);
+        let right = if self.has_right_child(height, position) {
+            self.calc_hash(height - 1, position * 2 + 1, txids)
+        } else {
+            left.clone()
+        };
+        compute_hash(&left, &right)
+    }
+
+    fn traverse_and_build(&mut self, height: u32, position: u64, txids: &[H256], matches: &[bool]) {
+        let first_leaf = usize::try_from(position << height).unwrap();
+        let end_leaf = usize::try_from((position + 1) << height)
+            .unwrap()
+            .min(txids.len());
+        let is_parent_of_match = matches[first_leaf..end_leaf].iter().any(|m| *m);
+        self.bits.push(is_parent_of_match);
+
+        if height == 0 || !is_parent_of_match {
+            let hash = self.calc_hash(height, position, txids);
+            self.hashes.push(hash);
+        } else {
+            self.traverse_and_build(height - 1, position * 2, txids, matches);
+            if self.has_right_child(height, position) {
+                self.traverse_and_build(height - 1, position * 2 + 1, txids, matches);
+            }
+        }
+    }
+
+    fn traverse_and_extract(
+        &self,
+        height: u32,
+        position: u64,
+        traversal: &mut Traversal,
+    ) -> Result<H256, MerkleBlockError> {
+        let is_parent_of_match = *self
+            .bits
+            .get(traversal.bits_used)
+            .ok_or(MerkleBlockError::Overflow)?;
+        traversal.bits_used += 1;
+
+        if height == 0 || !is_parent_of_match {
+            let hash = self
+                .hashes
+                .get(traversal.hashes_used)
+                .ok_or(MerkleBlockError::Overflow)?
+                .clone();
+            traversal.hashes_used += 1;
+            if height == 0 && is_parent_of_match {
+                traversal
+                    .matches
+                    .push((u32::try_from(position).unwrap(), hash.clone()));
+            }
+            return Ok(hash);
+        }
+
+        let left = self.traverse_and_extract(height - 1, position * 2, traversal)?;
+        let right = if self.has_right_child(height, position) {
+            let right = self.traverse_and_extract(height - 1, position * 2 + 1, traversal)?;
+            if right == left {
+                return Err(MerkleBlockError::MutatedTree);
+            }
+            right
+        } else {
+            left.clone()
+        };
+        Ok(compute_hash(&left, &right))
+    }
+}
+
+struct Traversal {
+    bits_used: usize,
+    hashes_used: usize,
+    matches: Vec<(u32, H256)>,
+}
+
+/// Block header with a partial merkle tree of its transactions, as returned by
+/// `gettxoutproof` and sent in the `merkleblock` message of BIP37.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleBlock {
+    pub header: Header,
+    pub txn: PartialMerkleTree,
+}
+
+impl MerkleBlock {
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.header.to_block_header_vec();
+        out.extend(self.txn.to_bytes());
+        out
+    }
+
+    /// Decodes a consensus-serialized merkle block that spans the whole input.
+    ///
+    /// # Errors
+    /// If the header or the partial merkle tree cannot be decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleBlockError> {
+        Self::from_bytes_with_auxpow(bytes, false)
+    }
+
+    /// Same as [`Self::from_bytes`], but if `auxpow` is set, the AuxPoW following a header
+    /// with the AuxPoW version bit is skipped, as in the merkle blocks of the merge-mined chains.
+    /// The AuxPoW is not kept, so it is not serialized by [`Self::to_bytes`].
+    ///
+    /// # Errors
+    /// If the header or the partial merkle tree cannot be decoded.
+    pub fn from_bytes_with_auxpow(bytes: &[u8], auxpow: bool) -> Result<Self, MerkleBlockError> {
+        let (header, header_len) = deserialize_partial_header(bytes, auxpow)?;
+        let txn = PartialMerkleTree::from_bytes(&bytes[header_len..])?;
+        Ok(Self { header, txn })
+    }
+
+    /// Extracts the matched transactions, checking the root of the partial merkle tree
+    /// against the merkle root of the header.
+    ///
+    /// # Errors
+    /// If the partial merkle tree is malformed or does not match the header.
+    pub fn extract_matches(&self) -> Result<Vec<(u32, H256)>, MerkleBlockError> {
+        let matches = self.txn.extract_matches()?;
+        if matches.merkle_root != self.header.merkle_root {
+            return Err(MerkleBlockError::MerkleRootMismatch);
+        }
+        Ok(matches.matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::leaves;
+    use crate::MerkleTree;
+
+    fn decode_hex(hex: &str) -> Vec<u8> {
+        hex::decode(hex).unwrap()
+    }
+
+    #[test]
+    fn test_build_and_extract() {
+        for count in 1..=17u8 {
+            let txids = leaves(count);
+            let root = MerkleTree::new(txids.clone()).unwrap().root();
+
+            for step in 1..=4 {
+                let matches: Vec<bool> = (0..txids.len()).map(|i| i % step == 0).collect();
+                let tree = PartialMerkleTree::new(&txids, &matches);
+                let tree = PartialMerkleTree::from_bytes(&tree.to_bytes()).unwrap();
+
+                let extracted = tree.extract_matches().unwrap();
+                assert_eq!(extracted.merkle_root, root);
+                let expected: Vec<(u32, H256)> = (0..count)
+                    .filter(|i| matches[usize::from(*i)])
+                    .map(|i| (u32::from(i), txids[usize::from(i)].clone()))
+                    .collect();
+                assert_eq!(extracted.matches, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_matches() {
+        let txids = leaves(5);
+        let tree = PartialMerkleTree::new(&txids, &[false; 5]);
+        assert_eq!(tree.hashes(), &[MerkleTree::new(txids).unwrap().root()]);
+        assert_eq!(tree.extract_matches().unwrap().matches, Vec::new());
+    }
+
+    #[test]
+    fn test_malformed_tree() {
+        let txids = leaves(7);
+        let mut matches = [false; 7];
+        matches[3] = true;
+        let bytes = PartialMerkleTree::new(&txids, &matches).to_bytes();
+
+        assert_eq!(
+            PartialMerkleTree::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(MerkleBlockError::InvalidLength)
+        );
+        assert_eq!(
+            PartialMerkleTree::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+            Err(MerkleBlockError::TrailingBytes)
+        );
+
+        let tree = PartialMerkleTree::from_bytes(&bytes).unwrap();
+        let mut no_transactions = tree.clone();
+        no_transactions.tx_count = 0;
+        assert_eq!(
+            no_transactions.extract_matches(),
+            Err(MerkleBlockError::NoTransactions)
+        );
+
+        let mut extra_hash = tree.clone();
+        extra_hash.hashes.push(H256::default());
+        assert_eq!(
+            extra_hash.extract_matches(),
+            Err(MerkleBlockError::UnusedData)
+        );
+
+        let mut missing_hash = tree.clone();
+        missing_hash.hashes.pop();
+        assert_eq!(
+            missing_hash.extract_matches(),
+            Err(MerkleBlockError::Overflow)
+        );
+
+        let mut extra_flag_byte = tree;
+        extra_flag_byte.bits.extend([false; 8]);
+        assert_eq!(
+            extra_flag_byte.extract_matches(),
+            Err(MerkleBlockError::UnusedData)
+        );
+    }
+
+    #[test]
+    fn test_mutated_tree() {
+        // [0, 1, 2] and [0, 1, 2, 2] have the same merkle root
+        let mut txids = leaves(3);
+        txids.push(txids[2].clone());
+        let tree = PartialMerkleTree::new(&txids, &[false, false, false, true]);
+        assert_eq!(tree.extract_matches(), Err(MerkleBlockError::MutatedTree));
+    }
+
+    #[test]
+    fn test_mainnet_merkle_block() {
+        // Block 100000 with its 4 transactions
+        let header = Header::from_block_header_vec(&decode_hex(
+            "0100000050120119172a610421a6c3011dd330d9df07b63616c2cc1f1cd00200000000006657a9252aacd5c0b2940996ecff952228c3067cc38d4885efb5a4ac4247e9f337221b4d4c86041b0f2b5710",
+        ))
+        .unwrap();
+        let txids: Vec<H256> = [
+            "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+            "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+            "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+            "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+        ]
+        .iter()
+        .map(|txid| txid.parse().unwrap())
+        .collect();
+
+        let merkle_block = MerkleBlock {
+            header,
+            txn: PartialMerkleTree::new(&txids, &[true, false, true, false]),
+        };
+        let bytes = merkle_block.to_bytes();
+        assert_eq!(bytes.len(), 80 + 4 + 1 + 4 * 32 + 1 + 1);
+
+        // The decoded flag bits are padded to whole bytes
+        let decoded = MerkleBlock::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.header, merkle_block.header);
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(
+            decoded.extract_matches(),
+            Ok(vec![(0, txids[0].clone()), (2, txids[2].clone())])
+        );
+        assert_eq!(
+            MerkleBlock::from_bytes(&bytes[..80]),
+            Err(MerkleBlockError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_merkle_block_root_mismatch() {
+        let txids = leaves(4);
+        let header = Header {
+            version: 1,
+            prev_block_hash: H256::default(),
+            merkle_root: MerkleTree::new(txids.clone()).unwrap().root(),
+            time: 0,
+            bits: 0x1d00_ffff,
+            nonce: 0,
+        };
+        let mut merkle_block = MerkleBlock {
+            header,
+            txn: PartialMerkleTree::new(&txids, &[false, true, false, false]),
+        };
+
+        let decoded = MerkleBlock::from_bytes(&merkle_block.to_bytes()).unwrap();
+        assert_eq!(decoded.header, merkle_block.header);
+        assert_eq!(decoded.extract_matches(), Ok(vec![(1, txids[1].clone())]));
+
+        merkle_block.header.merkle_root = H256::default();
+        assert_eq!(
+            merkle_block.extract_matches(),
+            Err(MerkleBlockError::MerkleRootMismatch)
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{compute_hash, H256};
+
+/// Bitcoin merkle tree with all levels kept in memory, so any number of proofs
+/// can be generated without hashing the transactions again.
+///
+/// Levels are stored without the duplicated last node of odd levels,
+/// the duplication is applied when the sibling is looked up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    levels: Vec<Vec<H256>>,
+}
+
+impl MerkleTree {
+    /// Builds the tree from the leaves (transaction ids in block order).
+    /// Returns `None` if there are no leaves.
+    #[must_use]
+    pub fn new(leaves: Vec<H256>) -> Option<Self> {
+        if leaves.is_empty() {
+            return None;
+        }
+
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let level = &levels[levels.len() - 1];
+            let next_level = level
+                .chunks(2)
+                .map(|pair| compute_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(next_level);
+        }
+
+        Some(Self { levels })
+    }
+
+    #[must_use]
+    pub fn root(&self) -> H256 {
+        self.levels[self.levels.len() - 1][0].clone()
+    }
+
+    #[must_use]
+    pub fn leaves(&self) -> &[H256] {
+        &self.levels[0]
+    }
+
+    #[must_use]
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Number of hashes in the proof of every leaf
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Merkle proof of the leaf at `index`, in the format expected by
+    /// `compute_root_from_merkle_proof`. Returns `None` if `index` is out of range.
+    #[must_use]
+    pub fn proof(&self, index: usize) -> Option<Vec<H256>> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+
+        let mut position = index;
+        let mut merkle_proof = Vec::with_capacity(self.depth());
+        for level in &self.levels[..self.depth()] {
+            let sibling = level.get(position ^ 1).unwrap_or(&level[position]);
+            merkle_proof.push(sibling.clone());
+            position /= 2;
+        }
+
+        Some(merkle_proof)
+    }
+
+    /// Merkle proofs of the leaves at `indices`, in the same order.
+    /// Returns `None` if any index is out of range.
+    #[must_use]
+    pub fn proofs(&self, indices: &[usize]) -> Option<Vec<Vec<H256>>> {
+        indices.iter().map(|&index| self.proof(index)).collect()
+    }
+}
+
+/// Merkle root with the proofs of the selected leaves, sorted by leaf index
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleBranches {
+    pub root: H256,
+    pub proofs: Vec<(usize, Vec<H256>)>,
+}
+
+/// Computes the merkle root and the proofs of selected leaves in a single pass
+/// over the leaves. Only one pending node per level and the branches of the
+/// selected leaves are kept in memory, so the leaves can be streamed from a block
+/// without collecting them.
+#[derive(Clone, Debug, Default)]
+pub struct StreamingMerkleTree {
+    /// Number of nodes pushed on each level
+    counts: Vec<usize>,
+    /// Last node of each level that is still waiting for its right sibling
+    pending: Vec<Option<H256>>,
+    /// Selected leaf indices with their branches, sorted by index
+    selected: Vec<(usize, Vec<H256>)>,
+}
+
+impl StreamingMerkleTree {
+    /// Creates a builder which collects the proofs of the leaves at `selected_indices`.
+    #[must_use]
+    pub fn new(selected_indices: &[usize]) -> Self {
+        let mut indices = selected_indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        Self {
+            selected: indices
+                .into_iter()
+                .map(|index| (index, Vec::new()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Number of leaves pushed so far
+    #[must_use]
+    pub fn leaf_count(&self) -> usize {
+        self.counts.first().copied().unwrap_or(0)
+    }
+
+    pub fn push(&mut self, leaf: H256) {
+        self.push_node(0, leaf);
+    }
+
+    /// Returns the merkle root and the proofs of the selected leaves.
+    /// Returns `None` if no leaves were pushed or a selected index is out of range.
+    #[must_use]
+    pub fn finish(mut self) -> Option<MerkleBranches> {
+        let leaf_count = self.leaf_count();
+        if leaf_count == 0
+            || self
+                .selected
+                .last()
+                .is_some_and(|(index, _)| *index >= leaf_count)
+        {
+            return None;
+        }
+
+        // The top level is the first one left with a single node. Each level
+        // below it with an odd number of nodes pairs its last node with itself.
+        let mut level = 0;
+        while self.counts[level] > 1 {
+            if self.counts[level] % 2 == 1 {
+                let last = self.pending[level].clone()?;
+                self.push_node(level, last);
+            }
+            level += 1;
+        }
+
+        let root = self.pending[level].take()?;
+        Some(MerkleBranches {
+            root,
+            proofs: self.selected,
+        })
+    }
+
+    fn push_node(&mut self, level: usize, node: H256) {
+        if self.counts.len() == level {
+            self.counts.push(0);
+            self.pending.push(None);
+        }
+
+        let position = self.counts[level];
+        self.counts[level] += 1;
+
+        if position % 2 == 0 {
+            self.pending[level] = Some(node);
+            return;
+        }
+
+        let left = self.pending[level]
+            .take()
+            .expect("left sibling is pushed before the right one");
+        self.add_siblings(level, position - 1, &left, &node);
+        let parent = compute_hash(&left, &node);
+        self.push_node(level + 1, parent);
+    }
+
+    /// Appends the siblings to the branches of the selected leaves under
+    /// the pair of nodes starting at `left_position` on `level`.
+    fn add_siblings(&mut self, level: usize, left_position: usize, left: &H256, right: &H256) {
+        let first_leaf = left_position << level;
+        let middle_leaf = first_leaf + (1 << level);
+        let end_leaf = middle_leaf + (1 << level);
+
+        let start = self
+            .selected
+            .partition_point(|(index, _)| *index < first_leaf);
+        let end = self
+            .selected
+            .partition_point(|(index, _)| *index < end_leaf);
+        for (index, branch) in &mut self.selected[start..end] {
+            if *index < middle_leaf {
+                branch.push(right.clone());
+            } else {
+                branch.push(left.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_root_from_merkle_proof;
+    use crate::test_utils::leaves;
+
+    #[test]
+    fn test_empty_tree() {
+        assert_eq!(MerkleTree::new(Vec::new()), None);
+        assert_eq!(StreamingMerkleTree::new(&[]).finish(), None);
+    }
+
+    #[test]
+    fn test_single_leaf() {
+        let leaf = H256::from([1; 32]);
+        let tree = MerkleTree::new(vec![leaf.clone()]).unwrap();
+        assert_eq!(tree.root(), leaf);
+        assert_eq!(tree.depth(), 0);
+        assert_eq!(tree.proof(0), Some(Vec::new()));
+        assert_eq!(tree.proof(1), None);
+
+        let mut stream = StreamingMerkleTree::new(&[0]);
+        stream.push(leaf.clone());
+        assert_eq!(
+            stream.finish(),
+            Some(MerkleBranches {
+                root: leaf,
+                proofs: vec![(0, Vec::new())]
+            })
+        );
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+            let root = tree.root();
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.len(), tree.depth());
+                assert_eq!(
+                    compute_root_from_merkle_proof(leaf.clone(), index, &proof),
+                    root
+                );
+            }
+            assert_eq!(tree.proof(leaves.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_multi_index_proofs() {
+        let tree = MerkleTree::new(leaves(11)).unwrap();
+
+        let proofs = tree.proofs(&[10, 0, 5]).unwrap();
+        assert_eq!(
+            proofs,
+            vec![
+                tree.proof(10).unwrap(),
+                tree.proof(0).unwrap(),
+                tree.proof(5).unwrap()
+            ]
+        );
+        assert_eq!(tree.proofs(&[0, 11]), None);
+    }
+
+    #[test]
+    fn test_streaming_matches_tree() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+            let leaves_count = leaves.len();
+            let all_indices: Vec<usize> = (0..leaves_count).rev().collect();
+
+            let mut stream = StreamingMerkleTree::new(&all_indices);
+            for leaf in leaves {
+                stream.push(leaf);
+            }
+            let branches = stream.finish().unwrap();
+
+            assert_eq!(branches.root, tree.root());
+            assert_eq!(branches.proofs.len(), leaves_count);
+            for (index, proof) in branches.proofs {
+                assert_eq!(Some(proof), tree.proof(index));
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_selected_out_of_range() {
+        let mut stream = StreamingMerkleTree::new(&[1, 3, 3]);
+        for leaf in leaves(3) {
+            stream.push(leaf);
+        }
+        assert_eq!(stream.finish(), None);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{compute_hash, H256};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MerkleProofError {
+    ZeroTxCount,
+    IndexOutOfRange {
+        tx_index: u64,
+        tx_count: u64,
+    },
+    InvalidDepth {
+        expected: usize,
+        actual: usize,
+    },
+    /// The last node of a level with an odd number of nodes is not paired with itself
+    InvalidDuplicate {
+        level: usize,
+    },
+    /// A sibling equals the node outside of the odd-node duplication, which is the case
+    /// of the mutated trees of CVE-2012-2459
+    MutatedTree {
+        level: usize,
+    },
+}
+
+impl std::fmt::Display for MerkleProofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MerkleProofError::ZeroTxCount => write!(f, "Transaction count must be positive"),
+            MerkleProofError::IndexOutOfRange { tx_index, tx_count } => write!(
+                f,
+                "Transaction index {tx_index} exceeds the number of transactions {tx_count}"
+            ),
+            MerkleProofError::InvalidDepth { expected, actual } => write!(
+                f,
+                "Merkle proof length {actual} does not match the tree depth {expected}"
+            ),
+            MerkleProofError::InvalidDuplicate { level } => write!(
+                f,
+                "Merkle proof sibling at level {level} must duplicate the last node"
+            ),
+            MerkleProofError::MutatedTree { level } => write!(
+                f,
+                "Merkle proof sibling at level {level} equals the node, the tree is mutated"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MerkleProofError {}
+
+/// Depth of the merkle tree of a block with `tx_count` transactions
+#[must_use]
+pub fn merkle_tree_depth(tx_count: u64) -> usize {
+    usize::try_from(u64::BITS - tx_count.saturating_sub(1).leading_zeros()).unwrap()
+}
+
+/// Same as `compute_root_from_merkle_proof`, but checks that the proof matches the shape
+/// of the merkle tree of a block with `tx_count` transactions: the index is in range, the
+/// proof has the depth of the tree, the last node of an odd level is paired with itself
+/// and no other sibling equals its node (CVE-2012-2459).
+///
+/// # Errors
+/// If the proof does not match the shape of the tree.
+pub fn compute_root_from_checked_merkle_proof(
+    transaction_hash: H256,
+    transaction_position: u64,
+    tx_count: u64,
+    merkle_proof: &[H256],
+) -> Result<H256, MerkleProofError> {
+    if tx_count == 0 {
+        return Err(MerkleProofError::ZeroTxCount);
+    }
+    if transaction_position >= tx_count {
+        return Err(MerkleProofError::IndexOutOfRange {
+            tx_index: transaction_position,
+            tx_count,
+        });
+    }
+    let depth = merkle_tree_depth(tx_count);
+    if merkle_proof.len() != depth {
+        return Err(MerkleProofError::InvalidDepth {
+            expected: depth,
+            actual: merkle_proof.len(),
+        });
+    }
+
+    let mut current_hash = transaction_hash;
+    let mut current_position = transaction_position;
+    let mut level_size = tx_count;
+
+    for (level, proof_hash) in merkle_proof.iter().enumerate() {
+        let is_duplicated = current_position == level_size - 1 && current_position % 2 == 0;
+        if is_duplicated && proof_hash != &current_hash {
+            return Err(MerkleProofError::InvalidDuplicate { level });
+        }
+        if !is_duplicated && proof_hash == &current_hash {
+            return Err(MerkleProofError::MutatedTree { level });
+        }
+
+        if current_position % 2 == 0 {
+            current_hash = compute_hash(&current_hash, proof_hash);
+        } else {
+            current_hash = compute_hash(proof_hash, &current_hash);
+        }
+        current_position /= 2;
+        level_size = level_size.div_ceil(2);
+    }
+
+    Ok(current_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::leaves;
+    use crate::MerkleTree;
+
+    #[test]
+    fn test_merkle_tree_depth() {
+        assert_eq!(merkle_tree_depth(1), 0);
+        assert_eq!(merkle_tree_depth(2), 1);
+        assert_eq!(merkle_tree_depth(3), 2);
+        assert_eq!(merkle_tree_depth(4), 2);
+        assert_eq!(merkle_tree_depth(5), 3);
+        assert_eq!(merkle_tree_depth(4096), 12);
+    }
+
+    #[test]
+    fn test_valid_proofs() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+            for (index, leaf) in leaves.into_iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(
+                    compute_root_from_checked_merkle_proof(
+                        leaf,
+                        index.try_into().unwrap(),
+                        count.into(),
+                        &proof
+                    ),
+                    Ok(tree.root())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_shape() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let proof = tree.proof(4).unwrap();
+
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[4].clone(), 4, 0, &proof),
+            Err(MerkleProofError::ZeroTxCount)
+        );
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[4].clone(), 5, 5, &proof),
+            Err(MerkleProofError::IndexOutOfRange {
+                tx_index: 5,
+                tx_count: 5
+            })
+        );
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[4].clone(), 4, 5, &proof[..2]),
+            Err(MerkleProofError::InvalidDepth {
+                expected: 3,
+                actual: 2
+            })
+        );
+
+        // The unchecked proof of a position beyond the leaves gives the same root
+        let mut shifted_proof = proof.clone();
+        shifted_proof[0] = leaves[4].clone();
+        assert_eq!(
+            crate::compute_root_from_merkle_proof(leaves[4].clone(), 5, &shifted_proof),
+            tree.root()
+        );
+
+        let mut wrong_duplicate = proof;
+        wrong_duplicate[0] = leaves[0].clone();
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[4].clone(), 4, 5, &wrong_duplicate),
+            Err(MerkleProofError::InvalidDuplicate { level: 0 })
+        );
+    }
+
+    #[test]
+    fn test_mutated_tree() {
+        // [0, 1, 2] and [0, 1, 2, 2] have the same merkle root
+        let leaves = leaves(3);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let mut mutated_leaves = leaves.clone();
+        mutated_leaves.push(leaves[2].clone());
+        let mutated_tree = MerkleTree::new(mutated_leaves).unwrap();
+        assert_eq!(tree.root(), mutated_tree.root());
+
+        let proof = mutated_tree.proof(3).unwrap();
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[2].clone(), 2, 3, &proof),
+            Ok(tree.root())
+        );
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[2].clone(), 3, 4, &proof),
+            Err(MerkleProofError::MutatedTree { level: 0 })
+        );
+
+        // [0..6] and [0..6, 4, 5] have the same merkle root, mutated at level 1
+        let leaves = self::leaves(6);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let mut mutated_leaves = leaves.clone();
+        mutated_leaves.extend_from_slice(&leaves[4..]);
+        let mutated_tree = MerkleTree::new(mutated_leaves).unwrap();
+        assert_eq!(tree.root(), mutated_tree.root());
+
+        let proof = mutated_tree.proof(6).unwrap();
+        assert_eq!(
+            compute_root_from_checked_merkle_proof(leaves[4].clone(), 6, 8, &proof),
+            Err(MerkleProofError::MutatedTree { level: 1 })
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::encode::{Reader, UnexpectedEnd};
+use crate::{double_sha256, MerkleTree, H256};
+
+/// Start of the script of the coinbase output committing to the witness merkle root:
+/// `OP_RETURN`, a push of 36 bytes and the commitment header `0xaa21a9ed`, see BIP141
+pub const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+
+/// Length of the script of the witness commitment output, without extra data
+const WITNESS_COMMITMENT_SCRIPT_LEN: usize = WITNESS_COMMITMENT_HEADER.len() + 32;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessCommitmentError {
+    InvalidTransaction,
+    NotCoinbase,
+    /// The coinbase transaction is not serialized with its witness
+    NoWitness,
+    InvalidWitnessReservedValue,
+    NoCommitment,
+    CommitmentMismatch,
+}
+
+impl std::fmt::Display for WitnessCommitmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WitnessCommitmentError::InvalidTransaction => {
+                write!(f, "Coinbase transaction cannot be decoded")
+            }
+            WitnessCommitmentError::NotCoinbase => write!(f, "Transaction is not a coinbase"),
+            WitnessCommitmentError::NoWitness => {
+                write!(f, "Coinbase transaction is not serialized with its witness")
+            }
+            WitnessCommitmentError::InvalidWitnessReservedValue => write!(
+                f,
+                "Coinbase witness must be a single 32-byte witness reserved value"
+            ),
+            WitnessCommitmentError::NoCommitment => {
+                write!(f, "Coinbase transaction has no witness commitment")
+            }
+            WitnessCommitmentError::CommitmentMismatch => write!(
+                f,
+                "Witness commitment does not match the witness merkle root"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WitnessCommitmentError {}
+
+impl From<UnexpectedEnd> for WitnessCommitmentError {
+    fn from(_: UnexpectedEnd) -> Self {
+        WitnessCommitmentError::InvalidTransaction
+    }
+}
+
+/// Witness commitment of a segwit coinbase transaction
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoinbaseWitnessCommitment {
+    /// Txid of the coinbase transaction, the hash of its serialization without witness
+    pub txid: H256,
+    pub commitment: H256,
+    pub witness_reserved_value: H256,
+}
+
+/// Builds the witness merkle tree of a block from the wtxids of its transactions.
+/// The wtxid of the coinbase transaction is replaced by zero.
+/// Returns `None` if there are no transactions.
+#[must_use]
+pub fn witness_merkle_tree(mut wtxids: Vec<H256>) -> Option<MerkleTree> {
+    if let Some(coinbase_wtxid) = wtxids.first_mut() {
+        *coinbase_wtxid = H256::default();
+    }
+    MerkleTree::new(wtxids)
+}
+
+/// Computes the witness merkle root of a block from the wtxids of its transactions.
+/// The wtxid of the coinbase transaction is replaced by zero.
+/// Returns `None` if there are no transactions.
+#[must_use]
+pub fn witness_merkle_root(wtxids: Vec<H256>) -> Option<H256> {
+    witness_merkle_tree(wtxids).map(|tree| tree.root())
+}
+
+/// Computes the witness commitment of the coinbase transaction,
+/// `SHA256d(witness_merkle_root || witness_reserved_value)`
+#[must_use]
+pub fn compute_witness_commitment(
+    witness_merkle_root: &H256,
+    witness_reserved_value: &H256,
+) -> H256 {
+    let mut concat_inputs = Vec::with_capacity(64);
+    concat_inputs.extend(witness_merkle_root.0);
+    concat_inputs.extend(witness_reserved_value.0);
+
+    double_sha256(&concat_inputs)
+}
+
+/// Extracts the witness commitment from a consensus-serialized coinbase transaction with
+/// its witness. If several outputs have a commitment, the last one is used, as in BIP141.
+///
+/// # Errors
+/// If the transaction is not a segwit coinbase transaction with a witness commitment.
+pub fn extract_witness_commitment(
+    coinbase_tx: &[u8],
+) -> Result<CoinbaseWitnessCommitment, WitnessCommitmentError> {
+    let mut reader = Reader::new(coinbase_tx);
+
+    let version = reader.read_array::<4>()?;
+    if reader.read_array::<2>()? != [0x00, 0x01] {
+        return Err(WitnessCommitmentError::NoWitness);
+    }
+    let inputs_start = reader.offset();
+
+    if reader.read_compact_size()? != 1 {
+        return Err(WitnessCommitmentError::NotCoinbase);
+    }
+    let prev_txid = reader.read_array::<32>()?;
+    let prev_vout = reader.read_array::<4>()?;
+    if prev_txid != [0; 32] || prev_vout != [0xff; 4] {
+        return Err(WitnessCommitmentError::NotCoinbase);
+    }
+    reader.read_var_slice()?; // script_sig
+    reader.read_array::<4>()?; // sequence
+
+    let mut commitment = None;
+    for _ in 0..reader.read_compact_size()? {
+        reader.read_array::<8>()?; // value
+        let script_pubkey = reader.read_var_slice()?;
+        if script_pubkey.len() >= WITNESS_COMMITMENT_SCRIPT_LEN
+            && script_pubkey.starts_with(&WITNESS_COMMITMENT_HEADER)
+        {
+            commitment = Some(H256::try_from(
+                &script_pubkey[WITNESS_COMMITMENT_HEADER.len()..WITNESS_COMMITMENT_SCRIPT_LEN],
+            ));
+        }
+    }
+    let inputs_end = reader.offset();
+
+    if reader.read_compact_size()? != 1 {
+        return Err(WitnessCommitmentError::InvalidWitnessReservedValue);
+    }
+    let witness_reserved_value = H256::try_from(reader.read_var_slice()?)
+        .map_err(|_| WitnessCommitmentError::InvalidWitnessReservedValue)?;
+
+    let lock_time = reader.read_array::<4>()?;
+    if reader.remaining() != 0 {
+        return Err(WitnessCommitmentError::InvalidTransaction);
+    }
+
+    let commitment = commitment
+        .ok_or(WitnessCommitmentError::NoCommitment)?
+        .map_err(|_| WitnessCommitmentError::InvalidTransaction)?;
+
+    let mut tx_without_witness = Vec::with_capacity(inputs_end - inputs_start + 8);
+    tx_without_witness.extend(version);
+    tx_without_witness.extend(&coinbase_tx[inputs_start..inputs_end]);
+    tx_without_witness.extend(lock_time);
+
+    Ok(CoinbaseWitnessCommitment {
+        txid: double_sha256(&tx_without_witness),
+        commitment,
+        witness_reserved_value,
+    })
+}
+
+/// Checks that the witness commitment of a coinbase transaction commits to the witness
+/// merkle root, and returns the extracted commitment.
+///
+/// # Errors
+/// If the commitment cannot be extracted or does not match the witness merkle root.
+pub fn verify_witness_commitment(
+    coinbase_tx: &[u8],
+    witness_merkle_root: &H256,
+) -> Result<CoinbaseWitnessCommitment, WitnessCommitmentError> {
+    let commitment = extract_witness_commitment(coinbase_tx)?;
+    if compute_witness_commitment(witness_merkle_root, &commitment.witness_reserved_value)
+        != commitment.commitment
+    {
+        return Err(WitnessCommitmentError::CommitmentMismatch);
+    }
+    Ok(commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_root_from_merkle_proof;
+    use crate::test_utils::leaves;
+
+    fn output(script_pubkey: &[u8]) -> Vec<u8> {
+        let mut output = 0u64.to_le_bytes().to_vec();
+        output.push(u8::try_from(script_pubkey.len()).unwrap());
+        output.extend(script_pubkey);
+        output
+    }
+
+    fn commitment_script(commitment: &H256) -> Vec<u8> {
+        [WITNESS_COMMITMENT_HEADER.as_slice(), &commitment.0].concat()
+    }
+
+    /// Returns a coinbase transaction with and without its witness
+    fn coinbase_tx(outputs: &[Vec<u8>], witness: &[&[u8]]) -> (Vec<u8>, Vec<u8>) {
+        let mut inputs_and_outputs = vec![1];
+        inputs_and_outputs.extend([0; 32]);
+        inputs_and_outputs.extend([0xff; 4]);
+        inputs_and_outputs.extend([3, 0x03, 0xa0, 0x86]);
+        inputs_and_outputs.extend([0xff; 4]);
+        inputs_and_outputs.push(u8::try_from(outputs.len()).unwrap());
+        for output in outputs {
+            inputs_and_outputs.extend(output);
+        }
+
+        let mut witness_bytes = vec![u8::try_from(witness.len()).unwrap()];
+        for item in witness {
+            witness_bytes.push(u8::try_from(item.len()).unwrap());
+            witness_bytes.extend(*item);
+        }
+
+        let version = 2u32.to_le_bytes();
+        let lock_time = [0; 4];
+        (
+            [
+                version.as_slice(),
+                &[0x00, 0x01],
+                &inputs_and_outputs,
+                &witness_bytes,
+                &lock_time,
+            ]
+            .concat(),
+            [version.as_slice(), &inputs_and_outputs, &lock_time].concat(),
+        )
+    }
+
+    #[test]
+    fn test_witness_merkle_root() {
+        assert_eq!(witness_merkle_root(Vec::new()), None);
+        assert_eq!(witness_merkle_root(leaves(1)), Some(H256::default()));
+
+        let wtxids = leaves(5);
+        let mut leaves = wtxids.clone();
+        leaves[0] = H256::default();
+        let tree = witness_merkle_tree(wtxids.clone()).unwrap();
+        assert_eq!(tree.root(), MerkleTree::new(leaves).unwrap().root());
+        assert_eq!(witness_merkle_root(wtxids.clone()), Some(tree.root()));
+
+        let proof = tree.proof(3).unwrap();
+        assert_eq!(
+            compute_root_from_merkle_proof(wtxids[3].clone(), 3, &proof),
+            tree.root()
+        );
+    }
+
+    #[test]
+    fn test_verify_witness_commitment() {
+        let witness_merkle_root = witness_merkle_root(leaves(4)).unwrap();
+        let witness_reserved_value = H256::from([0x42; 32]);
+        let commitment = compute_witness_commitment(&witness_merkle_root, &witness_reserved_value);
+
+        let (tx, tx_without_witness) = coinbase_tx(
+            &[
+                output(&[0x51]),
+                output(&commitment_script(&H256::default())),
+                output(&commitment_script(&commitment)),
+            ],
+            &[&witness_reserved_value.0],
+        );
+
+        let expected = CoinbaseWitnessCommitment {
+            txid: double_sha256(&tx_without_witness),
+            commitment,
+            witness_reserved_value,
+        };
+        assert_eq!(extract_witness_commitment(&tx), Ok(expected.clone()));
+        assert_eq!(
+            verify_witness_commitment(&tx, &witness_merkle_root),
+            Ok(expected)
+        );
+        assert_eq!(
+            verify_witness_commitment(&tx, &H256::default()),
+            Err(WitnessCommitmentError::CommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn test_invalid_coinbase() {
+        let reserved_value = [0; 32];
+        let commitment = output(&commitment_script(&H256::default()));
+
+        let (tx, tx_without_witness) = coinbase_tx(&[output(&[0x51])], &[&reserved_value]);
+        assert_eq!(
+            extract_witness_commitment(&tx),
+            Err(WitnessCommitmentError::NoCommitment)
+        );
+        assert_eq!(
+            extract_witness_commitment(&tx_without_witness),
+            Err(WitnessCommitmentError::NoWitness)
+        );
+
+        let (tx, _) = coinbase_tx(&[commitment.clone()], &[&reserved_value, &[]]);
+        assert_eq!(
+            extract_witness_commitment(&tx),
+            Err(WitnessCommitmentError::InvalidWitnessReservedValue)
+        );
+
+        let (tx, _) = coinbase_tx(&[commitment.clone()], &[&reserved_value[..31]]);
+        assert_eq!(
+            extract_witness_commitment(&tx),
+            Err(WitnessCommitmentError::InvalidWitnessReservedValue)
+        );
+
+        let (mut tx, _) = coinbase_tx(&[commitment], &[&reserved_value]);
+        assert_eq!(
+            extract_witness_commitment(&tx[..tx.len() - 1]),
+            Err(WitnessCommitmentError::InvalidTransaction)
+        );
+
+        // Spends an output instead of the null outpoint
+        tx[7] = 1;
+        assert_eq!(
+            extract_witness_commitment(&tx),
+            Err(WitnessCommitmentError::NotCoinbase)
+        );
+    }
+}
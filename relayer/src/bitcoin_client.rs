@@ -1,33 +1,15 @@
-#[cfg(not(feature = "zcash"))]
-use bitcoin::consensus::{encode, serialize};
-#[cfg(not(feature = "zcash"))]
-use bitcoin::Transaction;
-use bitcoin::TxMerkleNode;
-#[cfg(not(feature = "zcash"))]
-use bitcoincore_rpc::bitcoin::block::Header as BitcoinHeader;
-use bitcoincore_rpc::bitcoin::hashes::Hash;
 use bitcoincore_rpc::bitcoin::BlockHash;
 use bitcoincore_rpc::jsonrpc::minreq_http::HttpError;
 use bitcoincore_rpc::jsonrpc::Transport;
 use bitcoincore_rpc::{jsonrpc, RpcApi};
+use btc_types::aux::AuxData;
 use btc_types::header::Header;
+use btc_types::u256::U256;
 use jsonrpc::{Request, Response};
 use std::error::Error;
 
 use crate::config::Config;
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct AuxData {
-    pub(crate) coinbase_tx: bitcoin::Transaction,
-    pub(crate) parent_block_hash: BlockHash,
-    pub(crate) merkle_branch: Vec<TxMerkleNode>,
-    pub(crate) merkle_index: u32,
-    pub(crate) chainmerkle_branch: Vec<TxMerkleNode>,
-    pub(crate) chain_index: u32,
-    pub(crate) parent_block: Header,
-}
-
 struct CustomMinreqHttpTransport {
     url: String,
     timeout: std::time::Duration,
@@ -156,8 +138,27 @@ impl Client {
             "getblockheader",
             &[serde_json::to_value(block_hash)?, false.into()],
         )?;
-        let decoded_hex = hex::decode(hex)?;
-        Ok(Header::from_block_header_vec(&decoded_hex)?)
+        Ok(hex.parse::<Header>()?)
+    }
+
+    /// Get the cumulative chain work of a block, as reported by the node
+    ///
+    /// # Errors
+    /// * issue with connection to the Bitcoin Node
+    /// * no valid `chainwork` in the node response
+    pub fn get_block_chain_work(
+        &self,
+        block_hash: &BlockHash,
+    ) -> Result<U256, Box<dyn std::error::Error + Send + Sync>> {
+        let header_info: serde_json::Value = self.inner.call(
+            "getblockheader",
+            &[serde_json::to_value(block_hash)?, true.into()],
+        )?;
+        let chain_work = header_info["chainwork"]
+            .as_str()
+            .ok_or("no chainwork in the getblockheader response")?;
+
+        Ok(U256::from_hex(chain_work)?)
     }
 
     #[cfg(feature = "zcash")]
@@ -180,50 +181,16 @@ impl Client {
         let hex: String = self
             .inner
             .call("getblockheader", &[into_json(block_hash)?, false.into()])?;
-        if hex.len() == 160 {
-            let decoded_hex = hex::decode(hex)?;
-            let block1: Header = Header::from_block_header_vec(&decoded_hex)?;
-            return Ok((block1, None));
+        if hex.len() == 2 * Header::SIZE {
+            return Ok((hex.parse::<Header>()?, None));
         }
         let data_bytes = hex::decode(&hex)?;
-        let mut cursor = 0;
-        let (block1, readed_len): (BitcoinHeader, usize) =
-            encode::deserialize_partial(&data_bytes)?;
-        cursor += readed_len;
-        let (coinbase_tx, readed_len): (Transaction, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        cursor += readed_len;
-        let (parent_block_hash, readed_len): (BlockHash, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        cursor += readed_len;
-        let (merkle_branch, readed_len): (Vec<TxMerkleNode>, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        cursor += readed_len;
-        let (merkle_index, readed_len): (u32, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        cursor += readed_len;
-        let (chainmerkle_branch, readed_len): (Vec<TxMerkleNode>, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        cursor += readed_len;
-        let (chain_index, readed_len): (u32, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        cursor += readed_len;
-        let (parent_block, _readed_len): (BitcoinHeader, usize) =
-            encode::deserialize_partial(&data_bytes[cursor..])?;
-        let parent_block: Header = Header::from_block_header_vec(&serialize(&parent_block))?;
-
-        let aux_data = AuxData {
-            coinbase_tx: coinbase_tx.clone(),
-            parent_block_hash,
-            merkle_branch: merkle_branch.clone(),
-            merkle_index,
-            chainmerkle_branch: chainmerkle_branch.clone(),
-            chain_index,
-            parent_block,
-        };
-
-        let block1: Header = Header::from_block_header_vec(&serialize(&block1))?;
-        Ok((block1, Some(aux_data)))
+        let (header_bytes, aux_bytes) = data_bytes
+            .split_at_checked(Header::SIZE)
+            .ok_or("block header is too short")?;
+        let header = Header::from_block_header_vec(header_bytes)?;
+        let aux_data = AuxData::from_bytes(aux_bytes)?;
+        Ok((header, Some(aux_data)))
     }
 
     /// Get block header by bock height
@@ -262,21 +229,6 @@ impl Client {
         let block_hash = self.get_block_hash(height)?;
         self.get_block(&block_hash)
     }
-
-    #[must_use]
-    #[allow(dead_code)]
-    pub fn compute_merkle_proof(
-        block: &bitcoincore_rpc::bitcoin::Block,
-        transaction_position: usize,
-    ) -> Vec<merkle_tools::H256> {
-        let transactions = block
-            .txdata
-            .iter()
-            .map(|tx| tx.compute_txid().to_byte_array().into())
-            .collect();
-
-        merkle_tools::merkle_proof_calculator(transactions, transaction_position)
-    }
 }
 
 #[cfg(not(feature = "zcash"))]
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
-use btc_types::network::Network;
+use btc_types::network::{Chain, Network};
+use btc_types::u256::U256;
 use config::{Config as ConfigBuilder, Environment, File};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -54,6 +55,18 @@ pub struct InitConfig {
     pub gc_threshold: u64,
     pub skip_pow_verification: bool,
     pub init_height: u64,
+    /// Initialize the contract with the `chainwork` the node reports for the first block
+    #[serde(default)]
+    pub use_node_chain_work: bool,
+    /// Minimum chain work of the initial chain tip, as a hex string
+    #[serde(default, with = "btc_types::utils::serd_u256_hex_option")]
+    pub min_chain_work: Option<U256>,
+    /// Challenge script of a custom signet, as a hex string
+    #[serde(default, with = "btc_types::utils::serd_bytes_hex_option")]
+    pub signet_challenge: Option<Vec<u8>>,
+    /// Chain to follow, required by a contract built with several chains
+    #[serde(default)]
+    pub chain: Option<Chain>,
 }
 
 mod defaults {
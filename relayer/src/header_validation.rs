@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use btc_types::hash::H256;
+use btc_types::header::{ExtendedHeader, Header, LightHeader};
+use btc_types::network::{Chain, Network};
+use btc_types::utils::work_from_bits;
+use btc_types::validation::{get_recent_timestamps, BlocksGetter, ValidationError};
+#[cfg(feature = "zcash")]
+use btc_types::{network::get_zcash_config, validation::zcash};
+#[cfg(not(feature = "zcash"))]
+use btc_types::{
+    network::{
+        get_bitcoin_config, get_bitcoincash_config, get_dash_config, get_dogecoin_config,
+        get_litecoin_config, get_namecoin_config,
+    },
+    validation::{bitcoin, bitcoincash, dash, dogecoin, litecoin, namecoin},
+};
+
+/// Headers known to the relayer: the last header of the contract and the fetched ones
+/// following it
+struct FetchedHeaders {
+    headers: BTreeMap<H256, ExtendedHeader>,
+    height_to_hash: BTreeMap<u64, H256>,
+}
+
+impl FetchedHeaders {
+    fn new(last_header: ExtendedHeader) -> Self {
+        let mut fetched_headers = Self {
+            headers: BTreeMap::new(),
+            height_to_hash: BTreeMap::new(),
+        };
+        fetched_headers.insert(last_header);
+        fetched_headers
+    }
+
+    fn insert(&mut self, header: ExtendedHeader) {
+        self.height_to_hash
+            .insert(header.block_height, header.block_hash.clone());
+        self.headers.insert(header.block_hash.clone(), header);
+    }
+}
+
+impl BlocksGetter for FetchedHeaders {
+    fn find_prev_header(&self, current_header: &LightHeader) -> Option<ExtendedHeader> {
+        self.headers.get(&current_header.prev_block_hash).cloned()
+    }
+
+    fn find_header_by_height(&self, height: u64) -> Option<ExtendedHeader> {
+        self.height_to_hash
+            .get(&height)
+            .and_then(|hash| self.headers.get(hash))
+            .cloned()
+    }
+}
+
+/// Checks the headers following `last_header` with the same rules as the contract, except
+/// the proof of work hash, so that the batch is not submitted if the contract would reject it.
+/// The checks stop at the first header which needs a header that is not fetched, e.g. the first
+/// block of the difficulty adjustment period: the contract checks the remaining ones.
+///
+/// # Errors
+/// The index of the first invalid header and the rule it breaks
+pub fn check_headers<'a>(
+    chain: Chain,
+    network: Network,
+    last_header: ExtendedHeader,
+    headers: impl IntoIterator<Item = &'a Header>,
+    current_time: u32,
+) -> Result<(), (usize, ValidationError)> {
+    let mut fetched_headers = FetchedHeaders::new(last_header.clone());
+    let mut prev_block_header = last_header;
+
+    for (index, header) in headers.into_iter().enumerate() {
+        match check_header(
+            chain,
+            network,
+            header,
+            &prev_block_header,
+            current_time,
+            &fetched_headers,
+        ) {
+            Ok(()) => {}
+            Err(ValidationError::PrevBlockNotFound | ValidationError::HeaderNotFound { .. }) => {
+                return Ok(())
+            }
+            Err(err) => return Err((index, err)),
+        }
+
+        let Some(chain_work) = prev_block_header
+            .chain_work
+            .checked_add(work_from_bits(header.bits))
+        else {
+            return Err((index, ValidationError::ChainWorkOverflow));
+        };
+        let current_header = ExtendedHeader {
+            block_header: header.clone().into_light(),
+            block_hash: header.block_hash(),
+            chain_work,
+            block_height: prev_block_header.block_height + 1,
+            recent_timestamps: get_recent_timestamps(
+                header.time,
+                &prev_block_header,
+                &fetched_headers,
+            ),
+        };
+        fetched_headers.insert(current_header.clone());
+        prev_block_header = current_header;
+    }
+
+    Ok(())
+}
+
+fn check_header(
+    chain: Chain,
+    network: Network,
+    header: &Header,
+    prev_block_header: &ExtendedHeader,
+    current_time: u32,
+    blocks_getter: &FetchedHeaders,
+) -> Result<(), ValidationError> {
+    if header.prev_block_hash != prev_block_header.block_hash {
+        return Err(ValidationError::PrevBlockNotFound);
+    }
+
+    match chain {
+        #[cfg(not(feature = "zcash"))]
+        Chain::Bitcoin => bitcoin::check_header(
+            &get_bitcoin_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        #[cfg(not(feature = "zcash"))]
+        Chain::Litecoin => litecoin::check_header(
+            &get_litecoin_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        #[cfg(not(feature = "zcash"))]
+        Chain::Dogecoin => dogecoin::check_header(
+            &get_dogecoin_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        #[cfg(not(feature = "zcash"))]
+        Chain::Namecoin => namecoin::check_header(
+            &get_namecoin_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        #[cfg(not(feature = "zcash"))]
+        Chain::BitcoinCash => bitcoincash::check_header(
+            &get_bitcoincash_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        #[cfg(not(feature = "zcash"))]
+        Chain::Dash => dash::check_header(
+            &get_dash_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        #[cfg(feature = "zcash")]
+        Chain::Zcash => zcash::check_header(
+            &get_zcash_config(network),
+            header,
+            prev_block_header,
+            current_time,
+            blocks_getter,
+        ),
+        // The headers of the chain are decoded differently by this build, the contract checks them
+        #[allow(unreachable_patterns)]
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "zcash"))]
+mod tests {
+    use super::*;
+    use btc_types::network::MEDIAN_TIME_SPAN;
+
+    const FIRST_HEIGHT: u64 = 685_440;
+
+    fn mainnet_headers() -> Vec<Header> {
+        let headers: Vec<Vec<Header>> = serde_json::from_str(include_str!(
+            "../../contract/tests/data/blocks_headers_685440-687456_mainnet.json"
+        ))
+        .unwrap();
+        headers.into_iter().flatten().collect()
+    }
+
+    /// The last header of the contract, with the timestamps of its ancestors cached
+    fn last_header(headers: &[Header], index: usize) -> ExtendedHeader {
+        let header = headers[index].clone();
+        ExtendedHeader {
+            block_hash: header.block_hash(),
+            chain_work: work_from_bits(header.bits),
+            block_height: FIRST_HEIGHT + u64::try_from(index).unwrap(),
+            recent_timestamps: headers[index + 1 - MEDIAN_TIME_SPAN..=index]
+                .iter()
+                .rev()
+                .map(|header| header.time)
+                .collect(),
+            block_header: header.into_light(),
+        }
+    }
+
+    fn check_mainnet_headers(
+        headers: &[Header],
+        last_index: usize,
+        count: usize,
+    ) -> Result<(), (usize, ValidationError)> {
+        check_headers(
+            Chain::Bitcoin,
+            Network::Mainnet,
+            last_header(headers, last_index),
+            &headers[last_index + 1..=last_index + count],
+            headers[last_index + count].time,
+        )
+    }
+
+    #[test]
+    fn test_valid_headers() {
+        let headers = mainnet_headers();
+
+        assert_eq!(check_mainnet_headers(&headers, 1000, 100), Ok(()));
+    }
+
+    #[test]
+    fn test_stop_at_unknown_ancestor() {
+        let mut headers = mainnet_headers();
+        // The first header of the period needs the one 2016 blocks before, which is not fetched
+        headers[2017].bits = 0;
+
+        assert_eq!(check_mainnet_headers(&headers, 2000, 30), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let mut headers = mainnet_headers();
+        headers[1010].bits = headers[1010].bits.wrapping_add(1);
+
+        assert_eq!(
+            check_mainnet_headers(&headers, 1000, 100),
+            Err((
+                9,
+                ValidationError::BadDiffBits {
+                    expected: headers[1009].bits,
+                    actual: headers[1010].bits,
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_fork_is_not_checked() {
+        let mut headers = mainnet_headers();
+        headers[1001].prev_block_hash = H256::default();
+        headers[1005].bits = 0;
+
+        assert_eq!(check_mainnet_headers(&headers, 1000, 10), Ok(()));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod adaptive_batch;
 pub mod bitcoin_client;
 pub mod config;
+pub mod header_validation;
 pub mod near_client;
@@ -3,14 +3,16 @@ use std::sync::Arc;
 
 use bitcoin::hashes::Hash;
 use bitcoin::BlockHash;
-use bitcoin_client::AuxData;
+use btc_types::aux::AuxData;
 use btc_types::contract_args::InitArgs;
+use btc_types::network::{Chain, Network};
 use log::{info, trace, warn};
 use tokio::sync::Mutex;
 
 use crate::adaptive_batch::AdaptiveBatchSizer;
 use crate::bitcoin_client::Client as BitcoinClient;
 use crate::config::{Config, InitConfig};
+use crate::header_validation::check_headers;
 use crate::near_client::{CustomError, NearClient};
 use clap::Parser;
 use std::path::PathBuf;
@@ -18,6 +20,7 @@ use std::path::PathBuf;
 mod adaptive_batch;
 mod bitcoin_client;
 mod config;
+mod header_validation;
 mod near_client;
 
 struct Synchronizer {
@@ -229,6 +232,16 @@ impl Synchronizer {
         let first_block_height_to_submit = Arc::new(AtomicU64::new(
             self.get_last_correct_block_height().await.unwrap() + 1,
         ));
+        let chain_network = match (
+            self.near_client.get_chain().await,
+            self.near_client.get_network().await,
+        ) {
+            (Ok(chain), Ok(network)) => Some((chain, network)),
+            (Err(e), _) | (_, Err(e)) => {
+                warn!(target: "relay", "NEAR Client: Error on getting the chain, the blocks are not validated before the submission. Error: {e}");
+                None
+            }
+        };
 
         'main_loop: loop {
             let (current_fetch_size, current_batch_size) = {
@@ -272,6 +285,17 @@ impl Synchronizer {
                 continue;
             }
 
+            let blocks_to_submit = self
+                .drop_invalid_blocks(chain_network, blocks_to_submit)
+                .await;
+            if blocks_to_submit.is_empty() {
+                tokio::time::sleep(std::time::Duration::from_secs(
+                    self.config.sleep_time_on_fail_sec,
+                ))
+                .await;
+                continue;
+            }
+
             info!(
                 target: "relay",
                 "Adaptive batch: fetch_size={}, batch_size={}, blocks_fetched={}",
@@ -293,6 +317,44 @@ impl Synchronizer {
         }
     }
 
+    /// Drops the headers starting from the first one the contract would reject
+    async fn drop_invalid_blocks(
+        &self,
+        chain_network: Option<(Chain, Network)>,
+        mut blocks_to_submit: Vec<(u64, btc_types::header::Header, Option<AuxData>)>,
+    ) -> Vec<(u64, btc_types::header::Header, Option<AuxData>)> {
+        let Some((chain, network)) = chain_network else {
+            return blocks_to_submit;
+        };
+        let last_block_header = match self.near_client.get_last_block_header().await {
+            Ok(last_block_header) => last_block_header,
+            Err(e) => {
+                warn!(target: "relay", "NEAR Client: Error on get_last_block_header, the blocks are not validated. Error: {e}");
+                return blocks_to_submit;
+            }
+        };
+        let current_time = u32::try_from(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        )
+        .unwrap();
+
+        if let Err((index, err)) = check_headers(
+            chain,
+            network,
+            last_block_header,
+            blocks_to_submit.iter().map(|(_, header, _)| header),
+            current_time,
+        ) {
+            warn!(target: "relay", "Block at height {} is rejected by the validation: {err}", blocks_to_submit[index].0);
+            blocks_to_submit.truncate(index);
+        }
+
+        blocks_to_submit
+    }
+
     async fn get_last_correct_block_height(
         &self,
     ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
@@ -370,6 +432,12 @@ async fn init_contract(
 
     let genesis_block_height = init_config.init_height - init_config.num_of_blcoks_to_submit + 1;
 
+    let initial_chain_work = init_config.use_node_chain_work.then(|| {
+        bitcoin_client
+            .get_block_chain_work(&BlockHash::from_byte_array(headers[0].block_hash().0))
+            .expect("Failed to get initial chain work")
+    });
+
     let args = InitArgs {
         genesis_block_hash: headers[0].block_hash(),
         genesis_block_height,
@@ -377,6 +445,10 @@ async fn init_contract(
         gc_threshold: init_config.gc_threshold,
         network: init_config.network,
         submit_blocks: headers,
+        initial_chain_work,
+        min_chain_work: init_config.min_chain_work,
+        signet_challenge: init_config.signet_challenge,
+        chain: init_config.chain,
     };
 
     info!(
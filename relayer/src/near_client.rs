@@ -1,12 +1,11 @@
 use std::str::FromStr;
 
-#[cfg(feature = "dogecoin")]
-use bitcoin::consensus::serialize;
-#[cfg(feature = "dogecoin")]
-use bitcoincore_rpc::bitcoin::hashes::Hash;
 use borsh::to_vec;
-use btc_types::contract_args::InitArgs;
+use btc_types::aux::AuxData;
+use btc_types::contract_args::{InitArgs, ProofArgsV2};
 use btc_types::header::ExtendedHeader;
+use btc_types::network::{Chain, Network};
+use btc_types::validation::ErrorCode;
 use log::info;
 use merkle_tools::H256;
 use near_crypto::InMemorySigner;
@@ -21,7 +20,6 @@ use near_primitives::types::{AccountId, BlockReference};
 use near_primitives::views::TxExecutionStatus;
 use serde_json::{from_slice, json};
 
-use crate::bitcoin_client::AuxData;
 use tokio::time;
 
 use crate::config::NearConfig;
@@ -34,6 +32,8 @@ const VERIFY_TRANSACTION_INCLUSION: &str = "verify_transaction_inclusion";
 const VERIFY_TRANSACTION_INCLUSION_V2: &str = "verify_transaction_inclusion_v2";
 const RECEIVE_LAST_N_BLOCKS: &str = "get_last_n_blocks_hashes";
 const GET_HEIGHT_BY_BLOCK_HASH: &str = "get_height_by_block_hash";
+const GET_CHAIN: &str = "get_chain";
+const GET_NETWORK: &str = "get_network";
 
 #[derive(thiserror::Error, Debug)]
 pub enum CustomError {
@@ -41,6 +41,8 @@ pub enum CustomError {
     PrevBlockNotFound,
     #[error("Exceeded the maximum amount of gas")]
     GasExceeded,
+    #[error("Block rejected with {0}: {1}")]
+    BlockRejected(ErrorCode, String),
     #[error("Tx execution Error: {0:?}")]
     TxExecutionError(String),
 }
@@ -64,28 +66,6 @@ pub struct SignedSubmitTransaction {
     pub signed_tx: SignedTransaction,
 }
 
-#[cfg(feature = "dogecoin")]
-fn get_aux_data(aux_data: Option<AuxData>) -> Option<btc_types::aux::AuxData> {
-    match aux_data {
-        None => None,
-        Some(aux_data) => Some(btc_types::aux::AuxData {
-            coinbase_tx: serialize(&aux_data.coinbase_tx),
-            merkle_proof: aux_data
-                .merkle_branch
-                .iter()
-                .map(|h| H256::from(h.to_raw_hash().to_byte_array()))
-                .collect(),
-            chain_merkle_proof: aux_data
-                .chainmerkle_branch
-                .iter()
-                .map(|h| H256::from(h.to_raw_hash().to_byte_array()))
-                .collect(),
-            chain_id: aux_data.chain_index.try_into().unwrap(),
-            parent_block: aux_data.parent_block,
-        }),
-    }
-}
-
 impl NearClient {
     /// Create new Near client
     ///
@@ -240,13 +220,13 @@ impl NearClient {
                 return Err("No last block height in the current chunk".into());
             };
 
-            #[cfg(feature = "dogecoin")]
+            #[cfg(any(feature = "dogecoin", feature = "namecoin"))]
             let args: Vec<_> = header_chunk
                 .iter()
-                .map(|(_, header, aux_data)| (header.clone(), get_aux_data(aux_data.clone())))
+                .map(|(_, header, aux_data)| (header.clone(), aux_data.clone()))
                 .collect();
 
-            #[cfg(not(feature = "dogecoin"))]
+            #[cfg(not(any(feature = "dogecoin", feature = "namecoin")))]
             let args: Vec<_> = header_chunk
                 .iter()
                 .map(|(_, header, _)| header.clone())
@@ -319,9 +299,16 @@ impl NearClient {
 
             if let near_primitives::views::FinalExecutionStatus::Failure(ref err) = outcome.status {
                 let err_str = format!("{err:?}");
+                match ErrorCode::parse(&err_str) {
+                    Some(ErrorCode::PrevNotFound) => return Err(CustomError::PrevBlockNotFound),
+                    Some(code) => return Err(CustomError::BlockRejected(code, err_str)),
+                    None => {}
+                }
+                // Contracts deployed before the error codes panic without them
                 if err_str.contains("PrevBlockNotFound") {
                     return Err(CustomError::PrevBlockNotFound);
-                } else if err_str.contains("Exceeded the maximum amount of gas") {
+                }
+                if err_str.contains("Exceeded the maximum amount of gas") {
                     return Err(CustomError::GasExceeded);
                 }
                 return Err(CustomError::TxExecutionError(err_str));
@@ -359,6 +346,34 @@ impl NearClient {
         Ok(header)
     }
 
+    /// Get the chain followed by the contract
+    ///
+    /// # Errors
+    /// * Connection issue
+    /// * The contract is deployed before the chain is stored in the state
+    pub async fn get_chain(&self) -> Result<Chain, Box<dyn std::error::Error + Send + Sync>> {
+        let args = json!({});
+        let result = self
+            .submit_view_tx(GET_CHAIN, args.to_string().into_bytes())
+            .await?;
+
+        Ok(from_slice::<Chain>(&result)?)
+    }
+
+    /// Get the network of the chain followed by the contract
+    ///
+    /// # Errors
+    /// * Connection issue
+    pub async fn get_network(&self) -> Result<Network, Box<dyn std::error::Error + Send + Sync>> {
+        let args = json!({});
+        let result = self
+            .submit_view_tx(GET_NETWORK, args.to_string().into_bytes())
+            .await?;
+
+        let (_chain_name, network) = from_slice::<(String, Network)>(&result)?;
+        Ok(network)
+    }
+
     /// Check that block already submitted to Near
     /// If the block is on Near but from fork
     /// the function return false
@@ -439,31 +454,16 @@ impl NearClient {
     /// Verify transaction inclusion using the v2 contract method,
     /// which additionally validates a coinbase merkle proof
     /// (mitigates the 64-byte tx Merkle proof forgery vulnerability).
+    /// The arguments can be built from a block with `merkle_tools::BlockMerkleTree`.
     ///
     /// # Errors
     /// * Connection issue
     /// * Transaction fails
-    #[allow(dead_code, clippy::too_many_arguments)]
+    #[allow(dead_code)]
     pub async fn verify_transaction_inclusion_v2(
         &self,
-        transaction_hash: H256,
-        transaction_position: usize,
-        transaction_block_blockhash: H256,
-        merkle_proof: Vec<H256>,
-        coinbase_tx_id: H256,
-        coinbase_merkle_proof: Vec<H256>,
-        confirmations: u64,
+        args: ProofArgsV2,
     ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        let args = btc_types::contract_args::ProofArgsV2 {
-            tx_id: transaction_hash,
-            tx_block_blockhash: transaction_block_blockhash,
-            tx_index: transaction_position.try_into()?,
-            merkle_proof,
-            coinbase_tx_id,
-            coinbase_merkle_proof,
-            confirmations,
-        };
-
         let tx_hash = self
             .submit_tx(
                 self.sign_tx(VERIFY_TRANSACTION_INCLUSION_V2, to_vec(&args)?, 0, None)
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use bitcoincore_rpc::bitcoin::hashes::Hash;
 use log::{debug, error, info};
-use merkle_tools::H256;
+use merkle_tools::{BlockMerkleTree, H256};
 
 use btc_relayer_lib::bitcoin_client::Client as BitcoinClient;
 use btc_relayer_lib::config::Config;
@@ -123,15 +123,13 @@ async fn verify_transaction_flow(
         .unwrap();
     let transaction_block_blockhash = block.header.block_hash();
 
-    let transactions = block
-        .txdata
-        .iter()
-        .map(|tx| H256(tx.compute_txid().to_byte_array()))
-        .collect::<Vec<_>>();
-
     // Provide the transaction hash and merkle proof
-    let transaction_hash = transactions[transaction_position].clone(); // Provide the transaction hash
-    let merkle_proof = BitcoinClient::compute_merkle_proof(&block, transaction_position); // Provide the merkle proof
+    let proof_args = BlockMerkleTree::from_block(&block)
+        .unwrap()
+        .proof_args_at(transaction_position, 0)
+        .unwrap();
+    let transaction_hash = proof_args.tx_id;
+    let merkle_proof = proof_args.merkle_proof;
 
     // If we need to force some specific transaction hash
     let transaction_hash = if force_transaction_hash.is_empty() {
@@ -176,44 +174,27 @@ async fn verify_transaction_v2_flow(
             u64::try_from(transaction_block_height).expect("correct transaction height"),
         )
         .unwrap();
-    let transaction_block_blockhash = block.header.block_hash();
-
-    let transactions: Vec<H256> = block
-        .txdata
-        .iter()
-        .map(|tx| H256(tx.compute_txid().to_byte_array()))
-        .collect();
-
-    assert!(
-        transaction_position < transactions.len(),
-        "transaction_position out of bounds for block at height {transaction_block_height} ({} txs)",
-        transactions.len()
-    );
-
-    let transaction_hash = transactions[transaction_position].clone();
-    let merkle_proof = BitcoinClient::compute_merkle_proof(&block, transaction_position);
+    let block_merkle_tree = BlockMerkleTree::from_block(&block).unwrap();
 
-    let coinbase_tx_id = transactions[0].clone();
-    let coinbase_merkle_proof = BitcoinClient::compute_merkle_proof(&block, 0);
+    let proof_args = block_merkle_tree
+        .proof_args_at(transaction_position, 0)
+        .unwrap_or_else(|| {
+            panic!(
+                "transaction_position out of bounds for block at height {transaction_block_height} ({} txs)",
+                block_merkle_tree.txids().len()
+            )
+        });
 
     info!(
         "block at height {} has {} txs; merkle proof depth: tx = {}, coinbase = {}",
         transaction_block_height,
-        transactions.len(),
-        merkle_proof.len(),
-        coinbase_merkle_proof.len(),
+        block_merkle_tree.txids().len(),
+        proof_args.merkle_proof.len(),
+        proof_args.coinbase_merkle_proof.len(),
     );
 
     let result = near_client
-        .verify_transaction_inclusion_v2(
-            transaction_hash,
-            transaction_position,
-            transaction_block_blockhash.to_byte_array().into(),
-            merkle_proof,
-            coinbase_tx_id,
-            coinbase_merkle_proof,
-            0,
-        )
+        .verify_transaction_inclusion_v2(proof_args.into())
         .await;
 
     match result {